@@ -1,132 +1,28 @@
 // Benchmark suite for Geyser texture operations
 // Run with: cargo bench --features vulkan
 
-use criterion::{black_box, criterion_group, criterion_main, Criterion, BenchmarkId};
+use criterion::{criterion_group, criterion_main, Criterion};
+#[cfg(feature = "vulkan")]
+use criterion::{black_box, BenchmarkId};
+#[cfg(feature = "vulkan")]
 use geyser::{
-    common::{TextureDescriptor, TextureFormat, TextureUsage},
+    common::{TextureDescriptor, TextureFormat, TextureMemoryLocation, TextureTiling, TextureUsage},
     TextureShareManager,
 };
 
 #[cfg(feature = "vulkan")]
 use geyser::vulkan::VulkanTextureShareManager;
 #[cfg(feature = "vulkan")]
-use ash::{vk, Entry, Instance, Device};
-#[cfg(feature = "vulkan")]
-use std::{ffi::CString, sync::Arc};
-
-#[cfg(feature = "vulkan")]
-fn create_vulkan_context() -> (Arc<Instance>, Arc<Device>, vk::PhysicalDevice, u32) {
-    let entry = unsafe { Entry::load().expect("Failed to load Vulkan") };
-    let app_name = CString::new("GeyserBench").unwrap();
-    
-    let app_info = vk::ApplicationInfo {
-        s_type: vk::StructureType::APPLICATION_INFO,
-        p_next: std::ptr::null(),
-        p_application_name: app_name.as_ptr(),
-        application_version: 0,
-        p_engine_name: std::ptr::null(),
-        engine_version: 0,
-        api_version: vk::make_api_version(0, 1, 2, 0),
-        _marker: std::marker::PhantomData,
-    };
-
-    #[cfg(target_os = "windows")]
-    let extension_names = [
-        ash::khr::external_memory_capabilities::NAME.as_ptr(),
-        ash::khr::get_physical_device_properties2::NAME.as_ptr(),
-    ];
-    
-    #[cfg(target_os = "linux")]
-    let extension_names = [
-        ash::khr::external_memory_capabilities::NAME.as_ptr(),
-        ash::khr::get_physical_device_properties2::NAME.as_ptr(),
-    ];
-
-    let create_info = vk::InstanceCreateInfo {
-        s_type: vk::StructureType::INSTANCE_CREATE_INFO,
-        p_next: std::ptr::null(),
-        flags: vk::InstanceCreateFlags::empty(),
-        p_application_info: &app_info,
-        enabled_layer_count: 0,
-        pp_enabled_layer_names: std::ptr::null(),
-        enabled_extension_count: extension_names.len() as u32,
-        pp_enabled_extension_names: extension_names.as_ptr(),
-        _marker: std::marker::PhantomData,
-    };
-
-    let instance = unsafe { entry.create_instance(&create_info, None).expect("Failed to create instance") };
-
-    let physical_devices = unsafe { instance.enumerate_physical_devices().expect("No devices") };
-    let physical_device = physical_devices[0];
-
-    let queue_family_properties = unsafe { instance.get_physical_device_queue_family_properties(physical_device) };
-    let queue_family_index = queue_family_properties
-        .iter()
-        .enumerate()
-        .find_map(|(i, props)| {
-            if props.queue_flags.contains(vk::QueueFlags::GRAPHICS) {
-                Some(i as u32)
-            } else {
-                None
-            }
-        })
-        .expect("No suitable queue family");
-
-    let queue_priority = 1.0;
-    let queue_create_info = vk::DeviceQueueCreateInfo {
-        s_type: vk::StructureType::DEVICE_QUEUE_CREATE_INFO,
-        p_next: std::ptr::null(),
-        flags: vk::DeviceQueueCreateFlags::empty(),
-        queue_family_index,
-        queue_count: 1,
-        p_queue_priorities: &queue_priority,
-        _marker: std::marker::PhantomData,
-    };
-
-    #[cfg(target_os = "windows")]
-    let device_extension_names = [
-        ash::khr::external_memory::NAME.as_ptr(),
-        ash::khr::external_memory_win32::NAME.as_ptr(),
-        ash::khr::external_semaphore::NAME.as_ptr(),
-        ash::khr::external_semaphore_win32::NAME.as_ptr(),
-        ash::khr::external_fence::NAME.as_ptr(),
-        ash::khr::external_fence_win32::NAME.as_ptr(),
-    ];
-    
-    #[cfg(target_os = "linux")]
-    let device_extension_names = [
-        ash::khr::external_memory::NAME.as_ptr(),
-        ash::khr::external_memory_fd::NAME.as_ptr(),
-        ash::khr::external_semaphore::NAME.as_ptr(),
-        ash::khr::external_semaphore_fd::NAME.as_ptr(),
-        ash::khr::external_fence::NAME.as_ptr(),
-        ash::khr::external_fence_fd::NAME.as_ptr(),
-    ];
-
-    let device_create_info = vk::DeviceCreateInfo {
-        s_type: vk::StructureType::DEVICE_CREATE_INFO,
-        p_next: std::ptr::null(),
-        flags: vk::DeviceCreateFlags::empty(),
-        queue_create_info_count: 1,
-        p_queue_create_infos: &queue_create_info,
-        enabled_layer_count: 0,
-        pp_enabled_layer_names: std::ptr::null(),
-        enabled_extension_count: device_extension_names.len() as u32,
-        pp_enabled_extension_names: device_extension_names.as_ptr(),
-        p_enabled_features: std::ptr::null(),
-        _marker: std::marker::PhantomData,
-    };
-
-    let device = unsafe { instance.create_device(physical_device, &device_create_info, None).expect("Failed to create device") };
-
-    (Arc::new(instance), Arc::new(device), physical_device, queue_family_index)
+fn create_manager() -> VulkanTextureShareManager {
+    VulkanTextureShareManager::builder()
+        .application_name("GeyserBench")
+        .build()
+        .expect("Failed to create manager")
 }
 
 #[cfg(feature = "vulkan")]
 fn bench_texture_creation(c: &mut Criterion) {
-    let (instance, device, physical_device, queue_family_index) = create_vulkan_context();
-    let manager = VulkanTextureShareManager::new(instance, device, physical_device, queue_family_index)
-        .expect("Failed to create manager");
+    let manager = create_manager();
     
     let mut group = c.benchmark_group("texture_creation");
     
@@ -138,6 +34,8 @@ fn bench_texture_creation(c: &mut Criterion) {
             format: TextureFormat::Rgba8Unorm,
             usage: vec![TextureUsage::TextureBinding],
             label: Some(format!("Bench{}x{}", size, size)),
+            tiling: TextureTiling::Optimal,
+            memory_location: TextureMemoryLocation::GpuOnly,
         };
         
         group.bench_with_input(BenchmarkId::from_parameter(size), size, |b, _| {
@@ -154,9 +52,7 @@ fn bench_texture_creation(c: &mut Criterion) {
 
 #[cfg(feature = "vulkan")]
 fn bench_texture_export(c: &mut Criterion) {
-    let (instance, device, physical_device, queue_family_index) = create_vulkan_context();
-    let manager = VulkanTextureShareManager::new(instance, device, physical_device, queue_family_index)
-        .expect("Failed to create manager");
+    let manager = create_manager();
     
     let descriptor = TextureDescriptor {
         width: 1024,
@@ -164,6 +60,8 @@ fn bench_texture_export(c: &mut Criterion) {
         format: TextureFormat::Rgba8Unorm,
         usage: vec![TextureUsage::TextureBinding],
         label: Some("BenchExport".to_string()),
+        tiling: TextureTiling::Optimal,
+        memory_location: TextureMemoryLocation::GpuOnly,
     };
     
     let texture = manager.create_shareable_texture(&descriptor)
@@ -180,9 +78,7 @@ fn bench_texture_export(c: &mut Criterion) {
 
 #[cfg(feature = "vulkan")]
 fn bench_texture_formats(c: &mut Criterion) {
-    let (instance, device, physical_device, queue_family_index) = create_vulkan_context();
-    let manager = VulkanTextureShareManager::new(instance, device, physical_device, queue_family_index)
-        .expect("Failed to create manager");
+    let manager = create_manager();
     
     let mut group = c.benchmark_group("texture_formats");
     
@@ -200,6 +96,8 @@ fn bench_texture_formats(c: &mut Criterion) {
             format,
             usage: vec![TextureUsage::TextureBinding],
             label: Some(name.to_string()),
+            tiling: TextureTiling::Optimal,
+            memory_location: TextureMemoryLocation::GpuOnly,
         };
         
         group.bench_with_input(BenchmarkId::from_parameter(name), &descriptor, |b, desc| {
@@ -216,13 +114,9 @@ fn bench_texture_formats(c: &mut Criterion) {
 
 #[cfg(feature = "vulkan")]
 fn bench_export_import_roundtrip(c: &mut Criterion) {
-    let (instance1, device1, physical_device1, queue_family_index1) = create_vulkan_context();
-    let manager1 = VulkanTextureShareManager::new(instance1, device1, physical_device1, queue_family_index1)
-        .expect("Failed to create manager1");
+    let manager1 = create_manager();
     
-    let (instance2, device2, physical_device2, queue_family_index2) = create_vulkan_context();
-    let manager2 = VulkanTextureShareManager::new(instance2, device2, physical_device2, queue_family_index2)
-        .expect("Failed to create manager2");
+    let manager2 = create_manager();
     
     let descriptor = TextureDescriptor {
         width: 1024,
@@ -230,6 +124,8 @@ fn bench_export_import_roundtrip(c: &mut Criterion) {
         format: TextureFormat::Rgba8Unorm,
         usage: vec![TextureUsage::TextureBinding],
         label: Some("BenchRoundtrip".to_string()),
+        tiling: TextureTiling::Optimal,
+        memory_location: TextureMemoryLocation::GpuOnly,
     };
     
     c.bench_function("export_import_roundtrip", |b| {
@@ -263,9 +159,7 @@ fn bench_dummy(_c: &mut Criterion) {
 
 #[cfg(feature = "vulkan")]
 fn bench_semaphore_creation(c: &mut Criterion) {
-    let (instance, device, physical_device, queue_family_index) = create_vulkan_context();
-    let manager = VulkanTextureShareManager::new(instance, device, physical_device, queue_family_index)
-        .expect("Failed to create manager");
+    let manager = create_manager();
     
     c.bench_function("semaphore_creation", |b| {
         b.iter(|| {
@@ -278,9 +172,7 @@ fn bench_semaphore_creation(c: &mut Criterion) {
 
 #[cfg(feature = "vulkan")]
 fn bench_fence_creation(c: &mut Criterion) {
-    let (instance, device, physical_device, queue_family_index) = create_vulkan_context();
-    let manager = VulkanTextureShareManager::new(instance, device, physical_device, queue_family_index)
-        .expect("Failed to create manager");
+    let manager = create_manager();
     
     c.bench_function("fence_creation", |b| {
         b.iter(|| {
@@ -293,9 +185,7 @@ fn bench_fence_creation(c: &mut Criterion) {
 
 #[cfg(all(feature = "vulkan", target_os = "windows"))]
 fn bench_semaphore_export_import(c: &mut Criterion) {
-    let (instance, device, physical_device, queue_family_index) = create_vulkan_context();
-    let manager = VulkanTextureShareManager::new(instance, device, physical_device, queue_family_index)
-        .expect("Failed to create manager");
+    let manager = create_manager();
     
     c.bench_function("semaphore_export_import_win32", |b| {
         b.iter(|| {
@@ -314,9 +204,7 @@ fn bench_semaphore_export_import(c: &mut Criterion) {
 
 #[cfg(all(feature = "vulkan", target_os = "linux"))]
 fn bench_semaphore_export_import(c: &mut Criterion) {
-    let (instance, device, physical_device, queue_family_index) = create_vulkan_context();
-    let manager = VulkanTextureShareManager::new(instance, device, physical_device, queue_family_index)
-        .expect("Failed to create manager");
+    let manager = create_manager();
     
     c.bench_function("semaphore_export_import_fd", |b| {
         b.iter(|| {
@@ -335,9 +223,7 @@ fn bench_semaphore_export_import(c: &mut Criterion) {
 
 #[cfg(feature = "vulkan")]
 fn bench_memory_overhead(c: &mut Criterion) {
-    let (instance, device, physical_device, queue_family_index) = create_vulkan_context();
-    let manager = VulkanTextureShareManager::new(instance, device, physical_device, queue_family_index)
-        .expect("Failed to create manager");
+    let manager = create_manager();
     
     let mut group = c.benchmark_group("memory_overhead");
     
@@ -349,6 +235,8 @@ fn bench_memory_overhead(c: &mut Criterion) {
             format: TextureFormat::Rgba8Unorm,
             usage: vec![TextureUsage::TextureBinding],
             label: Some(format!("MemBench{}x{}", size, size)),
+            tiling: TextureTiling::Optimal,
+            memory_location: TextureMemoryLocation::GpuOnly,
         };
         
         group.bench_with_input(BenchmarkId::from_parameter(size), size, |b, _| {
@@ -368,9 +256,7 @@ fn bench_memory_overhead(c: &mut Criterion) {
 
 #[cfg(feature = "vulkan")]
 fn bench_timeline_semaphore_creation(c: &mut Criterion) {
-    let (instance, device, physical_device, queue_family_index) = create_vulkan_context();
-    let manager = VulkanTextureShareManager::new(instance, device, physical_device, queue_family_index)
-        .expect("Failed to create manager");
+    let manager = create_manager();
     
     c.bench_function("timeline_semaphore_creation", |b| {
         b.iter(|| {
@@ -383,9 +269,7 @@ fn bench_timeline_semaphore_creation(c: &mut Criterion) {
 
 #[cfg(feature = "vulkan")]
 fn bench_timeline_semaphore_signal_wait(c: &mut Criterion) {
-    let (instance, device, physical_device, queue_family_index) = create_vulkan_context();
-    let manager = VulkanTextureShareManager::new(instance.clone(), device.clone(), physical_device, queue_family_index)
-        .expect("Failed to create manager");
+    let manager = create_manager();
     
     let semaphore = manager.create_exportable_timeline_semaphore(0)
         .expect("Failed to create semaphore");
@@ -403,15 +287,13 @@ fn bench_timeline_semaphore_signal_wait(c: &mut Criterion) {
     });
     
     unsafe {
-        device.destroy_semaphore(semaphore, None);
+        manager.device().destroy_semaphore(semaphore, None);
     }
 }
 
 #[cfg(feature = "vulkan")]
 fn bench_timeline_vs_binary_semaphore(c: &mut Criterion) {
-    let (instance, device, physical_device, queue_family_index) = create_vulkan_context();
-    let manager = VulkanTextureShareManager::new(instance.clone(), device.clone(), physical_device, queue_family_index)
-        .expect("Failed to create manager");
+    let manager = create_manager();
     
     let mut group = c.benchmark_group("semaphore_comparison");
     
@@ -438,9 +320,7 @@ fn bench_timeline_vs_binary_semaphore(c: &mut Criterion) {
 
 #[cfg(feature = "vulkan")]
 fn bench_timeline_semaphore_query(c: &mut Criterion) {
-    let (instance, device, physical_device, queue_family_index) = create_vulkan_context();
-    let manager = VulkanTextureShareManager::new(instance.clone(), device.clone(), physical_device, queue_family_index)
-        .expect("Failed to create manager");
+    let manager = create_manager();
     
     let semaphore = manager.create_exportable_timeline_semaphore(0)
         .expect("Failed to create semaphore");
@@ -457,15 +337,13 @@ fn bench_timeline_semaphore_query(c: &mut Criterion) {
     });
     
     unsafe {
-        device.destroy_semaphore(semaphore, None);
+        manager.device().destroy_semaphore(semaphore, None);
     }
 }
 
 #[cfg(all(feature = "vulkan", target_os = "windows"))]
 fn bench_timeline_semaphore_export_import(c: &mut Criterion) {
-    let (instance, device, physical_device, queue_family_index) = create_vulkan_context();
-    let manager = VulkanTextureShareManager::new(instance, device, physical_device, queue_family_index)
-        .expect("Failed to create manager");
+    let manager = create_manager();
     
     c.bench_function("timeline_semaphore_export_import_win32", |b| {
         b.iter(|| {
@@ -484,9 +362,7 @@ fn bench_timeline_semaphore_export_import(c: &mut Criterion) {
 
 #[cfg(all(feature = "vulkan", target_os = "linux"))]
 fn bench_timeline_semaphore_export_import(c: &mut Criterion) {
-    let (instance, device, physical_device, queue_family_index) = create_vulkan_context();
-    let manager = VulkanTextureShareManager::new(instance, device, physical_device, queue_family_index)
-        .expect("Failed to create manager");
+    let manager = create_manager();
     
     c.bench_function("timeline_semaphore_export_import_fd", |b| {
         b.iter(|| {
@@ -1,262 +1,169 @@
 // Example: Bevy Integration with Geyser
-// This demonstrates how to integrate Geyser-managed textures with the Bevy game engine.
+// This demonstrates zero-copy texture sharing with the Bevy game engine through
+// `GeyserPlugin`, in both directions:
 //
-// IMPORTANT: This is a Phase 1 "conceptual integration" example that demonstrates
-// the data flow, but uses CPU-side copies. True zero-copy integration requires
-// deeper WGPU/Bevy integration which is a Phase 2/3 goal.
+// - A second camera renders a spinning sprite into an image that is exported with
+//   `ExportBevyTexture`. Every rendered frame is announced by `GeyserFrameExported`.
+// - The exported package is imported back with `ImportGeyserTexture` and shown on a
+//   sprite by the main camera, waiting on the exported timeline semaphore each frame
+//   through `GeyserSync`.
 //
-// Current limitations:
-// - Bevy uses WGPU internally, which abstracts over Vulkan/Metal/DX12
-// - WGPU doesn't expose direct APIs for importing arbitrary native texture handles
-// - This example demonstrates creating a Geyser texture and simulating updates
-//   by copying data to a Bevy Image (CPU transfer, not zero-copy)
+// In a real application the export would be consumed by another process or API and
+// the import would come from one; here both ends live in the same app so the example
+// runs standalone.
 //
-// Future improvements (Phase 2/3):
-// - Direct WGPU texture import from external handles
-// - Custom Bevy render plugin for zero-copy texture sharing
-// - Synchronization primitives for safe cross-process access
+// Run with: cargo run --example bevy_integration --features vulkan,bevy
 
 use bevy::{
+    asset::RenderAssetUsages,
+    camera::RenderTarget,
     prelude::*,
-    render::{
-        render_resource::{Extent3d, TextureDimension, TextureFormat as BevyTextureFormat},
-        texture::Image as BevyImage,
-    },
-    window::WindowMode,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages},
 };
-use anyhow::Result;
-
-#[cfg(target_os = "macos")]
-use geyser::{
-    metal::MetalTextureShareManager,
-    common::{ApiTextureHandle, TextureDescriptor, TextureFormat, TextureUsage},
-    TextureShareManager,
-    SharedTexture,
+use geyser::bevy_plugin::{
+    ExportBevyTexture, GeyserFrameExported, GeyserPlugin, GeyserState, GeyserSync,
+    GeyserTextureError, ImportGeyserTexture,
 };
 
-#[cfg(any(target_os = "linux", target_os = "windows"))]
-use geyser::{
-    vulkan::VulkanTextureShareManager,
-    common::{ApiTextureHandle, TextureDescriptor, TextureFormat, TextureUsage},
-    TextureShareManager,
-    SharedTexture,
-};
+const SIZE: u32 = 256;
 
-use std::sync::Arc;
+// Sprite rendered into the exported image
+#[derive(Component)]
+struct Spinner;
 
-// Resource to hold the Geyser texture and manager
-#[derive(Resource)]
-struct GeyserTextureHolder {
-    #[cfg(target_os = "macos")]
-    pub manager: Arc<MetalTextureShareManager>,
-    #[cfg(any(target_os = "linux", target_os = "windows"))]
-    pub manager: Arc<VulkanTextureShareManager>,
-
-    pub geyser_shared_texture: Box<dyn SharedTexture>,
-    pub geyser_texture_desc: TextureDescriptor,
-    pub api_handle: ApiTextureHandle,
-    pub image_handle: Handle<BevyImage>,
-}
+// Sprite showing the imported texture
+#[derive(Component)]
+struct Mirror;
 
 fn main() {
     println!("=== Geyser + Bevy Integration Example ===");
-    println!("This demonstrates conceptual integration between Geyser and Bevy.");
-    println!("Note: Phase 1 uses CPU-side copies. Zero-copy is a Phase 2/3 goal.\n");
 
     App::new()
-        .add_plugins(
-            DefaultPlugins.set(WindowPlugin {
-                primary_window: Some(Window {
-                    title: "Geyser + Bevy Integration".into(),
-                    resolution: (800., 600.).into(),
-                    mode: WindowMode::Windowed,
-                    ..default()
-                }),
+        // Must come before DefaultPlugins so Bevy's device is created with the
+        // external memory extensions Geyser needs
+        .add_plugins(GeyserPlugin)
+        .add_plugins(DefaultPlugins.set(WindowPlugin {
+            primary_window: Some(Window {
+                title: "Geyser + Bevy Integration".into(),
+                resolution: (800, 600).into(),
                 ..default()
-            })
-        )
-        .add_systems(Startup, setup_geyser_and_bevy)
-        .add_systems(Update, update_texture_animation)
+            }),
+            ..default()
+        }))
+        .add_systems(Startup, setup)
+        .add_systems(Update, (spin, import_exported_frames, show_imported, report_errors))
         .run();
 }
 
-fn setup_geyser_and_bevy(
+fn setup(
     mut commands: Commands,
-    mut images: ResMut<Assets<BevyImage>>,
+    mut images: ResMut<Assets<Image>>,
+    mut exports: MessageWriter<ExportBevyTexture>,
 ) {
-    println!("Initializing Geyser texture manager...");
-
-    let texture_desc = TextureDescriptor {
-        width: 256,
-        height: 256,
-        format: TextureFormat::Rgba8Unorm,
-        usage: vec![
-            TextureUsage::TextureBinding,
-            TextureUsage::RenderAttachment,
-            TextureUsage::CopySrc,
-            TextureUsage::CopyDst
-        ],
-        label: Some("BevyGeyserSharedTexture".to_string()),
-    };
-
-    // Platform-specific Geyser initialization
-    #[cfg(target_os = "macos")]
-    let (manager, geyser_texture, api_handle) = {
-        use metal::{Device, MTLDevice};
-        
-        let device = Arc::new(Device::system_default().expect("No Metal device found"));
-        let mgr = MetalTextureShareManager::new(device).expect("Failed to create MetalTextureShareManager");
-        let tex = mgr.create_shareable_texture(&texture_desc).expect("Failed to create Metal texture");
-        let handle = mgr.export_texture(tex.as_ref()).expect("Failed to export Metal texture");
-        
-        println!("✓ Geyser (Metal): Created and exported texture");
-        (Arc::new(mgr), tex, handle)
-    };
-
-    #[cfg(any(target_os = "linux", target_os = "windows"))]
-    let (manager, geyser_texture, api_handle) = {
-        use ash::{vk, Entry, Instance, Device};
-        use std::ffi::{CStr, CString};
-
-        println!("Creating Vulkan context...");
-        let entry = unsafe { Entry::load() }.expect("Failed to load Vulkan");
-        let app_name = CString::new("GeyserBevyExample").unwrap();
-        let engine_name = CString::new("Geyser").unwrap();
-
-        let app_info = vk::ApplicationInfo::builder()
-            .application_name(&app_name)
-            .application_version(0)
-            .engine_name(&engine_name)
-            .engine_version(0)
-            .api_version(vk::make_api_version(0, 1, 0, 0));
-
-        let create_info = vk::InstanceCreateInfo::builder().application_info(&app_info);
-        let instance = unsafe { entry.create_instance(&create_info, None) }.expect("Failed to create Vulkan instance");
-
-        let physical_devices = unsafe { instance.enumerate_physical_devices() }.expect("Failed to enumerate devices");
-        let physical_device = physical_devices[0];
-
-        let queue_family_properties = unsafe { instance.get_physical_device_queue_family_properties(physical_device) };
-        let queue_family_index = queue_family_properties
-            .iter()
-            .enumerate()
-            .find_map(|(i, props)| {
-                if props.queue_flags.contains(vk::QueueFlags::GRAPHICS) {
-                    Some(i as u32)
-                } else {
-                    None
-                }
-            })
-            .expect("No suitable queue family");
-
-        let queue_priority = 1.0;
-        let queue_create_info = vk::DeviceQueueCreateInfo::builder()
-            .queue_family_index(queue_family_index)
-            .queue_priorities(&[queue_priority]);
-
-        let device_extensions = [
-            #[cfg(target_os = "linux")]
-            ash::extensions::khr::ExternalMemoryFd::name().as_ptr(),
-            #[cfg(target_os = "windows")]
-            ash::extensions::khr::ExternalMemoryWin32::name().as_ptr(),
-            ash::extensions::khr::ExternalMemory::name().as_ptr(),
-        ];
-
-        let device_create_info = vk::DeviceCreateInfo::builder()
-            .queue_create_infos(&[*queue_create_info])
-            .enabled_extension_names(&device_extensions);
-
-        let device = unsafe { instance.create_device(physical_device, &device_create_info, None) }
-            .expect("Failed to create Vulkan device");
-
-        let mgr = VulkanTextureShareManager::new(
-            Arc::new(instance),
-            Arc::new(device),
-            physical_device,
-            queue_family_index,
-        ).expect("Failed to create VulkanTextureShareManager");
-        
-        let tex = mgr.create_shareable_texture(&texture_desc).expect("Failed to create Vulkan texture");
-        let handle = mgr.export_texture(tex.as_ref()).expect("Failed to export Vulkan texture");
-        
-        println!("✓ Geyser (Vulkan): Created and exported texture");
-        (Arc::new(mgr), tex, handle)
-    };
-
-    // Create a Bevy Image asset
-    println!("Creating Bevy image asset...");
-    let size = Extent3d {
-        width: texture_desc.width,
-        height: texture_desc.height,
-        depth_or_array_layers: 1,
-    };
-
-    let bevy_image = BevyImage::new_fill(
-        size,
+    let mut target = Image::new_fill(
+        Extent3d {
+            width: SIZE,
+            height: SIZE,
+            depth_or_array_layers: 1,
+        },
         TextureDimension::D2,
-        &[32, 32, 64, 255], // Dark blue initial color
-        BevyTextureFormat::Rgba8UnormSrgb,
+        &[0, 0, 0, 255],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
     );
-    let image_handle = images.add(bevy_image);
-
-    println!("✓ Bevy image created");
-
-    // Store the Geyser resources
-    commands.insert_resource(GeyserTextureHolder {
-        manager,
-        geyser_shared_texture: geyser_texture,
-        geyser_texture_desc: texture_desc.clone(),
-        api_handle,
-        image_handle: image_handle.clone(),
+    target.texture_descriptor.usage = TextureUsages::TEXTURE_BINDING
+        | TextureUsages::COPY_SRC
+        | TextureUsages::COPY_DST
+        | TextureUsages::RENDER_ATTACHMENT;
+    let target = images.add(target);
+
+    // Offscreen scene: a spinning square on its own render layer
+    let offscreen = bevy::camera::visibility::RenderLayers::layer(1);
+    let camera = commands
+        .spawn((
+            Camera2d,
+            Camera {
+                order: -1,
+                clear_color: ClearColorConfig::Custom(Color::srgb(0.1, 0.1, 0.25)),
+                ..default()
+            },
+            RenderTarget::Image(target.clone().into()),
+            offscreen.clone(),
+        ))
+        .id();
+    commands.spawn((
+        Sprite::from_color(Color::srgb(0.9, 0.5, 0.1), Vec2::splat(SIZE as f32 * 0.5)),
+        Spinner,
+        offscreen,
+    ));
+
+    exports.write(ExportBevyTexture {
+        image_handle: target,
+        source_entity: Some(camera),
     });
 
-    // Setup camera and sprite
-    commands.spawn(Camera2dBundle::default());
-    
-    commands.spawn(SpriteBundle {
-        texture: image_handle.clone(),
-        transform: Transform::from_scale(Vec3::splat(3.0)),
-        ..default()
-    });
+    // Window scene: the imported texture, once the first frame has been exported
+    commands.spawn(Camera2d);
+    commands.spawn((
+        Sprite::default(),
+        Transform::from_scale(Vec3::splat(2.0)),
+        Mirror,
+    ));
 
-    println!("✓ Bevy scene setup complete\n");
-    println!("The displayed texture is managed by Geyser and updated via CPU copies.");
-    println!("Watch the animated pattern to verify the integration!\n");
+    println!("✓ Scene set up; the square is rendered offscreen and shown through Geyser");
+}
+
+fn spin(time: Res<Time>, mut spinners: Query<&mut Transform, With<Spinner>>) {
+    for mut transform in &mut spinners {
+        transform.rotation = Quat::from_rotation_z(time.elapsed_secs());
+    }
 }
 
-fn update_texture_animation(
-    geyser_holder: Res<GeyserTextureHolder>,
-    mut images: ResMut<Assets<BevyImage>>,
-    time: Res<Time>,
+fn import_exported_frames(
+    mut commands: Commands,
+    mut frames: MessageReader<GeyserFrameExported>,
+    mut imports: MessageWriter<ImportGeyserTexture>,
+    mut mirrors: Query<(Entity, Option<&mut GeyserSync>), With<Mirror>>,
 ) {
-    // This system demonstrates updating the Bevy texture with animated data
-    // In a real zero-copy scenario, the GPU would render directly to the Geyser texture,
-    // and Bevy would read it without CPU involvement.
+    let Some(frame) = frames.read().last() else {
+        return;
+    };
+
+    for (entity, sync) in &mut mirrors {
+        match sync {
+            // Wait for the newest rendered frame before drawing it
+            Some(mut sync) => sync.acquire = frame.frame,
+            None => {
+                // The package is the same for every frame, so it is imported once
+                imports.write(ImportGeyserTexture {
+                    api_handle: frame.package.texture.clone(),
+                    descriptor: frame.descriptor.clone(),
+                    sync: frame.package.sync.clone(),
+                    target_entity: Some(entity),
+                });
+                commands.entity(entity).insert(GeyserSync {
+                    acquire: frame.frame,
+                    release: 0,
+                });
+                println!("✓ Imported exported texture ({}x{})", frame.descriptor.width, frame.descriptor.height);
+            }
+        }
+    }
+}
 
-    let width = geyser_holder.geyser_texture_desc.width as usize;
-    let height = geyser_holder.geyser_texture_desc.height as usize;
-    
-    if let Some(bevy_image) = images.get_mut(&geyser_holder.image_handle) {
-        let elapsed = time.elapsed_seconds();
-        let data = &mut bevy_image.data;
-        
-        // Generate animated pattern
-        for y in 0..height {
-            for x in 0..width {
-                let i = (y * width + x) * 4;
-                
-                let fx = x as f32 / width as f32;
-                let fy = y as f32 / height as f32;
-                
-                // Animated wave pattern
-                let wave = ((fx * 10.0 + elapsed * 2.0).sin() * 
-                           (fy * 10.0 + elapsed * 2.0).cos() * 0.5 + 0.5);
-                
-                data[i] = (fx * 255.0) as u8;                    // Red: horizontal gradient
-                data[i + 1] = (fy * 255.0) as u8;                // Green: vertical gradient
-                data[i + 2] = (wave * 255.0) as u8;              // Blue: animated wave
-                data[i + 3] = 255;                                // Alpha: fully opaque
+// Points the mirror sprite at the image the plugin created for the import
+fn show_imported(state: Res<GeyserState>, mut mirrors: Query<(Entity, &mut Sprite), With<Mirror>>) {
+    for (entity, mut sprite) in &mut mirrors {
+        if let Some(shared) = state.shared_textures.get(&entity) {
+            if sprite.image != shared.image_handle {
+                sprite.image = shared.image_handle.clone();
             }
         }
     }
 }
+
+fn report_errors(mut errors: MessageReader<GeyserTextureError>) {
+    for error in errors.read() {
+        eprintln!("Geyser texture error for {:?}: {}", error.entity, error.error);
+    }
+}
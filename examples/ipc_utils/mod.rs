@@ -27,6 +27,10 @@ pub enum IpcMessage {
         width: u32,
         height: u32,
         format: String,
+        /// Identity of the exporting device; the consumer must match it
+        device_uuid: [u8; 16],
+        driver_uuid: [u8; 16],
+        driver_version: u32,
     },
     /// Semaphore handle for synchronization
     SemaphoreHandle {
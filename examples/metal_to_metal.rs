@@ -5,7 +5,7 @@
 #[cfg(target_os = "macos")]
 use geyser::{
     metal::MetalTextureShareManager,
-    common::{TextureDescriptor, TextureFormat, TextureUsage},
+    common::{TextureDescriptor, TextureFormat, TextureMemoryLocation, TextureTiling, TextureUsage},
     TextureShareManager,
     SharedTexture,
 };
@@ -38,6 +38,8 @@ fn main() -> Result<()> {
             TextureUsage::CopyDst
         ],
         label: Some("SharedTextureFromApp1".to_string()),
+        tiling: TextureTiling::Optimal,
+        memory_location: TextureMemoryLocation::GpuOnly,
     };
 
     println!("App 1: Creating shareable texture...");
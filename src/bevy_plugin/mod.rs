@@ -6,35 +6,51 @@
 //! # Features
 //! - Import Geyser-managed textures as Bevy Image assets
 //! - Export Bevy textures for cross-process sharing
-//! - Automatic synchronization and lifecycle management
+//! - Stream another process's texture onto an entity with `GeyserStreamSource`
+//! - Per-frame timeline semaphore synchronization via `GeyserSync`
+//! - Automatic lifecycle management
 //! - Support for Vulkan backend (wgpu-hal)
 //!
 //! # Usage
+//! Add `GeyserPlugin` before `DefaultPlugins`, so that Bevy creates its Vulkan
+//! device with the external memory extensions Geyser needs:
 //! ```ignore
 //! use bevy::prelude::*;
 //! use geyser::bevy_plugin::GeyserPlugin;
 //!
 //! App::new()
-//!     .add_plugins(DefaultPlugins)
 //!     .add_plugins(GeyserPlugin)
+//!     .add_plugins(DefaultPlugins)
 //!     .run();
 //! ```
 
-mod wgpu_bridge;
-pub use wgpu_bridge::{WgpuTextureHandle, WgpuBackendType};
+pub use crate::wgpu_bridge::{WgpuTextureHandle, WgpuBackendType};
 
 use bevy::prelude::*;
 use bevy::render::{
-    RenderApp,
+    Render, RenderApp, RenderSystems, ExtractSchedule,
     extract_resource::ExtractResource,
+    render_asset::{prepare_assets, RenderAssets},
+    render_resource::{Extent3d, TextureDimension, TextureViewDescriptor},
+    renderer::{raw_vulkan_init::RawVulkanInitSettings, RenderDevice},
+    texture::{DefaultImageSampler, GpuImage},
 };
 use bevy::render::Extract;
-use std::sync::Arc;
+use bevy::platform::collections::{HashMap, HashSet};
+use crate::wgpu_bridge;
+use bevy::ecs::entity::Entities;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
 
 #[cfg(feature = "vulkan")]
 use crate::{
-    vulkan::VulkanTextureShareManager,
-    common::{ApiTextureHandle, TextureDescriptor},
+    vulkan::{VulkanSharedTexture, VulkanTextureShareManager},
+    common::{
+        ApiTextureHandle, SyncPrimitives, TextureDescriptor, TextureMemoryLocation, TextureSharePackage, TextureTiling,
+        TextureUsage,
+    },
+    error::GeyserError,
+    TextureShareManager,
 };
 
 /// Bevy plugin for Geyser texture sharing
@@ -44,28 +60,98 @@ impl Plugin for GeyserPlugin {
     fn build(&self, app: &mut App) {
         // Add resources to main app
         app.init_resource::<GeyserState>();
+        app.init_resource::<GeyserDiagnostics>();
+        app.init_resource::<GeyserStreamEndpoints>();
+
+        // Bevy reads these when its render plugin is built and creates the device later
+        if app.get_sub_app(RenderApp).is_some() {
+            warn!("GeyserPlugin was added after the render plugin; add it before DefaultPlugins to enable texture sharing");
+        }
+        let mut vulkan_settings = app.world_mut().get_resource_or_init::<RawVulkanInitSettings>();
+        // SAFETY: only extensions the adapter supports are added
+        unsafe {
+            vulkan_settings.add_create_device_callback(|args, adapter, _| {
+                wgpu_bridge::enable_shareable_extensions(args.extensions, adapter);
+            });
+        }
         
-        // Register events
-        app.add_event::<ImportGeyserTexture>();
-        app.add_event::<ExportBevyTexture>();
+        // Register messages
+        app.add_message::<ImportGeyserTexture>();
+        app.add_message::<ExportBevyTexture>();
+        app.add_message::<GeyserFrameExported>();
+        app.add_message::<GeyserTextureError>();
         
         // Add systems for texture management
         app.add_systems(Update, (
-            process_shared_texture_events,
-            cleanup_expired_textures,
+            (receive_stream_messages, process_shared_texture_events, update_stream_images).chain(),
+            // Needs the `GeyserSharedTexture` components inserted by new imports, or it
+            // would unregister them as despawned
+            cleanup_expired_textures.after(process_shared_texture_events),
+            emit_exported_frames,
+            emit_render_errors,
         ));
+        #[cfg(all(feature = "ipc", target_os = "linux"))]
+        app.add_systems(Update, connect_stream_sources.before(receive_stream_messages));
     }
 
     fn finish(&self, app: &mut App) {
         // Initialize render-world resources
+        if app.get_sub_app(RenderApp).is_none() {
+            return;
+        }
+
+        // Frames exported by the render world, and its failures, are reported back as
+        // main-world messages
+        let (frame_sender, frame_receiver) = mpsc::channel();
+        let (error_sender, error_receiver) = mpsc::channel();
+        let mut state = app.world_mut().resource_mut::<GeyserState>();
+        state.exported_frames = Some(Mutex::new(frame_receiver));
+        state.render_errors = Some(Mutex::new(error_receiver));
+        // Both worlds share the counters
+        let diagnostics = app.world().resource::<GeyserDiagnostics>().clone();
+
+        #[cfg(all(feature = "ipc", target_os = "linux"))]
+        let mut stream_device = None;
         if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+            let mut render_state = GeyserRenderState {
+                frame_sender: Some(frame_sender),
+                error_sender: Some(error_sender),
+                ..Default::default()
+            };
+
+            // Imports must land on the same VkDevice Bevy renders with
+            if let Some(render_device) = render_app.world().get_resource::<RenderDevice>() {
+                match wgpu_bridge::manager_from_wgpu_device(render_device.wgpu_device()) {
+                    Ok(manager) => render_state.manager = Some(Arc::new(manager)),
+                    Err(e) => warn!("Geyser texture import unavailable on Bevy's render device: {}", e),
+                }
+            }
+            #[cfg(all(feature = "ipc", target_os = "linux"))]
+            {
+                stream_device = render_state.manager.as_ref().map(|manager| manager.device_identity());
+            }
+
             render_app
-                .init_resource::<GeyserRenderState>()
+                .insert_resource(render_state)
+                .insert_resource(diagnostics)
+                .add_systems(ExtractSchedule, extract_geyser_textures)
                 .add_systems(
-                    bevy::render::Render,
-                    extract_geyser_textures.in_set(bevy::render::RenderSet::ExtractCommands),
+                    Render,
+                    (release_expired_textures, (import_geyser_textures, export_geyser_textures))
+                        .chain()
+                        .in_set(RenderSystems::PrepareAssets)
+                        .after(prepare_assets::<GpuImage>),
+                )
+                .add_systems(Render, (acquire_exported_frames, acquire_geyser_textures).in_set(RenderSystems::Prepare))
+                .add_systems(
+                    Render,
+                    (signal_exported_frames, release_geyser_textures).in_set(RenderSystems::Cleanup),
                 );
         }
+        #[cfg(all(feature = "ipc", target_os = "linux"))]
+        {
+            app.world_mut().resource_mut::<GeyserStreamEndpoints>().device = stream_device;
+        }
     }
 }
 
@@ -74,20 +160,158 @@ impl Plugin for GeyserPlugin {
 pub struct GeyserState {
     /// Mapping from entity to shared texture handle
     pub shared_textures: std::collections::HashMap<Entity, SharedTextureData>,
+    /// Bevy images being exported, keyed by asset id
+    pub exported_textures: std::collections::HashMap<AssetId<Image>, ExportedTextureData>,
+    // Receives a `GeyserFrameExported` from the render world after each frame
+    exported_frames: Option<Mutex<mpsc::Receiver<GeyserFrameExported>>>,
+    // Receives import/export failures from the render world
+    render_errors: Option<Mutex<mpsc::Receiver<GeyserTextureError>>>,
+}
+
+/// Number of shared textures alive on Bevy's render device, available in both the
+/// main and render worlds. A texture stops counting once wgpu has dropped it and its
+/// memory has been released through the render world's manager.
+#[derive(Resource, Clone, Default)]
+pub struct GeyserDiagnostics {
+    imported: Arc<AtomicUsize>,
+    exported: Arc<AtomicUsize>,
+}
+
+impl GeyserDiagnostics {
+    /// Imported textures still alive
+    pub fn live_imports(&self) -> usize {
+        self.imported.load(Ordering::Relaxed)
+    }
+
+    /// Exported textures still alive
+    pub fn live_exports(&self) -> usize {
+        self.exported.load(Ordering::Relaxed)
+    }
+
+    /// All shared textures still alive
+    pub fn live_textures(&self) -> usize {
+        self.live_imports() + self.live_exports()
+    }
 }
 
 /// Data for a shared texture
 pub struct SharedTextureData {
     pub api_handle: ApiTextureHandle,
+    pub sync: SyncPrimitives,
     pub descriptor: TextureDescriptor,
     pub image_handle: Handle<Image>,
 }
 
+/// Data for a Bevy image exported through Geyser
+pub struct ExportedTextureData {
+    pub image_handle: Handle<Image>,
+    pub descriptor: TextureDescriptor,
+    pub source_entity: Option<Entity>,
+}
+
 /// Render-world state for Geyser
 #[derive(Resource, Default, ExtractResource, Clone)]
 pub struct GeyserRenderState {
+    /// Manager sharing Bevy's Vulkan device; `None` if the device lacks the
+    /// external memory extensions
     #[cfg(feature = "vulkan")]
     pub manager: Option<Arc<VulkanTextureShareManager>>,
+    /// Images whose import or export has been queued or attempted
+    requested: HashSet<AssetId<Image>>,
+    /// Images no longer registered in the main world, waiting for `release_expired_textures`
+    expired: Vec<AssetId<Image>>,
+    /// Imports extracted this frame, waiting for `import_geyser_textures`
+    pending_imports: Vec<PendingImport>,
+    /// Exports extracted this frame, waiting for `export_geyser_textures`
+    pending_exports: Vec<PendingExport>,
+    /// GPU images backed by shared memory, keyed by the `Image` they replace
+    shared_images: HashMap<AssetId<Image>, GpuImage>,
+    /// Exported images whose ready semaphore is signalled after every frame
+    exported: HashMap<AssetId<Image>, ExportedFrameState>,
+    /// Imported images with a timeline semaphore usable by `GeyserSync`
+    imported_sync: HashMap<AssetId<Image>, ImportedSyncState>,
+    /// `GeyserSync` values extracted this frame
+    frame_sync: HashMap<AssetId<Image>, GeyserSync>,
+    frame_sender: Option<mpsc::Sender<GeyserFrameExported>>,
+    error_sender: Option<mpsc::Sender<GeyserTextureError>>,
+}
+
+impl GeyserRenderState {
+    // Logs a render-world failure and reports it as a main-world `GeyserTextureError`
+    fn report_error(&self, entity: Option<Entity>, context: &str, error: GeyserError) {
+        error!("{}: {}", context, error);
+        if let Some(sender) = &self.error_sender {
+            let _ = sender.send(GeyserTextureError { entity, error });
+        }
+    }
+}
+
+#[derive(Clone)]
+struct PendingImport {
+    image: AssetId<Image>,
+    entity: Entity,
+    api_handle: ApiTextureHandle,
+    sync: SyncPrimitives,
+    descriptor: TextureDescriptor,
+}
+
+#[derive(Clone)]
+struct PendingExport {
+    image: AssetId<Image>,
+    descriptor: TextureDescriptor,
+    source_entity: Option<Entity>,
+}
+
+#[derive(Clone)]
+struct ExportedFrameState {
+    // Both owned by the wgpu texture in `shared_images`
+    vk_image: ash::vk::Image,
+    ready_semaphore: ash::vk::Semaphore,
+    package: TextureSharePackage,
+    descriptor: TextureDescriptor,
+    source_entity: Option<Entity>,
+    frame: u64,
+}
+
+// Drop guard handed to wgpu with each shared image. wgpu drops it once the texture is
+// no longer in use, including when the render world is torn down on shutdown.
+struct SharedTextureGuard {
+    texture: Option<VulkanSharedTexture>,
+    handle: ApiTextureHandle,
+    manager: Arc<VulkanTextureShareManager>,
+    live: Arc<AtomicUsize>,
+}
+
+impl SharedTextureGuard {
+    fn new(
+        texture: VulkanSharedTexture,
+        handle: ApiTextureHandle,
+        manager: Arc<VulkanTextureShareManager>,
+        live: &Arc<AtomicUsize>,
+    ) -> Self {
+        live.fetch_add(1, Ordering::Relaxed);
+        Self { texture: Some(texture), handle, manager, live: live.clone() }
+    }
+}
+
+impl Drop for SharedTextureGuard {
+    fn drop(&mut self) {
+        if let Some(texture) = self.texture.take() {
+            if let Err(e) = self.manager.destroy_texture(texture, self.handle.clone()) {
+                error!("Failed to release Geyser texture: {}", e);
+            }
+        }
+        self.live.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[derive(Clone)]
+struct ImportedSyncState {
+    // Owned by the wgpu texture in `shared_images`
+    semaphore: ash::vk::Semaphore,
+    // Last values waited on and signalled, so unchanged values are not resubmitted
+    acquired: u64,
+    released: u64,
 }
 
 /// Component to mark an entity as having a Geyser-managed texture
@@ -96,28 +320,232 @@ pub struct GeyserSharedTexture {
     pub api_handle: ApiTextureHandle,
 }
 
-/// Event to request importing a Geyser texture into Bevy
-#[derive(Event)]
+/// Shows the texture streamed to `endpoint` on this entity. The plugin imports each
+/// texture the producer sends, re-importing when it is replaced (e.g. on resize), and
+/// keeps the entity's `GeyserStreamImage` (and `Sprite`, if it has one) pointing at
+/// the current one. Frame messages update the entity's `GeyserSync`.
+///
+/// Only one entity should read from a given endpoint.
+#[derive(Component, Clone, Debug)]
+pub struct GeyserStreamSource {
+    pub endpoint: String,
+}
+
+/// Image currently showing a `GeyserStreamSource`'s texture, maintained by the plugin
+#[derive(Component, Clone, Debug, PartialEq)]
+pub struct GeyserStreamImage(pub Handle<Image>);
+
+/// Message from a stream producer, delivered through a `GeyserStreamEndpoints` endpoint
+#[derive(Debug, Clone)]
+pub enum GeyserStreamMessage {
+    /// A newly exported texture replacing the previous one, e.g. after a resize
+    Texture {
+        package: TextureSharePackage,
+        descriptor: TextureDescriptor,
+    },
+    /// A frame of the most recent `Texture` is ready once its timeline semaphore
+    /// reaches `acquire`; `release` is signalled after Bevy has rendered with it, and 0
+    /// signals nothing. Frames of a replaced texture must not be sent after its successor.
+    Frame { acquire: u64, release: u64 },
+}
+
+/// Named endpoints feeding `GeyserStreamSource` entities.
+///
+/// With the `ipc` feature, a source whose endpoint has not been opened is connected
+/// to the `ipc::ProducerSession` listening on the Unix socket at that path (see
+/// `connect`). Otherwise the app pushes messages into the sender returned by `open`,
+/// for example from its own transport.
+#[derive(Resource, Default)]
+pub struct GeyserStreamEndpoints {
+    endpoints: std::collections::HashMap<String, StreamEndpoint>,
+    // Identity of Bevy's render device, known once it can import textures
+    #[cfg(all(feature = "ipc", target_os = "linux"))]
+    device: Option<crate::common::DeviceIdentity>,
+}
+
+struct StreamEndpoint {
+    receiver: Mutex<mpsc::Receiver<GeyserStreamMessage>>,
+    // Dropped when the endpoint is closed or reopened, which stops its session thread
+    _open: Arc<()>,
+}
+
+impl GeyserStreamEndpoints {
+    /// Opens `endpoint` and returns the sender its producer messages are pushed into.
+    /// Reopening an endpoint disconnects the previous sender.
+    pub fn open(&mut self, endpoint: impl Into<String>) -> mpsc::Sender<GeyserStreamMessage> {
+        self.open_endpoint(endpoint.into()).0
+    }
+
+    fn open_endpoint(&mut self, endpoint: String) -> (mpsc::Sender<GeyserStreamMessage>, std::sync::Weak<()>) {
+        let (sender, receiver) = mpsc::channel();
+        let open = Arc::new(());
+        let weak = Arc::downgrade(&open);
+        self.endpoints.insert(endpoint, StreamEndpoint { receiver: Mutex::new(receiver), _open: open });
+        (sender, weak)
+    }
+
+    /// Opens `endpoint` and connects it to the producer session listening on the Unix
+    /// socket at that path. A background thread forwards each texture the producer
+    /// shares, including replacements after a resize, and each finished frame, whose
+    /// number is waited on as the texture's timeline value. It reconnects whenever the
+    /// producer goes away, until the endpoint is closed.
+    ///
+    /// Fails with `OperationNotSupported` until Bevy's render device can import textures.
+    #[cfg(all(feature = "ipc", target_os = "linux"))]
+    pub fn connect(&mut self, endpoint: impl Into<String>) -> Result<(), GeyserError> {
+        let device = self.device.ok_or(GeyserError::OperationNotSupported)?;
+        let endpoint = endpoint.into();
+        let (sender, open) = self.open_endpoint(endpoint.clone());
+        let config = crate::ipc::SessionConfig::new(device, STREAM_FORMATS.to_vec());
+        std::thread::Builder::new()
+            .name(format!("geyser-stream {}", endpoint))
+            .spawn(move || run_stream_session(&endpoint, config, sender, open))
+            .map_err(|e| GeyserError::IpcError(format!("Failed to start stream thread: {}", e)))?;
+        Ok(())
+    }
+
+    /// Closes `endpoint`; entities reading from it keep showing the last texture
+    pub fn close(&mut self, endpoint: &str) {
+        self.endpoints.remove(endpoint);
+    }
+}
+
+// Color formats a stream may be negotiated in; all of them can be wrapped by Bevy
+#[cfg(all(feature = "ipc", target_os = "linux"))]
+const STREAM_FORMATS: [crate::common::TextureFormat; 6] = [
+    crate::common::TextureFormat::Bgra8Unorm,
+    crate::common::TextureFormat::Rgba8Unorm,
+    crate::common::TextureFormat::Bgra8Srgb,
+    crate::common::TextureFormat::Rgba8Srgb,
+    crate::common::TextureFormat::Rgba16Float,
+    crate::common::TextureFormat::Rgba32Float,
+];
+
+// Body of the thread started by `GeyserStreamEndpoints::connect`. Runs consumer
+// sessions against `path` until the endpoint behind `open` is closed.
+#[cfg(all(feature = "ipc", target_os = "linux"))]
+fn run_stream_session(
+    path: &str,
+    config: crate::ipc::SessionConfig,
+    sender: mpsc::Sender<GeyserStreamMessage>,
+    open: std::sync::Weak<()>,
+) {
+    use crate::ipc::{ConsumerSession, SessionEvent, UnixTransport};
+    use std::time::Duration;
+
+    const RETRY_INTERVAL: Duration = Duration::from_millis(500);
+    // Bounds how long a closed endpoint keeps its thread alive
+    const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+    while open.strong_count() > 0 {
+        let session = UnixTransport::connect(path).and_then(|transport| ConsumerSession::connect(transport, config.clone()));
+        let mut session = match session {
+            Ok(session) => session,
+            Err(e) => {
+                debug!("Geyser stream {} is not available: {}", path, e);
+                std::thread::sleep(RETRY_INTERVAL);
+                continue;
+            }
+        };
+
+        // Generation of the last texture forwarded; generations restart with each session
+        let mut generation = None;
+        while open.strong_count() > 0 {
+            let forward = |package: TextureSharePackage, descriptor: &TextureDescriptor| {
+                sender
+                    .send(GeyserStreamMessage::Texture { package, descriptor: descriptor.clone() })
+                    .map_err(|e| {
+                        if let GeyserStreamMessage::Texture { package, .. } = e.0 {
+                            crate::ipc::wire::close_package_fds(&package);
+                        }
+                        GeyserError::IpcError("Stream endpoint was closed".to_string())
+                    })
+            };
+            match session.poll_forwarding(POLL_INTERVAL, forward) {
+                Ok(Some(SessionEvent::TextureChanged { generation: changed, .. })) => generation = Some(changed),
+                // A frame rendered into a texture that was replaced since must not be
+                // waited on with the new texture's semaphore
+                Ok(Some(SessionEvent::Frame { generation: rendered, frame, .. })) if Some(rendered) == generation => {
+                    // The protocol has no per-frame release: producers only reuse memory
+                    // once a new generation was imported, so Bevy has nothing to signal
+                    let _ = sender.send(GeyserStreamMessage::Frame { acquire: frame, release: 0 });
+                }
+                Ok(Some(SessionEvent::Closed)) => break,
+                Ok(_) => {}
+                Err(e) => {
+                    warn!("Geyser stream {} disconnected: {}", path, e);
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Message to request importing a Geyser texture into Bevy
+#[derive(Message)]
 pub struct ImportGeyserTexture {
     pub api_handle: ApiTextureHandle,
     pub descriptor: TextureDescriptor,
+    /// Semaphore and fence exported alongside the texture; a timeline semaphore
+    /// enables `GeyserSync` on the target entity
+    pub sync: SyncPrimitives,
     /// Optional entity to attach the texture to
     pub target_entity: Option<Entity>,
 }
 
-/// Event to request exporting a Bevy texture via Geyser
-#[derive(Event)]
+/// Per-frame synchronization for an imported texture, placed on the entity that
+/// holds its `GeyserSharedTexture`.
+///
+/// Each frame, Bevy's queue waits for the texture's timeline semaphore to reach
+/// `acquire` before any of the frame's work and signals `release` once that work
+/// has completed. Update the values as frames are handed over; values that have not
+/// increased since the previous frame are skipped, so 0 disables either side.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GeyserSync {
+    pub acquire: u64,
+    pub release: u64,
+}
+
+/// Message to request exporting a Bevy texture via Geyser.
+/// The image is reallocated in exportable memory; it should be used as a camera
+/// render target with `TextureUsages::RENDER_ATTACHMENT`.
+#[derive(Message)]
 pub struct ExportBevyTexture {
     pub image_handle: Handle<Image>,
     /// Optional entity to track the exported texture
     pub source_entity: Option<Entity>,
 }
 
+/// Sent when an import or export request is rejected, e.g. for a texture format
+/// that cannot be shared with Bevy's renderer, or fails in the render world.
+#[derive(Message)]
+pub struct GeyserTextureError {
+    /// Target entity of the import, or source entity of the export
+    pub entity: Option<Entity>,
+    pub error: GeyserError,
+}
+
+/// Sent after every frame rendered into an exported image. Consumers import
+/// `package` once and wait for its semaphore to reach `frame` before reading.
+/// The image is released to `VK_QUEUE_FAMILY_EXTERNAL` in `SHARED_IMAGE_LAYOUT`, and
+/// taken back before Bevy renders the next frame into it.
+#[derive(Message, Clone)]
+pub struct GeyserFrameExported {
+    pub image: AssetId<Image>,
+    pub source_entity: Option<Entity>,
+    /// Texture memory and ready semaphore/fence handles
+    pub package: TextureSharePackage,
+    pub descriptor: TextureDescriptor,
+    /// Timeline value the ready semaphore reaches once this frame has rendered
+    pub frame: u64,
+}
+
 /// System to process texture import/export requests
 fn process_shared_texture_events(
     mut state: ResMut<GeyserState>,
-    mut import_events: EventReader<ImportGeyserTexture>,
-    mut export_events: EventReader<ExportBevyTexture>,
+    mut import_events: MessageReader<ImportGeyserTexture>,
+    mut export_events: MessageReader<ExportBevyTexture>,
+    mut error_events: MessageWriter<GeyserTextureError>,
     mut commands: Commands,
     mut images: ResMut<Assets<Image>>,
 ) {
@@ -125,29 +553,29 @@ fn process_shared_texture_events(
     for event in import_events.read() {
         info!("Processing Geyser texture import request");
         
-        // For now, create a placeholder Bevy image
-        // TODO: Use wgpu-hal to import the actual Vulkan texture
-        let size = bevy::render::render_resource::Extent3d {
+        // The asset is a placeholder; the render world swaps its `GpuImage` for one
+        // backed by the imported memory (see `import_geyser_textures`)
+        let size = Extent3d {
             width: event.descriptor.width,
             height: event.descriptor.height,
             depth_or_array_layers: 1,
         };
         
-        let format = match event.descriptor.format {
-            crate::common::TextureFormat::Rgba8Unorm => {
-                bevy::render::render_resource::TextureFormat::Rgba8Unorm
-            }
-            _ => {
-                warn!("Unsupported texture format, defaulting to Rgba8Unorm");
-                bevy::render::render_resource::TextureFormat::Rgba8Unorm
+        let format = match to_bevy_format(event.descriptor.format) {
+            Ok(format) => format,
+            Err(error) => {
+                warn!("Rejected Geyser texture import: {}", error);
+                error_events.write(GeyserTextureError { entity: event.target_entity, error });
+                continue;
             }
         };
         
         let mut image = Image::default();
         image.texture_descriptor.size = size;
-        image.texture_descriptor.dimension = bevy::render::render_resource::TextureDimension::D2;
+        image.texture_descriptor.dimension = TextureDimension::D2;
         image.texture_descriptor.format = format;
-        image.data = vec![0; (size.width * size.height * 4) as usize];
+        // The imported memory holds the contents, so no CPU-side copy is kept
+        image.data = None;
         
         let image_handle = images.add(image);
         
@@ -162,6 +590,7 @@ fn process_shared_texture_events(
             entity,
             SharedTextureData {
                 api_handle: event.api_handle.clone(),
+                sync: event.sync.clone(),
                 descriptor: event.descriptor.clone(),
                 image_handle,
             },
@@ -173,31 +602,573 @@ fn process_shared_texture_events(
     // Process export requests
     for event in export_events.read() {
         info!("Processing Bevy texture export request");
-        // TODO: Implement export from Bevy Image to Geyser handle
-        warn!("Texture export not yet implemented");
+
+        let Some(image) = images.get(&event.image_handle) else {
+            warn!("Cannot export Bevy texture: image asset not loaded");
+            continue;
+        };
+        let format = match from_bevy_format(image.texture_descriptor.format) {
+            Ok(format) => format,
+            Err(error) => {
+                warn!("Rejected Bevy texture export: {}", error);
+                error_events.write(GeyserTextureError { entity: event.source_entity, error });
+                continue;
+            }
+        };
+
+        let descriptor = TextureDescriptor {
+            width: image.width(),
+            height: image.height(),
+            format,
+            usage: from_bevy_usage(image.texture_descriptor.usage),
+            label: image.texture_descriptor.label.map(str::to_string),
+            tiling: TextureTiling::Optimal,
+            memory_location: TextureMemoryLocation::GpuOnly,
+        };
+        state.exported_textures.insert(
+            event.image_handle.id(),
+            ExportedTextureData {
+                image_handle: event.image_handle.clone(),
+                descriptor,
+                source_entity: event.source_entity,
+            },
+        );
+    }
+}
+
+// Closes the descriptors of a streamed package that will never be imported
+fn discard_stream_package(package: &TextureSharePackage) {
+    #[cfg(all(feature = "ipc", target_os = "linux"))]
+    crate::ipc::wire::close_package_fds(package);
+    #[cfg(not(all(feature = "ipc", target_os = "linux")))]
+    let _ = package;
+}
+
+/// System to connect stream sources whose endpoint the app has not opened itself
+#[cfg(all(feature = "ipc", target_os = "linux"))]
+fn connect_stream_sources(
+    mut endpoints: ResMut<GeyserStreamEndpoints>,
+    sources: Query<&GeyserStreamSource, Added<GeyserStreamSource>>,
+) {
+    if endpoints.device.is_none() {
+        return;
+    }
+    for source in &sources {
+        if endpoints.endpoints.contains_key(&source.endpoint) {
+            continue;
+        }
+        if let Err(e) = endpoints.connect(source.endpoint.clone()) {
+            error!("Failed to connect Geyser stream {}: {}", source.endpoint, e);
+        }
     }
 }
 
-/// System to clean up expired shared textures
+/// System to turn stream messages into imports and `GeyserSync` updates
+fn receive_stream_messages(
+    endpoints: Res<GeyserStreamEndpoints>,
+    mut state: ResMut<GeyserState>,
+    mut images: ResMut<Assets<Image>>,
+    mut import_events: MessageWriter<ImportGeyserTexture>,
+    mut commands: Commands,
+    sources: Query<(Entity, &GeyserStreamSource)>,
+) {
+    for (entity, source) in &sources {
+        let Some(endpoint) = endpoints.endpoints.get(&source.endpoint) else {
+            continue;
+        };
+        // Only the newest texture and frame matter if several arrived since last frame
+        let mut texture = None;
+        let mut frame = None;
+        for message in endpoint.receiver.lock().unwrap().try_iter() {
+            match message {
+                GeyserStreamMessage::Texture { package, descriptor } => {
+                    // A texture replaced before it was imported is never used
+                    if let Some((replaced, _)) = texture.replace((package, descriptor)) {
+                        discard_stream_package(&replaced);
+                    }
+                    // Frames sent before it were rendered into the texture it replaces
+                    frame = None;
+                }
+                GeyserStreamMessage::Frame { acquire, release } => frame = Some(GeyserSync { acquire, release }),
+            }
+        }
+
+        if let Some((package, descriptor)) = texture {
+            // The previous import is released once it is no longer registered
+            if let Some(previous) = state.shared_textures.remove(&entity) {
+                images.remove(&previous.image_handle);
+            }
+            import_events.write(ImportGeyserTexture {
+                api_handle: package.texture,
+                descriptor,
+                sync: package.sync,
+                target_entity: Some(entity),
+            });
+        }
+        if let Some(sync) = frame {
+            commands.entity(entity).insert(sync);
+        }
+    }
+}
+
+/// System to point each stream entity's image at its current import
+fn update_stream_images(
+    state: Res<GeyserState>,
+    mut commands: Commands,
+    sources: Query<(Entity, Option<&GeyserStreamImage>), With<GeyserStreamSource>>,
+    mut sprites: Query<&mut Sprite>,
+) {
+    for (entity, image) in &sources {
+        let Some(data) = state.shared_textures.get(&entity) else {
+            continue;
+        };
+        if image.is_some_and(|image| image.0 == data.image_handle) {
+            continue;
+        }
+        commands.entity(entity).insert(GeyserStreamImage(data.image_handle.clone()));
+        if let Ok(mut sprite) = sprites.get_mut(entity) {
+            sprite.image = data.image_handle.clone();
+        }
+    }
+}
+
+/// System to forward frames exported by the render world as messages
+fn emit_exported_frames(
+    state: Res<GeyserState>,
+    mut exported_events: MessageWriter<GeyserFrameExported>,
+) {
+    if let Some(receiver) = &state.exported_frames {
+        let receiver = receiver.lock().unwrap();
+        exported_events.write_batch(receiver.try_iter());
+    }
+}
+
+/// System to forward import/export failures from the render world as messages
+fn emit_render_errors(
+    state: Res<GeyserState>,
+    mut error_events: MessageWriter<GeyserTextureError>,
+) {
+    if let Some(receiver) = &state.render_errors {
+        let receiver = receiver.lock().unwrap();
+        error_events.write_batch(receiver.try_iter());
+    }
+}
+
+/// System to unregister textures whose entity was despawned or whose image was
+/// removed; the render world then releases them (see `release_expired_textures`)
 fn cleanup_expired_textures(
     mut state: ResMut<GeyserState>,
+    mut image_events: MessageReader<AssetEvent<Image>>,
+    mut images: ResMut<Assets<Image>>,
     query: Query<Entity, With<GeyserSharedTexture>>,
+    entities: &Entities,
 ) {
-    // Remove entries for despawned entities
-    state.shared_textures.retain(|entity, _| query.contains(*entity));
+    let removed: HashSet<AssetId<Image>> = image_events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Removed { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+
+    state.shared_textures.retain(|entity, data| {
+        if !query.contains(*entity) {
+            // The placeholder only exists for the import
+            images.remove(&data.image_handle);
+            return false;
+        }
+        !removed.contains(&data.image_handle.id())
+    });
+
+    state.exported_textures.retain(|id, data| {
+        if removed.contains(id) {
+            return false;
+        }
+        if data.source_entity.is_some_and(|entity| !entities.contains(entity)) {
+            // Touch the image so Bevy recreates it in regular memory
+            images.get_mut(*id);
+            return false;
+        }
+        true
+    });
 }
 
 /// Extract system to move Geyser state to render world
 fn extract_geyser_textures(
-    _state: Extract<Res<GeyserState>>,
-    _render_state: ResMut<GeyserRenderState>,
+    state: Extract<Res<GeyserState>>,
+    sync_query: Extract<Query<&GeyserSync>>,
+    mut render_state: ResMut<GeyserRenderState>,
 ) {
-    // TODO: Extract texture handles and prepare for rendering
+    render_state.frame_sync.clear();
+
+    // Release images the main world no longer tracks
+    let live: HashSet<AssetId<Image>> = state
+        .shared_textures
+        .values()
+        .map(|data| data.image_handle.id())
+        .chain(state.exported_textures.keys().copied())
+        .collect();
+    let expired: Vec<AssetId<Image>> = render_state
+        .requested
+        .iter()
+        .filter(|image| !live.contains(*image))
+        .copied()
+        .collect();
+    for image in expired {
+        render_state.requested.remove(&image);
+        render_state.expired.push(image);
+    }
+
+    // Queue each newly registered shared texture for import exactly once
+    for (entity, data) in &state.shared_textures {
+        let image = data.image_handle.id();
+        if let Ok(sync) = sync_query.get(*entity) {
+            render_state.frame_sync.insert(image, *sync);
+        }
+        if render_state.requested.insert(image) {
+            render_state.pending_imports.push(PendingImport {
+                image,
+                entity: *entity,
+                api_handle: data.api_handle.clone(),
+                sync: data.sync.clone(),
+                descriptor: data.descriptor.clone(),
+            });
+        }
+    }
+
+    for (image, data) in &state.exported_textures {
+        if render_state.requested.insert(*image) {
+            render_state.pending_exports.push(PendingExport {
+                image: *image,
+                descriptor: data.descriptor.clone(),
+                source_entity: data.source_entity,
+            });
+        }
+    }
+}
+
+/// Drops the GPU images of expired textures. The memory is released once wgpu is done
+/// with them (see `SharedTextureGuard`).
+fn release_expired_textures(
+    mut render_state: ResMut<GeyserRenderState>,
+    mut gpu_images: ResMut<RenderAssets<GpuImage>>,
+) {
+    let expired = std::mem::take(&mut render_state.expired);
+    for image in expired {
+        render_state.imported_sync.remove(&image);
+        render_state.exported.remove(&image);
+        let Some(shared) = render_state.shared_images.remove(&image) else {
+            continue;
+        };
+        // Leave alone a GPU image Bevy has already recreated from the asset
+        if gpu_images.get(image).is_some_and(|gpu_image| gpu_image.texture.id() == shared.texture.id()) {
+            gpu_images.remove(image);
+        }
+    }
+}
+
+/// Imports queued textures and points their `Image` assets at the imported memory
+fn import_geyser_textures(
+    mut render_state: ResMut<GeyserRenderState>,
+    diagnostics: Res<GeyserDiagnostics>,
+    render_device: Res<RenderDevice>,
+    default_sampler: Res<DefaultImageSampler>,
+    mut gpu_images: ResMut<RenderAssets<GpuImage>>,
+) {
+    let pending = std::mem::take(&mut render_state.pending_imports);
+    for import in pending {
+        let Some(manager) = render_state.manager.clone() else {
+            render_state.report_error(
+                Some(import.entity),
+                "Cannot import Geyser texture without a texture share manager",
+                GeyserError::OperationNotSupported,
+            );
+            continue;
+        };
+        let package = TextureSharePackage {
+            texture: import.api_handle.clone(),
+            sync: import.sync.clone(),
+        };
+        let texture = match manager.import_vulkan_texture_with_sync(package, &import.descriptor) {
+            Ok(texture) => texture,
+            Err(e) => {
+                render_state.report_error(Some(import.entity), "Failed to import Geyser texture", e);
+                continue;
+            }
+        };
+        if let (Some(semaphore), true) = (texture.ready_semaphore(), texture.ready_semaphore_is_timeline()) {
+            render_state.imported_sync.insert(
+                import.image,
+                ImportedSyncState { semaphore, acquired: 0, released: 0 },
+            );
+        }
+        let guard = SharedTextureGuard::new(texture, import.api_handle, manager, &diagnostics.imported);
+        match wrap_gpu_image(&render_device, &default_sampler, guard, &import.descriptor) {
+            Ok(gpu_image) => {
+                render_state.shared_images.insert(import.image, gpu_image);
+            }
+            Err(e) => {
+                render_state.imported_sync.remove(&import.image);
+                render_state.report_error(Some(import.entity), "Failed to wrap imported Geyser texture", e);
+            }
+        }
+    }
+
+    // Re-applied every frame since `prepare_assets` replaces the entry whenever the
+    // placeholder asset changes
+    for (id, gpu_image) in &render_state.shared_images {
+        gpu_images.insert(*id, gpu_image.clone());
+    }
+}
+
+/// Reallocates images requested for export in exportable memory
+fn export_geyser_textures(
+    mut render_state: ResMut<GeyserRenderState>,
+    diagnostics: Res<GeyserDiagnostics>,
+    render_device: Res<RenderDevice>,
+    default_sampler: Res<DefaultImageSampler>,
+    mut gpu_images: ResMut<RenderAssets<GpuImage>>,
+) {
+    let pending = std::mem::take(&mut render_state.pending_exports);
+    for export in pending {
+        let Some(manager) = render_state.manager.clone() else {
+            render_state.report_error(
+                export.source_entity,
+                "Cannot export Bevy texture without a texture share manager",
+                GeyserError::OperationNotSupported,
+            );
+            continue;
+        };
+        match export_gpu_image(&manager, &export.descriptor) {
+            Ok((texture, package)) => {
+                let Some(ready_semaphore) = texture.ready_semaphore() else {
+                    render_state.report_error(
+                        export.source_entity,
+                        "Exported Bevy texture has no ready semaphore",
+                        GeyserError::OperationNotSupported,
+                    );
+                    continue;
+                };
+                let vk_image = texture.image();
+                let guard = SharedTextureGuard::new(texture, package.texture.clone(), manager, &diagnostics.exported);
+                let gpu_image = match wrap_gpu_image(&render_device, &default_sampler, guard, &export.descriptor) {
+                    Ok(gpu_image) => gpu_image,
+                    Err(e) => {
+                        render_state.report_error(export.source_entity, "Failed to wrap exported Bevy texture", e);
+                        continue;
+                    }
+                };
+                gpu_images.insert(export.image, gpu_image.clone());
+                render_state.shared_images.insert(export.image, gpu_image);
+                render_state.exported.insert(
+                    export.image,
+                    ExportedFrameState {
+                        vk_image,
+                        ready_semaphore,
+                        package,
+                        descriptor: export.descriptor,
+                        source_entity: export.source_entity,
+                        frame: 0,
+                    },
+                );
+            }
+            Err(e) => render_state.report_error(export.source_entity, "Failed to export Bevy texture", e),
+        }
+    }
+}
+
+// Allocates exportable memory with a timeline ready semaphore and exports both
+fn export_gpu_image(
+    manager: &VulkanTextureShareManager,
+    descriptor: &TextureDescriptor,
+) -> Result<(VulkanSharedTexture, TextureSharePackage), GeyserError> {
+    let texture = manager.create_vulkan_texture_with_sync(descriptor)?;
+    if !texture.ready_semaphore_is_timeline() {
+        // Per-frame values need a timeline semaphore
+        return Err(GeyserError::OperationNotSupported);
+    }
+    let package = manager.export_texture_with_sync(&texture)?;
+    Ok((texture, package))
+}
+
+// Layout wgpu leaves an exported image in after rendering to it, and expects it in when
+// the next frame starts
+const EXPORTED_IMAGE_LAYOUT: ash::vk::ImageLayout = ash::vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL;
+
+/// Takes each exported image back from consumers before this frame renders to it
+fn acquire_exported_frames(
+    render_state: Res<GeyserRenderState>,
+) {
+    let Some(manager) = &render_state.manager else {
+        return;
+    };
+
+    for exported in render_state.exported.values() {
+        // Until the first release the image is still in wgpu's hands, undefined
+        if exported.frame == 0 {
+            continue;
+        }
+        if let Err(e) = manager.queue_acquire_image(exported.vk_image, EXPORTED_IMAGE_LAYOUT) {
+            error!("Failed to acquire exported Bevy texture: {}", e);
+        }
+    }
+}
+
+/// Releases each exported image to consumers and signals its semaphore once this
+/// frame's rendering completes
+fn signal_exported_frames(
+    mut render_state: ResMut<GeyserRenderState>,
+) {
+    let GeyserRenderState { manager, exported, frame_sender, .. } = &mut *render_state;
+    let Some(manager) = manager else {
+        return;
+    };
+
+    for (image, exported) in exported.iter_mut() {
+        // Submitted after Bevy's frame on the same queue, so the signal covers its rendering
+        let released = manager.queue_release_image(
+            exported.vk_image,
+            EXPORTED_IMAGE_LAYOUT,
+            exported.ready_semaphore,
+            exported.frame + 1,
+        );
+        if let Err(e) = released {
+            error!("Failed to signal exported Bevy texture: {}", e);
+            continue;
+        }
+        exported.frame += 1;
+
+        if let Some(sender) = frame_sender {
+            let _ = sender.send(GeyserFrameExported {
+                image: *image,
+                source_entity: exported.source_entity,
+                package: exported.package.clone(),
+                descriptor: exported.descriptor.clone(),
+                frame: exported.frame,
+            });
+        }
+    }
+}
+
+/// Makes this frame's rendering wait for each synced import's acquire value
+fn acquire_geyser_textures(
+    mut render_state: ResMut<GeyserRenderState>,
+) {
+    let GeyserRenderState { manager, imported_sync, frame_sync, .. } = &mut *render_state;
+    let Some(manager) = manager else {
+        return;
+    };
+
+    for (image, sync) in frame_sync.iter() {
+        let Some(state) = imported_sync.get_mut(image) else {
+            warn_once!("GeyserSync needs a texture imported with a timeline semaphore");
+            continue;
+        };
+        if sync.acquire <= state.acquired {
+            continue;
+        }
+        // Submitted on Bevy's queue ahead of the frame, so all of its passes wait. The
+        // manager cannot hold wgpu's queue lock; `Prepare` and `Cleanup` never overlap
+        // the `Render` set, which does Bevy's submits and presents
+        if let Err(e) = manager.queue_wait_semaphore(state.semaphore, sync.acquire) {
+            error!("Failed to wait on imported Geyser texture: {}", e);
+            continue;
+        }
+        state.acquired = sync.acquire;
+    }
+}
+
+/// Signals each synced import's release value once this frame's rendering completes
+fn release_geyser_textures(
+    mut render_state: ResMut<GeyserRenderState>,
+) {
+    let GeyserRenderState { manager, imported_sync, frame_sync, .. } = &mut *render_state;
+    let Some(manager) = manager else {
+        return;
+    };
+
+    for (image, sync) in frame_sync.iter() {
+        let Some(state) = imported_sync.get_mut(image) else {
+            continue;
+        };
+        if sync.release <= state.released {
+            continue;
+        }
+        if let Err(e) = manager.queue_signal_semaphore(state.semaphore, sync.release) {
+            error!("Failed to release imported Geyser texture: {}", e);
+            continue;
+        }
+        state.released = sync.release;
+    }
+}
+
+// Wraps a texture created or imported on Bevy's device as a `GpuImage`
+fn wrap_gpu_image(
+    render_device: &RenderDevice,
+    default_sampler: &DefaultImageSampler,
+    guard: SharedTextureGuard,
+    descriptor: &TextureDescriptor,
+) -> Result<GpuImage, GeyserError> {
+    // Checked before the texture is wrapped, so the guard releases it on failure
+    let format = to_bevy_format(descriptor.format)?;
+
+    let image = guard.texture.as_ref().map(VulkanSharedTexture::image).unwrap_or_default();
+    let texture = unsafe {
+        wgpu_bridge::texture_from_vulkan(render_device.wgpu_device(), image, Box::new(move || drop(guard)), descriptor)
+    };
+    let texture = bevy::render::render_resource::Texture::from(texture);
+    let texture_view = texture.create_view(&TextureViewDescriptor::default());
+
+    Ok(GpuImage {
+        texture,
+        texture_view,
+        texture_format: format,
+        texture_view_format: None,
+        sampler: (**default_sampler).clone(),
+        size: Extent3d {
+            width: descriptor.width,
+            height: descriptor.height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        had_data: false,
+    })
+}
+
+// Geyser format for a Bevy image, rejecting formats that cannot be shared
+fn from_bevy_format(format: bevy::render::render_resource::TextureFormat) -> Result<crate::common::TextureFormat, GeyserError> {
+    let geyser_format = wgpu_bridge::from_wgpu_format(format)?;
+    // Round-trip so formats wgpu cannot wrap are rejected on export as well
+    wgpu_bridge::to_wgpu_shared_format(geyser_format)?;
+    Ok(geyser_format)
+}
+
+// Geyser usages matching a Bevy image's wgpu usages
+fn from_bevy_usage(usage: bevy::render::render_resource::TextureUsages) -> Vec<TextureUsage> {
+    use bevy::render::render_resource::TextureUsages;
+
+    [
+        (TextureUsages::COPY_SRC, TextureUsage::CopySrc),
+        (TextureUsages::COPY_DST, TextureUsage::CopyDst),
+        (TextureUsages::TEXTURE_BINDING, TextureUsage::TextureBinding),
+        (TextureUsages::RENDER_ATTACHMENT, TextureUsage::RenderAttachment),
+        (TextureUsages::STORAGE_BINDING, TextureUsage::StorageBinding),
+    ]
+    .into_iter()
+    .filter(|(flag, _)| usage.contains(*flag))
+    .map(|(_, usage)| usage)
+    .collect()
+}
+
+// Bevy texture format used for both the placeholder and the imported texture
+fn to_bevy_format(format: crate::common::TextureFormat) -> Result<bevy::render::render_resource::TextureFormat, GeyserError> {
+    wgpu_bridge::to_wgpu_shared_format(format)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::common::TextureFormat;
 
     #[test]
     fn plugin_builds() {
@@ -205,4 +1176,245 @@ mod tests {
         app.add_plugins(GeyserPlugin);
         // Plugin should build without errors
     }
+
+    fn test_app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Image>()
+            .add_plugins(GeyserPlugin);
+        app
+    }
+
+    fn test_import(target_entity: Entity, sync: SyncPrimitives) -> ImportGeyserTexture {
+        ImportGeyserTexture {
+            api_handle: ApiTextureHandle::Vulkan(crate::vulkan::VulkanTextureShareHandle {
+                raw_handle: 3,
+                memory_type_index: 0,
+                size: 64 * 64 * 4,
+                handle_type: ash::vk::ExternalMemoryHandleTypeFlags::OPAQUE_FD,
+                dedicated_allocation: true,
+                device_identity: Default::default(),
+                plane_layouts: Vec::new(),
+            }),
+            descriptor: TextureDescriptor {
+                width: 64,
+                height: 64,
+                format: TextureFormat::Rgba8Unorm,
+                usage: vec![TextureUsage::TextureBinding],
+                label: None,
+                tiling: TextureTiling::Optimal,
+                memory_location: TextureMemoryLocation::GpuOnly,
+            },
+            sync,
+            target_entity: Some(target_entity),
+        }
+    }
+
+    #[test]
+    fn import_keeps_sync_primitives() {
+        let mut app = test_app();
+        let entity = app.world_mut().spawn(GeyserSync { acquire: 1, release: 2 }).id();
+        let semaphore = crate::vulkan::VulkanSemaphoreHandle {
+            raw_handle: 7,
+            handle_type: ash::vk::ExternalSemaphoreHandleTypeFlags::OPAQUE_FD,
+            is_timeline: true,
+        };
+        let sync = SyncPrimitives {
+            semaphore: Some(crate::common::SyncHandle::VulkanSemaphore(semaphore)),
+            fence: None,
+        };
+        app.world_mut().write_message(test_import(entity, sync));
+        app.update();
+
+        let state = app.world().resource::<GeyserState>();
+        let data = &state.shared_textures[&entity];
+        assert!(matches!(
+            &data.sync.semaphore,
+            Some(crate::common::SyncHandle::VulkanSemaphore(handle)) if handle.is_timeline
+        ));
+        assert!(app.world().get::<GeyserSharedTexture>(entity).is_some());
+        assert_eq!(app.world().get::<GeyserSync>(entity), Some(&GeyserSync { acquire: 1, release: 2 }));
+    }
+
+    #[test]
+    fn despawn_unregisters_import() {
+        let mut app = test_app();
+        let entity = app.world_mut().spawn_empty().id();
+        app.world_mut().write_message(test_import(entity, SyncPrimitives::default()));
+        app.update();
+
+        let placeholder = app.world().resource::<GeyserState>().shared_textures[&entity].image_handle.id();
+        // The placeholder keeps no CPU-side pixels
+        assert!(app.world().resource::<Assets<Image>>().get(placeholder).unwrap().data.is_none());
+
+        app.world_mut().despawn(entity);
+        app.update();
+
+        assert!(app.world().resource::<GeyserState>().shared_textures.is_empty());
+        assert!(!app.world().resource::<Assets<Image>>().contains(placeholder));
+        // Nothing reached a render device
+        assert_eq!(app.world().resource::<GeyserDiagnostics>().live_textures(), 0);
+    }
+
+    #[test]
+    fn stream_source_reimports_on_resize() {
+        let mut app = test_app();
+        let sender = app.world_mut().resource_mut::<GeyserStreamEndpoints>().open("camera0");
+        let entity = app.world_mut().spawn(GeyserStreamSource { endpoint: "camera0".to_string() }).id();
+
+        let texture = |width| {
+            let import = test_import(entity, SyncPrimitives::default());
+            GeyserStreamMessage::Texture {
+                package: TextureSharePackage { texture: import.api_handle, sync: import.sync },
+                descriptor: TextureDescriptor { width, ..import.descriptor },
+            }
+        };
+        sender.send(texture(64)).unwrap();
+        app.update();
+
+        let first = app.world().get::<GeyserStreamImage>(entity).unwrap().0.clone();
+        assert_eq!(app.world().resource::<Assets<Image>>().get(&first).unwrap().width(), 64);
+
+        // A late frame of the old texture arriving with its replacement is dropped
+        sender.send(GeyserStreamMessage::Frame { acquire: 4, release: 0 }).unwrap();
+        sender.send(texture(128)).unwrap();
+        app.update();
+
+        let second = app.world().get::<GeyserStreamImage>(entity).unwrap().0.clone();
+        assert_ne!(first, second);
+        assert_eq!(app.world().resource::<Assets<Image>>().get(&second).unwrap().width(), 128);
+        assert!(!app.world().resource::<Assets<Image>>().contains(&first));
+        assert_eq!(app.world().get::<GeyserSync>(entity), None);
+
+        sender.send(GeyserStreamMessage::Frame { acquire: 5, release: 6 }).unwrap();
+        app.update();
+        assert_eq!(app.world().get::<GeyserSync>(entity), Some(&GeyserSync { acquire: 5, release: 6 }));
+    }
+
+    #[cfg(all(feature = "ipc", target_os = "linux"))]
+    #[test]
+    fn stream_source_connects_to_ipc_producer() {
+        use crate::ipc::{ProducerSession, SessionConfig, UnixTransport};
+        use std::os::{fd::IntoRawFd, unix::net::UnixListener};
+
+        let path = std::env::temp_dir().join(format!("geyser-bevy-stream-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        let device = crate::common::DeviceIdentity::default();
+
+        let producer = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let config = SessionConfig::new(device, vec![TextureFormat::Bgra8Unorm]);
+            let mut session = ProducerSession::accept(UnixTransport::new(stream), config).unwrap();
+            let file = std::fs::File::open("/dev/null").unwrap();
+            let package = TextureSharePackage {
+                texture: ApiTextureHandle::DmaBuf(crate::common::DmaBufHandle {
+                    fd: file.into_raw_fd(),
+                    fourcc: 0,
+                    modifier: crate::common::DRM_FORMAT_MOD_LINEAR,
+                    offset: 0,
+                    stride: 256,
+                }),
+                sync: SyncPrimitives::default(),
+            };
+            let descriptor = TextureDescriptor {
+                width: 64,
+                height: 64,
+                format: TextureFormat::Bgra8Unorm,
+                usage: vec![TextureUsage::TextureBinding],
+                label: None,
+                tiling: TextureTiling::Linear,
+                memory_location: TextureMemoryLocation::GpuOnly,
+            };
+            session.share(&descriptor, &package).unwrap();
+            session.frame_ready(3).unwrap();
+            // Stay connected until the consumer has seen both messages
+            let _ = session.poll(std::time::Duration::from_secs(2));
+            crate::ipc::wire::close_package_fds(&package);
+        });
+
+        let mut app = test_app();
+        app.world_mut().resource_mut::<GeyserStreamEndpoints>().device = Some(device);
+        let endpoint = path.to_string_lossy().into_owned();
+        let entity = app.world_mut().spawn(GeyserStreamSource { endpoint: endpoint.clone() }).id();
+
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while app.world().get::<GeyserSync>(entity).is_none() && std::time::Instant::now() < deadline {
+            app.update();
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        app.update();
+
+        let image = app.world().get::<GeyserStreamImage>(entity).unwrap().0.clone();
+        assert_eq!(app.world().resource::<Assets<Image>>().get(&image).unwrap().width(), 64);
+        assert_eq!(app.world().get::<GeyserSync>(entity), Some(&GeyserSync { acquire: 3, release: 0 }));
+
+        app.world_mut().resource_mut::<GeyserStreamEndpoints>().close(&endpoint);
+        producer.join().unwrap();
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn render_errors_become_messages() {
+        let mut app = test_app();
+        let (error_sender, error_receiver) = mpsc::channel();
+        app.world_mut().resource_mut::<GeyserState>().render_errors = Some(Mutex::new(error_receiver));
+        let render_state = GeyserRenderState { error_sender: Some(error_sender), ..Default::default() };
+
+        let entity = app.world_mut().spawn_empty().id();
+        render_state.report_error(Some(entity), "Failed to import Geyser texture", GeyserError::InvalidTextureHandle);
+        app.update();
+
+        let messages = app.world().resource::<Messages<GeyserTextureError>>();
+        let errors: Vec<_> = messages.iter_current_update_messages().collect();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].entity, Some(entity));
+        assert!(matches!(errors[0].error, GeyserError::InvalidTextureHandle));
+    }
+
+    #[test]
+    fn export_descriptor_conversions() {
+        use bevy::render::render_resource::{TextureFormat as BevyFormat, TextureUsages};
+
+        assert_eq!(
+            from_bevy_format(BevyFormat::Bgra8UnormSrgb).unwrap(),
+            crate::common::TextureFormat::Bgra8Srgb
+        );
+        assert!(from_bevy_format(BevyFormat::Rgba16Snorm).is_err());
+
+        let usage = from_bevy_usage(TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC);
+        assert_eq!(usage, [TextureUsage::CopySrc, TextureUsage::RenderAttachment]);
+    }
+
+    #[test]
+    fn format_coverage() {
+        let shareable = [
+            TextureFormat::Rgba8Unorm,
+            TextureFormat::Bgra8Unorm,
+            TextureFormat::Rgba8Srgb,
+            TextureFormat::Bgra8Srgb,
+            TextureFormat::R8Unorm,
+            TextureFormat::Rg8Unorm,
+            TextureFormat::R16Float,
+            TextureFormat::Rg16Float,
+            TextureFormat::Rgba16Float,
+            TextureFormat::R16Uint,
+            TextureFormat::R16Sint,
+            TextureFormat::R32Float,
+            TextureFormat::Rg32Float,
+            TextureFormat::Rgba32Float,
+            TextureFormat::R32Uint,
+            TextureFormat::R32Sint,
+            TextureFormat::Depth32Float,
+            TextureFormat::Rg11b10Float,
+        ];
+        for format in shareable {
+            let bevy_format = to_bevy_format(format).unwrap();
+            assert_eq!(from_bevy_format(bevy_format).unwrap(), format);
+        }
+
+        for format in [TextureFormat::Depth24Plus, TextureFormat::Depth24PlusStencil8, TextureFormat::Rgb10a2Unorm] {
+            assert!(matches!(to_bevy_format(format), Err(GeyserError::UnsupportedFormat(_))));
+        }
+    }
 }
@@ -2,6 +2,8 @@
 
 use std::fmt;
 
+use crate::error::{GeyserError, Result};
+
 /// Represents the intended usage of a texture, influencing how it's created and shared.
 /// This is similar to `TextureUsage` in WebGPU.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -61,6 +63,30 @@ impl fmt::Display for TextureFormat {
     }
 }
 
+/// Memory layout of a shareable texture's image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TextureTiling {
+    /// Driver-specific layout; fastest on the GPU, but only meaningful to the same device and driver
+    #[default]
+    Optimal,
+    /// Rows stored one after another, readable by the CPU and by other devices
+    Linear,
+    /// Layout described by a DRM format modifier that every consumer understands
+    DrmModifier(u64),
+}
+
+/// Memory a shareable texture is allocated from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TextureMemoryLocation {
+    /// Device-local memory the CPU cannot access
+    #[default]
+    GpuOnly,
+    /// Host-visible memory written by the CPU and read by the GPU
+    CpuToGpu,
+    /// Host-visible, cached memory written by the GPU and read back by the CPU
+    GpuToCpu,
+}
+
 /// A descriptor for creating a new shareable texture.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct TextureDescriptor {
@@ -69,6 +95,43 @@ pub struct TextureDescriptor {
     pub format: TextureFormat,
     pub usage: Vec<TextureUsage>, // A texture can have multiple usages
     pub label: Option<String>,
+    /// Image tiling. Honored by the Vulkan backend; other backends pick their own layout.
+    pub tiling: TextureTiling,
+    /// Memory location. Honored by the Vulkan backend; other backends pick their own memory.
+    pub memory_location: TextureMemoryLocation,
+}
+
+impl TextureDescriptor {
+    /// Creates a descriptor for an unlabeled texture with optimal tiling in GPU-only memory.
+    pub fn new(width: u32, height: u32, format: TextureFormat, usage: Vec<TextureUsage>) -> Self {
+        Self {
+            width,
+            height,
+            format,
+            usage,
+            label: None,
+            tiling: TextureTiling::default(),
+            memory_location: TextureMemoryLocation::default(),
+        }
+    }
+
+    /// Sets the debug label.
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// Sets the image tiling.
+    pub fn with_tiling(mut self, tiling: TextureTiling) -> Self {
+        self.tiling = tiling;
+        self
+    }
+
+    /// Sets the memory the texture is allocated from.
+    pub fn with_memory_location(mut self, memory_location: TextureMemoryLocation) -> Self {
+        self.memory_location = memory_location;
+        self
+    }
 }
 
 /// Opaque handle for sharing textures between APIs or processes.
@@ -80,11 +143,78 @@ pub enum ApiTextureHandle {
     Vulkan(crate::vulkan::VulkanTextureShareHandle),
     #[cfg(feature = "metal")]
     Metal(crate::metal::MetalTextureShareHandle),
-    // #[cfg(feature = "webgpu")]
-    // WebGpu(crate::webgpu::WebGpuTextureShareHandle),
+    /// Staging copy of a wgpu texture, only importable within the exporting process.
+    /// It cannot be sent through `ipc`.
+    #[cfg(feature = "webgpu")]
+    WebGpu(crate::webgpu::WebGpuTextureShareHandle),
+    DmaBuf(DmaBufHandle),
     // Add more variants for other APIs
 }
 
+/// Linux dma-buf holding a single-plane texture, as exchanged with EGL and other
+/// dma-buf consumers. The file descriptor is owned by whoever holds the handle;
+/// importing it transfers ownership to the importer.
+#[derive(Debug, Clone)]
+pub struct DmaBufHandle {
+    pub fd: i32,
+    /// DRM fourcc code of the pixel format
+    pub fourcc: u32,
+    /// DRM format modifier, or `DRM_FORMAT_MOD_INVALID` for an implicit layout
+    pub modifier: u64,
+    pub offset: u32,
+    pub stride: u32,
+}
+
+/// Modifier for dma-bufs whose layout is implied by the driver
+pub const DRM_FORMAT_MOD_INVALID: u64 = 0x00ff_ffff_ffff_ffff;
+
+/// Modifier for dma-bufs stored row by row, `stride` bytes apart
+pub const DRM_FORMAT_MOD_LINEAR: u64 = 0;
+
+/// Size in bytes of one texel of a color format. Depth formats have no defined
+/// buffer layout and return `None`.
+pub fn bytes_per_texel(format: TextureFormat) -> Option<u32> {
+    match format {
+        TextureFormat::R8Unorm => Some(1),
+        TextureFormat::Rg8Unorm | TextureFormat::R16Float | TextureFormat::R16Uint | TextureFormat::R16Sint => Some(2),
+        TextureFormat::Rgba8Unorm | TextureFormat::Bgra8Unorm | TextureFormat::Rgba8Srgb | TextureFormat::Bgra8Srgb
+        | TextureFormat::Rg16Float | TextureFormat::R32Float | TextureFormat::R32Uint | TextureFormat::R32Sint
+        | TextureFormat::Rgb10a2Unorm | TextureFormat::Rg11b10Float => Some(4),
+        TextureFormat::Rgba16Float | TextureFormat::Rg32Float => Some(8),
+        TextureFormat::Rgba32Float => Some(16),
+        TextureFormat::Depth32Float | TextureFormat::Depth24Plus | TextureFormat::Depth24PlusStencil8 => None,
+    }
+}
+
+/// Convert a Geyser format to the DRM fourcc code of a dma-buf holding it.
+/// sRGB formats are rejected since dma-bufs carry no color space.
+pub fn to_drm_fourcc(format: TextureFormat) -> Result<u32> {
+    match format {
+        TextureFormat::Rgba8Unorm => Ok(fourcc_code(b"AB24")),
+        TextureFormat::Bgra8Unorm => Ok(fourcc_code(b"AR24")),
+        TextureFormat::R8Unorm => Ok(fourcc_code(b"R8  ")),
+        TextureFormat::Rg8Unorm => Ok(fourcc_code(b"GR88")),
+        TextureFormat::Rgba16Float => Ok(fourcc_code(b"AB4H")),
+        TextureFormat::Rgb10a2Unorm => Ok(fourcc_code(b"AR30")),
+        _ => Err(GeyserError::UnsupportedFormat(format!("{} has no DRM fourcc", format))),
+    }
+}
+
+fn fourcc_code(code: &[u8; 4]) -> u32 {
+    u32::from_le_bytes(*code)
+}
+
+/// How an imported texture reaches the exporter's memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SharingStrategy {
+    /// The texture is bound directly to the exported memory
+    #[default]
+    ZeroCopy,
+    /// The exported memory could not be bound to a local image (for example because it
+    /// was allocated on another GPU), so its contents are copied into one
+    Copy,
+}
+
 /// Handle for sharing synchronization primitives between processes.
 /// Used to coordinate GPU access to shared textures.
 #[derive(Debug, Clone)]
@@ -99,7 +229,7 @@ pub enum SyncHandle {
 
 /// Synchronization primitives associated with a shared texture.
 /// Used for coordinating access between multiple processes or contexts.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct SyncPrimitives {
     /// Optional semaphore for signaling when texture is ready
     pub semaphore: Option<SyncHandle>,
@@ -107,12 +237,45 @@ pub struct SyncPrimitives {
     pub fence: Option<SyncHandle>,
 }
 
-impl Default for SyncPrimitives {
-    fn default() -> Self {
-        Self {
-            semaphore: None,
-            fence: None,
+/// A shared texture exported together with its synchronization primitives.
+/// Sending this single package to another process lets the receiver set up both
+/// the memory import and the matching semaphore/fence in one step.
+#[derive(Debug, Clone)]
+pub struct TextureSharePackage {
+    /// Handle to the texture's external memory
+    pub texture: ApiTextureHandle,
+    /// Semaphore and fence guarding access to the texture
+    pub sync: SyncPrimitives,
+}
+
+/// Identifies the physical device and driver that allocated a shared resource.
+/// Opaque memory handles can only be imported on a device with matching UUIDs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct DeviceIdentity {
+    pub device_uuid: [u8; 16],
+    pub driver_uuid: [u8; 16],
+    /// Vendor-specific encoding; informational only
+    pub driver_version: u32,
+}
+
+impl DeviceIdentity {
+    /// Returns true if memory exported from `self` can be imported on `other`.
+    pub fn is_compatible_with(&self, other: &DeviceIdentity) -> bool {
+        self.device_uuid == other.device_uuid && self.driver_uuid == other.driver_uuid
+    }
+}
+
+impl fmt::Display for DeviceIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "device ")?;
+        for byte in self.device_uuid {
+            write!(f, "{:02x}", byte)?;
+        }
+        write!(f, ", driver ")?;
+        for byte in self.driver_uuid {
+            write!(f, "{:02x}", byte)?;
         }
+        write!(f, " (version {:#x})", self.driver_version)
     }
 }
 
@@ -10,6 +10,8 @@ fn test_texture_descriptor_creation() {
         format: TextureFormat::Rgba8Unorm,
         usage: vec![TextureUsage::RenderAttachment, TextureUsage::TextureBinding],
         label: Some("TestTexture".to_string()),
+        tiling: TextureTiling::Optimal,
+        memory_location: TextureMemoryLocation::GpuOnly,
     };
 
     assert_eq!(desc.width, 1920);
@@ -20,6 +22,22 @@ fn test_texture_descriptor_creation() {
     assert!(desc.usage.contains(&TextureUsage::TextureBinding));
 }
 
+#[test]
+fn test_texture_descriptor_new_defaults() {
+    let desc = TextureDescriptor::new(64, 32, TextureFormat::Bgra8Unorm, vec![TextureUsage::CopySrc]);
+    assert_eq!(desc.label, None);
+    assert_eq!(desc.tiling, TextureTiling::Optimal);
+    assert_eq!(desc.memory_location, TextureMemoryLocation::GpuOnly);
+
+    let desc = desc
+        .with_label("Readback")
+        .with_tiling(TextureTiling::Linear)
+        .with_memory_location(TextureMemoryLocation::GpuToCpu);
+    assert_eq!(desc.label.as_deref(), Some("Readback"));
+    assert_eq!(desc.tiling, TextureTiling::Linear);
+    assert_eq!(desc.memory_location, TextureMemoryLocation::GpuToCpu);
+}
+
 #[test]
 fn test_texture_format_equality() {
     assert_eq!(TextureFormat::Rgba8Unorm, TextureFormat::Rgba8Unorm);
@@ -36,7 +54,7 @@ fn test_texture_format_display() {
 
 #[test]
 fn test_texture_usage_flags() {
-    let usages = vec![
+    let usages = [
         TextureUsage::CopySrc,
         TextureUsage::CopyDst,
         TextureUsage::TextureBinding,
@@ -65,6 +83,7 @@ fn test_sync_primitives_with_values() {
         let semaphore = SyncHandle::VulkanSemaphore(VulkanSemaphoreHandle {
             raw_handle: 12345,
             handle_type: vk::ExternalSemaphoreHandleTypeFlags::OPAQUE_WIN32,
+            is_timeline: false,
         });
 
         let fence = SyncHandle::VulkanFence(VulkanFenceHandle {
@@ -90,6 +109,8 @@ fn test_texture_descriptor_clone() {
         format: TextureFormat::R16Float,
         usage: vec![TextureUsage::StorageBinding],
         label: Some("Clone Test".to_string()),
+        tiling: TextureTiling::Optimal,
+        memory_location: TextureMemoryLocation::GpuOnly,
     };
 
     let desc2 = desc1.clone();
@@ -104,7 +125,7 @@ fn test_texture_descriptor_clone() {
 #[test]
 fn test_all_texture_formats_exist() {
     // Ensure all documented formats can be instantiated
-    let formats = vec![
+    let formats = [
         // 8-bit
         TextureFormat::Rgba8Unorm,
         TextureFormat::Bgra8Unorm,
@@ -146,6 +167,8 @@ fn test_texture_descriptor_hash() {
         format: TextureFormat::Rgba8Unorm,
         usage: vec![TextureUsage::TextureBinding],
         label: Some("A".to_string()),
+        tiling: TextureTiling::Optimal,
+        memory_location: TextureMemoryLocation::GpuOnly,
     };
 
     let desc2 = TextureDescriptor {
@@ -154,6 +177,8 @@ fn test_texture_descriptor_hash() {
         format: TextureFormat::Rgba8Unorm,
         usage: vec![TextureUsage::TextureBinding],
         label: Some("B".to_string()),
+        tiling: TextureTiling::Optimal,
+        memory_location: TextureMemoryLocation::GpuOnly,
     };
 
     let mut set = HashSet::new();
@@ -163,3 +188,49 @@ fn test_texture_descriptor_hash() {
     // Even though labels differ, they should hash differently
     assert_eq!(set.len(), 2);
 }
+
+#[test]
+fn test_device_identity_compatibility() {
+    let identity = DeviceIdentity {
+        device_uuid: [1; 16],
+        driver_uuid: [2; 16],
+        driver_version: 100,
+    };
+
+    // Driver version alone does not affect compatibility
+    let newer_driver = DeviceIdentity { driver_version: 101, ..identity };
+    assert!(identity.is_compatible_with(&newer_driver));
+
+    let other_device = DeviceIdentity { device_uuid: [3; 16], ..identity };
+    assert!(!identity.is_compatible_with(&other_device));
+
+    let other_driver = DeviceIdentity { driver_uuid: [4; 16], ..identity };
+    assert!(!identity.is_compatible_with(&other_driver));
+}
+
+#[test]
+fn test_device_identity_display() {
+    let identity = DeviceIdentity {
+        device_uuid: [0x0f; 16],
+        driver_uuid: [0xa0; 16],
+        driver_version: 0x42,
+    };
+
+    let display = identity.to_string();
+    assert!(display.contains(&"0f".repeat(16)));
+    assert!(display.contains(&"a0".repeat(16)));
+    assert!(display.contains("0x42"));
+}
+
+#[test]
+fn test_texture_layout_defaults() {
+    assert_eq!(TextureTiling::default(), TextureTiling::Optimal);
+    assert_eq!(TextureMemoryLocation::default(), TextureMemoryLocation::GpuOnly);
+    assert_ne!(TextureTiling::DrmModifier(DRM_FORMAT_MOD_LINEAR), TextureTiling::Linear);
+}
+
+#[test]
+fn test_to_drm_fourcc() {
+    assert_eq!(to_drm_fourcc(TextureFormat::Rgba8Unorm).unwrap(), 0x3432_4241);
+    assert!(matches!(to_drm_fourcc(TextureFormat::Rgba8Srgb), Err(GeyserError::UnsupportedFormat(_))));
+}
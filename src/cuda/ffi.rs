@@ -0,0 +1,386 @@
+//! The parts of the CUDA driver API used for external memory and semaphore interop,
+//! loaded at runtime, behind a trait the tests replace with a mock.
+
+#![allow(non_camel_case_types)]
+
+use std::ffi::{c_char, c_int, c_uint, c_void, CStr};
+
+use libloading::Library;
+
+use crate::error::{GeyserError, Result};
+
+pub type CUresult = c_int;
+pub type CUdevice = c_int;
+pub type CUdeviceptr = u64;
+pub type CUcontext = *mut c_void;
+pub type CUstream = *mut c_void;
+pub type CUarray = *mut c_void;
+pub type CUmipmappedArray = *mut c_void;
+pub type CUexternalMemory = *mut c_void;
+pub type CUexternalSemaphore = *mut c_void;
+
+pub const CUDA_SUCCESS: CUresult = 0;
+
+// `CUexternalMemoryHandleType`
+pub const CU_EXTERNAL_MEMORY_HANDLE_TYPE_OPAQUE_FD: c_uint = 1;
+pub const CU_EXTERNAL_MEMORY_HANDLE_TYPE_OPAQUE_WIN32: c_uint = 2;
+pub const CUDA_EXTERNAL_MEMORY_DEDICATED: c_uint = 0x1;
+
+// `CUexternalSemaphoreHandleType`
+pub const CU_EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_FD: c_uint = 1;
+pub const CU_EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_WIN32: c_uint = 2;
+pub const CU_EXTERNAL_SEMAPHORE_HANDLE_TYPE_TIMELINE_SEMAPHORE_FD: c_uint = 9;
+pub const CU_EXTERNAL_SEMAPHORE_HANDLE_TYPE_TIMELINE_SEMAPHORE_WIN32: c_uint = 10;
+
+// `CUarray_format`
+pub const CU_AD_FORMAT_UNSIGNED_INT8: c_uint = 0x01;
+pub const CU_AD_FORMAT_UNSIGNED_INT16: c_uint = 0x02;
+pub const CU_AD_FORMAT_UNSIGNED_INT32: c_uint = 0x03;
+pub const CU_AD_FORMAT_SIGNED_INT16: c_uint = 0x09;
+pub const CU_AD_FORMAT_SIGNED_INT32: c_uint = 0x0a;
+pub const CU_AD_FORMAT_HALF: c_uint = 0x10;
+pub const CU_AD_FORMAT_FLOAT: c_uint = 0x20;
+
+pub const CUDA_ARRAY3D_SURFACE_LDST: c_uint = 0x02;
+pub const CUDA_ARRAY3D_COLOR_ATTACHMENT: c_uint = 0x20;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct CUDA_EXTERNAL_WIN32_HANDLE {
+    pub handle: *mut c_void,
+    pub name: *const c_void,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub union CUDA_EXTERNAL_HANDLE {
+    pub fd: c_int,
+    pub win32: CUDA_EXTERNAL_WIN32_HANDLE,
+    pub nv_sci_object: *const c_void,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct CUDA_EXTERNAL_MEMORY_HANDLE_DESC {
+    pub type_: c_uint,
+    pub handle: CUDA_EXTERNAL_HANDLE,
+    pub size: u64,
+    pub flags: c_uint,
+    pub reserved: [c_uint; 16],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CUDA_EXTERNAL_MEMORY_BUFFER_DESC {
+    pub offset: u64,
+    pub size: u64,
+    pub flags: c_uint,
+    pub reserved: [c_uint; 16],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CUDA_ARRAY3D_DESCRIPTOR {
+    pub width: usize,
+    pub height: usize,
+    pub depth: usize,
+    pub format: c_uint,
+    pub num_channels: c_uint,
+    pub flags: c_uint,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CUDA_EXTERNAL_MEMORY_MIPMAPPED_ARRAY_DESC {
+    pub offset: u64,
+    pub array_desc: CUDA_ARRAY3D_DESCRIPTOR,
+    pub num_levels: c_uint,
+    pub reserved: [c_uint; 16],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct CUDA_EXTERNAL_SEMAPHORE_HANDLE_DESC {
+    pub type_: c_uint,
+    pub handle: CUDA_EXTERNAL_HANDLE,
+    pub flags: c_uint,
+    pub reserved: [c_uint; 16],
+}
+
+// The `params` of the signal and wait parameter structs: a fence value followed by
+// members for NvSciSync and keyed mutexes, which Geyser does not use
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CUDA_EXTERNAL_SEMAPHORE_PARAMS {
+    pub fence_value: u64,
+    pub reserved: [u64; 8],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CUDA_EXTERNAL_SEMAPHORE_SIGNAL_PARAMS {
+    pub params: CUDA_EXTERNAL_SEMAPHORE_PARAMS,
+    pub flags: c_uint,
+    pub reserved: [c_uint; 16],
+}
+
+pub type CUDA_EXTERNAL_SEMAPHORE_WAIT_PARAMS = CUDA_EXTERNAL_SEMAPHORE_SIGNAL_PARAMS;
+
+/// Result of a driver call; the error is the failing `CUresult`.
+pub type CuResult<T> = std::result::Result<T, CUresult>;
+
+/// The driver entry points the wrappers call. Implemented by `LibCuda`, and by a
+/// recording mock in the tests.
+pub(crate) trait Driver: Send + Sync {
+    fn init(&self) -> CuResult<()>;
+    fn device_get(&self, ordinal: c_int) -> CuResult<CUdevice>;
+    fn device_get_uuid(&self, device: CUdevice) -> CuResult<[u8; 16]>;
+    fn primary_ctx_retain(&self, device: CUdevice) -> CuResult<CUcontext>;
+    fn primary_ctx_release(&self, device: CUdevice) -> CuResult<()>;
+    fn ctx_push_current(&self, context: CUcontext) -> CuResult<()>;
+    fn ctx_pop_current(&self) -> CuResult<()>;
+    fn import_external_memory(&self, desc: &CUDA_EXTERNAL_MEMORY_HANDLE_DESC) -> CuResult<CUexternalMemory>;
+    fn external_memory_get_mapped_buffer(
+        &self,
+        memory: CUexternalMemory,
+        desc: &CUDA_EXTERNAL_MEMORY_BUFFER_DESC,
+    ) -> CuResult<CUdeviceptr>;
+    fn external_memory_get_mapped_mipmapped_array(
+        &self,
+        memory: CUexternalMemory,
+        desc: &CUDA_EXTERNAL_MEMORY_MIPMAPPED_ARRAY_DESC,
+    ) -> CuResult<CUmipmappedArray>;
+    fn mipmapped_array_get_level(&self, mipmap: CUmipmappedArray, level: c_uint) -> CuResult<CUarray>;
+    fn mipmapped_array_destroy(&self, mipmap: CUmipmappedArray) -> CuResult<()>;
+    fn mem_free(&self, ptr: CUdeviceptr) -> CuResult<()>;
+    fn destroy_external_memory(&self, memory: CUexternalMemory) -> CuResult<()>;
+    fn import_external_semaphore(&self, desc: &CUDA_EXTERNAL_SEMAPHORE_HANDLE_DESC) -> CuResult<CUexternalSemaphore>;
+    fn signal_external_semaphore(
+        &self,
+        semaphore: CUexternalSemaphore,
+        params: &CUDA_EXTERNAL_SEMAPHORE_SIGNAL_PARAMS,
+        stream: CUstream,
+    ) -> CuResult<()>;
+    fn wait_external_semaphore(
+        &self,
+        semaphore: CUexternalSemaphore,
+        params: &CUDA_EXTERNAL_SEMAPHORE_WAIT_PARAMS,
+        stream: CUstream,
+    ) -> CuResult<()>;
+    fn destroy_external_semaphore(&self, semaphore: CUexternalSemaphore) -> CuResult<()>;
+    /// Returns the name of `result`, e.g. `CUDA_ERROR_INVALID_VALUE`.
+    fn error_name(&self, result: CUresult) -> String {
+        format!("CUresult {}", result)
+    }
+}
+
+/// Entry points of the CUDA driver library.
+pub(crate) struct LibCuda {
+    init: unsafe extern "C" fn(flags: c_uint) -> CUresult,
+    device_get: unsafe extern "C" fn(device: *mut CUdevice, ordinal: c_int) -> CUresult,
+    device_get_uuid: unsafe extern "C" fn(uuid: *mut [u8; 16], device: CUdevice) -> CUresult,
+    primary_ctx_retain: unsafe extern "C" fn(context: *mut CUcontext, device: CUdevice) -> CUresult,
+    primary_ctx_release: unsafe extern "C" fn(device: CUdevice) -> CUresult,
+    ctx_push_current: unsafe extern "C" fn(context: CUcontext) -> CUresult,
+    ctx_pop_current: unsafe extern "C" fn(context: *mut CUcontext) -> CUresult,
+    import_external_memory:
+        unsafe extern "C" fn(memory: *mut CUexternalMemory, desc: *const CUDA_EXTERNAL_MEMORY_HANDLE_DESC) -> CUresult,
+    external_memory_get_mapped_buffer: unsafe extern "C" fn(
+        ptr: *mut CUdeviceptr,
+        memory: CUexternalMemory,
+        desc: *const CUDA_EXTERNAL_MEMORY_BUFFER_DESC,
+    ) -> CUresult,
+    external_memory_get_mapped_mipmapped_array: unsafe extern "C" fn(
+        mipmap: *mut CUmipmappedArray,
+        memory: CUexternalMemory,
+        desc: *const CUDA_EXTERNAL_MEMORY_MIPMAPPED_ARRAY_DESC,
+    ) -> CUresult,
+    mipmapped_array_get_level:
+        unsafe extern "C" fn(array: *mut CUarray, mipmap: CUmipmappedArray, level: c_uint) -> CUresult,
+    mipmapped_array_destroy: unsafe extern "C" fn(mipmap: CUmipmappedArray) -> CUresult,
+    mem_free: unsafe extern "C" fn(ptr: CUdeviceptr) -> CUresult,
+    destroy_external_memory: unsafe extern "C" fn(memory: CUexternalMemory) -> CUresult,
+    import_external_semaphore: unsafe extern "C" fn(
+        semaphore: *mut CUexternalSemaphore,
+        desc: *const CUDA_EXTERNAL_SEMAPHORE_HANDLE_DESC,
+    ) -> CUresult,
+    signal_external_semaphores_async: unsafe extern "C" fn(
+        semaphores: *const CUexternalSemaphore,
+        params: *const CUDA_EXTERNAL_SEMAPHORE_SIGNAL_PARAMS,
+        count: c_uint,
+        stream: CUstream,
+    ) -> CUresult,
+    wait_external_semaphores_async: unsafe extern "C" fn(
+        semaphores: *const CUexternalSemaphore,
+        params: *const CUDA_EXTERNAL_SEMAPHORE_WAIT_PARAMS,
+        count: c_uint,
+        stream: CUstream,
+    ) -> CUresult,
+    destroy_external_semaphore: unsafe extern "C" fn(semaphore: CUexternalSemaphore) -> CUresult,
+    get_error_name: unsafe extern "C" fn(result: CUresult, name: *mut *const c_char) -> CUresult,
+    // Keeps the function pointers above valid
+    _library: Library,
+}
+
+// Maps a `CUresult` to `Ok(value)` on success
+fn check<T>(result: CUresult, value: T) -> CuResult<T> {
+    if result == CUDA_SUCCESS {
+        Ok(value)
+    } else {
+        Err(result)
+    }
+}
+
+impl LibCuda {
+    /// Loads the CUDA driver library, which ships with the NVIDIA driver.
+    pub fn load() -> Result<Self> {
+        #[cfg(target_os = "windows")]
+        const LIBRARY: &str = "nvcuda.dll";
+        #[cfg(not(target_os = "windows"))]
+        const LIBRARY: &str = "libcuda.so.1";
+
+        unsafe {
+            let library = Library::new(LIBRARY)
+                .map_err(|e| GeyserError::CudaError(format!("Failed to load {}: {}", LIBRARY, e)))?;
+            macro_rules! symbol {
+                ($name:literal) => {
+                    *library
+                        .get(concat!($name, "\0").as_bytes())
+                        .map_err(|e| GeyserError::CudaError(format!("Missing {}: {}", $name, e)))?
+                };
+            }
+            Ok(Self {
+                init: symbol!("cuInit"),
+                device_get: symbol!("cuDeviceGet"),
+                device_get_uuid: symbol!("cuDeviceGetUuid"),
+                primary_ctx_retain: symbol!("cuDevicePrimaryCtxRetain"),
+                primary_ctx_release: symbol!("cuDevicePrimaryCtxRelease_v2"),
+                ctx_push_current: symbol!("cuCtxPushCurrent_v2"),
+                ctx_pop_current: symbol!("cuCtxPopCurrent_v2"),
+                import_external_memory: symbol!("cuImportExternalMemory"),
+                external_memory_get_mapped_buffer: symbol!("cuExternalMemoryGetMappedBuffer"),
+                external_memory_get_mapped_mipmapped_array: symbol!("cuExternalMemoryGetMappedMipmappedArray"),
+                mipmapped_array_get_level: symbol!("cuMipmappedArrayGetLevel"),
+                mipmapped_array_destroy: symbol!("cuMipmappedArrayDestroy"),
+                mem_free: symbol!("cuMemFree_v2"),
+                destroy_external_memory: symbol!("cuDestroyExternalMemory"),
+                import_external_semaphore: symbol!("cuImportExternalSemaphore"),
+                signal_external_semaphores_async: symbol!("cuSignalExternalSemaphoresAsync"),
+                wait_external_semaphores_async: symbol!("cuWaitExternalSemaphoresAsync"),
+                destroy_external_semaphore: symbol!("cuDestroyExternalSemaphore"),
+                get_error_name: symbol!("cuGetErrorName"),
+                _library: library,
+            })
+        }
+    }
+}
+
+impl Driver for LibCuda {
+    fn init(&self) -> CuResult<()> {
+        check(unsafe { (self.init)(0) }, ())
+    }
+
+    fn device_get(&self, ordinal: c_int) -> CuResult<CUdevice> {
+        let mut device = 0;
+        check(unsafe { (self.device_get)(&mut device, ordinal) }, device)
+    }
+
+    fn device_get_uuid(&self, device: CUdevice) -> CuResult<[u8; 16]> {
+        let mut uuid = [0; 16];
+        check(unsafe { (self.device_get_uuid)(&mut uuid, device) }, uuid)
+    }
+
+    fn primary_ctx_retain(&self, device: CUdevice) -> CuResult<CUcontext> {
+        let mut context = std::ptr::null_mut();
+        check(unsafe { (self.primary_ctx_retain)(&mut context, device) }, context)
+    }
+
+    fn primary_ctx_release(&self, device: CUdevice) -> CuResult<()> {
+        check(unsafe { (self.primary_ctx_release)(device) }, ())
+    }
+
+    fn ctx_push_current(&self, context: CUcontext) -> CuResult<()> {
+        check(unsafe { (self.ctx_push_current)(context) }, ())
+    }
+
+    fn ctx_pop_current(&self) -> CuResult<()> {
+        let mut context = std::ptr::null_mut();
+        check(unsafe { (self.ctx_pop_current)(&mut context) }, ())
+    }
+
+    fn import_external_memory(&self, desc: &CUDA_EXTERNAL_MEMORY_HANDLE_DESC) -> CuResult<CUexternalMemory> {
+        let mut memory = std::ptr::null_mut();
+        check(unsafe { (self.import_external_memory)(&mut memory, desc) }, memory)
+    }
+
+    fn external_memory_get_mapped_buffer(
+        &self,
+        memory: CUexternalMemory,
+        desc: &CUDA_EXTERNAL_MEMORY_BUFFER_DESC,
+    ) -> CuResult<CUdeviceptr> {
+        let mut ptr = 0;
+        check(unsafe { (self.external_memory_get_mapped_buffer)(&mut ptr, memory, desc) }, ptr)
+    }
+
+    fn external_memory_get_mapped_mipmapped_array(
+        &self,
+        memory: CUexternalMemory,
+        desc: &CUDA_EXTERNAL_MEMORY_MIPMAPPED_ARRAY_DESC,
+    ) -> CuResult<CUmipmappedArray> {
+        let mut mipmap = std::ptr::null_mut();
+        check(unsafe { (self.external_memory_get_mapped_mipmapped_array)(&mut mipmap, memory, desc) }, mipmap)
+    }
+
+    fn mipmapped_array_get_level(&self, mipmap: CUmipmappedArray, level: c_uint) -> CuResult<CUarray> {
+        let mut array = std::ptr::null_mut();
+        check(unsafe { (self.mipmapped_array_get_level)(&mut array, mipmap, level) }, array)
+    }
+
+    fn mipmapped_array_destroy(&self, mipmap: CUmipmappedArray) -> CuResult<()> {
+        check(unsafe { (self.mipmapped_array_destroy)(mipmap) }, ())
+    }
+
+    fn mem_free(&self, ptr: CUdeviceptr) -> CuResult<()> {
+        check(unsafe { (self.mem_free)(ptr) }, ())
+    }
+
+    fn destroy_external_memory(&self, memory: CUexternalMemory) -> CuResult<()> {
+        check(unsafe { (self.destroy_external_memory)(memory) }, ())
+    }
+
+    fn import_external_semaphore(&self, desc: &CUDA_EXTERNAL_SEMAPHORE_HANDLE_DESC) -> CuResult<CUexternalSemaphore> {
+        let mut semaphore = std::ptr::null_mut();
+        check(unsafe { (self.import_external_semaphore)(&mut semaphore, desc) }, semaphore)
+    }
+
+    fn signal_external_semaphore(
+        &self,
+        semaphore: CUexternalSemaphore,
+        params: &CUDA_EXTERNAL_SEMAPHORE_SIGNAL_PARAMS,
+        stream: CUstream,
+    ) -> CuResult<()> {
+        check(unsafe { (self.signal_external_semaphores_async)(&semaphore, params, 1, stream) }, ())
+    }
+
+    fn wait_external_semaphore(
+        &self,
+        semaphore: CUexternalSemaphore,
+        params: &CUDA_EXTERNAL_SEMAPHORE_WAIT_PARAMS,
+        stream: CUstream,
+    ) -> CuResult<()> {
+        check(unsafe { (self.wait_external_semaphores_async)(&semaphore, params, 1, stream) }, ())
+    }
+
+    fn destroy_external_semaphore(&self, semaphore: CUexternalSemaphore) -> CuResult<()> {
+        check(unsafe { (self.destroy_external_semaphore)(semaphore) }, ())
+    }
+
+    fn error_name(&self, result: CUresult) -> String {
+        let mut name = std::ptr::null();
+        if unsafe { (self.get_error_name)(result, &mut name) } == CUDA_SUCCESS && !name.is_null() {
+            unsafe { CStr::from_ptr(name) }.to_string_lossy().into_owned()
+        } else {
+            format!("CUresult {}", result)
+        }
+    }
+}
@@ -0,0 +1,526 @@
+//! CUDA interop: importing shared Vulkan textures and semaphores into CUDA.
+//!
+//! `CudaContext` retains the primary context of a CUDA device and imports:
+//! - `VulkanTextureShareHandle`s as `CudaExternalMemory`, through
+//!   `cuImportExternalMemory`. The memory can be mapped as a linear `CudaBuffer`, or
+//!   as a `CudaArray` laid out like the optimally tiled Vulkan image, which kernels
+//!   read through texture or surface objects.
+//! - `VulkanSemaphoreHandle`s as `CudaExternalSemaphore`s, through
+//!   `cuImportExternalSemaphore`, to order CUDA streams against Vulkan queues.
+//!
+//! The CUDA driver library is loaded at runtime, so the feature builds without the
+//! CUDA toolkit; creating a context fails where no NVIDIA driver is installed. The
+//! exporting Vulkan device must be the CUDA device: their device UUIDs are compared.
+
+mod ffi;
+
+pub use ffi::{CUarray, CUdeviceptr, CUstream};
+
+use std::{ffi::c_uint, sync::Arc};
+
+use ash::vk;
+
+use crate::common::{DeviceIdentity, TextureDescriptor, TextureFormat, TextureTiling, TextureUsage};
+use crate::error::{GeyserError, Result};
+use crate::vulkan::{VulkanSemaphoreHandle, VulkanTextureShareHandle};
+use ffi::*;
+
+// A retained primary context, shared with everything imported into it so that it
+// is released last
+struct Context {
+    driver: Arc<dyn Driver>,
+    device: CUdevice,
+    context: CUcontext,
+}
+
+// The primary context may be made current on any thread
+unsafe impl Send for Context {}
+unsafe impl Sync for Context {}
+
+impl Context {
+    fn make_current(&self) -> Result<CurrentGuard<'_>> {
+        self.driver
+            .ctx_push_current(self.context)
+            .map_err(|r| self.error("cuCtxPushCurrent", r))?;
+        Ok(CurrentGuard(&*self.driver))
+    }
+
+    fn error(&self, call: &str, result: CUresult) -> GeyserError {
+        GeyserError::CudaError(format!("{} failed: {}", call, self.driver.error_name(result)))
+    }
+}
+
+impl Drop for Context {
+    fn drop(&mut self) {
+        let _ = self.driver.primary_ctx_release(self.device);
+    }
+}
+
+// Keeps a context current on this thread; popped when dropped
+struct CurrentGuard<'a>(&'a dyn Driver);
+
+impl Drop for CurrentGuard<'_> {
+    fn drop(&mut self) {
+        let _ = self.0.ctx_pop_current();
+    }
+}
+
+/// A CUDA device's primary context. It stays retained until the context and
+/// everything imported into it are dropped.
+pub struct CudaContext {
+    context: Arc<Context>,
+    device_uuid: [u8; 16],
+}
+
+impl CudaContext {
+    /// Loads the CUDA driver and retains the primary context of device `ordinal`.
+    pub fn new(ordinal: u32) -> Result<Self> {
+        Self::with_driver(Arc::new(LibCuda::load()?), ordinal)
+    }
+
+    pub(crate) fn with_driver(driver: Arc<dyn Driver>, ordinal: u32) -> Result<Self> {
+        let error = |call: &str, result| {
+            GeyserError::CudaError(format!("{} failed: {}", call, driver.error_name(result)))
+        };
+        driver.init().map_err(|r| error("cuInit", r))?;
+        let device = driver.device_get(ordinal as i32).map_err(|r| error("cuDeviceGet", r))?;
+        let device_uuid = driver.device_get_uuid(device).map_err(|r| error("cuDeviceGetUuid", r))?;
+        let context = driver
+            .primary_ctx_retain(device)
+            .map_err(|r| error("cuDevicePrimaryCtxRetain", r))?;
+        Ok(Self {
+            context: Arc::new(Context { driver, device, context }),
+            device_uuid,
+        })
+    }
+
+    /// Returns the device UUID, which matches `DeviceIdentity::device_uuid` of a
+    /// Vulkan device on the same GPU.
+    pub fn device_uuid(&self) -> [u8; 16] {
+        self.device_uuid
+    }
+
+    /// Imports the memory of an exported Vulkan texture. An fd handle is consumed: CUDA
+    /// owns it on success, and it is closed on failure. Win32 handles stay owned by the
+    /// caller.
+    pub fn import_memory(&self, handle: VulkanTextureShareHandle) -> Result<CudaExternalMemory> {
+        let result = self.import_memory_handle(&handle);
+        if result.is_err() {
+            close_memory_fd(&handle);
+        }
+        result
+    }
+
+    fn import_memory_handle(&self, handle: &VulkanTextureShareHandle) -> Result<CudaExternalMemory> {
+        if handle.device_identity.device_uuid != self.device_uuid {
+            return Err(GeyserError::IncompatibleDevice {
+                exporter: handle.device_identity,
+                importer: DeviceIdentity {
+                    device_uuid: self.device_uuid,
+                    ..Default::default()
+                },
+            });
+        }
+        let desc = memory_handle_desc(handle)?;
+        let context = &self.context;
+        let _current = context.make_current()?;
+        let memory = context
+            .driver
+            .import_external_memory(&desc)
+            .map_err(|r| context.error("cuImportExternalMemory", r))?;
+        Ok(CudaExternalMemory {
+            context: context.clone(),
+            memory,
+            size: handle.size,
+        })
+    }
+
+    /// Imports the memory of an exported Vulkan texture described by `descriptor` and
+    /// maps it as a `CudaArray`. The texture must use optimal tiling; map linear
+    /// textures with `import_memory` and `CudaExternalMemory::map_buffer` instead. The
+    /// handle is consumed as by `import_memory`.
+    pub fn import_texture(&self, handle: VulkanTextureShareHandle, descriptor: &TextureDescriptor) -> Result<CudaArray> {
+        let desc = match mipmapped_array_desc(descriptor) {
+            Ok(desc) => desc,
+            Err(e) => {
+                close_memory_fd(&handle);
+                return Err(e);
+            }
+        };
+        let memory = self.import_memory(handle)?;
+        let context = &self.context;
+        let _current = context.make_current()?;
+        let mipmap = context
+            .driver
+            .external_memory_get_mapped_mipmapped_array(memory.memory, &desc)
+            .map_err(|r| context.error("cuExternalMemoryGetMappedMipmappedArray", r))?;
+        let array = match context.driver.mipmapped_array_get_level(mipmap, 0) {
+            Ok(array) => array,
+            Err(r) => {
+                let _ = context.driver.mipmapped_array_destroy(mipmap);
+                return Err(context.error("cuMipmappedArrayGetLevel", r));
+            }
+        };
+        Ok(CudaArray {
+            mipmap,
+            array,
+            descriptor: descriptor.clone(),
+            memory,
+        })
+    }
+
+    /// Imports an exported Vulkan semaphore, binary or timeline. An fd handle is
+    /// consumed: CUDA owns it on success, and it is closed on failure. Win32 handles stay
+    /// owned by the caller.
+    pub fn import_semaphore(&self, handle: VulkanSemaphoreHandle) -> Result<CudaExternalSemaphore> {
+        let result = self.import_semaphore_handle(&handle);
+        if result.is_err() {
+            close_semaphore_fd(&handle);
+        }
+        result
+    }
+
+    fn import_semaphore_handle(&self, handle: &VulkanSemaphoreHandle) -> Result<CudaExternalSemaphore> {
+        let desc = semaphore_handle_desc(handle)?;
+        let context = &self.context;
+        let _current = context.make_current()?;
+        let semaphore = context
+            .driver
+            .import_external_semaphore(&desc)
+            .map_err(|r| context.error("cuImportExternalSemaphore", r))?;
+        Ok(CudaExternalSemaphore {
+            context: context.clone(),
+            semaphore,
+            is_timeline: handle.is_timeline,
+        })
+    }
+}
+
+/// Memory of a Vulkan texture imported into CUDA.
+pub struct CudaExternalMemory {
+    context: Arc<Context>,
+    memory: CUexternalMemory,
+    size: u64,
+}
+
+unsafe impl Send for CudaExternalMemory {}
+unsafe impl Sync for CudaExternalMemory {}
+
+impl CudaExternalMemory {
+    /// Returns the size of the imported allocation in bytes.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Maps `size` bytes from `offset` as linear device memory.
+    pub fn map_buffer(&self, offset: u64, size: u64) -> Result<CudaBuffer<'_>> {
+        if offset.checked_add(size).is_none_or(|end| end > self.size) {
+            return Err(GeyserError::Other(format!(
+                "Range {}..{} exceeds the {} imported bytes",
+                offset,
+                offset.saturating_add(size),
+                self.size
+            )));
+        }
+        let desc = CUDA_EXTERNAL_MEMORY_BUFFER_DESC {
+            offset,
+            size,
+            ..Default::default()
+        };
+        let _current = self.context.make_current()?;
+        let ptr = self
+            .context
+            .driver
+            .external_memory_get_mapped_buffer(self.memory, &desc)
+            .map_err(|r| self.context.error("cuExternalMemoryGetMappedBuffer", r))?;
+        Ok(CudaBuffer { memory: self, ptr, size })
+    }
+}
+
+impl Drop for CudaExternalMemory {
+    fn drop(&mut self) {
+        if let Ok(_current) = self.context.make_current() {
+            let _ = self.context.driver.destroy_external_memory(self.memory);
+        }
+    }
+}
+
+/// Imported memory mapped as a linear range of device memory.
+pub struct CudaBuffer<'a> {
+    memory: &'a CudaExternalMemory,
+    ptr: CUdeviceptr,
+    size: u64,
+}
+
+impl CudaBuffer<'_> {
+    /// Returns the device pointer to the start of the range.
+    pub fn device_ptr(&self) -> CUdeviceptr {
+        self.ptr
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+}
+
+impl Drop for CudaBuffer<'_> {
+    fn drop(&mut self) {
+        let context = &self.memory.context;
+        if let Ok(_current) = context.make_current() {
+            let _ = context.driver.mem_free(self.ptr);
+        }
+    }
+}
+
+/// A Vulkan texture imported as a CUDA array. Owns the imported memory.
+pub struct CudaArray {
+    mipmap: CUmipmappedArray,
+    array: CUarray,
+    descriptor: TextureDescriptor,
+    // Dropped after the mipmapped array, which maps it
+    memory: CudaExternalMemory,
+}
+
+unsafe impl Send for CudaArray {}
+unsafe impl Sync for CudaArray {}
+
+impl CudaArray {
+    /// Returns the array of the texture's only mip level, for `cuMemcpy2D` or to
+    /// create texture and surface objects. It is valid while `self` lives.
+    pub fn array(&self) -> CUarray {
+        self.array
+    }
+
+    pub fn descriptor(&self) -> &TextureDescriptor {
+        &self.descriptor
+    }
+}
+
+impl Drop for CudaArray {
+    fn drop(&mut self) {
+        let context = &self.memory.context;
+        if let Ok(_current) = context.make_current() {
+            let _ = context.driver.mipmapped_array_destroy(self.mipmap);
+        }
+    }
+}
+
+/// A Vulkan semaphore imported into CUDA.
+pub struct CudaExternalSemaphore {
+    context: Arc<Context>,
+    semaphore: CUexternalSemaphore,
+    is_timeline: bool,
+}
+
+unsafe impl Send for CudaExternalSemaphore {}
+unsafe impl Sync for CudaExternalSemaphore {}
+
+impl CudaExternalSemaphore {
+    pub fn is_timeline(&self) -> bool {
+        self.is_timeline
+    }
+
+    /// Signals the semaphore once the work queued on `stream` so far is done. A
+    /// timeline semaphore is signaled to `value`; binary semaphores ignore it.
+    pub fn signal(&self, value: u64, stream: CUstream) -> Result<()> {
+        let params = CUDA_EXTERNAL_SEMAPHORE_SIGNAL_PARAMS {
+            params: CUDA_EXTERNAL_SEMAPHORE_PARAMS {
+                fence_value: self.fence_value(value),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let _current = self.context.make_current()?;
+        self.context
+            .driver
+            .signal_external_semaphore(self.semaphore, &params, stream)
+            .map_err(|r| self.context.error("cuSignalExternalSemaphoresAsync", r))
+    }
+
+    /// Makes work queued on `stream` afterwards wait for the semaphore, reaching
+    /// `value` for a timeline semaphore.
+    pub fn wait(&self, value: u64, stream: CUstream) -> Result<()> {
+        let params = CUDA_EXTERNAL_SEMAPHORE_WAIT_PARAMS {
+            params: CUDA_EXTERNAL_SEMAPHORE_PARAMS {
+                fence_value: self.fence_value(value),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let _current = self.context.make_current()?;
+        self.context
+            .driver
+            .wait_external_semaphore(self.semaphore, &params, stream)
+            .map_err(|r| self.context.error("cuWaitExternalSemaphoresAsync", r))
+    }
+
+    fn fence_value(&self, value: u64) -> u64 {
+        if self.is_timeline {
+            value
+        } else {
+            0
+        }
+    }
+
+}
+
+impl Drop for CudaExternalSemaphore {
+    fn drop(&mut self) {
+        if let Ok(_current) = self.context.make_current() {
+            let _ = self.context.driver.destroy_external_semaphore(self.semaphore);
+        }
+    }
+}
+
+// Describes an exported Vulkan allocation to `cuImportExternalMemory`
+fn memory_handle_desc(handle: &VulkanTextureShareHandle) -> Result<CUDA_EXTERNAL_MEMORY_HANDLE_DESC> {
+    let (type_, handle_union) = if handle.handle_type == vk::ExternalMemoryHandleTypeFlags::OPAQUE_FD {
+        (CU_EXTERNAL_MEMORY_HANDLE_TYPE_OPAQUE_FD, CUDA_EXTERNAL_HANDLE { fd: handle.raw_handle as i32 })
+    } else if handle.handle_type == vk::ExternalMemoryHandleTypeFlags::OPAQUE_WIN32 {
+        (CU_EXTERNAL_MEMORY_HANDLE_TYPE_OPAQUE_WIN32, win32_handle(handle.raw_handle))
+    } else {
+        // CUDA cannot import dma-bufs
+        return Err(GeyserError::CudaError(format!(
+            "CUDA cannot import {:?} memory handles",
+            handle.handle_type
+        )));
+    };
+    Ok(CUDA_EXTERNAL_MEMORY_HANDLE_DESC {
+        type_,
+        handle: handle_union,
+        size: handle.size,
+        flags: if handle.dedicated_allocation {
+            CUDA_EXTERNAL_MEMORY_DEDICATED
+        } else {
+            0
+        },
+        reserved: [0; 16],
+    })
+}
+
+// Describes a Vulkan semaphore to `cuImportExternalSemaphore`
+fn semaphore_handle_desc(handle: &VulkanSemaphoreHandle) -> Result<CUDA_EXTERNAL_SEMAPHORE_HANDLE_DESC> {
+    let (type_, handle_union) = match (handle.handle_type, handle.is_timeline) {
+        (vk::ExternalSemaphoreHandleTypeFlags::OPAQUE_FD, false) => {
+            (CU_EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_FD, CUDA_EXTERNAL_HANDLE { fd: handle.raw_handle as i32 })
+        }
+        (vk::ExternalSemaphoreHandleTypeFlags::OPAQUE_FD, true) => (
+            CU_EXTERNAL_SEMAPHORE_HANDLE_TYPE_TIMELINE_SEMAPHORE_FD,
+            CUDA_EXTERNAL_HANDLE { fd: handle.raw_handle as i32 },
+        ),
+        (vk::ExternalSemaphoreHandleTypeFlags::OPAQUE_WIN32, false) => {
+            (CU_EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_WIN32, win32_handle(handle.raw_handle))
+        }
+        (vk::ExternalSemaphoreHandleTypeFlags::OPAQUE_WIN32, true) => {
+            (CU_EXTERNAL_SEMAPHORE_HANDLE_TYPE_TIMELINE_SEMAPHORE_WIN32, win32_handle(handle.raw_handle))
+        }
+        (handle_type, _) => {
+            return Err(GeyserError::CudaError(format!(
+                "CUDA cannot import {:?} semaphore handles",
+                handle_type
+            )))
+        }
+    };
+    Ok(CUDA_EXTERNAL_SEMAPHORE_HANDLE_DESC {
+        type_,
+        handle: handle_union,
+        flags: 0,
+        reserved: [0; 16],
+    })
+}
+
+// Closes an fd memory handle that CUDA did not take over
+fn close_memory_fd(handle: &VulkanTextureShareHandle) {
+    let is_fd = handle.handle_type == vk::ExternalMemoryHandleTypeFlags::OPAQUE_FD
+        || handle.handle_type == vk::ExternalMemoryHandleTypeFlags::DMA_BUF_EXT;
+    close_fd(handle.raw_handle, is_fd);
+}
+
+// Closes an fd semaphore handle that CUDA did not take over
+fn close_semaphore_fd(handle: &VulkanSemaphoreHandle) {
+    let is_fd = handle.handle_type == vk::ExternalSemaphoreHandleTypeFlags::OPAQUE_FD
+        || handle.handle_type == vk::ExternalSemaphoreHandleTypeFlags::SYNC_FD;
+    close_fd(handle.raw_handle, is_fd);
+}
+
+#[cfg(unix)]
+fn close_fd(raw_handle: u64, is_fd: bool) {
+    use std::os::fd::{FromRawFd, OwnedFd};
+    if is_fd {
+        drop(unsafe { OwnedFd::from_raw_fd(raw_handle as i32) });
+    }
+}
+
+#[cfg(not(unix))]
+fn close_fd(_raw_handle: u64, _is_fd: bool) {}
+
+fn win32_handle(raw_handle: u64) -> CUDA_EXTERNAL_HANDLE {
+    CUDA_EXTERNAL_HANDLE {
+        win32: CUDA_EXTERNAL_WIN32_HANDLE {
+            handle: raw_handle as usize as *mut _,
+            name: std::ptr::null(),
+        },
+    }
+}
+
+// The CUDA array element format and channel count of a texture format. Channels
+// keep the texture's memory order, so BGRA textures read as BGRA; packed formats
+// read as one 32-bit integer per texel.
+fn array_format(format: TextureFormat) -> Option<(c_uint, c_uint)> {
+    match format {
+        TextureFormat::R8Unorm => Some((CU_AD_FORMAT_UNSIGNED_INT8, 1)),
+        TextureFormat::Rg8Unorm => Some((CU_AD_FORMAT_UNSIGNED_INT8, 2)),
+        TextureFormat::Rgba8Unorm | TextureFormat::Bgra8Unorm | TextureFormat::Rgba8Srgb | TextureFormat::Bgra8Srgb => {
+            Some((CU_AD_FORMAT_UNSIGNED_INT8, 4))
+        }
+        TextureFormat::R16Float => Some((CU_AD_FORMAT_HALF, 1)),
+        TextureFormat::Rg16Float => Some((CU_AD_FORMAT_HALF, 2)),
+        TextureFormat::Rgba16Float => Some((CU_AD_FORMAT_HALF, 4)),
+        TextureFormat::R16Uint => Some((CU_AD_FORMAT_UNSIGNED_INT16, 1)),
+        TextureFormat::R16Sint => Some((CU_AD_FORMAT_SIGNED_INT16, 1)),
+        TextureFormat::R32Float => Some((CU_AD_FORMAT_FLOAT, 1)),
+        TextureFormat::Rg32Float => Some((CU_AD_FORMAT_FLOAT, 2)),
+        TextureFormat::Rgba32Float => Some((CU_AD_FORMAT_FLOAT, 4)),
+        TextureFormat::R32Uint | TextureFormat::Rgb10a2Unorm | TextureFormat::Rg11b10Float => {
+            Some((CU_AD_FORMAT_UNSIGNED_INT32, 1))
+        }
+        TextureFormat::R32Sint => Some((CU_AD_FORMAT_SIGNED_INT32, 1)),
+        TextureFormat::Depth32Float | TextureFormat::Depth24Plus | TextureFormat::Depth24PlusStencil8 => None,
+    }
+}
+
+// Describes the image of a Vulkan texture to `cuExternalMemoryGetMappedMipmappedArray`.
+// The array flags have to match the image's usage.
+fn mipmapped_array_desc(descriptor: &TextureDescriptor) -> Result<CUDA_EXTERNAL_MEMORY_MIPMAPPED_ARRAY_DESC> {
+    if descriptor.tiling != TextureTiling::Optimal {
+        return Err(GeyserError::CudaError(format!(
+            "{:?} textures cannot be mapped as CUDA arrays; map them as buffers",
+            descriptor.tiling
+        )));
+    }
+    let (format, num_channels) = array_format(descriptor.format)
+        .ok_or_else(|| GeyserError::UnsupportedFormat(format!("{} has no CUDA array format", descriptor.format)))?;
+    let mut flags = 0;
+    if descriptor.usage.contains(&TextureUsage::StorageBinding) {
+        flags |= CUDA_ARRAY3D_SURFACE_LDST;
+    }
+    if descriptor.usage.contains(&TextureUsage::RenderAttachment) {
+        flags |= CUDA_ARRAY3D_COLOR_ATTACHMENT;
+    }
+    Ok(CUDA_EXTERNAL_MEMORY_MIPMAPPED_ARRAY_DESC {
+        offset: 0,
+        array_desc: CUDA_ARRAY3D_DESCRIPTOR {
+            width: descriptor.width as usize,
+            height: descriptor.height as usize,
+            depth: 0,
+            format,
+            num_channels,
+            flags,
+        },
+        num_levels: 1,
+        reserved: [0; 16],
+    })
+}
+
+#[cfg(test)]
+mod tests;
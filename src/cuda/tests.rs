@@ -0,0 +1,408 @@
+//! Unit tests for CUDA interop against a mocked driver API
+
+use std::os::fd::IntoRawFd;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use super::*;
+
+const UUID: [u8; 16] = [7; 16];
+
+// Records every call; `fail` makes the named call return CUDA_ERROR_INVALID_VALUE
+#[derive(Default)]
+struct MockDriver {
+    calls: Mutex<Vec<String>>,
+    memory_imports: Mutex<Vec<(c_uint, i32, u64, c_uint)>>,
+    buffers: Mutex<Vec<CUDA_EXTERNAL_MEMORY_BUFFER_DESC>>,
+    arrays: Mutex<Vec<CUDA_EXTERNAL_MEMORY_MIPMAPPED_ARRAY_DESC>>,
+    semaphore_imports: Mutex<Vec<(c_uint, i32)>>,
+    fence_values: Mutex<Vec<u64>>,
+    fail: Option<&'static str>,
+}
+
+const CUDA_ERROR_INVALID_VALUE: CUresult = 1;
+
+impl MockDriver {
+    fn call(&self, name: &str) -> CuResult<()> {
+        self.calls.lock().unwrap().push(name.to_string());
+        if self.fail == Some(name) {
+            Err(CUDA_ERROR_INVALID_VALUE)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn calls(&self) -> Vec<String> {
+        self.calls.lock().unwrap().clone()
+    }
+}
+
+// Distinct non-null values for the opaque handles the mock hands out
+fn fake_handle(value: usize) -> *mut std::ffi::c_void {
+    value as *mut _
+}
+
+impl Driver for MockDriver {
+    fn init(&self) -> CuResult<()> {
+        self.call("init")
+    }
+
+    fn device_get(&self, ordinal: i32) -> CuResult<CUdevice> {
+        self.call("device_get").map(|_| ordinal)
+    }
+
+    fn device_get_uuid(&self, _device: CUdevice) -> CuResult<[u8; 16]> {
+        self.call("device_get_uuid").map(|_| UUID)
+    }
+
+    fn primary_ctx_retain(&self, _device: CUdevice) -> CuResult<CUcontext> {
+        self.call("primary_ctx_retain").map(|_| fake_handle(1))
+    }
+
+    fn primary_ctx_release(&self, _device: CUdevice) -> CuResult<()> {
+        self.call("primary_ctx_release")
+    }
+
+    fn ctx_push_current(&self, context: CUcontext) -> CuResult<()> {
+        assert_eq!(context, fake_handle(1));
+        self.call("push")
+    }
+
+    fn ctx_pop_current(&self) -> CuResult<()> {
+        self.call("pop")
+    }
+
+    fn import_external_memory(&self, desc: &CUDA_EXTERNAL_MEMORY_HANDLE_DESC) -> CuResult<CUexternalMemory> {
+        self.call("import_external_memory")?;
+        let fd = unsafe { desc.handle.fd };
+        self.memory_imports.lock().unwrap().push((desc.type_, fd, desc.size, desc.flags));
+        Ok(fake_handle(2))
+    }
+
+    fn external_memory_get_mapped_buffer(
+        &self,
+        memory: CUexternalMemory,
+        desc: &CUDA_EXTERNAL_MEMORY_BUFFER_DESC,
+    ) -> CuResult<CUdeviceptr> {
+        assert_eq!(memory, fake_handle(2));
+        self.call("get_mapped_buffer")?;
+        self.buffers.lock().unwrap().push(*desc);
+        Ok(0x7000_0000 + desc.offset)
+    }
+
+    fn external_memory_get_mapped_mipmapped_array(
+        &self,
+        memory: CUexternalMemory,
+        desc: &CUDA_EXTERNAL_MEMORY_MIPMAPPED_ARRAY_DESC,
+    ) -> CuResult<CUmipmappedArray> {
+        assert_eq!(memory, fake_handle(2));
+        self.call("get_mapped_mipmapped_array")?;
+        self.arrays.lock().unwrap().push(*desc);
+        Ok(fake_handle(3))
+    }
+
+    fn mipmapped_array_get_level(&self, mipmap: CUmipmappedArray, level: c_uint) -> CuResult<CUarray> {
+        assert_eq!((mipmap, level), (fake_handle(3), 0));
+        self.call("mipmapped_array_get_level").map(|_| fake_handle(4))
+    }
+
+    fn mipmapped_array_destroy(&self, _mipmap: CUmipmappedArray) -> CuResult<()> {
+        self.call("mipmapped_array_destroy")
+    }
+
+    fn mem_free(&self, _ptr: CUdeviceptr) -> CuResult<()> {
+        self.call("mem_free")
+    }
+
+    fn destroy_external_memory(&self, _memory: CUexternalMemory) -> CuResult<()> {
+        self.call("destroy_external_memory")
+    }
+
+    fn import_external_semaphore(&self, desc: &CUDA_EXTERNAL_SEMAPHORE_HANDLE_DESC) -> CuResult<CUexternalSemaphore> {
+        self.call("import_external_semaphore")?;
+        let fd = unsafe { desc.handle.fd };
+        self.semaphore_imports.lock().unwrap().push((desc.type_, fd));
+        Ok(fake_handle(5))
+    }
+
+    fn signal_external_semaphore(
+        &self,
+        _semaphore: CUexternalSemaphore,
+        params: &CUDA_EXTERNAL_SEMAPHORE_SIGNAL_PARAMS,
+        _stream: CUstream,
+    ) -> CuResult<()> {
+        self.call("signal")?;
+        self.fence_values.lock().unwrap().push(params.params.fence_value);
+        Ok(())
+    }
+
+    fn wait_external_semaphore(
+        &self,
+        _semaphore: CUexternalSemaphore,
+        params: &CUDA_EXTERNAL_SEMAPHORE_WAIT_PARAMS,
+        _stream: CUstream,
+    ) -> CuResult<()> {
+        self.call("wait")?;
+        self.fence_values.lock().unwrap().push(params.params.fence_value);
+        Ok(())
+    }
+
+    fn destroy_external_semaphore(&self, _semaphore: CUexternalSemaphore) -> CuResult<()> {
+        self.call("destroy_external_semaphore")
+    }
+
+    fn error_name(&self, result: CUresult) -> String {
+        assert_eq!(result, CUDA_ERROR_INVALID_VALUE);
+        "CUDA_ERROR_INVALID_VALUE".to_string()
+    }
+}
+
+fn context(driver: &Arc<MockDriver>) -> CudaContext {
+    CudaContext::with_driver(driver.clone(), 0).unwrap()
+}
+
+fn memory_handle(handle_type: vk::ExternalMemoryHandleTypeFlags) -> VulkanTextureShareHandle {
+    VulkanTextureShareHandle {
+        raw_handle: 42,
+        memory_type_index: 0,
+        size: 1 << 20,
+        handle_type,
+        dedicated_allocation: true,
+        device_identity: DeviceIdentity {
+            device_uuid: UUID,
+            driver_uuid: [1; 16],
+            driver_version: 1,
+        },
+        plane_layouts: Vec::new(),
+    }
+}
+
+// The write end of a pipe to hand over as an fd handle. While the read end is open the
+// pipe is unique, so `fd_closed` cannot mistake a reused descriptor number for it
+fn pipe_fd() -> (std::io::PipeReader, u64, PathBuf) {
+    let (reader, writer) = std::io::pipe().unwrap();
+    let fd = writer.into_raw_fd();
+    let link = std::fs::read_link(format!("/proc/self/fd/{}", fd)).unwrap();
+    (reader, fd as u64, link)
+}
+
+fn fd_closed(fd: u64, link: &PathBuf) -> bool {
+    std::fs::read_link(format!("/proc/self/fd/{}", fd)).ok().as_ref() != Some(link)
+}
+
+fn descriptor(format: TextureFormat, usage: Vec<TextureUsage>) -> TextureDescriptor {
+    TextureDescriptor::new(256, 128, format, usage)
+}
+
+#[test]
+fn test_struct_layouts_match_cuda_h() {
+    assert_eq!(std::mem::size_of::<CUDA_EXTERNAL_MEMORY_HANDLE_DESC>(), 104);
+    assert_eq!(std::mem::size_of::<CUDA_EXTERNAL_MEMORY_BUFFER_DESC>(), 88);
+    assert_eq!(std::mem::size_of::<CUDA_ARRAY3D_DESCRIPTOR>(), 40);
+    assert_eq!(std::mem::size_of::<CUDA_EXTERNAL_MEMORY_MIPMAPPED_ARRAY_DESC>(), 120);
+    assert_eq!(std::mem::size_of::<CUDA_EXTERNAL_SEMAPHORE_HANDLE_DESC>(), 96);
+    assert_eq!(std::mem::size_of::<CUDA_EXTERNAL_SEMAPHORE_SIGNAL_PARAMS>(), 144);
+}
+
+#[test]
+fn test_context_retains_and_releases_primary_context() {
+    let driver = Arc::new(MockDriver::default());
+    let cuda = context(&driver);
+    assert_eq!(cuda.device_uuid(), UUID);
+    drop(cuda);
+    assert_eq!(
+        driver.calls(),
+        ["init", "device_get", "device_get_uuid", "primary_ctx_retain", "primary_ctx_release"]
+    );
+}
+
+#[test]
+fn test_memory_import_and_buffer_mapping() {
+    let driver = Arc::new(MockDriver::default());
+    let cuda = context(&driver);
+    let memory = cuda.import_memory(memory_handle(vk::ExternalMemoryHandleTypeFlags::OPAQUE_FD)).unwrap();
+    assert_eq!(
+        driver.memory_imports.lock().unwrap()[0],
+        (CU_EXTERNAL_MEMORY_HANDLE_TYPE_OPAQUE_FD, 42, 1 << 20, CUDA_EXTERNAL_MEMORY_DEDICATED)
+    );
+
+    {
+        let buffer = memory.map_buffer(4096, 8192).unwrap();
+        assert_eq!(buffer.device_ptr(), 0x7000_1000);
+        assert_eq!(buffer.size(), 8192);
+        let desc = driver.buffers.lock().unwrap()[0];
+        assert_eq!((desc.offset, desc.size, desc.flags), (4096, 8192, 0));
+    }
+    assert!(memory.map_buffer(1 << 20, 1).is_err());
+    assert!(memory.map_buffer(u64::MAX, 2).is_err());
+
+    // The primary context outlives everything imported into it
+    drop(cuda);
+    drop(memory);
+    let calls = driver.calls();
+    assert_eq!(
+        calls[calls.len() - 5..],
+        ["pop", "push", "destroy_external_memory", "pop", "primary_ctx_release"]
+    );
+    let position = |name: &str| calls.iter().position(|call| call == name).unwrap();
+    assert!(position("mem_free") < position("destroy_external_memory"));
+}
+
+#[test]
+fn test_memory_import_checks_device_and_handle_type() {
+    let driver = Arc::new(MockDriver::default());
+    let cuda = context(&driver);
+
+    let (_reader, fd, link) = pipe_fd();
+    let mut handle = VulkanTextureShareHandle {
+        raw_handle: fd,
+        ..memory_handle(vk::ExternalMemoryHandleTypeFlags::OPAQUE_FD)
+    };
+    handle.device_identity.device_uuid = [9; 16];
+    assert!(matches!(cuda.import_memory(handle), Err(GeyserError::IncompatibleDevice { .. })));
+    assert!(fd_closed(fd, &link));
+    let (_reader, fd, link) = pipe_fd();
+    let dma_buf = VulkanTextureShareHandle {
+        raw_handle: fd,
+        ..memory_handle(vk::ExternalMemoryHandleTypeFlags::DMA_BUF_EXT)
+    };
+    assert!(matches!(cuda.import_memory(dma_buf), Err(GeyserError::CudaError(_))));
+    assert!(fd_closed(fd, &link));
+    // Both were rejected before the driver saw them
+    assert!(driver.memory_imports.lock().unwrap().is_empty());
+
+    let win32 = memory_handle(vk::ExternalMemoryHandleTypeFlags::OPAQUE_WIN32);
+    let desc = memory_handle_desc(&win32).unwrap();
+    assert_eq!(desc.type_, CU_EXTERNAL_MEMORY_HANDLE_TYPE_OPAQUE_WIN32);
+    assert_eq!(unsafe { desc.handle.win32.handle } as usize, 42);
+}
+
+#[test]
+fn test_texture_import_maps_array() {
+    let driver = Arc::new(MockDriver::default());
+    let cuda = context(&driver);
+    let desc = descriptor(TextureFormat::Rgba16Float, vec![TextureUsage::StorageBinding, TextureUsage::RenderAttachment]);
+    let array = cuda
+        .import_texture(memory_handle(vk::ExternalMemoryHandleTypeFlags::OPAQUE_FD), &desc)
+        .unwrap();
+    assert_eq!(array.array(), fake_handle(4));
+    assert_eq!(array.descriptor(), &desc);
+
+    let mapped = driver.arrays.lock().unwrap()[0];
+    assert_eq!(mapped.num_levels, 1);
+    assert_eq!(
+        mapped.array_desc,
+        CUDA_ARRAY3D_DESCRIPTOR {
+            width: 256,
+            height: 128,
+            depth: 0,
+            format: CU_AD_FORMAT_HALF,
+            num_channels: 4,
+            flags: CUDA_ARRAY3D_SURFACE_LDST | CUDA_ARRAY3D_COLOR_ATTACHMENT,
+        }
+    );
+
+    drop(array);
+    let calls = driver.calls();
+    let position = |name: &str| calls.iter().position(|call| call == name).unwrap();
+    assert!(position("mipmapped_array_destroy") < position("destroy_external_memory"));
+}
+
+#[test]
+fn test_texture_import_rejects_unmappable_textures() {
+    let driver = Arc::new(MockDriver::default());
+    let cuda = context(&driver);
+    let handle = |fd| VulkanTextureShareHandle {
+        raw_handle: fd,
+        ..memory_handle(vk::ExternalMemoryHandleTypeFlags::OPAQUE_FD)
+    };
+
+    let (_reader, fd, link) = pipe_fd();
+    let depth = descriptor(TextureFormat::Depth32Float, vec![TextureUsage::RenderAttachment]);
+    assert!(matches!(cuda.import_texture(handle(fd), &depth), Err(GeyserError::UnsupportedFormat(_))));
+    assert!(fd_closed(fd, &link));
+    let (_reader, fd, link) = pipe_fd();
+    let linear = descriptor(TextureFormat::Rgba8Unorm, vec![]).with_tiling(TextureTiling::Linear);
+    assert!(cuda.import_texture(handle(fd), &linear).is_err());
+    assert!(fd_closed(fd, &link));
+    assert!(driver.memory_imports.lock().unwrap().is_empty());
+
+    assert_eq!(array_format(TextureFormat::Bgra8Srgb), Some((CU_AD_FORMAT_UNSIGNED_INT8, 4)));
+    assert_eq!(array_format(TextureFormat::Rgb10a2Unorm), Some((CU_AD_FORMAT_UNSIGNED_INT32, 1)));
+}
+
+#[test]
+fn test_semaphore_import_and_signaling() {
+    let driver = Arc::new(MockDriver::default());
+    let cuda = context(&driver);
+    let timeline = cuda
+        .import_semaphore(VulkanSemaphoreHandle {
+            raw_handle: 17,
+            handle_type: vk::ExternalSemaphoreHandleTypeFlags::OPAQUE_FD,
+            is_timeline: true,
+        })
+        .unwrap();
+    let binary = cuda
+        .import_semaphore(VulkanSemaphoreHandle {
+            raw_handle: 18,
+            handle_type: vk::ExternalSemaphoreHandleTypeFlags::OPAQUE_FD,
+            is_timeline: false,
+        })
+        .unwrap();
+    assert_eq!(
+        *driver.semaphore_imports.lock().unwrap(),
+        [
+            (CU_EXTERNAL_SEMAPHORE_HANDLE_TYPE_TIMELINE_SEMAPHORE_FD, 17),
+            (CU_EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_FD, 18)
+        ]
+    );
+    assert!(timeline.is_timeline() && !binary.is_timeline());
+
+    let stream = std::ptr::null_mut();
+    timeline.wait(5, stream).unwrap();
+    timeline.signal(6, stream).unwrap();
+    // Binary semaphores carry no value
+    binary.signal(6, stream).unwrap();
+    assert_eq!(*driver.fence_values.lock().unwrap(), [5, 6, 0]);
+
+    let (_reader, fd, link) = pipe_fd();
+    let sync_fd = VulkanSemaphoreHandle {
+        raw_handle: fd,
+        handle_type: vk::ExternalSemaphoreHandleTypeFlags::SYNC_FD,
+        is_timeline: false,
+    };
+    assert!(cuda.import_semaphore(sync_fd).is_err());
+    assert!(fd_closed(fd, &link));
+}
+
+#[test]
+fn test_driver_errors_are_named() {
+    let driver = Arc::new(MockDriver {
+        fail: Some("import_external_memory"),
+        ..Default::default()
+    });
+    let cuda = context(&driver);
+    let (_reader, fd, link) = pipe_fd();
+    let error = cuda
+        .import_memory(VulkanTextureShareHandle {
+            raw_handle: fd,
+            ..memory_handle(vk::ExternalMemoryHandleTypeFlags::OPAQUE_FD)
+        })
+        .err()
+        .unwrap();
+    assert_eq!(
+        error.to_string(),
+        "CUDA error: cuImportExternalMemory failed: CUDA_ERROR_INVALID_VALUE"
+    );
+    // The context was made current for the call and popped again
+    assert_eq!(driver.calls()[4..], ["push", "import_external_memory", "pop"]);
+    // CUDA did not take the descriptor over, so the import closed it
+    assert!(fd_closed(fd, &link));
+
+    let failing_init = Arc::new(MockDriver {
+        fail: Some("init"),
+        ..Default::default()
+    });
+    assert!(CudaContext::with_driver(failing_init.clone(), 0).is_err());
+    assert_eq!(failing_init.calls(), ["init"]);
+}
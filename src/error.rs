@@ -2,6 +2,8 @@
 
 use thiserror::Error;
 
+use crate::common::DeviceIdentity;
+
 pub type Result<T> = std::result::Result<T, GeyserError>;
 
 #[derive(Error, Debug)]
@@ -10,10 +12,29 @@ pub enum GeyserError {
     VulkanInitializationError(String),
     #[error("Vulkan API error: {0}")]
     VulkanApiError(String),
+    #[error("Missing required Vulkan extensions: {}", .0.join(", "))]
+    MissingExtensions(Vec<String>),
+    #[error("Texture was exported from an incompatible device ({exporter}); importer is {importer}")]
+    IncompatibleDevice {
+        exporter: DeviceIdentity,
+        importer: DeviceIdentity,
+    },
     #[error("Failed to initialize Metal: {0}")]
     MetalInitializationError(String),
     #[error("Metal API error: {0}")]
     MetalApiError(String),
+    #[error("OpenGL error: {0}")]
+    OpenGlError(String),
+    #[error("WebGPU error: {0}")]
+    WebGpuError(String),
+    #[error("IPC error: {0}")]
+    IpcError(String),
+    #[error("PipeWire error: {0}")]
+    PipeWireError(String),
+    #[error("Wayland error: {0}")]
+    WaylandError(String),
+    #[error("CUDA error: {0}")]
+    CudaError(String),
     #[error("Unsupported texture format: {0}")]
     UnsupportedTextureFormat(String),
     #[error("Unsupported format: {0}")]
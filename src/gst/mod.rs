@@ -0,0 +1,88 @@
+//! GStreamer elements that carry video frames over Geyser sessions (Linux).
+//!
+//! - `geysersink` copies incoming frames, from system memory or mappable dma-bufs, into
+//!   a ring of linear Vulkan textures exported as dma-bufs. It serves them to one
+//!   consumer at a time over a `ProducerSession` listening on `socket-path`, sharing
+//!   each frame's texture as a new generation and announcing the frame with its
+//!   presentation timestamp. A texture is reused only once the consumer has imported a
+//!   later one; frames are dropped while it still reads all of them.
+//! - `geysersrc` connects to `socket-path` as a `ConsumerSession` and outputs the frames
+//!   as `video/x-raw(memory:DMABuf)` wrapping the shared dma-buf, or copies them into
+//!   system memory if downstream does not accept dma-bufs.
+//!
+//! Register the elements once after initializing GStreamer:
+//!
+//! ```ignore
+//! gstreamer::init()?;
+//! geyser::gst::register(None)?;
+//! let producer = gstreamer::parse::launch(
+//!     "videotestsrc ! video/x-raw,format=BGRA ! geysersink socket-path=/tmp/camera0.sock",
+//! )?;
+//! let consumer = gstreamer::parse::launch("geysersrc socket-path=/tmp/camera0.sock ! autovideosink")?;
+//! ```
+
+mod sink;
+mod source;
+
+pub use sink::GeyserSink;
+pub use source::GeyserSrc;
+
+use gstreamer as gst;
+use gstreamer::glib;
+use gstreamer::prelude::*;
+use gstreamer_video as gst_video;
+
+use crate::common::{TextureDescriptor, TextureFormat};
+
+/// Registers `geysersink` and `geysersrc`, with `plugin` if called from a plugin's
+/// init function.
+pub fn register(plugin: Option<&gst::Plugin>) -> Result<(), glib::BoolError> {
+    gst::Element::register(plugin, "geysersink", gst::Rank::NONE, GeyserSink::static_type())?;
+    gst::Element::register(plugin, "geysersrc", gst::Rank::NONE, GeyserSrc::static_type())
+}
+
+// Texture formats both elements carry, in order of preference
+const FORMATS: [TextureFormat; 3] = [TextureFormat::Bgra8Unorm, TextureFormat::Rgba8Unorm, TextureFormat::R8Unorm];
+
+/// The GStreamer video format of a texture format.
+pub fn video_format(format: TextureFormat) -> Option<gst_video::VideoFormat> {
+    match format {
+        TextureFormat::Bgra8Unorm => Some(gst_video::VideoFormat::Bgra),
+        TextureFormat::Rgba8Unorm => Some(gst_video::VideoFormat::Rgba),
+        TextureFormat::R8Unorm => Some(gst_video::VideoFormat::Gray8),
+        _ => None,
+    }
+}
+
+/// The texture format frames of a GStreamer video format are shared in.
+pub fn texture_format(format: gst_video::VideoFormat) -> Option<TextureFormat> {
+    FORMATS.into_iter().find(|&texture_format| video_format(texture_format) == Some(format))
+}
+
+// Raw video caps for the supported formats, with and without the dma-buf feature
+fn template_caps() -> gst::Caps {
+    let formats = FORMATS.iter().filter_map(|&format| video_format(format));
+    let mut caps = gst_video::VideoCapsBuilder::new()
+        .features([gstreamer_allocators::CAPS_FEATURE_MEMORY_DMABUF])
+        .format_list(formats.clone())
+        .build();
+    caps.merge(gst_video::VideoCapsBuilder::new().format_list(formats).build());
+    caps
+}
+
+// Fixed caps describing frames of `descriptor`
+fn descriptor_caps(descriptor: &TextureDescriptor, dma_buf: bool) -> Option<gst::Caps> {
+    let builder = gst_video::VideoCapsBuilder::new()
+        .format(video_format(descriptor.format)?)
+        .width(descriptor.width as i32)
+        .height(descriptor.height as i32)
+        .framerate(gst::Fraction::new(0, 1));
+    Some(if dma_buf {
+        builder.features([gstreamer_allocators::CAPS_FEATURE_MEMORY_DMABUF]).build()
+    } else {
+        builder.build()
+    })
+}
+
+#[cfg(test)]
+mod tests;
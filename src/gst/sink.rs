@@ -0,0 +1,454 @@
+//! `geysersink`: publishes incoming video frames over a producer session.
+
+use gstreamer as gst;
+use gstreamer::glib;
+
+glib::wrapper! {
+    /// Serves incoming frames to a Geyser consumer, e.g. `geysersrc`. See the module docs.
+    pub struct GeyserSink(ObjectSubclass<imp::GeyserSink>) @extends gstreamer_base::BaseSink, gst::Element, gst::Object;
+}
+
+mod imp {
+    use std::{
+        io,
+        os::unix::net::UnixListener,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, LazyLock, Mutex,
+        },
+        thread::JoinHandle,
+        time::Duration,
+    };
+
+    use gstreamer as gst;
+    use gstreamer::glib;
+    use gstreamer::prelude::*;
+    use gstreamer::subclass::prelude::*;
+    use gstreamer_base::subclass::prelude::*;
+    use gstreamer_video as gst_video;
+    use gstreamer_video::prelude::*;
+
+    use crate::common::{
+        ApiTextureHandle, DeviceIdentity, DmaBufHandle, SyncPrimitives, TextureDescriptor, TextureFormat,
+        TextureMemoryLocation, TextureSharePackage, TextureTiling, TextureUsage,
+    };
+    use crate::error::Result;
+    use crate::ipc::{ProducerSession, SessionConfig, SessionEvent, UnixTransport};
+    use crate::vulkan::{VulkanSharedTexture, VulkanTextureShareManager};
+
+    static CAT: LazyLock<gst::DebugCategory> = LazyLock::new(|| {
+        gst::DebugCategory::new("geysersink", gst::DebugColorFlags::empty(), Some("Geyser texture sink"))
+    });
+
+    // Textures frames are copied into, so the consumer can read one while the next is written
+    const SLOT_COUNT: usize = 3;
+    // How often the session thread checks for consumers and their messages
+    const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+    // A linear texture exported as a dma-buf. Every frame copied into it is shared as a
+    // new generation.
+    struct SharedFrame {
+        texture: VulkanSharedTexture,
+        descriptor: TextureDescriptor,
+        dma_buf: DmaBufHandle,
+        // Connection and generation it was last shared as
+        shared: Option<(u64, u64)>,
+    }
+
+    impl SharedFrame {
+        fn new(manager: &VulkanTextureShareManager, descriptor: TextureDescriptor) -> Result<Self> {
+            let texture = manager.create_vulkan_texture(&descriptor)?;
+            match manager.export_dma_buf(&texture) {
+                Ok(dma_buf) => Ok(Self {
+                    texture,
+                    descriptor,
+                    dma_buf,
+                    shared: None,
+                }),
+                Err(e) => {
+                    let unexported = DmaBufHandle { fd: -1, fourcc: 0, modifier: 0, offset: 0, stride: 0 };
+                    let _ = manager.destroy_texture(texture, ApiTextureHandle::DmaBuf(unexported));
+                    Err(e)
+                }
+            }
+        }
+
+        fn package(&self) -> TextureSharePackage {
+            TextureSharePackage {
+                texture: ApiTextureHandle::DmaBuf(self.dma_buf.clone()),
+                sync: SyncPrimitives::default(),
+            }
+        }
+
+        // Whether the consumer may still read the frame: it was shared with the connected
+        // consumer, which has not imported a later generation since
+        fn in_use(&self, consumer: Option<Consumer>) -> bool {
+            match (self.shared, consumer) {
+                (Some((connection, generation)), Some(consumer)) if connection == consumer.connection => {
+                    consumer.imported.is_none_or(|imported| imported <= generation)
+                }
+                _ => false,
+            }
+        }
+
+        fn destroy(self, manager: &VulkanTextureShareManager) {
+            unsafe { libc::close(self.dma_buf.fd) };
+            let _ = manager.destroy_texture(self.texture, ApiTextureHandle::DmaBuf(self.dma_buf));
+        }
+    }
+
+    // The connected consumer, as `render` sees it
+    #[derive(Clone, Copy)]
+    struct Consumer {
+        connection: u64,
+        // The newest generation it imported; importing one releases all earlier ones
+        imported: Option<u64>,
+    }
+
+    // A consumer session and the number of the connection it runs on
+    struct Connection {
+        id: u64,
+        session: ProducerSession<UnixTransport>,
+    }
+
+    // Shared with the session thread, which accepts consumers and handles their messages
+    // and heartbeats, so `render` never waits on the socket
+    struct Shared {
+        connection: Mutex<Option<Connection>>,
+        // Format of the negotiated caps; consumers are accepted once it is known
+        format: Mutex<Option<TextureFormat>>,
+        stop: AtomicBool,
+    }
+
+    impl Shared {
+        fn consumer(&self) -> Option<Consumer> {
+            let connection = self.connection.lock().unwrap();
+            connection.as_ref().map(|connection| Consumer {
+                connection: connection.id,
+                imported: connection.session.consumer_generation(),
+            })
+        }
+    }
+
+    // Runs the session thread until the sink stops
+    fn serve(listener: UnixListener, shared: Arc<Shared>, device: DeviceIdentity) {
+        let mut next_id = 0;
+        while !shared.stop.load(Ordering::SeqCst) {
+            if shared.connection.lock().unwrap().is_some() {
+                poll(&shared);
+            } else if let Some(session) = accept(&listener, &shared, device) {
+                gst::info!(CAT, "Consumer connected");
+                *shared.connection.lock().unwrap() = Some(Connection { id: next_id, session });
+                next_id += 1;
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    // Accepts a waiting consumer, if any, and completes the handshake
+    fn accept(listener: &UnixListener, shared: &Shared, device: DeviceIdentity) -> Option<ProducerSession<UnixTransport>> {
+        let format = (*shared.format.lock().unwrap())?;
+        let stream = match listener.accept() {
+            Ok((stream, _)) => stream,
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => return None,
+            Err(e) => {
+                gst::warning!(CAT, "Failed to accept consumer: {}", e);
+                return None;
+            }
+        };
+        if let Err(e) = stream.set_nonblocking(false) {
+            gst::warning!(CAT, "Failed to configure consumer socket: {}", e);
+            return None;
+        }
+        let config = SessionConfig::new(device, vec![format]);
+        ProducerSession::accept(UnixTransport::new(stream), config)
+            .inspect_err(|e| gst::warning!(CAT, "Session handshake failed: {}", e))
+            .ok()
+    }
+
+    // Handles what the consumer sent, sending heartbeats as needed; ends the session if
+    // it left. Imports are tracked by the session itself.
+    fn poll(shared: &Shared) {
+        let mut connection = shared.connection.lock().unwrap();
+        let Some(Connection { session, .. }) = connection.as_mut() else { return };
+        let ended = loop {
+            match session.poll(Duration::ZERO) {
+                Ok(None) => break false,
+                Ok(Some(SessionEvent::Closed)) => {
+                    gst::info!(CAT, "Consumer disconnected");
+                    break true;
+                }
+                Ok(Some(_)) => {}
+                Err(e) => {
+                    gst::info!(CAT, "Consumer lost: {}", e);
+                    break true;
+                }
+            }
+        };
+        if ended {
+            *connection = None;
+        }
+    }
+
+    struct State {
+        manager: VulkanTextureShareManager,
+        shared: Arc<Shared>,
+        server: Option<JoinHandle<()>>,
+        info: Option<gst_video::VideoInfo>,
+        slots: Vec<SharedFrame>,
+        // Slots replaced after a caps change, kept until the consumer releases them
+        retired: Vec<SharedFrame>,
+        frame: u64,
+    }
+
+    impl State {
+        // Destroys the retired slots the consumer no longer reads
+        fn collect(&mut self, consumer: Option<Consumer>) {
+            let (in_use, released): (Vec<_>, Vec<_>) =
+                self.retired.drain(..).partition(|frame| frame.in_use(consumer));
+            self.retired = in_use;
+            for frame in released {
+                frame.destroy(&self.manager);
+            }
+        }
+
+        // Replaces the slots after a caps change
+        fn resize(&mut self, info: gst_video::VideoInfo) -> Result<()> {
+            let format = super::super::texture_format(info.format()).ok_or_else(|| {
+                crate::error::GeyserError::UnsupportedFormat(format!("{:?}", info.format()))
+            })?;
+            let descriptor = TextureDescriptor {
+                width: info.width(),
+                height: info.height(),
+                format,
+                usage: vec![TextureUsage::CopySrc, TextureUsage::TextureBinding],
+                label: Some("geysersink frame".to_string()),
+                tiling: TextureTiling::Linear,
+                memory_location: TextureMemoryLocation::CpuToGpu,
+            };
+            let mut slots = Vec::with_capacity(SLOT_COUNT);
+            for _ in 0..SLOT_COUNT {
+                match SharedFrame::new(&self.manager, descriptor.clone()) {
+                    Ok(frame) => slots.push(frame),
+                    Err(e) => {
+                        for frame in slots {
+                            frame.destroy(&self.manager);
+                        }
+                        return Err(e);
+                    }
+                }
+            }
+            // A format change cannot be shared in the negotiated session; sharing the
+            // next frame fails and the consumer reconnects and negotiates again
+            self.retired.append(&mut self.slots);
+            self.slots = slots;
+            *self.shared.format.lock().unwrap() = Some(format);
+            self.info = Some(info);
+            Ok(())
+        }
+
+        fn destroy(mut self) {
+            self.shared.stop.store(true, Ordering::SeqCst);
+            if let Some(server) = self.server.take() {
+                let _ = server.join();
+            }
+            *self.shared.connection.lock().unwrap() = None;
+            for frame in self.retired.drain(..).chain(self.slots.drain(..)) {
+                frame.destroy(&self.manager);
+            }
+        }
+    }
+
+    #[derive(Default)]
+    pub struct GeyserSink {
+        socket_path: Mutex<Option<String>>,
+        state: Mutex<Option<State>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for GeyserSink {
+        const NAME: &'static str = "GeyserSink";
+        type Type = super::GeyserSink;
+        type ParentType = gstreamer_base::BaseSink;
+    }
+
+    impl ObjectImpl for GeyserSink {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: LazyLock<Vec<glib::ParamSpec>> = LazyLock::new(|| {
+                vec![glib::ParamSpecString::builder("socket-path")
+                    .nick("Socket path")
+                    .blurb("Unix socket consumers connect to")
+                    .mutable_ready()
+                    .build()]
+            });
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            if pspec.name() == "socket-path" {
+                *self.socket_path.lock().unwrap() = value.get().unwrap();
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            match pspec.name() {
+                "socket-path" => self.socket_path.lock().unwrap().to_value(),
+                // GObject only asks for the properties registered in `properties`
+                name => unreachable!("geysersink has no property {}", name),
+            }
+        }
+    }
+
+    impl GstObjectImpl for GeyserSink {}
+
+    impl ElementImpl for GeyserSink {
+        fn metadata() -> Option<&'static gst::subclass::ElementMetadata> {
+            static METADATA: LazyLock<gst::subclass::ElementMetadata> = LazyLock::new(|| {
+                gst::subclass::ElementMetadata::new(
+                    "Geyser texture sink",
+                    "Sink/Video",
+                    "Shares video frames as a dma-buf texture over a Geyser session",
+                    "Geyser contributors",
+                )
+            });
+            Some(&*METADATA)
+        }
+
+        fn pad_templates() -> &'static [gst::PadTemplate] {
+            static PAD_TEMPLATES: LazyLock<Vec<gst::PadTemplate>> = LazyLock::new(|| {
+                vec![gst::PadTemplate::new(
+                    "sink",
+                    gst::PadDirection::Sink,
+                    gst::PadPresence::Always,
+                    &super::super::template_caps(),
+                )
+                .unwrap()]
+            });
+            PAD_TEMPLATES.as_ref()
+        }
+    }
+
+    impl BaseSinkImpl for GeyserSink {
+        fn start(&self) -> std::result::Result<(), gst::ErrorMessage> {
+            let path = self.socket_path.lock().unwrap().clone().ok_or_else(|| {
+                gst::error_msg!(gst::ResourceError::Settings, ["The socket-path property is not set"])
+            })?;
+            let manager = VulkanTextureShareManager::builder().application_name("geysersink").build().map_err(|e| {
+                gst::error_msg!(gst::ResourceError::OpenWrite, ["Failed to create Vulkan device: {}", e])
+            })?;
+            if !manager.dma_buf_import_supported() {
+                return Err(gst::error_msg!(
+                    gst::ResourceError::OpenWrite,
+                    ["The Vulkan device cannot export dma-bufs"]
+                ));
+            }
+
+            // A socket left behind by a sink that crashed would make bind fail
+            let _ = std::fs::remove_file(&path);
+            let listener = UnixListener::bind(&path)
+                .and_then(|listener| listener.set_nonblocking(true).map(|_| listener))
+                .map_err(|e| gst::error_msg!(gst::ResourceError::OpenWrite, ["Failed to listen on {}: {}", path, e]))?;
+
+            let shared = Arc::new(Shared {
+                connection: Mutex::new(None),
+                format: Mutex::new(None),
+                stop: AtomicBool::new(false),
+            });
+            let server = {
+                let shared = shared.clone();
+                let device = manager.device_identity();
+                std::thread::Builder::new()
+                    .name("geysersink-session".to_string())
+                    .spawn(move || serve(listener, shared, device))
+                    .map_err(|e| {
+                        gst::error_msg!(gst::ResourceError::OpenWrite, ["Failed to start session thread: {}", e])
+                    })?
+            };
+
+            *self.state.lock().unwrap() = Some(State {
+                manager,
+                shared,
+                server: Some(server),
+                info: None,
+                slots: Vec::new(),
+                retired: Vec::new(),
+                frame: 0,
+            });
+            Ok(())
+        }
+
+        fn stop(&self) -> std::result::Result<(), gst::ErrorMessage> {
+            if let Some(state) = self.state.lock().unwrap().take() {
+                state.destroy();
+            }
+            if let Some(path) = self.socket_path.lock().unwrap().as_ref() {
+                let _ = std::fs::remove_file(path);
+            }
+            Ok(())
+        }
+
+        fn set_caps(&self, caps: &gst::Caps) -> std::result::Result<(), gst::LoggableError> {
+            let info = gst_video::VideoInfo::from_caps(caps)
+                .map_err(|_| gst::loggable_error!(CAT, "Invalid caps {}", caps))?;
+            let mut state = self.state.lock().unwrap();
+            let state = state.as_mut().ok_or_else(|| gst::loggable_error!(CAT, "Caps set before start"))?;
+            state
+                .resize(info)
+                .map_err(|e| gst::loggable_error!(CAT, "Failed to create shared texture: {}", e))
+        }
+
+        fn render(&self, buffer: &gst::Buffer) -> std::result::Result<gst::FlowSuccess, gst::FlowError> {
+            let mut state = self.state.lock().unwrap();
+            let state = state.as_mut().ok_or(gst::FlowError::Flushing)?;
+            let consumer = state.shared.consumer();
+            state.collect(consumer);
+            // Frames are dropped while no consumer is connected
+            if consumer.is_none() {
+                return Ok(gst::FlowSuccess::Ok);
+            }
+            let Some(slot) = state.slots.iter_mut().find(|slot| !slot.in_use(consumer)) else {
+                gst::debug!(CAT, imp = self, "The consumer still reads every slot; dropping frame");
+                return Ok(gst::FlowSuccess::Ok);
+            };
+
+            let info = state.info.as_ref().ok_or(gst::FlowError::NotNegotiated)?;
+            let frame = gst_video::VideoFrameRef::from_buffer_ref_readable(buffer.as_ref(), info).map_err(|_| {
+                gst::element_imp_error!(self, gst::StreamError::Failed, ["Failed to map buffer"]);
+                gst::FlowError::Error
+            })?;
+            let stride = frame.plane_stride()[0] as usize;
+            let data = frame.plane_data(0).map_err(|_| gst::FlowError::Error)?;
+            {
+                let mut mapped = slot.texture.map().map_err(|e| {
+                    gst::element_imp_error!(self, gst::StreamError::Failed, ["Failed to map shared texture: {}", e]);
+                    gst::FlowError::Error
+                })?;
+                for y in 0..mapped.height() {
+                    let row = mapped.row_mut(y);
+                    let start = y as usize * stride;
+                    row.copy_from_slice(&data[start..start + row.len()]);
+                }
+            }
+
+            state.frame += 1;
+            let frame = state.frame;
+            let mut connection = state.shared.connection.lock().unwrap();
+            let result = match connection.as_mut() {
+                Some(Connection { id, session }) => session.share(&slot.descriptor, &slot.package()).and_then(|generation| {
+                    slot.shared = Some((*id, generation));
+                    match buffer.pts() {
+                        Some(pts) => session.frame_ready_at(frame, Duration::from_nanos(pts.nseconds())),
+                        None => session.frame_ready(frame),
+                    }
+                }),
+                // The consumer left while the frame was copied
+                None => Ok(()),
+            };
+            if let Err(e) = result {
+                gst::info!(CAT, "Consumer lost: {}", e);
+                *connection = None;
+            }
+            Ok(gst::FlowSuccess::Ok)
+        }
+    }
+}
@@ -0,0 +1,459 @@
+//! `geysersrc`: outputs the frames of a consumer session.
+
+use gstreamer as gst;
+use gstreamer::glib;
+
+glib::wrapper! {
+    /// Outputs the frames a Geyser producer, e.g. `geysersink`, shares. See the module docs.
+    pub struct GeyserSrc(ObjectSubclass<imp::GeyserSrc>) @extends gstreamer_base::PushSrc, gstreamer_base::BaseSrc, gst::Element, gst::Object;
+}
+
+mod imp {
+    use std::{
+        os::fd::IntoRawFd,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, Condvar, LazyLock, Mutex,
+        },
+        thread::JoinHandle,
+        time::{Duration, Instant},
+    };
+
+    use gstreamer as gst;
+    use gstreamer::glib;
+    use gstreamer::prelude::*;
+    use gstreamer::subclass::prelude::*;
+    use gstreamer_allocators::prelude::*;
+    use gstreamer_base::subclass::base_src::CreateSuccess;
+    use gstreamer_base::subclass::prelude::*;
+    use gstreamer_video as gst_video;
+    use gstreamer_video::prelude::*;
+
+    use crate::common::{DeviceIdentity, TextureDescriptor};
+    use crate::ipc::{ConsumerSession, DmaBufShareManager, DmaBufTexture, SessionConfig, SessionEvent, UnixTransport};
+
+    static CAT: LazyLock<gst::DebugCategory> = LazyLock::new(|| {
+        gst::DebugCategory::new("geysersrc", gst::DebugColorFlags::empty(), Some("Geyser texture source"))
+    });
+
+    // How long `start` waits for the producer to share its first texture
+    const FIRST_TEXTURE_TIMEOUT: Duration = Duration::from_secs(10);
+    // How often `create` checks whether it was unlocked while waiting for a frame, and
+    // the session thread whether the element stopped
+    const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+    // What the session thread received, waiting for the streaming thread
+    #[derive(Default)]
+    struct Mailbox {
+        // The texture the producer shares now
+        texture: Option<Arc<DmaBufTexture>>,
+        // The newest frame not yet output, with the texture it was rendered into
+        frame: Option<(Arc<DmaBufTexture>, Option<Duration>)>,
+        // Set once the session ended: by the producer, or with an error
+        ended: Option<std::result::Result<(), String>>,
+    }
+
+    // Shared with the session thread. The session holds its import as a
+    // `Box<dyn SharedTexture>`, which is not `Send`, so it stays on that thread and hands
+    // out clones of the dma-buf instead.
+    struct Shared {
+        mailbox: Mutex<Mailbox>,
+        // Signalled when the mailbox changes
+        changed: Condvar,
+        stop: AtomicBool,
+    }
+
+    impl Shared {
+        fn end(&self, result: std::result::Result<(), String>) {
+            self.mailbox.lock().unwrap().ended = Some(result);
+            self.changed.notify_all();
+        }
+    }
+
+    // Connects to the producer and runs the session, with its heartbeats, until it ends
+    // or the element stops
+    fn run_session(path: String, shared: Arc<Shared>) {
+        let transport = match UnixTransport::connect(&path) {
+            Ok(transport) => transport,
+            Err(e) => return shared.end(Err(format!("Failed to connect to {}: {}", path, e))),
+        };
+        // Dma-bufs are imported by mapping them, so any device can consume them
+        let config = SessionConfig::new(DeviceIdentity::default(), super::super::FORMATS.to_vec());
+        let mut session = match ConsumerSession::connect(transport, config) {
+            Ok(session) => session,
+            Err(e) => return shared.end(Err(format!("Session handshake failed: {}", e))),
+        };
+
+        let manager = Arc::new(DmaBufShareManager::new());
+        while !shared.stop.load(Ordering::SeqCst) {
+            match session.poll(&manager, POLL_INTERVAL) {
+                Ok(None) | Ok(Some(SessionEvent::Imported { .. })) => {}
+                Ok(Some(SessionEvent::TextureChanged { .. })) => {
+                    let texture = session.texture().and_then(|texture| texture.as_any().downcast_ref::<DmaBufTexture>());
+                    match texture.map(DmaBufTexture::try_clone) {
+                        Some(Ok(texture)) => {
+                            shared.mailbox.lock().unwrap().texture = Some(Arc::new(texture));
+                            shared.changed.notify_all();
+                        }
+                        Some(Err(e)) => return shared.end(Err(e.to_string())),
+                        None => return shared.end(Err("The producer shared no dma-buf".to_string())),
+                    }
+                }
+                Ok(Some(SessionEvent::Frame { timestamp, .. })) => {
+                    let mut mailbox = shared.mailbox.lock().unwrap();
+                    if let Some(texture) = mailbox.texture.clone() {
+                        // A frame the streaming thread did not get to yet is dropped
+                        mailbox.frame = Some((texture, timestamp));
+                        shared.changed.notify_all();
+                    }
+                }
+                Ok(Some(SessionEvent::Closed)) => return shared.end(Ok(())),
+                Err(e) => return shared.end(Err(format!("Producer lost: {}", e))),
+            }
+        }
+        let _ = session.close();
+    }
+
+    struct State {
+        shared: Arc<Shared>,
+        session: Option<JoinHandle<()>>,
+        info: Option<gst_video::VideoInfo>,
+        dma_buf_output: bool,
+        // Producer timestamp and our running time of the first timestamped frame
+        epoch: Option<(Duration, gst::ClockTime)>,
+    }
+
+    impl State {
+        fn stop(mut self) {
+            self.shared.stop.store(true, Ordering::SeqCst);
+            if let Some(session) = self.session.take() {
+                let _ = session.join();
+            }
+        }
+    }
+
+    #[derive(Default)]
+    pub struct GeyserSrc {
+        socket_path: Mutex<Option<String>>,
+        state: Mutex<Option<State>>,
+        flushing: AtomicBool,
+    }
+
+    impl GeyserSrc {
+        fn descriptor(&self) -> Option<TextureDescriptor> {
+            let state = self.state.lock().unwrap();
+            let mailbox = state.as_ref()?.shared.mailbox.lock().unwrap();
+            Some(mailbox.texture.as_ref()?.descriptor().clone())
+        }
+
+        // Waits for the next frame from the session thread
+        fn next_frame(&self, shared: &Shared) -> Result<(Arc<DmaBufTexture>, Option<Duration>), gst::FlowError> {
+            loop {
+                if self.flushing.load(Ordering::SeqCst) {
+                    return Err(gst::FlowError::Flushing);
+                }
+                let mut mailbox = shared.mailbox.lock().unwrap();
+                if mailbox.frame.is_none() && mailbox.ended.is_none() {
+                    mailbox = shared.changed.wait_timeout(mailbox, POLL_INTERVAL).unwrap().0;
+                }
+                if let Some(frame) = mailbox.frame.take() {
+                    return Ok(frame);
+                }
+                match &mailbox.ended {
+                    Some(Ok(())) => return Err(gst::FlowError::Eos),
+                    Some(Err(e)) => {
+                        gst::element_imp_error!(self, gst::ResourceError::Read, ["{}", e]);
+                        return Err(gst::FlowError::Error);
+                    }
+                    None => {}
+                }
+            }
+        }
+
+        // Whether the negotiated caps describe frames of `descriptor`
+        fn negotiated_for(&self, descriptor: &TextureDescriptor) -> bool {
+            let state = self.state.lock().unwrap();
+            state.as_ref().and_then(|state| state.info.as_ref()).is_some_and(|info| {
+                super::super::video_format(descriptor.format) == Some(info.format())
+                    && (info.width(), info.height()) == (descriptor.width, descriptor.height)
+            })
+        }
+
+        // Presentation timestamp of a frame, in our running time
+        fn pts(&self, state: &mut State, timestamp: Option<Duration>) -> Option<gst::ClockTime> {
+            let now = self.obj().current_running_time()?;
+            let Some(timestamp) = timestamp else { return Some(now) };
+            let (first, start) = *state.epoch.get_or_insert((timestamp, now));
+            let elapsed = timestamp.checked_sub(first)?;
+            Some(start + gst::ClockTime::from_nseconds(elapsed.as_nanos() as u64))
+        }
+
+        // Wraps the shared dma-buf itself. The producer reuses it once a later frame is
+        // imported, so downstream must be done with the buffer by then.
+        fn dma_buf_buffer(
+            &self,
+            texture: &DmaBufTexture,
+            info: &gst_video::VideoInfo,
+        ) -> Result<gst::Buffer, gst::FlowError> {
+            let fd = texture.try_clone_fd().map_err(|e| {
+                gst::element_imp_error!(self, gst::ResourceError::Read, ["{}", e]);
+                gst::FlowError::Error
+            })?;
+            let allocator = gstreamer_allocators::DmaBufAllocator::new();
+            let memory = unsafe { allocator.alloc_dmabuf(fd.into_raw_fd(), texture.size()) }.map_err(|e| {
+                gst::element_imp_error!(self, gst::ResourceError::Read, ["Failed to wrap dma-buf: {}", e]);
+                gst::FlowError::Error
+            })?;
+            let mut buffer = gst::Buffer::new();
+            {
+                let buffer = buffer.get_mut().unwrap();
+                buffer.append_memory(memory);
+                let handle = texture.handle();
+                gst_video::VideoMeta::add_full(
+                    buffer,
+                    gst_video::VideoFrameFlags::empty(),
+                    info.format(),
+                    info.width(),
+                    info.height(),
+                    &[handle.offset as usize],
+                    &[handle.stride as i32],
+                )
+                .map_err(|_| gst::FlowError::Error)?;
+            }
+            Ok(buffer)
+        }
+
+        // Copies the frame into system memory laid out as `info` describes
+        fn system_memory_buffer(
+            &self,
+            texture: &DmaBufTexture,
+            info: &gst_video::VideoInfo,
+        ) -> Result<gst::Buffer, gst::FlowError> {
+            let mut buffer = gst::Buffer::with_size(info.size()).map_err(|_| gst::FlowError::Error)?;
+            let mapped = texture.map().map_err(|e| {
+                gst::element_imp_error!(self, gst::ResourceError::Read, ["{}", e]);
+                gst::FlowError::Error
+            })?;
+            {
+                let mut frame = gst_video::VideoFrameRef::from_buffer_ref_writable(buffer.get_mut().unwrap(), info)
+                    .map_err(|_| gst::FlowError::Error)?;
+                let stride = frame.plane_stride()[0] as usize;
+                let data = frame.plane_data_mut(0).map_err(|_| gst::FlowError::Error)?;
+                for y in 0..mapped.height() {
+                    let row = mapped.row(y);
+                    let start = y as usize * stride;
+                    data[start..start + row.len()].copy_from_slice(row);
+                }
+            }
+            Ok(buffer)
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for GeyserSrc {
+        const NAME: &'static str = "GeyserSrc";
+        type Type = super::GeyserSrc;
+        type ParentType = gstreamer_base::PushSrc;
+    }
+
+    impl ObjectImpl for GeyserSrc {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: LazyLock<Vec<glib::ParamSpec>> = LazyLock::new(|| {
+                vec![glib::ParamSpecString::builder("socket-path")
+                    .nick("Socket path")
+                    .blurb("Unix socket of the producer to connect to")
+                    .mutable_ready()
+                    .build()]
+            });
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            if pspec.name() == "socket-path" {
+                *self.socket_path.lock().unwrap() = value.get().unwrap();
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            match pspec.name() {
+                "socket-path" => self.socket_path.lock().unwrap().to_value(),
+                // GObject only asks for the properties registered in `properties`
+                name => unreachable!("geysersrc has no property {}", name),
+            }
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+            obj.set_live(true);
+            obj.set_format(gst::Format::Time);
+        }
+    }
+
+    impl GstObjectImpl for GeyserSrc {}
+
+    impl ElementImpl for GeyserSrc {
+        fn metadata() -> Option<&'static gst::subclass::ElementMetadata> {
+            static METADATA: LazyLock<gst::subclass::ElementMetadata> = LazyLock::new(|| {
+                gst::subclass::ElementMetadata::new(
+                    "Geyser texture source",
+                    "Source/Video",
+                    "Outputs the frames of a texture shared over a Geyser session",
+                    "Geyser contributors",
+                )
+            });
+            Some(&*METADATA)
+        }
+
+        fn pad_templates() -> &'static [gst::PadTemplate] {
+            static PAD_TEMPLATES: LazyLock<Vec<gst::PadTemplate>> = LazyLock::new(|| {
+                vec![gst::PadTemplate::new(
+                    "src",
+                    gst::PadDirection::Src,
+                    gst::PadPresence::Always,
+                    &super::super::template_caps(),
+                )
+                .unwrap()]
+            });
+            PAD_TEMPLATES.as_ref()
+        }
+    }
+
+    impl BaseSrcImpl for GeyserSrc {
+        fn start(&self) -> Result<(), gst::ErrorMessage> {
+            let path = self.socket_path.lock().unwrap().clone().ok_or_else(|| {
+                gst::error_msg!(gst::ResourceError::Settings, ["The socket-path property is not set"])
+            })?;
+            let shared = Arc::new(Shared {
+                mailbox: Mutex::new(Mailbox::default()),
+                changed: Condvar::new(),
+                stop: AtomicBool::new(false),
+            });
+            let session = {
+                let shared = shared.clone();
+                std::thread::Builder::new()
+                    .name("geysersrc-session".to_string())
+                    .spawn(move || run_session(path, shared))
+                    .map_err(|e| {
+                        gst::error_msg!(gst::ResourceError::OpenRead, ["Failed to start session thread: {}", e])
+                    })?
+            };
+            let state = State {
+                shared,
+                session: Some(session),
+                info: None,
+                dma_buf_output: false,
+                epoch: None,
+            };
+
+            // Caps are only known once the producer shares its texture
+            let deadline = Instant::now() + FIRST_TEXTURE_TIMEOUT;
+            let mut mailbox = state.shared.mailbox.lock().unwrap();
+            let error = loop {
+                if mailbox.texture.is_some() {
+                    break None;
+                }
+                match &mailbox.ended {
+                    Some(Ok(())) => break Some("The producer ended the session".to_string()),
+                    Some(Err(e)) => break Some(e.clone()),
+                    None => {}
+                }
+                let timeout = deadline.saturating_duration_since(Instant::now());
+                if timeout.is_zero() {
+                    break Some(format!("The producer shared no texture within {:?}", FIRST_TEXTURE_TIMEOUT));
+                }
+                mailbox = state.shared.changed.wait_timeout(mailbox, timeout).unwrap().0;
+            };
+            drop(mailbox);
+            if let Some(error) = error {
+                state.stop();
+                return Err(gst::error_msg!(gst::ResourceError::OpenRead, ["{}", error]));
+            }
+
+            *self.state.lock().unwrap() = Some(state);
+            Ok(())
+        }
+
+        fn stop(&self) -> Result<(), gst::ErrorMessage> {
+            if let Some(state) = self.state.lock().unwrap().take() {
+                state.stop();
+            }
+            Ok(())
+        }
+
+        fn caps(&self, filter: Option<&gst::Caps>) -> Option<gst::Caps> {
+            // Dma-buf output first: it avoids a copy whenever downstream takes it
+            let mut caps = match self.descriptor() {
+                Some(descriptor) => {
+                    let mut caps = super::super::descriptor_caps(&descriptor, true)?;
+                    caps.merge(super::super::descriptor_caps(&descriptor, false)?);
+                    caps
+                }
+                None => super::super::template_caps(),
+            };
+            if let Some(filter) = filter {
+                caps = filter.intersect_with_mode(&caps, gst::CapsIntersectMode::First);
+            }
+            Some(caps)
+        }
+
+        fn set_caps(&self, caps: &gst::Caps) -> Result<(), gst::LoggableError> {
+            let info = gst_video::VideoInfo::from_caps(caps)
+                .map_err(|_| gst::loggable_error!(CAT, "Invalid caps {}", caps))?;
+            let dma_buf_output = caps
+                .features(0)
+                .is_some_and(|features| features.contains(gstreamer_allocators::CAPS_FEATURE_MEMORY_DMABUF));
+            gst::debug!(CAT, imp = self, "Negotiated {} (dma-buf: {})", caps, dma_buf_output);
+
+            let mut state = self.state.lock().unwrap();
+            let state = state.as_mut().ok_or_else(|| gst::loggable_error!(CAT, "Caps set before start"))?;
+            state.info = Some(info);
+            state.dma_buf_output = dma_buf_output;
+            Ok(())
+        }
+
+        fn unlock(&self) -> Result<(), gst::ErrorMessage> {
+            self.flushing.store(true, Ordering::SeqCst);
+            Ok(())
+        }
+
+        fn unlock_stop(&self) -> Result<(), gst::ErrorMessage> {
+            self.flushing.store(false, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    impl PushSrcImpl for GeyserSrc {
+        fn create(&self, _buffer: Option<&mut gst::BufferRef>) -> Result<CreateSuccess, gst::FlowError> {
+            let shared = self.state.lock().unwrap().as_ref().ok_or(gst::FlowError::Flushing)?.shared.clone();
+            loop {
+                let (texture, timestamp) = self.next_frame(&shared)?;
+                if !self.negotiated_for(texture.descriptor()) {
+                    let descriptor = texture.descriptor();
+                    gst::debug!(CAT, imp = self, "Texture changed to {}x{}", descriptor.width, descriptor.height);
+                    // `negotiate` asks for our caps, so the state must not be locked
+                    if let Err(e) = BaseSrcImpl::negotiate(self) {
+                        e.log_with_imp(self);
+                        return Err(gst::FlowError::NotNegotiated);
+                    }
+                    // The caps follow the newest texture, which may already have replaced this one
+                    if !self.negotiated_for(texture.descriptor()) {
+                        continue;
+                    }
+                }
+
+                let mut guard = self.state.lock().unwrap();
+                let state = guard.as_mut().ok_or(gst::FlowError::Flushing)?;
+                let info = state.info.clone().ok_or(gst::FlowError::NotNegotiated)?;
+                let mut buffer = if state.dma_buf_output {
+                    self.dma_buf_buffer(&texture, &info)?
+                } else {
+                    self.system_memory_buffer(&texture, &info)?
+                };
+                let pts = self.pts(state, timestamp);
+                buffer.get_mut().unwrap().set_pts(pts);
+                return Ok(CreateSuccess::NewBuffer(buffer));
+            }
+        }
+    }
+}
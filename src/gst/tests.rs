@@ -0,0 +1,104 @@
+//! Unit tests for GStreamer format and caps mapping
+
+use std::sync::{Arc, Mutex};
+
+use super::*;
+use crate::common::{TextureMemoryLocation, TextureTiling, TextureUsage};
+
+fn descriptor(format: TextureFormat) -> TextureDescriptor {
+    TextureDescriptor::new(320, 240, format, vec![TextureUsage::CopySrc])
+        .with_tiling(TextureTiling::Linear)
+        .with_memory_location(TextureMemoryLocation::CpuToGpu)
+}
+
+#[test]
+fn test_video_formats_roundtrip() {
+    for format in FORMATS {
+        assert_eq!(texture_format(video_format(format).unwrap()), Some(format));
+    }
+    assert_eq!(video_format(TextureFormat::Rgba16Float), None);
+    assert_eq!(texture_format(gst_video::VideoFormat::I420), None);
+}
+
+#[test]
+fn test_descriptor_caps_are_fixed() {
+    gst::init().unwrap();
+    let caps = descriptor_caps(&descriptor(TextureFormat::Bgra8Unorm), false).unwrap();
+    assert!(caps.is_fixed());
+    let info = gst_video::VideoInfo::from_caps(&caps).unwrap();
+    assert_eq!(info.format(), gst_video::VideoFormat::Bgra);
+    assert_eq!((info.width(), info.height()), (320, 240));
+
+    let dma_buf = descriptor_caps(&descriptor(TextureFormat::Bgra8Unorm), true).unwrap();
+    assert!(dma_buf.features(0).unwrap().contains(gstreamer_allocators::CAPS_FEATURE_MEMORY_DMABUF));
+    assert!(template_caps().can_intersect(&dma_buf));
+    assert!(descriptor_caps(&descriptor(TextureFormat::Rgba16Float), false).is_none());
+}
+
+// Needs the GStreamer base plugins and a Vulkan device that exports dma-bufs; skipped
+// elsewhere
+#[test]
+fn test_videotestsrc_round_trip() {
+    if let Err(e) = gst::init() {
+        eprintln!("Skipping: {}", e);
+        return;
+    }
+    if ["videotestsrc", "capsfilter", "fakesink"].iter().any(|name| gst::ElementFactory::find(name).is_none()) {
+        eprintln!("Skipping: the GStreamer base plugins are not installed");
+        return;
+    }
+    register(None).unwrap();
+
+    let socket = std::env::temp_dir().join(format!("geyser-gst-test-{}.sock", std::process::id()));
+    let socket = socket.to_str().unwrap();
+    // Solid ARGB 0xff112233, which BGRA stores as 33 22 11 ff
+    let producer = gst::parse::launch(&format!(
+        "videotestsrc is-live=true pattern=solid-color foreground-color=0xff112233 \
+         ! video/x-raw,format=BGRA,width=64,height=48 ! geysersink socket-path={}",
+        socket
+    ))
+    .unwrap();
+    if let Err(e) = producer.set_state(gst::State::Playing) {
+        eprintln!("Skipping: geysersink could not start ({})", e);
+        let _ = producer.set_state(gst::State::Null);
+        return;
+    }
+
+    let consumer = gst::parse::launch(&format!(
+        "geysersrc socket-path={} num-buffers=5 ! video/x-raw,format=BGRA ! fakesink name=sink signal-handoffs=true",
+        socket
+    ))
+    .unwrap()
+    .downcast::<gst::Pipeline>()
+    .unwrap();
+    let received = Arc::new(Mutex::new(Vec::new()));
+    {
+        let received = received.clone();
+        consumer.by_name("sink").unwrap().connect("handoff", false, move |values| {
+            let buffer = values[1].get::<gst::Buffer>().unwrap();
+            let map = buffer.map_readable().unwrap();
+            received.lock().unwrap().push((map.size(), map[..4].to_vec()));
+            None
+        });
+    }
+    consumer.set_state(gst::State::Playing).unwrap();
+
+    let message = consumer.bus().unwrap().timed_pop_filtered(
+        gst::ClockTime::from_seconds(20),
+        &[gst::MessageType::Eos, gst::MessageType::Error],
+    );
+    consumer.set_state(gst::State::Null).unwrap();
+    producer.set_state(gst::State::Null).unwrap();
+    match message.as_ref().map(|message| message.view()) {
+        Some(gst::MessageView::Eos(_)) => {}
+        Some(gst::MessageView::Error(e)) => panic!("Consumer failed: {}", e.error()),
+        _ => panic!("Timed out waiting for frames"),
+    }
+
+    let received = received.lock().unwrap();
+    assert_eq!(received.len(), 5);
+    for (size, first_texel) in received.iter() {
+        assert_eq!(*size, 64 * 48 * 4);
+        assert_eq!(first_texel, &[0x33, 0x22, 0x11, 0xff]);
+    }
+}
@@ -0,0 +1,243 @@
+//! Dma-buf textures for consumers that read frames on the CPU or hand the descriptor
+//! on to another API, without a GPU context of their own.
+
+use std::{
+    any::Any,
+    os::fd::{AsRawFd, FromRawFd, IntoRawFd, OwnedFd},
+};
+
+use crate::common::{
+    bytes_per_texel, to_drm_fourcc, ApiTextureHandle, DmaBufHandle, TextureDescriptor, TextureFormat, TextureUsage,
+    DRM_FORMAT_MOD_LINEAR,
+};
+use crate::error::{GeyserError, Result};
+use crate::{SharedTexture, TextureShareManager};
+
+// `DMA_BUF_IOCTL_SYNC` from linux/dma-buf.h
+const DMA_BUF_IOCTL_SYNC: libc::c_ulong = 0x4008_6200;
+const DMA_BUF_SYNC_READ: u64 = 1 << 0;
+const DMA_BUF_SYNC_START: u64 = 0;
+const DMA_BUF_SYNC_END: u64 = 1 << 2;
+
+/// A linear dma-buf imported by `DmaBufShareManager`. Owns its descriptor.
+#[derive(Debug)]
+pub struct DmaBufTexture {
+    descriptor: TextureDescriptor,
+    handle: DmaBufHandle,
+    fd: OwnedFd,
+}
+
+impl DmaBufTexture {
+    /// Returns the dma-buf's layout. Its `fd` stays owned by the texture.
+    pub fn handle(&self) -> &DmaBufHandle {
+        &self.handle
+    }
+
+    pub fn descriptor(&self) -> &TextureDescriptor {
+        &self.descriptor
+    }
+
+    /// Duplicates the descriptor, e.g. to wrap it in another API's memory object.
+    pub fn try_clone_fd(&self) -> Result<OwnedFd> {
+        self.fd
+            .try_clone()
+            .map_err(|e| GeyserError::IpcError(format!("Failed to duplicate dma-buf descriptor: {}", e)))
+    }
+
+    /// Duplicates the texture with a descriptor of its own, e.g. to read it on another
+    /// thread while the session that imported it moves on.
+    pub fn try_clone(&self) -> Result<Self> {
+        let fd = self.try_clone_fd()?;
+        Ok(Self {
+            descriptor: self.descriptor.clone(),
+            handle: DmaBufHandle {
+                fd: fd.as_raw_fd(),
+                ..self.handle.clone()
+            },
+            fd,
+        })
+    }
+
+    // Bytes of texels in one row; import checked the format has a texel size
+    fn row_size(&self) -> usize {
+        self.descriptor.width as usize * bytes_per_texel(self.descriptor.format).unwrap_or(0) as usize
+    }
+
+    /// Returns the number of bytes from the start of the dma-buf to the end of the
+    /// last row.
+    pub fn size(&self) -> usize {
+        self.handle.offset as usize
+            + self.handle.stride as usize * self.descriptor.height.saturating_sub(1) as usize
+            + self.row_size()
+    }
+
+    /// Maps the dma-buf for reading. The CPU access is bracketed with
+    /// `DMA_BUF_IOCTL_SYNC`, so the mapping sees the producer's completed writes.
+    pub fn map(&self) -> Result<DmaBufMapping<'_>> {
+        let len = self.size();
+        let ptr = unsafe {
+            libc::mmap(std::ptr::null_mut(), len, libc::PROT_READ, libc::MAP_SHARED, self.fd.as_raw_fd(), 0)
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(GeyserError::IpcError(format!(
+                "Failed to map dma-buf: {}",
+                std::io::Error::last_os_error()
+            )));
+        }
+        let mapping = DmaBufMapping { texture: self, ptr, len };
+        mapping.sync(DMA_BUF_SYNC_START)?;
+        Ok(mapping)
+    }
+}
+
+impl SharedTexture for DmaBufTexture {
+    fn width(&self) -> u32 {
+        self.descriptor.width
+    }
+
+    fn height(&self) -> u32 {
+        self.descriptor.height
+    }
+
+    fn format(&self) -> TextureFormat {
+        self.descriptor.format
+    }
+
+    fn usage(&self) -> &[TextureUsage] {
+        &self.descriptor.usage
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// A read-only CPU mapping of a `DmaBufTexture`, unmapped when dropped.
+pub struct DmaBufMapping<'a> {
+    texture: &'a DmaBufTexture,
+    ptr: *mut libc::c_void,
+    len: usize,
+}
+
+impl DmaBufMapping<'_> {
+    // Descriptors that are not dma-bufs, such as memfds, need no cache maintenance
+    fn sync(&self, flags: u64) -> Result<()> {
+        let sync = DMA_BUF_SYNC_READ | flags;
+        let result = unsafe { libc::ioctl(self.texture.fd.as_raw_fd(), DMA_BUF_IOCTL_SYNC, &sync as *const u64) };
+        let error = std::io::Error::last_os_error();
+        if result < 0 && error.raw_os_error() != Some(libc::ENOTTY) {
+            return Err(GeyserError::IpcError(format!("Failed to synchronize dma-buf access: {}", error)));
+        }
+        Ok(())
+    }
+
+    /// Returns the number of bytes between the starts of consecutive rows.
+    pub fn row_pitch(&self) -> usize {
+        self.texture.handle.stride as usize
+    }
+
+    /// Returns the number of rows.
+    pub fn height(&self) -> u32 {
+        self.texture.descriptor.height
+    }
+
+    /// Returns the texels of row `y`, without the padding after them.
+    pub fn row(&self, y: u32) -> &[u8] {
+        let start = self.texture.handle.offset as usize + y as usize * self.row_pitch();
+        &self.as_bytes()[start..start + self.texture.row_size()]
+    }
+
+    /// Returns the whole mapping from the start of the dma-buf, padding included.
+    pub fn as_bytes(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.ptr as *const u8, self.len) }
+    }
+}
+
+impl Drop for DmaBufMapping<'_> {
+    fn drop(&mut self) {
+        let _ = self.sync(DMA_BUF_SYNC_END);
+        unsafe { libc::munmap(self.ptr, self.len) };
+    }
+}
+
+/// Imports linear dma-bufs as `DmaBufTexture`s, for consumers such as media pipelines
+/// that read frames on the CPU or pass the descriptor on. It cannot create textures,
+/// and it rejects packages carrying semaphores or fences.
+#[derive(Debug, Default)]
+pub struct DmaBufShareManager;
+
+impl DmaBufShareManager {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl TextureShareManager for DmaBufShareManager {
+    fn create_shareable_texture(&self, _descriptor: &TextureDescriptor) -> Result<Box<dyn SharedTexture>> {
+        Err(GeyserError::OperationNotSupported)
+    }
+
+    fn export_texture(&self, texture: &dyn SharedTexture) -> Result<ApiTextureHandle> {
+        let texture = texture
+            .as_any()
+            .downcast_ref::<DmaBufTexture>()
+            .ok_or(GeyserError::Other("Provided texture is not a DmaBufTexture".to_string()))?;
+        let fd = texture.try_clone_fd()?;
+        Ok(ApiTextureHandle::DmaBuf(DmaBufHandle {
+            fd: fd.into_raw_fd(),
+            ..texture.handle.clone()
+        }))
+    }
+
+    fn import_texture(&self, handle: ApiTextureHandle, descriptor: &TextureDescriptor) -> Result<Box<dyn SharedTexture>> {
+        let handle = match handle {
+            ApiTextureHandle::DmaBuf(handle) => handle,
+            #[allow(unreachable_patterns)]
+            _ => return Err(GeyserError::InvalidTextureHandle),
+        };
+        // Owned from here on, so it is closed on every error path
+        let fd = unsafe { OwnedFd::from_raw_fd(handle.fd) };
+
+        if handle.fourcc != to_drm_fourcc(descriptor.format)? {
+            return Err(GeyserError::UnsupportedFormat(format!(
+                "dma-buf fourcc {:#x} does not hold {}",
+                handle.fourcc, descriptor.format
+            )));
+        }
+        if handle.modifier != DRM_FORMAT_MOD_LINEAR {
+            return Err(GeyserError::UnsupportedFormat(format!(
+                "dma-buf modifier {:#x} cannot be read on the CPU; only linear dma-bufs can",
+                handle.modifier
+            )));
+        }
+        let texel_size = bytes_per_texel(descriptor.format).ok_or_else(|| {
+            GeyserError::UnsupportedTextureFormat(format!("{} has no linear layout", descriptor.format))
+        })?;
+        if (handle.stride as u64) < descriptor.width as u64 * texel_size as u64 {
+            return Err(GeyserError::Other(format!(
+                "dma-buf stride {} does not fit {} texels of {} bytes",
+                handle.stride, descriptor.width, texel_size
+            )));
+        }
+
+        Ok(Box::new(DmaBufTexture {
+            descriptor: descriptor.clone(),
+            handle: DmaBufHandle {
+                fd: fd.as_raw_fd(),
+                ..handle
+            },
+            fd,
+        }))
+    }
+
+    fn release_texture_handle(&self, handle: ApiTextureHandle) -> Result<()> {
+        match handle {
+            ApiTextureHandle::DmaBuf(handle) => {
+                drop(unsafe { OwnedFd::from_raw_fd(handle.fd) });
+                Ok(())
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(GeyserError::InvalidTextureHandle),
+        }
+    }
+}
@@ -0,0 +1,354 @@
+//! Named publish/subscribe of shared textures between processes (Linux).
+//!
+//! A producer publishes a `TextureSharePackage` under a name; any number of consumers
+//! subscribe to that name without a server process in between:
+//!
+//! - The name, descriptor and a generation counter are listed in a shared-memory
+//!   `Registry` that every process opens.
+//! - The handles themselves are file descriptors, so the publisher serves them on a
+//!   Unix socket and passes them with `SCM_RIGHTS` to each consumer that connects.
+//! - When the publisher replaces the texture (for example after a resize) it bumps the
+//!   generation; `Subscription::poll` notices and fetches the new handles.
+//!
+//! For a long-lived one-to-one connection with format negotiation, resizes and
+//! liveness checks, see `session`. Consumers without a GPU context can import linear
+//! dma-bufs with `DmaBufShareManager`.
+//!
+//! ```ignore
+//! // Producer
+//! let package = manager.export_texture_with_sync(texture.as_ref())?;
+//! let publication = geyser::ipc::publish("camera0", &descriptor, package)?;
+//!
+//! // Consumer
+//! let mut subscription = geyser::ipc::subscribe("camera0")?;
+//! let package = subscription.take_package().unwrap();
+//! let texture = manager.import_texture_with_sync(package, subscription.descriptor())?;
+//! ```
+
+pub mod dmabuf;
+pub mod registry;
+pub mod session;
+pub mod socket;
+pub mod wire;
+
+pub use dmabuf::{DmaBufMapping, DmaBufShareManager, DmaBufTexture};
+pub use registry::{default_registry_path, process_start_time, Registry, RegistryEntry};
+pub use session::{
+    ConsumerSession, LoopbackTransport, ProducerSession, SessionConfig, SessionEvent, SessionState, Transport,
+    UnixTransport,
+};
+
+use std::{
+    os::{
+        fd::{AsRawFd, BorrowedFd, OwnedFd, RawFd},
+        unix::net::{UnixListener, UnixStream},
+    },
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    thread::JoinHandle,
+};
+
+use crate::common::{TextureDescriptor, TextureSharePackage};
+use crate::error::{GeyserError, Result};
+use wire::{read_descriptor, read_package, write_descriptor, write_package, Reader, Writer};
+
+/// Publishes `package` under `name` in the default registry.
+pub fn publish(name: &str, descriptor: &TextureDescriptor, package: TextureSharePackage) -> Result<Publication> {
+    Publication::new(&Registry::open_default()?, name, descriptor, package)
+}
+
+/// Subscribes to the texture published under `name` in the default registry.
+pub fn subscribe(name: &str) -> Result<Subscription> {
+    Subscription::new(&Registry::open_default()?, name)
+}
+
+// Numbers the publications of this process, which share its pid and start time, so
+// each gets its own socket
+static NEXT_PUBLICATION: AtomicU64 = AtomicU64::new(0);
+
+// What the publisher currently serves
+struct PublishedTexture {
+    generation: u64,
+    descriptor: TextureDescriptor,
+    package: TextureSharePackage,
+}
+
+/// A texture published in a registry. Serves its handles to subscribers from a
+/// background thread and unpublishes the texture when dropped.
+///
+/// The descriptors in the published package stay owned by the caller and must remain
+/// open while they are published; subscribers receive duplicates.
+pub struct Publication {
+    name: String,
+    registry: Registry,
+    // Identifies this process in the registry together with its pid
+    start_time: u64,
+    socket_path: PathBuf,
+    published: Arc<Mutex<PublishedTexture>>,
+    stop: Arc<AtomicBool>,
+    server: Option<JoinHandle<()>>,
+}
+
+impl Publication {
+    /// Publishes `package` under `name` in `registry`.
+    pub fn new(registry: &Registry, name: &str, descriptor: &TextureDescriptor, package: TextureSharePackage) -> Result<Self> {
+        registry::validate_name(name)?;
+        // Fail before binding the socket if the package cannot be sent
+        encode_texture(0, descriptor, &package)?;
+
+        let pid = std::process::id();
+        let start_time = registry::process_start_time(pid)
+            .ok_or_else(|| GeyserError::IpcError("Failed to read the process start time".to_string()))?;
+        let runtime_dir = std::env::var_os("XDG_RUNTIME_DIR").map(PathBuf::from).unwrap_or_else(std::env::temp_dir);
+        let publication = NEXT_PUBLICATION.fetch_add(1, Ordering::Relaxed);
+        let socket_path = runtime_dir.join(format!("geyser-{}-{}-{}.sock", name, pid, publication));
+        let _ = std::fs::remove_file(&socket_path);
+        let listener = UnixListener::bind(&socket_path)
+            .map_err(|e| GeyserError::IpcError(format!("Failed to bind {}: {}", socket_path.display(), e)))?;
+
+        let registry = Registry::open(registry.path())?;
+        let entry = RegistryEntry {
+            name: name.to_string(),
+            pid,
+            start_time,
+            generation: 0,
+            descriptor: descriptor.clone(),
+            socket_path: socket_path.clone(),
+        };
+        if let Err(e) = registry.insert(&entry) {
+            let _ = std::fs::remove_file(&socket_path);
+            return Err(e);
+        }
+
+        let published = Arc::new(Mutex::new(PublishedTexture {
+            generation: 0,
+            descriptor: descriptor.clone(),
+            package,
+        }));
+        let stop = Arc::new(AtomicBool::new(false));
+        let server = {
+            let published = published.clone();
+            let stop = stop.clone();
+            std::thread::spawn(move || serve(listener, published, stop))
+        };
+
+        Ok(Self {
+            name: name.to_string(),
+            registry,
+            start_time,
+            socket_path,
+            published,
+            stop,
+            server: Some(server),
+        })
+    }
+
+    /// Returns the name the texture is published under.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the generation subscribers currently receive.
+    pub fn generation(&self) -> u64 {
+        self.published.lock().unwrap().generation
+    }
+
+    /// Replaces the published texture, e.g. after re-exporting it at a new size, and
+    /// returns the new generation. Subscribers pick it up on their next `poll`.
+    ///
+    /// The previous package's descriptors are no longer served and may be closed.
+    pub fn update(&self, descriptor: &TextureDescriptor, package: TextureSharePackage) -> Result<u64> {
+        let mut published = self.published.lock().unwrap();
+        let generation = published.generation + 1;
+        encode_texture(generation, descriptor, &package)?;
+
+        self.registry.insert(&RegistryEntry {
+            name: self.name.clone(),
+            pid: std::process::id(),
+            start_time: self.start_time,
+            generation,
+            descriptor: descriptor.clone(),
+            socket_path: self.socket_path.clone(),
+        })?;
+        *published = PublishedTexture {
+            generation,
+            descriptor: descriptor.clone(),
+            package,
+        };
+        Ok(generation)
+    }
+}
+
+impl Drop for Publication {
+    fn drop(&mut self) {
+        let _ = self.registry.remove(&self.name, std::process::id());
+        // Wake the server thread out of `accept` so it sees the stop flag
+        self.stop.store(true, Ordering::SeqCst);
+        let _ = UnixStream::connect(&self.socket_path);
+        if let Some(server) = self.server.take() {
+            let _ = server.join();
+        }
+        let _ = std::fs::remove_file(&self.socket_path);
+    }
+}
+
+fn encode_texture(generation: u64, descriptor: &TextureDescriptor, package: &TextureSharePackage) -> Result<Writer> {
+    let mut writer = Writer::new();
+    writer.u64(generation);
+    write_descriptor(&mut writer, descriptor);
+    write_package(&mut writer, package)?;
+    Ok(writer)
+}
+
+// Sends the current texture to every consumer that connects
+fn serve(listener: UnixListener, published: Arc<Mutex<PublishedTexture>>, stop: Arc<AtomicBool>) {
+    for stream in listener.incoming() {
+        if stop.load(Ordering::SeqCst) {
+            break;
+        }
+        let Ok(stream) = stream else {
+            continue;
+        };
+        // Encoded under the lock with duplicated descriptors, so `update` neither waits
+        // for a slow consumer nor closes descriptors that are still being sent
+        let message = {
+            let published = published.lock().unwrap();
+            encode_texture(published.generation, &published.descriptor, &published.package).and_then(|writer| {
+                let (bytes, fds) = writer.finish();
+                Ok((bytes, duplicate_fds(&fds)?))
+            })
+        };
+        if let Ok((bytes, fds)) = message {
+            let fds: Vec<_> = fds.iter().map(|fd| fd.as_raw_fd()).collect();
+            // A consumer that hangs up early only affects itself
+            let _ = socket::send_message(&stream, &bytes, &fds);
+        }
+    }
+}
+
+fn duplicate_fds(fds: &[RawFd]) -> Result<Vec<OwnedFd>> {
+    fds.iter()
+        .map(|&fd| {
+            unsafe { BorrowedFd::borrow_raw(fd) }
+                .try_clone_to_owned()
+                .map_err(|e| GeyserError::IpcError(format!("Failed to duplicate descriptor: {}", e)))
+        })
+        .collect()
+}
+
+/// A subscription to a texture published in a registry.
+pub struct Subscription {
+    name: String,
+    registry: Registry,
+    // The publication the handles came from; a publisher restarting under the same
+    // name starts over at generation 0 with a different pid, start time or socket
+    pid: u32,
+    start_time: u64,
+    socket_path: PathBuf,
+    generation: u64,
+    descriptor: TextureDescriptor,
+    // Handles received for `generation` that the caller has not taken yet
+    package: Option<TextureSharePackage>,
+}
+
+impl Subscription {
+    /// Looks up `name` in `registry` and fetches the published handles.
+    pub fn new(registry: &Registry, name: &str) -> Result<Self> {
+        let registry = Registry::open(registry.path())?;
+        let entry = registry
+            .lookup(name)?
+            .ok_or_else(|| GeyserError::IpcError(format!("Nothing is published as '{}'", name)))?;
+        let (generation, descriptor, package) = fetch(&entry)?;
+
+        Ok(Self {
+            name: name.to_string(),
+            registry,
+            pid: entry.pid,
+            start_time: entry.start_time,
+            socket_path: entry.socket_path,
+            generation,
+            descriptor,
+            package: Some(package),
+        })
+    }
+
+    /// Returns the name subscribed to.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the generation of the most recently received handles.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Returns the descriptor of the most recently received handles.
+    pub fn descriptor(&self) -> &TextureDescriptor {
+        &self.descriptor
+    }
+
+    /// Takes the most recently received handles, to be imported by a
+    /// `TextureShareManager`. Returns `None` if they were already taken.
+    pub fn take_package(&mut self) -> Option<TextureSharePackage> {
+        self.package.take()
+    }
+
+    /// Checks the registry for a new generation or a new publisher and fetches its
+    /// handles if there is one. Returns true if `take_package` now yields a new texture.
+    ///
+    /// Fails if the publisher is gone.
+    pub fn poll(&mut self) -> Result<bool> {
+        let entry = self
+            .registry
+            .lookup(&self.name)?
+            .ok_or_else(|| GeyserError::IpcError(format!("'{}' is no longer published", self.name)))?;
+        let same_publication =
+            entry.pid == self.pid && entry.start_time == self.start_time && entry.socket_path == self.socket_path;
+        if same_publication && entry.generation == self.generation {
+            return Ok(false);
+        }
+
+        let (generation, descriptor, package) = fetch(&entry)?;
+        if let Some(stale) = self.package.replace(package) {
+            wire::close_package_fds(&stale);
+        }
+        self.pid = entry.pid;
+        self.start_time = entry.start_time;
+        self.socket_path = entry.socket_path;
+        self.generation = generation;
+        self.descriptor = descriptor;
+        Ok(true)
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        if let Some(package) = self.package.take() {
+            wire::close_package_fds(&package);
+        }
+    }
+}
+
+// Connects to the publisher's socket and receives its current texture
+fn fetch(entry: &RegistryEntry) -> Result<(u64, TextureDescriptor, TextureSharePackage)> {
+    let stream = UnixStream::connect(&entry.socket_path)
+        .map_err(|e| GeyserError::IpcError(format!("Failed to connect to '{}': {}", entry.name, e)))?;
+    let (bytes, fds) = socket::recv_message(&stream)
+        .map_err(|e| GeyserError::IpcError(format!("Failed to receive '{}': {}", entry.name, e)))?;
+
+    let mut reader = Reader::new(&bytes, fds);
+    let generation = reader.u64()?;
+    let descriptor = read_descriptor(&mut reader)?;
+    let package = read_package(&mut reader)?;
+    if let Err(e) = reader.finish() {
+        wire::close_package_fds(&package);
+        return Err(e);
+    }
+    Ok((generation, descriptor, package))
+}
+
+#[cfg(test)]
+mod tests;
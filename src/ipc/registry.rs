@@ -0,0 +1,279 @@
+//! Shared-memory directory of published textures.
+//!
+//! The registry is a fixed-size file in the user's runtime directory (see
+//! `default_registry_path`) holding one slot per published texture: its name, the
+//! publishing process, a generation counter, the texture descriptor and the socket the
+//! handles are served from. Every process of the user opens the same file, so no broker
+//! is needed. Access is serialized with `flock`, slots left behind by processes that
+//! died are reclaimed on the next publish, and slots that fail to decode are skipped.
+
+use std::{
+    fs::{File, OpenOptions},
+    os::unix::fs::{FileExt, OpenOptionsExt},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use super::wire::{read_descriptor, write_descriptor, Reader, Writer};
+use crate::common::TextureDescriptor;
+use crate::error::{GeyserError, Result};
+
+/// Returns the registry opened by `ipc::publish` and `ipc::subscribe`:
+/// `$XDG_RUNTIME_DIR/geyser-registry`, or a per-user file in the temporary directory
+/// when no runtime directory is set.
+pub fn default_registry_path() -> PathBuf {
+    match std::env::var_os("XDG_RUNTIME_DIR") {
+        Some(runtime_dir) => PathBuf::from(runtime_dir).join("geyser-registry"),
+        None => std::env::temp_dir().join(format!("geyser-registry-{}", unsafe { libc::getuid() })),
+    }
+}
+
+/// Longest name a texture can be published under, in bytes.
+pub const MAX_NAME_LEN: usize = 63;
+
+const MAGIC: &[u8; 8] = b"GEYSERRG";
+// Version 2 added the publisher's start time
+const VERSION: u32 = 2;
+const HEADER_SIZE: u64 = 16;
+const SLOT_COUNT: u32 = 64;
+const SLOT_SIZE: usize = 512;
+
+/// A texture published in the registry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegistryEntry {
+    pub name: String,
+    /// Process that published the texture
+    pub pid: u32,
+    /// Start time of the publishing process in clock ticks since boot (see
+    /// `process_start_time`), which tells it apart from a later process reusing `pid`
+    pub start_time: u64,
+    /// Incremented whenever the publisher replaces the texture, e.g. on resize
+    pub generation: u64,
+    pub descriptor: TextureDescriptor,
+    /// Unix socket the publisher serves the texture's handles on
+    pub socket_path: PathBuf,
+}
+
+/// Handle to a registry file. Cheap to open; each process opens its own.
+#[derive(Debug)]
+pub struct Registry {
+    path: PathBuf,
+    // flock does not exclude threads sharing one open file, so they take this first
+    file: Mutex<File>,
+}
+
+impl Registry {
+    /// Opens the registry at `default_registry_path()`, creating it if needed.
+    pub fn open_default() -> Result<Self> {
+        Self::open(default_registry_path())
+    }
+
+    /// Opens the registry at `path`, creating it if needed. A new file is only
+    /// accessible to the current user.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .mode(0o600)
+            .open(&path)
+            .map_err(|e| GeyserError::IpcError(format!("Failed to open registry {}: {}", path.display(), e)))?;
+
+        let registry = Self {
+            path,
+            file: Mutex::new(file),
+        };
+        registry.with_file(true, |file| {
+            let size = HEADER_SIZE + SLOT_COUNT as u64 * SLOT_SIZE as u64;
+            let mut header = [0u8; HEADER_SIZE as usize];
+            if file.metadata().map_err(io_error)?.len() == 0 {
+                header[..8].copy_from_slice(MAGIC);
+                header[8..12].copy_from_slice(&VERSION.to_le_bytes());
+                header[12..16].copy_from_slice(&SLOT_COUNT.to_le_bytes());
+                file.set_len(size).map_err(io_error)?;
+                file.write_all_at(&header, 0).map_err(io_error)?;
+                return Ok(());
+            }
+
+            file.read_exact_at(&mut header, 0).map_err(io_error)?;
+            let version = u32::from_le_bytes(header[8..12].try_into().unwrap());
+            let slot_count = u32::from_le_bytes(header[12..16].try_into().unwrap());
+            if &header[..8] != MAGIC || version != VERSION || slot_count != SLOT_COUNT {
+                return Err(GeyserError::IpcError("Registry file has an unknown layout".to_string()));
+            }
+            Ok(())
+        })?;
+        Ok(registry)
+    }
+
+    /// Returns the path of the registry file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the live entry published under `name`, if any.
+    pub fn lookup(&self, name: &str) -> Result<Option<RegistryEntry>> {
+        Ok(self.entries()?.into_iter().find(|entry| entry.name == name))
+    }
+
+    /// Returns every entry whose publisher is still running. Slots that fail to decode
+    /// are skipped.
+    pub fn entries(&self) -> Result<Vec<RegistryEntry>> {
+        self.with_file(false, |file| {
+            let mut entries = Vec::new();
+            for index in 0..SLOT_COUNT {
+                if let Some(entry) = read_slot(file, index)? {
+                    if process_alive(entry.pid, entry.start_time) {
+                        entries.push(entry);
+                    }
+                }
+            }
+            Ok(entries)
+        })
+    }
+
+    /// Adds `entry`, or replaces the entry of the same name published by this process.
+    /// Fails if another live process has published the name.
+    pub(crate) fn insert(&self, entry: &RegistryEntry) -> Result<()> {
+        validate_name(&entry.name)?;
+        let slot = encode_slot(entry)?;
+        self.with_file(true, |file| {
+            let mut free = None;
+            for index in 0..SLOT_COUNT {
+                match read_slot(file, index)? {
+                    Some(existing) if existing.name == entry.name && process_alive(existing.pid, existing.start_time) => {
+                        if existing.pid != entry.pid || existing.start_time != entry.start_time {
+                            return Err(GeyserError::IpcError(format!(
+                                "'{}' is already published by process {}",
+                                entry.name, existing.pid
+                            )));
+                        }
+                        free = Some(index);
+                        break;
+                    }
+                    Some(existing) if process_alive(existing.pid, existing.start_time) => {}
+                    _ => {
+                        free.get_or_insert(index);
+                    }
+                }
+            }
+            let index = free.ok_or_else(|| GeyserError::IpcError("Registry is full".to_string()))?;
+            file.write_all_at(&slot, slot_offset(index)).map_err(io_error)
+        })
+    }
+
+    /// Removes the entry published under `name` by process `pid`.
+    pub(crate) fn remove(&self, name: &str, pid: u32) -> Result<()> {
+        self.with_file(true, |file| {
+            for index in 0..SLOT_COUNT {
+                if let Some(existing) = read_slot(file, index)? {
+                    if existing.name == name && existing.pid == pid {
+                        file.write_all_at(&[0u8; SLOT_SIZE], slot_offset(index)).map_err(io_error)?;
+                    }
+                }
+            }
+            Ok(())
+        })
+    }
+
+    // Runs `f` holding the thread lock and an exclusive or shared file lock
+    fn with_file<T>(&self, exclusive: bool, f: impl FnOnce(&File) -> Result<T>) -> Result<T> {
+        let file = self.file.lock().unwrap();
+        if exclusive { file.lock() } else { file.lock_shared() }.map_err(io_error)?;
+        let result = f(&file);
+        let _ = file.unlock();
+        result
+    }
+}
+
+fn io_error(e: std::io::Error) -> GeyserError {
+    GeyserError::IpcError(format!("Registry I/O failed: {}", e))
+}
+
+fn slot_offset(index: u32) -> u64 {
+    HEADER_SIZE + index as u64 * SLOT_SIZE as u64
+}
+
+/// Checks that `name` can be published: 1 to `MAX_NAME_LEN` ASCII letters, digits,
+/// `-`, `_` or `.`.
+pub fn validate_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_NAME_LEN
+        && name.bytes().all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(GeyserError::IpcError(format!("Invalid publication name '{}'", name)))
+    }
+}
+
+/// Returns the start time of process `pid` in clock ticks since boot, as listed in
+/// `/proc/<pid>/stat`, or `None` if there is no such process.
+pub fn process_start_time(pid: u32) -> Option<u64> {
+    let stat = std::fs::read_to_string(Path::new("/proc").join(pid.to_string()).join("stat")).ok()?;
+    // The command name may contain spaces and parentheses, so count fields from the
+    // last `)`; the start time is the 22nd field, 20th after the name
+    let (_, fields) = stat.rsplit_once(')')?;
+    fields.split_whitespace().nth(19)?.parse().ok()
+}
+
+// Publishers are tracked by pid and start time; an entry whose process is gone, or
+// whose pid now belongs to another process, is free to reuse
+fn process_alive(pid: u32, start_time: u64) -> bool {
+    pid != 0 && process_start_time(pid) == Some(start_time)
+}
+
+fn encode_slot(entry: &RegistryEntry) -> Result<Vec<u8>> {
+    let socket_path = entry
+        .socket_path
+        .to_str()
+        .ok_or_else(|| GeyserError::IpcError("Socket path is not valid UTF-8".to_string()))?;
+
+    let mut writer = Writer::new();
+    writer.u8(1);
+    writer.u32(entry.pid);
+    writer.u64(entry.start_time);
+    writer.u64(entry.generation);
+    writer.str(&entry.name);
+    writer.str(socket_path);
+    write_descriptor(&mut writer, &entry.descriptor);
+    let (mut bytes, _) = writer.finish();
+
+    if bytes.len() > SLOT_SIZE {
+        return Err(GeyserError::IpcError(format!("Entry for '{}' does not fit in a registry slot", entry.name)));
+    }
+    bytes.resize(SLOT_SIZE, 0);
+    Ok(bytes)
+}
+
+fn read_slot(file: &File, index: u32) -> Result<Option<RegistryEntry>> {
+    let mut slot = [0u8; SLOT_SIZE];
+    file.read_exact_at(&mut slot, slot_offset(index)).map_err(io_error)?;
+    // A slot torn by a crashed writer or overwritten by another program reads as free
+    Ok(decode_slot(&slot).unwrap_or(None))
+}
+
+fn decode_slot(slot: &[u8]) -> Result<Option<RegistryEntry>> {
+    let mut reader = Reader::new(slot, Vec::new());
+    if reader.u8()? == 0 {
+        return Ok(None);
+    }
+    let pid = reader.u32()?;
+    let start_time = reader.u64()?;
+    let generation = reader.u64()?;
+    let name = reader.str()?.to_string();
+    let socket_path = PathBuf::from(reader.str()?);
+    let descriptor = read_descriptor(&mut reader)?;
+
+    // The rest of the slot is zero padding
+    Ok(Some(RegistryEntry {
+        name,
+        pid,
+        start_time,
+        generation,
+        descriptor,
+        socket_path,
+    }))
+}
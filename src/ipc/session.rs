@@ -0,0 +1,904 @@
+//! Producer/consumer session protocol.
+//!
+//! A session connects one producer to one consumer over a `Transport`:
+//!
+//! 1. The consumer sends `Hello` with its protocol version, device and the formats it
+//!    can import; the producer answers `Welcome` with the negotiated version and format,
+//!    or `Reject`.
+//! 2. The producer sends `Texture` with a new generation whenever it (re-)exports the
+//!    texture, e.g. after a resize; the consumer imports it, releases the previous
+//!    import and acknowledges with `Imported`.
+//! 3. `Frame` announces that the producer finished rendering a frame. From protocol
+//!    version 2 on it can carry the frame's presentation timestamp.
+//! 4. Both sides send `Heartbeat` when idle. A peer that stays silent for longer than
+//!    the configured timeout, or whose transport disconnects, is considered lost.
+//! 5. `Goodbye` ends the session; the consumer releases its imported texture.
+//!
+//! A lost or closed session can be resumed once the peer is back: the consumer calls
+//! `ConsumerSession::reconnect` with a new transport and the producer
+//! `ProducerSession::reaccept`, which repeat the handshake. Generations restart, so
+//! the producer shares its texture again.
+
+use std::{
+    io,
+    os::{
+        fd::{FromRawFd, OwnedFd, RawFd},
+        unix::net::UnixStream,
+    },
+    path::Path,
+    sync::{mpsc, Arc},
+    time::{Duration, Instant},
+};
+
+use super::{
+    socket,
+    wire::{self, Reader, Writer},
+};
+use crate::common::{
+    ApiTextureHandle, DeviceIdentity, SharingStrategy, TextureDescriptor, TextureFormat, TextureSharePackage,
+};
+use crate::error::{GeyserError, Result};
+use crate::{SharedTexture, TextureShareManager};
+
+/// Protocol version spoken by this build. Version 2 added frame timestamps.
+pub const PROTOCOL_VERSION: u32 = 2;
+
+/// Oldest protocol version this build still accepts.
+pub const MIN_PROTOCOL_VERSION: u32 = 1;
+
+const MSG_HELLO: u8 = 1;
+const MSG_WELCOME: u8 = 2;
+const MSG_REJECT: u8 = 3;
+const MSG_TEXTURE: u8 = 4;
+const MSG_IMPORTED: u8 = 5;
+const MSG_FRAME: u8 = 6;
+const MSG_HEARTBEAT: u8 = 7;
+const MSG_GOODBYE: u8 = 8;
+const MSG_TIMED_FRAME: u8 = 9;
+
+/// Carries session messages and the descriptors attached to them.
+pub trait Transport: Send {
+    /// Sends one message. The descriptors are duplicated for the peer; the caller
+    /// keeps its own.
+    fn send(&mut self, data: &[u8], fds: &[RawFd]) -> Result<()>;
+
+    /// Waits up to `timeout` for one message. Returns `None` on timeout and an error
+    /// if the peer disconnected.
+    fn recv(&mut self, timeout: Duration) -> Result<Option<(Vec<u8>, Vec<OwnedFd>)>>;
+}
+
+/// Transport over a connected Unix stream socket.
+#[derive(Debug)]
+pub struct UnixTransport {
+    stream: UnixStream,
+    // Keeps a message whose rest had not arrived when `recv` timed out
+    reader: socket::MessageReader,
+}
+
+impl UnixTransport {
+    pub fn new(stream: UnixStream) -> Self {
+        Self {
+            stream,
+            reader: socket::MessageReader::default(),
+        }
+    }
+
+    /// Connects to a producer listening on `path`.
+    pub fn connect(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        UnixStream::connect(path)
+            .map(Self::new)
+            .map_err(|e| GeyserError::IpcError(format!("Failed to connect to {}: {}", path.display(), e)))
+    }
+}
+
+impl Transport for UnixTransport {
+    fn send(&mut self, data: &[u8], fds: &[RawFd]) -> Result<()> {
+        socket::send_message(&self.stream, data, fds).map_err(|e| GeyserError::IpcError(format!("Failed to send: {}", e)))
+    }
+
+    fn recv(&mut self, timeout: Duration) -> Result<Option<(Vec<u8>, Vec<OwnedFd>)>> {
+        // A zero timeout would mean "block forever"
+        self.stream
+            .set_read_timeout(Some(timeout.max(Duration::from_millis(1))))
+            .map_err(|e| GeyserError::IpcError(format!("Failed to set timeout: {}", e)))?;
+        match self.reader.read(&self.stream) {
+            Ok(message) => Ok(Some(message)),
+            Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => Ok(None),
+            Err(e) => Err(GeyserError::IpcError(format!("Failed to receive: {}", e))),
+        }
+    }
+}
+
+type LoopbackMessage = (Vec<u8>, Vec<OwnedFd>);
+
+/// In-process transport, mainly for tests. Descriptors are duplicated on send, as
+/// `SCM_RIGHTS` would.
+#[derive(Debug)]
+pub struct LoopbackTransport {
+    sender: mpsc::Sender<LoopbackMessage>,
+    receiver: mpsc::Receiver<LoopbackMessage>,
+}
+
+impl LoopbackTransport {
+    /// Returns two connected ends.
+    pub fn pair() -> (Self, Self) {
+        let (a_sender, b_receiver) = mpsc::channel();
+        let (b_sender, a_receiver) = mpsc::channel();
+        (
+            Self {
+                sender: a_sender,
+                receiver: a_receiver,
+            },
+            Self {
+                sender: b_sender,
+                receiver: b_receiver,
+            },
+        )
+    }
+}
+
+impl Transport for LoopbackTransport {
+    fn send(&mut self, data: &[u8], fds: &[RawFd]) -> Result<()> {
+        let fds = fds
+            .iter()
+            .map(|&fd| {
+                let duplicate = unsafe { libc::fcntl(fd, libc::F_DUPFD_CLOEXEC, 0) };
+                if duplicate < 0 {
+                    return Err(GeyserError::IpcError(format!(
+                        "Failed to duplicate descriptor: {}",
+                        io::Error::last_os_error()
+                    )));
+                }
+                Ok(unsafe { OwnedFd::from_raw_fd(duplicate) })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.sender
+            .send((data.to_vec(), fds))
+            .map_err(|_| GeyserError::IpcError("Peer closed the connection".to_string()))
+    }
+
+    fn recv(&mut self, timeout: Duration) -> Result<Option<(Vec<u8>, Vec<OwnedFd>)>> {
+        match self.receiver.recv_timeout(timeout) {
+            Ok(message) => Ok(Some(message)),
+            Err(mpsc::RecvTimeoutError::Timeout) => Ok(None),
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                Err(GeyserError::IpcError("Peer closed the connection".to_string()))
+            }
+        }
+    }
+}
+
+/// A message exchanged by the two ends of a session.
+#[derive(Debug)]
+pub enum SessionMessage {
+    /// Consumer → producer: opens the session
+    Hello {
+        version: u32,
+        device: DeviceIdentity,
+        /// Formats the consumer can import
+        formats: Vec<TextureFormat>,
+    },
+    /// Producer → consumer: accepts the session
+    Welcome {
+        version: u32,
+        device: DeviceIdentity,
+        format: TextureFormat,
+    },
+    /// Producer → consumer: refuses the session
+    Reject { reason: String },
+    /// Producer → consumer: a newly exported texture replacing any previous one
+    Texture {
+        generation: u64,
+        descriptor: TextureDescriptor,
+        package: TextureSharePackage,
+    },
+    /// Consumer → producer: the texture of `generation` was imported
+    Imported { generation: u64 },
+    /// Producer → consumer: `frame` was rendered into the texture of `generation`;
+    /// `timestamp` is its presentation time in nanoseconds (version 2 and later)
+    Frame {
+        generation: u64,
+        frame: u64,
+        timestamp: Option<u64>,
+    },
+    Heartbeat,
+    Goodbye,
+}
+
+impl SessionMessage {
+    /// Encodes the message; descriptors of a `Texture` package are attached.
+    pub fn encode(&self) -> Result<Writer> {
+        let mut writer = Writer::new();
+        match self {
+            SessionMessage::Hello {
+                version,
+                device,
+                formats,
+            } => {
+                writer.u8(MSG_HELLO);
+                writer.u32(*version);
+                wire::write_device_identity(&mut writer, device);
+                writer.u8(formats.len() as u8);
+                for &format in formats {
+                    wire::write_format(&mut writer, format);
+                }
+            }
+            SessionMessage::Welcome {
+                version,
+                device,
+                format,
+            } => {
+                writer.u8(MSG_WELCOME);
+                writer.u32(*version);
+                wire::write_device_identity(&mut writer, device);
+                wire::write_format(&mut writer, *format);
+            }
+            SessionMessage::Reject { reason } => {
+                writer.u8(MSG_REJECT);
+                writer.str(reason);
+            }
+            SessionMessage::Texture {
+                generation,
+                descriptor,
+                package,
+            } => {
+                writer.u8(MSG_TEXTURE);
+                writer.u64(*generation);
+                wire::write_descriptor(&mut writer, descriptor);
+                wire::write_package(&mut writer, package)?;
+            }
+            SessionMessage::Imported { generation } => {
+                writer.u8(MSG_IMPORTED);
+                writer.u64(*generation);
+            }
+            SessionMessage::Frame {
+                generation,
+                frame,
+                timestamp,
+            } => {
+                writer.u8(if timestamp.is_some() { MSG_TIMED_FRAME } else { MSG_FRAME });
+                writer.u64(*generation);
+                writer.u64(*frame);
+                if let Some(timestamp) = timestamp {
+                    writer.u64(*timestamp);
+                }
+            }
+            SessionMessage::Heartbeat => writer.u8(MSG_HEARTBEAT),
+            SessionMessage::Goodbye => writer.u8(MSG_GOODBYE),
+        }
+        Ok(writer)
+    }
+
+    /// Decodes a message received with `fds`. Descriptors the message does not use
+    /// are closed.
+    pub fn decode(bytes: &[u8], fds: Vec<OwnedFd>) -> Result<Self> {
+        let mut reader = Reader::new(bytes, fds);
+        let message = match reader.u8()? {
+            MSG_HELLO => {
+                let version = reader.u32()?;
+                let device = wire::read_device_identity(&mut reader)?;
+                let formats = (0..reader.u8()?)
+                    .map(|_| wire::read_format(&mut reader))
+                    .collect::<Result<Vec<_>>>()?;
+                SessionMessage::Hello {
+                    version,
+                    device,
+                    formats,
+                }
+            }
+            MSG_WELCOME => SessionMessage::Welcome {
+                version: reader.u32()?,
+                device: wire::read_device_identity(&mut reader)?,
+                format: wire::read_format(&mut reader)?,
+            },
+            MSG_REJECT => SessionMessage::Reject {
+                reason: reader.str()?.to_string(),
+            },
+            MSG_TEXTURE => {
+                let generation = reader.u64()?;
+                let descriptor = wire::read_descriptor(&mut reader)?;
+                let package = wire::read_package(&mut reader)?;
+                SessionMessage::Texture {
+                    generation,
+                    descriptor,
+                    package,
+                }
+            }
+            MSG_IMPORTED => SessionMessage::Imported {
+                generation: reader.u64()?,
+            },
+            MSG_FRAME => SessionMessage::Frame {
+                generation: reader.u64()?,
+                frame: reader.u64()?,
+                timestamp: None,
+            },
+            MSG_TIMED_FRAME => SessionMessage::Frame {
+                generation: reader.u64()?,
+                frame: reader.u64()?,
+                timestamp: Some(reader.u64()?),
+            },
+            MSG_HEARTBEAT => SessionMessage::Heartbeat,
+            MSG_GOODBYE => SessionMessage::Goodbye,
+            _ => return Err(GeyserError::IpcError("Malformed message: unknown message type".to_string())),
+        };
+        if let Err(e) = reader.finish() {
+            if let SessionMessage::Texture { package, .. } = &message {
+                wire::close_package_fds(package);
+            }
+            return Err(e);
+        }
+        Ok(message)
+    }
+}
+
+/// Timing and capabilities of one end of a session.
+#[derive(Debug, Clone)]
+pub struct SessionConfig {
+    device: DeviceIdentity,
+    formats: Vec<TextureFormat>,
+    heartbeat_interval: Duration,
+    peer_timeout: Duration,
+}
+
+impl SessionConfig {
+    /// `formats` are the formats this end can export (producer) or import (consumer).
+    /// The producer picks the first of its formats that the consumer supports.
+    pub fn new(device: DeviceIdentity, formats: Vec<TextureFormat>) -> Self {
+        Self {
+            device,
+            formats,
+            heartbeat_interval: Duration::from_secs(1),
+            peer_timeout: Duration::from_secs(5),
+        }
+    }
+
+    /// How long this end may stay silent before it sends a heartbeat (default 1s).
+    pub fn with_heartbeat_interval(mut self, interval: Duration) -> Self {
+        self.heartbeat_interval = interval;
+        self
+    }
+
+    /// How long the peer may stay silent before it is considered lost, and how long
+    /// the handshake may take (default 5s).
+    pub fn with_peer_timeout(mut self, timeout: Duration) -> Self {
+        self.peer_timeout = timeout;
+        self
+    }
+}
+
+/// The outcome of the handshake.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Negotiated {
+    pub version: u32,
+    pub format: TextureFormat,
+    pub peer_device: DeviceIdentity,
+    /// `ZeroCopy` if both ends run on the same device; otherwise the producer should
+    /// share linear dma-bufs, which the consumer imports by copy
+    pub strategy: SharingStrategy,
+}
+
+impl Negotiated {
+    fn new(version: u32, format: TextureFormat, local: &DeviceIdentity, peer_device: DeviceIdentity) -> Self {
+        let strategy = if local.is_compatible_with(&peer_device) {
+            SharingStrategy::ZeroCopy
+        } else {
+            SharingStrategy::Copy
+        };
+        Self {
+            version,
+            format,
+            peer_device,
+            strategy,
+        }
+    }
+}
+
+/// Where a session is in its lifetime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionState {
+    /// Handshake completed; textures and frames flow
+    Active,
+    /// Ended by either side with `Goodbye`
+    Closed,
+    /// The peer disconnected or stopped responding
+    PeerLost,
+}
+
+/// Something that happened in a session, returned by `poll`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionEvent {
+    /// Producer side: the consumer imported the texture of `generation`. Exports of
+    /// older generations may now be released.
+    Imported { generation: u64 },
+    /// Consumer side: the texture of `generation` was imported and replaced the
+    /// previous one
+    TextureChanged {
+        generation: u64,
+        descriptor: TextureDescriptor,
+    },
+    /// Consumer side: the producer finished `frame` in the texture of `generation`,
+    /// with its presentation timestamp if the producer sent one
+    Frame {
+        generation: u64,
+        frame: u64,
+        timestamp: Option<Duration>,
+    },
+    /// The peer ended the session
+    Closed,
+}
+
+// Shared by both ends: heartbeats, dead-peer detection and teardown
+struct Endpoint<T: Transport> {
+    transport: T,
+    config: SessionConfig,
+    state: SessionState,
+    last_sent: Instant,
+    last_received: Instant,
+}
+
+impl<T: Transport> Endpoint<T> {
+    fn new(transport: T, config: SessionConfig) -> Self {
+        let now = Instant::now();
+        Self {
+            transport,
+            config,
+            state: SessionState::Active,
+            last_sent: now,
+            last_received: now,
+        }
+    }
+
+    fn send(&mut self, message: &SessionMessage) -> Result<()> {
+        self.ensure_active()?;
+        let (bytes, fds) = message.encode()?.finish();
+        if let Err(e) = self.transport.send(&bytes, &fds) {
+            self.state = SessionState::PeerLost;
+            return Err(e);
+        }
+        self.last_sent = Instant::now();
+        Ok(())
+    }
+
+    fn ensure_active(&self) -> Result<()> {
+        match self.state {
+            SessionState::Active => Ok(()),
+            SessionState::Closed => Err(GeyserError::IpcError("Session is closed".to_string())),
+            SessionState::PeerLost => Err(GeyserError::IpcError("Session peer was lost".to_string())),
+        }
+    }
+
+    // Waits up to `timeout` for a message other than a heartbeat, sending heartbeats
+    // while idle. A `Goodbye` closes the session and is returned to the caller.
+    fn next_message(&mut self, timeout: Duration) -> Result<Option<SessionMessage>> {
+        self.ensure_active()?;
+        let deadline = Instant::now() + timeout;
+        loop {
+            let now = Instant::now();
+            if now.duration_since(self.last_received) > self.config.peer_timeout {
+                self.state = SessionState::PeerLost;
+                return Err(GeyserError::IpcError("Session peer stopped responding".to_string()));
+            }
+            if now.duration_since(self.last_sent) >= self.config.heartbeat_interval {
+                self.send(&SessionMessage::Heartbeat)?;
+            }
+            if now >= deadline && timeout > Duration::ZERO {
+                return Ok(None);
+            }
+
+            // Wake up in time for the next heartbeat or peer deadline
+            let wait = deadline
+                .min(self.last_sent + self.config.heartbeat_interval)
+                .min(self.last_received + self.config.peer_timeout)
+                .saturating_duration_since(now);
+            let received = match self.transport.recv(wait) {
+                Ok(received) => received,
+                Err(e) => {
+                    self.state = SessionState::PeerLost;
+                    return Err(e);
+                }
+            };
+            if let Some((bytes, fds)) = received {
+                self.last_received = Instant::now();
+                match SessionMessage::decode(&bytes, fds)? {
+                    SessionMessage::Heartbeat => {}
+                    SessionMessage::Goodbye => {
+                        self.state = SessionState::Closed;
+                        return Ok(Some(SessionMessage::Goodbye));
+                    }
+                    message => return Ok(Some(message)),
+                }
+            } else if timeout == Duration::ZERO {
+                return Ok(None);
+            }
+        }
+    }
+
+    // Waits for the handshake reply, which is not subject to heartbeats
+    fn handshake_message(&mut self) -> Result<SessionMessage> {
+        let received = self.transport.recv(self.config.peer_timeout)?;
+        let (bytes, fds) = received.ok_or_else(|| GeyserError::IpcError("Handshake timed out".to_string()))?;
+        let now = Instant::now();
+        self.last_received = now;
+        self.last_sent = now;
+        SessionMessage::decode(&bytes, fds)
+    }
+
+    fn close(&mut self) -> Result<()> {
+        if self.state != SessionState::Active {
+            return Ok(());
+        }
+        let result = self.send(&SessionMessage::Goodbye);
+        self.state = SessionState::Closed;
+        result
+    }
+}
+
+// Closes the descriptors an unexpected message carries and describes it as an error
+fn unexpected(message: SessionMessage) -> GeyserError {
+    if let SessionMessage::Texture { package, .. } = &message {
+        wire::close_package_fds(package);
+    }
+    GeyserError::IpcError(format!("Unexpected session message: {:?}", message))
+}
+
+/// The producer end of a session.
+pub struct ProducerSession<T: Transport> {
+    endpoint: Endpoint<T>,
+    negotiated: Negotiated,
+    generation: Option<u64>,
+    consumer_generation: Option<u64>,
+}
+
+impl<T: Transport> ProducerSession<T> {
+    /// Waits for the consumer's `Hello` and completes the handshake.
+    ///
+    /// Fails, after telling the consumer why, if the protocol versions are
+    /// incompatible or the consumer supports none of `config`'s formats.
+    pub fn accept(transport: T, config: SessionConfig) -> Result<Self> {
+        let mut endpoint = Endpoint::new(transport, config);
+        let (version, peer_device, formats) = match endpoint.handshake_message()? {
+            SessionMessage::Hello {
+                version,
+                device,
+                formats,
+            } => (version, device, formats),
+            message => return Err(unexpected(message)),
+        };
+
+        let version = version.min(PROTOCOL_VERSION);
+        let format = endpoint.config.formats.iter().copied().find(|format| formats.contains(format));
+        let format = match (version >= MIN_PROTOCOL_VERSION, format) {
+            (true, Some(format)) => format,
+            (compatible, _) => {
+                let reason = if compatible {
+                    "No common texture format".to_string()
+                } else {
+                    format!("Unsupported protocol version {}", version)
+                };
+                let _ = endpoint.send(&SessionMessage::Reject { reason: reason.clone() });
+                endpoint.state = SessionState::Closed;
+                return Err(GeyserError::IpcError(reason));
+            }
+        };
+
+        endpoint.send(&SessionMessage::Welcome {
+            version,
+            device: endpoint.config.device,
+            format,
+        })?;
+        let negotiated = Negotiated::new(version, format, &endpoint.config.device, peer_device);
+        Ok(Self {
+            endpoint,
+            negotiated,
+            generation: None,
+            consumer_generation: None,
+        })
+    }
+
+    pub fn negotiated(&self) -> &Negotiated {
+        &self.negotiated
+    }
+
+    pub fn state(&self) -> SessionState {
+        self.endpoint.state
+    }
+
+    /// Returns the generation of the most recently shared texture.
+    pub fn generation(&self) -> Option<u64> {
+        self.generation
+    }
+
+    /// Returns the newest generation the consumer has imported.
+    pub fn consumer_generation(&self) -> Option<u64> {
+        self.consumer_generation
+    }
+
+    /// Shares a newly exported texture, replacing the previous one (for example after
+    /// a resize), and returns its generation. The package's descriptors stay owned by
+    /// the caller; keep the export alive until `consumer_generation` reaches the
+    /// returned generation.
+    pub fn share(&mut self, descriptor: &TextureDescriptor, package: &TextureSharePackage) -> Result<u64> {
+        if descriptor.format != self.negotiated.format {
+            return Err(GeyserError::UnsupportedFormat(format!(
+                "{:?} was shared but {:?} was negotiated",
+                descriptor.format, self.negotiated.format
+            )));
+        }
+        let generation = self.generation.map_or(0, |generation| generation + 1);
+        self.endpoint.send(&SessionMessage::Texture {
+            generation,
+            descriptor: descriptor.clone(),
+            package: package.clone(),
+        })?;
+        self.generation = Some(generation);
+        Ok(generation)
+    }
+
+    /// Announces that `frame` was rendered into the current texture.
+    pub fn frame_ready(&mut self, frame: u64) -> Result<()> {
+        self.send_frame(frame, None)
+    }
+
+    /// Announces that `frame`, to be presented at `timestamp`, was rendered into the
+    /// current texture. Consumers speaking protocol version 1 receive it without the
+    /// timestamp.
+    pub fn frame_ready_at(&mut self, frame: u64, timestamp: Duration) -> Result<()> {
+        let timestamp = (self.negotiated.version >= 2).then_some(timestamp.as_nanos() as u64);
+        self.send_frame(frame, timestamp)
+    }
+
+    fn send_frame(&mut self, frame: u64, timestamp: Option<u64>) -> Result<()> {
+        let generation = self
+            .generation
+            .ok_or_else(|| GeyserError::IpcError("No texture has been shared yet".to_string()))?;
+        self.endpoint.send(&SessionMessage::Frame {
+            generation,
+            frame,
+            timestamp,
+        })
+    }
+
+    /// Processes messages from the consumer for up to `timeout`, sending heartbeats
+    /// as needed. A zero timeout only handles what has already arrived.
+    ///
+    /// Fails once the consumer is lost.
+    pub fn poll(&mut self, timeout: Duration) -> Result<Option<SessionEvent>> {
+        match self.endpoint.next_message(timeout)? {
+            None => Ok(None),
+            Some(SessionMessage::Goodbye) => Ok(Some(SessionEvent::Closed)),
+            Some(SessionMessage::Imported { generation }) => {
+                self.consumer_generation = Some(generation);
+                Ok(Some(SessionEvent::Imported { generation }))
+            }
+            Some(message) => Err(unexpected(message)),
+        }
+    }
+
+    /// Resumes the session with a consumer that connected again on `transport`, for
+    /// example after the previous one was lost. Generations restart, so the texture
+    /// must be shared again.
+    pub fn reaccept(&mut self, transport: T) -> Result<()> {
+        *self = Self::accept(transport, self.endpoint.config.clone())?;
+        Ok(())
+    }
+
+    /// Ends the session.
+    pub fn close(mut self) -> Result<()> {
+        self.endpoint.close()
+    }
+}
+
+impl<T: Transport> Drop for ProducerSession<T> {
+    fn drop(&mut self) {
+        let _ = self.endpoint.close();
+    }
+}
+
+/// The consumer end of a session. Imports every texture the producer shares and
+/// releases it, and its handle with the importing manager, when replaced or when the
+/// session ends.
+pub struct ConsumerSession<T: Transport> {
+    endpoint: Endpoint<T>,
+    negotiated: Negotiated,
+    generation: Option<u64>,
+    descriptor: Option<TextureDescriptor>,
+    texture: Option<Box<dyn SharedTexture>>,
+    release_handle: Option<ReleaseHandle>,
+}
+
+// Releases an imported handle with the manager that imported it
+type ReleaseHandle = Box<dyn FnOnce() + Send>;
+
+impl<T: Transport> ConsumerSession<T> {
+    /// Sends `Hello` and waits for the producer to accept.
+    pub fn connect(transport: T, config: SessionConfig) -> Result<Self> {
+        let mut endpoint = Endpoint::new(transport, config);
+        endpoint.send(&SessionMessage::Hello {
+            version: PROTOCOL_VERSION,
+            device: endpoint.config.device,
+            formats: endpoint.config.formats.clone(),
+        })?;
+
+        let negotiated = match endpoint.handshake_message()? {
+            SessionMessage::Welcome {
+                version,
+                device,
+                format,
+            } => {
+                if !(MIN_PROTOCOL_VERSION..=PROTOCOL_VERSION).contains(&version) {
+                    return Err(GeyserError::IpcError(format!("Unsupported protocol version {}", version)));
+                }
+                if !endpoint.config.formats.contains(&format) {
+                    return Err(GeyserError::UnsupportedFormat(format!("{:?} was not offered", format)));
+                }
+                Negotiated::new(version, format, &endpoint.config.device, device)
+            }
+            SessionMessage::Reject { reason } => {
+                endpoint.state = SessionState::Closed;
+                return Err(GeyserError::IpcError(format!("Producer rejected the session: {}", reason)));
+            }
+            message => return Err(unexpected(message)),
+        };
+
+        Ok(Self {
+            endpoint,
+            negotiated,
+            generation: None,
+            descriptor: None,
+            texture: None,
+            release_handle: None,
+        })
+    }
+
+    /// Resumes the session on `transport` after the producer was lost or restarted,
+    /// releasing the imported texture and repeating the handshake.
+    pub fn reconnect(&mut self, transport: T) -> Result<()> {
+        self.release();
+        *self = Self::connect(transport, self.endpoint.config.clone())?;
+        Ok(())
+    }
+
+    pub fn negotiated(&self) -> &Negotiated {
+        &self.negotiated
+    }
+
+    pub fn state(&self) -> SessionState {
+        self.endpoint.state
+    }
+
+    /// Returns the generation of the imported texture.
+    pub fn generation(&self) -> Option<u64> {
+        self.generation
+    }
+
+    /// Returns the descriptor of the imported texture.
+    pub fn descriptor(&self) -> Option<&TextureDescriptor> {
+        self.descriptor.as_ref()
+    }
+
+    /// Returns the imported texture, if the producer has shared one.
+    pub fn texture(&self) -> Option<&dyn SharedTexture> {
+        self.texture.as_deref()
+    }
+
+    /// Processes messages from the producer for up to `timeout`, importing shared
+    /// textures with `manager`. A zero timeout only handles what has already arrived.
+    ///
+    /// When the session ends, or fails because the producer is lost, the imported
+    /// texture is released and its handle passed to `manager.release_texture_handle`.
+    pub fn poll<M>(&mut self, manager: &Arc<M>, timeout: Duration) -> Result<Option<SessionEvent>>
+    where
+        M: TextureShareManager + Send + Sync + ?Sized + 'static,
+    {
+        self.poll_with(timeout, |package, descriptor| {
+            let handle = package.texture.clone();
+            let texture = manager.import_texture_with_sync(package, descriptor)?;
+            Ok(Some((texture, release_handle(manager.clone(), handle))))
+        })
+    }
+
+    /// Like `poll`, but hands each shared texture's package to `forward` instead of
+    /// importing it, for consumers that import elsewhere (for example on a render
+    /// thread). `forward` owns the package's descriptors; `texture` stays `None`.
+    pub fn poll_forwarding(
+        &mut self,
+        timeout: Duration,
+        forward: impl FnOnce(TextureSharePackage, &TextureDescriptor) -> Result<()>,
+    ) -> Result<Option<SessionEvent>> {
+        self.poll_with(timeout, |package, descriptor| forward(package, descriptor).map(|_| None))
+    }
+
+    // `import` takes over a newly shared package and returns the texture to hold, if
+    // any, with what releases its handle
+    fn poll_with(
+        &mut self,
+        timeout: Duration,
+        import: impl FnOnce(TextureSharePackage, &TextureDescriptor) -> Result<Option<(Box<dyn SharedTexture>, Option<ReleaseHandle>)>>,
+    ) -> Result<Option<SessionEvent>> {
+        let message = match self.endpoint.next_message(timeout) {
+            Ok(message) => message,
+            Err(e) => {
+                self.release();
+                return Err(e);
+            }
+        };
+        match message {
+            None => Ok(None),
+            Some(SessionMessage::Goodbye) => {
+                self.release();
+                Ok(Some(SessionEvent::Closed))
+            }
+            Some(SessionMessage::Texture {
+                generation,
+                descriptor,
+                package,
+            }) => {
+                if descriptor.format != self.negotiated.format {
+                    wire::close_package_fds(&package);
+                    return Err(GeyserError::UnsupportedFormat(format!(
+                        "{:?} was shared but {:?} was negotiated",
+                        descriptor.format, self.negotiated.format
+                    )));
+                }
+                // Release the previous import before the new one takes its memory
+                self.release();
+                if let Some((texture, release_handle)) = import(package, &descriptor)? {
+                    self.texture = Some(texture);
+                    self.release_handle = release_handle;
+                }
+                self.generation = Some(generation);
+                self.descriptor = Some(descriptor.clone());
+                self.endpoint.send(&SessionMessage::Imported { generation })?;
+                Ok(Some(SessionEvent::TextureChanged { generation, descriptor }))
+            }
+            Some(SessionMessage::Frame {
+                generation,
+                frame,
+                timestamp,
+            }) => Ok(Some(SessionEvent::Frame {
+                generation,
+                frame,
+                timestamp: timestamp.map(Duration::from_nanos),
+            })),
+            Some(message) => Err(unexpected(message)),
+        }
+    }
+
+    // The texture goes first, since the handle's memory may back it
+    fn release(&mut self) {
+        self.texture = None;
+        if let Some(release_handle) = self.release_handle.take() {
+            release_handle();
+        }
+        self.descriptor = None;
+        self.generation = None;
+    }
+
+    /// Ends the session and releases the imported texture.
+    pub fn close(mut self) -> Result<()> {
+        self.release();
+        self.endpoint.close()
+    }
+}
+
+impl<T: Transport> Drop for ConsumerSession<T> {
+    fn drop(&mut self) {
+        self.release();
+        let _ = self.endpoint.close();
+    }
+}
+
+// Dma-buf imports own their descriptor outright. Other handles, such as Vulkan memory
+// imported by `VulkanTextureShareManager`, stay with the manager until released.
+fn release_handle<M>(manager: Arc<M>, handle: ApiTextureHandle) -> Option<ReleaseHandle>
+where
+    M: TextureShareManager + Send + Sync + ?Sized + 'static,
+{
+    if matches!(handle, ApiTextureHandle::DmaBuf(_)) {
+        return None;
+    }
+    Some(Box::new(move || {
+        let _ = manager.release_texture_handle(handle);
+    }))
+}
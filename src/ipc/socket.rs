@@ -0,0 +1,181 @@
+//! Length-prefixed messages with file descriptors attached (`SCM_RIGHTS`).
+
+use std::{
+    io::{self, Read, Write},
+    os::{
+        fd::{AsRawFd, FromRawFd, OwnedFd, RawFd},
+        unix::net::UnixStream,
+    },
+};
+
+/// Upper bound on descriptors attached to one message.
+pub const MAX_FDS: usize = 8;
+
+// Larger messages are rejected as malformed
+const MAX_MESSAGE_SIZE: usize = 64 * 1024;
+
+// Control buffer large enough for `MAX_FDS` descriptors
+fn control_buffer() -> Vec<u8> {
+    let space = unsafe { libc::CMSG_SPACE((MAX_FDS * std::mem::size_of::<RawFd>()) as u32) };
+    vec![0u8; space as usize]
+}
+
+/// Sends `data` as one message, passing `fds` to the peer. The descriptors are
+/// duplicated into the receiving process; the caller keeps its own.
+pub fn send_message(stream: &UnixStream, data: &[u8], fds: &[RawFd]) -> io::Result<()> {
+    if fds.len() > MAX_FDS {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "Too many file descriptors for one message"));
+    }
+    if data.len() > MAX_MESSAGE_SIZE {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "Message too large"));
+    }
+
+    let header = (data.len() as u32).to_le_bytes();
+    let mut iov = [
+        libc::iovec {
+            iov_base: header.as_ptr() as *mut libc::c_void,
+            iov_len: header.len(),
+        },
+        libc::iovec {
+            iov_base: data.as_ptr() as *mut libc::c_void,
+            iov_len: data.len(),
+        },
+    ];
+    let mut control = control_buffer();
+    let mut msg: libc::msghdr = unsafe { std::mem::zeroed() };
+    msg.msg_iov = iov.as_mut_ptr();
+    msg.msg_iovlen = iov.len() as _;
+
+    if !fds.is_empty() {
+        let fd_bytes = std::mem::size_of_val(fds);
+        msg.msg_control = control.as_mut_ptr() as *mut libc::c_void;
+        msg.msg_controllen = unsafe { libc::CMSG_SPACE(fd_bytes as u32) } as _;
+        unsafe {
+            let cmsg = libc::CMSG_FIRSTHDR(&msg);
+            (*cmsg).cmsg_level = libc::SOL_SOCKET;
+            (*cmsg).cmsg_type = libc::SCM_RIGHTS;
+            (*cmsg).cmsg_len = libc::CMSG_LEN(fd_bytes as u32) as _;
+            std::ptr::copy_nonoverlapping(fds.as_ptr() as *const u8, libc::CMSG_DATA(cmsg), fd_bytes);
+        }
+    }
+
+    let sent = unsafe { libc::sendmsg(stream.as_raw_fd(), &msg, libc::MSG_NOSIGNAL) };
+    if sent < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    // The descriptors travel with the first byte; write whatever did not fit
+    let sent = sent as usize;
+    let mut writer = stream;
+    if sent < header.len() {
+        writer.write_all(&header[sent..])?;
+        writer.write_all(data)
+    } else {
+        writer.write_all(&data[sent - header.len()..])
+    }
+}
+
+/// Receives one message sent by `send_message`, together with its descriptors.
+pub fn recv_message(stream: &UnixStream) -> io::Result<(Vec<u8>, Vec<OwnedFd>)> {
+    MessageReader::default().read(stream)
+}
+
+/// Reassembles messages sent by `send_message` across reads that time out.
+///
+/// A read timeout can fire after part of a message arrived. The reader keeps the bytes
+/// and descriptors received so far, and the next `read` continues the same message
+/// instead of taking the rest of it for a new header.
+#[derive(Debug, Default)]
+pub struct MessageReader {
+    header: [u8; 4],
+    header_len: usize,
+    // Allocated once the header is complete
+    data: Option<Vec<u8>>,
+    data_len: usize,
+    fds: Vec<OwnedFd>,
+}
+
+impl MessageReader {
+    /// Reads until a whole message arrived. A `WouldBlock` or `TimedOut` error leaves
+    /// what was received so far for the next call.
+    pub fn read(&mut self, stream: &UnixStream) -> io::Result<(Vec<u8>, Vec<OwnedFd>)> {
+        if self.header_len == 0 {
+            self.header_len = self.recv_first(stream)?;
+        }
+
+        let mut reader = stream;
+        while self.header_len < self.header.len() {
+            self.header_len += read_some(&mut reader, &mut self.header[self.header_len..])?;
+        }
+        if self.data.is_none() {
+            let len = u32::from_le_bytes(self.header) as usize;
+            if len > MAX_MESSAGE_SIZE {
+                *self = Self::default();
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "Message too large"));
+            }
+            self.data = Some(vec![0u8; len]);
+        }
+        let data = self.data.as_mut().unwrap();
+        while self.data_len < data.len() {
+            self.data_len += read_some(&mut reader, &mut data[self.data_len..])?;
+        }
+
+        let message = std::mem::take(self);
+        Ok((message.data.unwrap(), message.fds))
+    }
+
+    // Receives the first bytes of the header, which carry the descriptors
+    fn recv_first(&mut self, stream: &UnixStream) -> io::Result<usize> {
+        let mut iov = [libc::iovec {
+            iov_base: self.header.as_mut_ptr() as *mut libc::c_void,
+            iov_len: self.header.len(),
+        }];
+        let mut control = control_buffer();
+        let mut msg: libc::msghdr = unsafe { std::mem::zeroed() };
+        msg.msg_iov = iov.as_mut_ptr();
+        msg.msg_iovlen = iov.len() as _;
+        msg.msg_control = control.as_mut_ptr() as *mut libc::c_void;
+        msg.msg_controllen = control.len() as _;
+
+        let received = unsafe { libc::recvmsg(stream.as_raw_fd(), &mut msg, libc::MSG_CMSG_CLOEXEC) };
+        if received < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        if received == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Peer closed the connection"));
+        }
+
+        // Take ownership of the descriptors first so they are closed on any error below
+        let mut fds = Vec::new();
+        unsafe {
+            let mut cmsg = libc::CMSG_FIRSTHDR(&msg);
+            while !cmsg.is_null() {
+                if (*cmsg).cmsg_level == libc::SOL_SOCKET && (*cmsg).cmsg_type == libc::SCM_RIGHTS {
+                    let payload = (*cmsg).cmsg_len as usize - libc::CMSG_LEN(0) as usize;
+                    let data = libc::CMSG_DATA(cmsg) as *const RawFd;
+                    for i in 0..payload / std::mem::size_of::<RawFd>() {
+                        fds.push(OwnedFd::from_raw_fd(data.add(i).read_unaligned()));
+                    }
+                }
+                cmsg = libc::CMSG_NXTHDR(&msg, cmsg);
+            }
+        }
+        if msg.msg_flags & libc::MSG_CTRUNC != 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "File descriptors were truncated"));
+        }
+        self.fds = fds;
+        Ok(received as usize)
+    }
+}
+
+// Reads at least one byte into `buf`, treating end of stream as an error
+fn read_some(reader: &mut &UnixStream, buf: &mut [u8]) -> io::Result<usize> {
+    loop {
+        match reader.read(buf) {
+            Ok(0) => return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Peer closed the connection")),
+            Ok(read) => return Ok(read),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
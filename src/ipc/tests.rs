@@ -0,0 +1,976 @@
+//! Unit tests for the IPC registry, wire format and fd passing
+
+use super::*;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use std::time::Duration;
+use crate::common::{
+    ApiTextureHandle, DmaBufHandle, SyncPrimitives, TextureFormat, TextureMemoryLocation, TextureTiling,
+    TextureUsage, DRM_FORMAT_MOD_LINEAR,
+};
+use std::{
+    fs::File,
+    os::{
+        fd::{AsRawFd, FromRawFd, IntoRawFd, OwnedFd, RawFd},
+        unix::fs::MetadataExt,
+    },
+    path::Path,
+};
+
+fn descriptor(width: u32) -> TextureDescriptor {
+    TextureDescriptor::new(width, 720, TextureFormat::Bgra8Unorm, vec![TextureUsage::TextureBinding, TextureUsage::CopySrc])
+        .with_label("camera")
+        .with_tiling(TextureTiling::DrmModifier(0x0100_0000_0000_0001))
+        .with_memory_location(TextureMemoryLocation::GpuToCpu)
+}
+
+// A dma-buf package whose fd refers to `file`; the fd is owned by the package
+fn dma_buf_package(file: &File) -> TextureSharePackage {
+    TextureSharePackage {
+        texture: ApiTextureHandle::DmaBuf(DmaBufHandle {
+            fd: file.try_clone().unwrap().into_raw_fd(),
+            fourcc: 0x3432_5241,
+            modifier: DRM_FORMAT_MOD_LINEAR,
+            offset: 0,
+            stride: 5120,
+        }),
+        sync: SyncPrimitives::default(),
+    }
+}
+
+fn package_inode(package: &TextureSharePackage) -> u64 {
+    match &package.texture {
+        ApiTextureHandle::DmaBuf(handle) => {
+            let file = unsafe { File::from_raw_fd(libc::dup(handle.fd)) };
+            file.metadata().unwrap().ino()
+        }
+        #[allow(unreachable_patterns)]
+        _ => panic!("Wrong variant"),
+    }
+}
+
+// Registry file unique to one test
+fn test_registry(name: &str) -> Registry {
+    let path = std::env::temp_dir().join(format!("geyser-test-registry-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    Registry::open(path).unwrap()
+}
+
+fn cleanup(registry: &Registry) {
+    let _ = std::fs::remove_file(registry.path());
+}
+
+#[test]
+fn test_descriptor_roundtrip() {
+    let mut writer = Writer::new();
+    write_descriptor(&mut writer, &descriptor(1280));
+    let (bytes, fds) = writer.finish();
+    assert!(fds.is_empty());
+
+    let mut reader = Reader::new(&bytes, Vec::new());
+    assert_eq!(read_descriptor(&mut reader).unwrap(), descriptor(1280));
+    reader.finish().unwrap();
+}
+
+#[test]
+fn test_truncated_message_is_rejected() {
+    let mut writer = Writer::new();
+    write_descriptor(&mut writer, &descriptor(1280));
+    let (bytes, _) = writer.finish();
+
+    let mut reader = Reader::new(&bytes[..bytes.len() - 1], Vec::new());
+    assert!(matches!(read_descriptor(&mut reader), Err(GeyserError::IpcError(_))));
+}
+
+#[test]
+fn test_package_fds_travel_over_socket() {
+    let file = File::open("/dev/null").unwrap();
+    let package = dma_buf_package(&file);
+
+    let mut writer = Writer::new();
+    write_package(&mut writer, &package).unwrap();
+    let (bytes, fds) = writer.finish();
+    assert_eq!(fds.len(), 1);
+
+    let (sender, receiver) = UnixStream::pair().unwrap();
+    socket::send_message(&sender, &bytes, &fds).unwrap();
+    let (received, received_fds) = socket::recv_message(&receiver).unwrap();
+    assert_eq!(received, bytes);
+
+    let mut reader = Reader::new(&received, received_fds);
+    let decoded = read_package(&mut reader).unwrap();
+    reader.finish().unwrap();
+
+    // A different descriptor for the same file
+    match (&decoded.texture, &package.texture) {
+        (ApiTextureHandle::DmaBuf(decoded_handle), ApiTextureHandle::DmaBuf(handle)) => {
+            assert_ne!(decoded_handle.fd, handle.fd);
+            assert_eq!(decoded_handle.stride, 5120);
+        }
+        #[allow(unreachable_patterns)]
+        _ => panic!("Wrong variant"),
+    }
+    assert_eq!(package_inode(&decoded), file.metadata().unwrap().ino());
+    wire::close_package_fds(&decoded);
+    wire::close_package_fds(&package);
+}
+
+#[cfg(feature = "webgpu")]
+#[test]
+fn test_staging_handles_are_not_sent() {
+    let package = TextureSharePackage {
+        texture: ApiTextureHandle::WebGpu(crate::webgpu::WebGpuTextureShareHandle {
+            staging: Arc::default(),
+            width: 64,
+            height: 64,
+            format: TextureFormat::Rgba8Unorm,
+        }),
+        sync: SyncPrimitives::default(),
+    };
+
+    let mut writer = Writer::new();
+    assert!(matches!(write_package(&mut writer, &package), Err(GeyserError::IpcError(_))));
+}
+
+#[cfg(feature = "vulkan")]
+#[test]
+fn test_vulkan_package_keeps_plane_layouts() {
+    use ash::vk;
+
+    let file = File::open("/dev/null").unwrap();
+    let plane = |offset, row_pitch| vk::SubresourceLayout {
+        offset,
+        row_pitch,
+        ..Default::default()
+    };
+    let package = TextureSharePackage {
+        texture: ApiTextureHandle::Vulkan(crate::vulkan::VulkanTextureShareHandle {
+            raw_handle: file.try_clone().unwrap().into_raw_fd() as u64,
+            memory_type_index: 2,
+            size: 1 << 20,
+            handle_type: vk::ExternalMemoryHandleTypeFlags::OPAQUE_FD,
+            dedicated_allocation: true,
+            device_identity: Default::default(),
+            plane_layouts: vec![plane(0, 5120), plane(1 << 19, 256)],
+        }),
+        sync: SyncPrimitives::default(),
+    };
+
+    let mut writer = Writer::new();
+    write_package(&mut writer, &package).unwrap();
+    let (bytes, fds) = writer.finish();
+    let (sender, receiver) = UnixStream::pair().unwrap();
+    socket::send_message(&sender, &bytes, &fds).unwrap();
+    let (received, received_fds) = socket::recv_message(&receiver).unwrap();
+
+    let mut reader = Reader::new(&received, received_fds);
+    let decoded = read_package(&mut reader).unwrap();
+    reader.finish().unwrap();
+    match &decoded.texture {
+        ApiTextureHandle::Vulkan(handle) => {
+            let layouts: Vec<_> = handle.plane_layouts.iter().map(|layout| (layout.offset, layout.row_pitch)).collect();
+            assert_eq!(layouts, vec![(0, 5120), (1 << 19, 256)]);
+        }
+        _ => panic!("Wrong variant"),
+    }
+    wire::close_package_fds(&decoded);
+    wire::close_package_fds(&package);
+}
+
+#[test]
+fn test_reader_closes_unused_fds() {
+    let file = File::open("/dev/null").unwrap();
+    let fd = OwnedFd::from(file.try_clone().unwrap());
+    let raw = fd.as_raw_fd();
+
+    drop(Reader::new(&[], vec![fd]));
+    assert_eq!(unsafe { libc::fcntl(raw, libc::F_GETFD) }, -1);
+}
+
+#[test]
+fn test_registry_insert_lookup_remove() {
+    let registry = test_registry("lookup");
+    let entry = RegistryEntry {
+        name: "camera0".to_string(),
+        pid: std::process::id(),
+        start_time: process_start_time(std::process::id()).unwrap(),
+        generation: 3,
+        descriptor: descriptor(640),
+        socket_path: Path::new("/tmp/geyser-camera0.sock").to_path_buf(),
+    };
+
+    registry.insert(&entry).unwrap();
+    assert_eq!(registry.lookup("camera0").unwrap(), Some(entry.clone()));
+
+    // Another handle to the same file sees the entry
+    let other = Registry::open(registry.path()).unwrap();
+    assert_eq!(other.entries().unwrap(), vec![entry]);
+
+    registry.remove("camera0", std::process::id()).unwrap();
+    assert_eq!(other.lookup("camera0").unwrap(), None);
+    cleanup(&registry);
+}
+
+#[test]
+fn test_registry_reclaims_entries_of_dead_processes() {
+    let registry = test_registry("stale");
+    let stale = RegistryEntry {
+        name: "camera0".to_string(),
+        // No process has pid 0
+        pid: 0,
+        start_time: 0,
+        generation: 0,
+        descriptor: descriptor(640),
+        socket_path: Path::new("/tmp/stale.sock").to_path_buf(),
+    };
+    registry.insert(&stale).unwrap();
+    assert_eq!(registry.lookup("camera0").unwrap(), None);
+
+    let live = RegistryEntry {
+        pid: std::process::id(),
+        start_time: process_start_time(std::process::id()).unwrap(),
+        ..stale
+    };
+    registry.insert(&live).unwrap();
+    assert_eq!(registry.entries().unwrap(), vec![live]);
+    cleanup(&registry);
+}
+
+#[test]
+fn test_registry_ignores_reused_pids() {
+    let registry = test_registry("reused");
+    let start_time = process_start_time(std::process::id()).unwrap();
+    // Our pid, but published by an earlier process that held it
+    let stale = RegistryEntry {
+        name: "camera0".to_string(),
+        pid: std::process::id(),
+        start_time: start_time - 1,
+        generation: 0,
+        descriptor: descriptor(640),
+        socket_path: Path::new("/tmp/reused.sock").to_path_buf(),
+    };
+    registry.insert(&stale).unwrap();
+    assert_eq!(registry.lookup("camera0").unwrap(), None);
+
+    let live = RegistryEntry { start_time, ..stale };
+    registry.insert(&live).unwrap();
+    assert_eq!(registry.entries().unwrap(), vec![live]);
+    cleanup(&registry);
+}
+
+#[test]
+fn test_registry_skips_corrupt_slots() {
+    use std::os::unix::fs::FileExt;
+
+    let registry = test_registry("corrupt");
+    let entry = RegistryEntry {
+        name: "camera0".to_string(),
+        pid: std::process::id(),
+        start_time: process_start_time(std::process::id()).unwrap(),
+        generation: 0,
+        descriptor: descriptor(640),
+        socket_path: Path::new("/tmp/corrupt.sock").to_path_buf(),
+    };
+    // Fill the first slot (right after the 16-byte header) with garbage
+    let file = std::fs::OpenOptions::new().write(true).open(registry.path()).unwrap();
+    file.write_all_at(&[0xff; 64], 16).unwrap();
+
+    assert_eq!(registry.entries().unwrap(), Vec::new());
+    registry.insert(&entry).unwrap();
+    assert_eq!(registry.entries().unwrap(), vec![entry]);
+    cleanup(&registry);
+}
+
+#[test]
+fn test_default_registry_is_private_to_the_user() {
+    let path = default_registry_path();
+    match std::env::var_os("XDG_RUNTIME_DIR") {
+        Some(runtime_dir) => assert_eq!(path, Path::new(&runtime_dir).join("geyser-registry")),
+        None => assert!(path.starts_with(std::env::temp_dir())),
+    }
+
+    let registry = test_registry("mode");
+    let mode = std::fs::metadata(registry.path()).unwrap().mode();
+    assert_eq!(mode & 0o077, 0);
+    cleanup(&registry);
+}
+
+#[test]
+fn test_registry_rejects_invalid_names() {
+    assert!(registry::validate_name("camera0").is_ok());
+    assert!(registry::validate_name("").is_err());
+    assert!(registry::validate_name("../camera").is_err());
+    assert!(registry::validate_name(&"a".repeat(registry::MAX_NAME_LEN + 1)).is_err());
+}
+
+#[test]
+fn test_publish_subscribe_and_update() {
+    let registry = test_registry("pubsub");
+    let first = File::open("/dev/null").unwrap();
+    let second = File::open("/proc/self/exe").unwrap();
+
+    let first_package = dma_buf_package(&first);
+    let publication = Publication::new(&registry, "pubsub", &descriptor(1280), first_package.clone()).unwrap();
+
+    let mut subscription = Subscription::new(&registry, "pubsub").unwrap();
+    assert_eq!(subscription.generation(), 0);
+    assert_eq!(subscription.descriptor(), &descriptor(1280));
+    assert!(!subscription.poll().unwrap());
+    let package = subscription.take_package().unwrap();
+    assert_eq!(package_inode(&package), first.metadata().unwrap().ino());
+    wire::close_package_fds(&package);
+
+    let second_package = dma_buf_package(&second);
+    assert_eq!(publication.update(&descriptor(1920), second_package.clone()).unwrap(), 1);
+    assert!(subscription.poll().unwrap());
+    assert_eq!(subscription.generation(), 1);
+    assert_eq!(subscription.descriptor().width, 1920);
+    let package = subscription.take_package().unwrap();
+    assert_eq!(package_inode(&package), second.metadata().unwrap().ino());
+    wire::close_package_fds(&package);
+
+    drop(publication);
+    assert!(subscription.poll().is_err());
+    wire::close_package_fds(&first_package);
+    wire::close_package_fds(&second_package);
+    cleanup(&registry);
+}
+
+#[test]
+fn test_subscription_follows_a_republished_name() {
+    let registry = test_registry("republish");
+    let first = File::open("/dev/null").unwrap();
+    let second = File::open("/proc/self/exe").unwrap();
+
+    let first_package = dma_buf_package(&first);
+    let publication = Publication::new(&registry, "republish", &descriptor(1280), first_package.clone()).unwrap();
+    let mut subscription = Subscription::new(&registry, "republish").unwrap();
+    wire::close_package_fds(&subscription.take_package().unwrap());
+    drop(publication);
+
+    // The new publisher starts over at generation 0
+    let second_package = dma_buf_package(&second);
+    let publication = Publication::new(&registry, "republish", &descriptor(1920), second_package.clone()).unwrap();
+    assert!(subscription.poll().unwrap());
+    assert_eq!(subscription.generation(), 0);
+    assert_eq!(subscription.descriptor().width, 1920);
+    let package = subscription.take_package().unwrap();
+    assert_eq!(package_inode(&package), second.metadata().unwrap().ino());
+    wire::close_package_fds(&package);
+    assert!(!subscription.poll().unwrap());
+
+    drop(publication);
+    wire::close_package_fds(&first_package);
+    wire::close_package_fds(&second_package);
+    cleanup(&registry);
+}
+
+// Imports without a GPU: each live texture holds the received descriptor open
+struct CountingManager {
+    live: Arc<AtomicUsize>,
+    released: AtomicUsize,
+}
+
+struct CountingTexture {
+    descriptor: TextureDescriptor,
+    package: TextureSharePackage,
+    live: Arc<AtomicUsize>,
+}
+
+impl crate::SharedTexture for CountingTexture {
+    fn width(&self) -> u32 {
+        self.descriptor.width
+    }
+    fn height(&self) -> u32 {
+        self.descriptor.height
+    }
+    fn format(&self) -> TextureFormat {
+        self.descriptor.format
+    }
+    fn usage(&self) -> &[TextureUsage] {
+        &self.descriptor.usage
+    }
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl Drop for CountingTexture {
+    fn drop(&mut self) {
+        wire::close_package_fds(&self.package);
+        self.live.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl crate::TextureShareManager for CountingManager {
+    fn create_shareable_texture(&self, _descriptor: &TextureDescriptor) -> Result<Box<dyn crate::SharedTexture>> {
+        Err(GeyserError::OperationNotSupported)
+    }
+    fn export_texture(&self, _texture: &dyn crate::SharedTexture) -> Result<ApiTextureHandle> {
+        Err(GeyserError::OperationNotSupported)
+    }
+    fn import_texture(&self, handle: ApiTextureHandle, descriptor: &TextureDescriptor) -> Result<Box<dyn crate::SharedTexture>> {
+        self.live.fetch_add(1, Ordering::SeqCst);
+        Ok(Box::new(CountingTexture {
+            descriptor: descriptor.clone(),
+            package: TextureSharePackage {
+                texture: handle,
+                sync: SyncPrimitives::default(),
+            },
+            live: self.live.clone(),
+        }))
+    }
+    fn release_texture_handle(&self, _handle: ApiTextureHandle) -> Result<()> {
+        // The texture must be gone before its handle is released
+        assert_eq!(self.live.load(Ordering::SeqCst), 0);
+        self.released.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+fn counting_manager() -> Arc<CountingManager> {
+    Arc::new(CountingManager {
+        live: Default::default(),
+        released: Default::default(),
+    })
+}
+
+fn session_config(device: u8, formats: Vec<TextureFormat>) -> SessionConfig {
+    let identity = crate::common::DeviceIdentity {
+        device_uuid: [device; 16],
+        driver_uuid: [1; 16],
+        driver_version: 1,
+    };
+    SessionConfig::new(identity, formats)
+        .with_heartbeat_interval(Duration::from_millis(20))
+        .with_peer_timeout(Duration::from_millis(200))
+}
+
+fn connect_sessions<T: Transport + 'static>(
+    producer_transport: T,
+    consumer_transport: T,
+    producer_config: SessionConfig,
+    consumer_config: SessionConfig,
+) -> (Result<ProducerSession<T>>, Result<ConsumerSession<T>>) {
+    let producer = std::thread::spawn(move || ProducerSession::accept(producer_transport, producer_config));
+    let consumer = ConsumerSession::connect(consumer_transport, consumer_config);
+    (producer.join().unwrap(), consumer)
+}
+
+#[test]
+fn test_session_negotiates_format_and_strategy() {
+    let (a, b) = LoopbackTransport::pair();
+    let (producer, consumer) = connect_sessions(
+        a,
+        b,
+        session_config(1, vec![TextureFormat::Rgba16Float, TextureFormat::Bgra8Unorm]),
+        session_config(2, vec![TextureFormat::Bgra8Unorm, TextureFormat::Rgba8Unorm]),
+    );
+    let (producer, consumer) = (producer.unwrap(), consumer.unwrap());
+
+    assert_eq!(producer.negotiated().format, TextureFormat::Bgra8Unorm);
+    assert_eq!(consumer.negotiated().format, TextureFormat::Bgra8Unorm);
+    assert_eq!(producer.negotiated().version, session::PROTOCOL_VERSION);
+    assert_eq!(producer.negotiated().peer_device.device_uuid, [2; 16]);
+    // Different devices share by copy
+    assert_eq!(consumer.negotiated().strategy, crate::SharingStrategy::Copy);
+    assert_eq!(producer.state(), SessionState::Active);
+}
+
+#[test]
+fn test_session_rejects_without_common_format() {
+    let (a, b) = LoopbackTransport::pair();
+    let (producer, consumer) = connect_sessions(
+        a,
+        b,
+        session_config(1, vec![TextureFormat::Rgba16Float]),
+        session_config(1, vec![TextureFormat::Bgra8Unorm]),
+    );
+    assert!(producer.is_err());
+    match consumer {
+        Err(GeyserError::IpcError(message)) => assert!(message.contains("No common texture format")),
+        other => panic!("Unexpected result: {:?}", other.err()),
+    }
+}
+
+#[test]
+fn test_session_resize_replaces_imported_texture() {
+    let (a, b) = LoopbackTransport::pair();
+    let formats = vec![TextureFormat::Bgra8Unorm];
+    let (producer, consumer) = connect_sessions(a, b, session_config(1, formats.clone()), session_config(1, formats));
+    let (mut producer, mut consumer) = (producer.unwrap(), consumer.unwrap());
+    assert_eq!(consumer.negotiated().strategy, crate::SharingStrategy::ZeroCopy);
+    let manager = counting_manager();
+    let file = File::open("/dev/null").unwrap();
+    let package = dma_buf_package(&file);
+
+    assert_eq!(producer.share(&descriptor(1280), &package).unwrap(), 0);
+    let event = consumer.poll(&manager, Duration::from_secs(1)).unwrap();
+    assert_eq!(
+        event,
+        Some(SessionEvent::TextureChanged {
+            generation: 0,
+            descriptor: descriptor(1280),
+        })
+    );
+    assert_eq!(producer.poll(Duration::from_secs(1)).unwrap(), Some(SessionEvent::Imported { generation: 0 }));
+
+    assert_eq!(producer.share(&descriptor(1920), &package).unwrap(), 1);
+    producer.frame_ready(7).unwrap();
+    consumer.poll(&manager, Duration::from_secs(1)).unwrap();
+    assert_eq!(consumer.texture().unwrap().width(), 1920);
+    assert_eq!(consumer.generation(), Some(1));
+    // The first import was released when the second replaced it
+    assert_eq!(manager.live.load(Ordering::SeqCst), 1);
+    assert_eq!(
+        consumer.poll(&manager, Duration::from_secs(1)).unwrap(),
+        Some(SessionEvent::Frame {
+            generation: 1,
+            frame: 7,
+            timestamp: None
+        })
+    );
+    assert_eq!(producer.poll(Duration::from_secs(1)).unwrap(), Some(SessionEvent::Imported { generation: 1 }));
+    assert_eq!(producer.consumer_generation(), Some(1));
+
+    // Shares are limited to the negotiated format
+    let other_format = TextureDescriptor {
+        format: TextureFormat::Rgba8Unorm,
+        ..descriptor(1920)
+    };
+    assert!(producer.share(&other_format, &package).is_err());
+
+    producer.close().unwrap();
+    assert_eq!(consumer.poll(&manager, Duration::from_secs(1)).unwrap(), Some(SessionEvent::Closed));
+    assert_eq!(consumer.state(), SessionState::Closed);
+    assert!(consumer.texture().is_none());
+    assert_eq!(manager.live.load(Ordering::SeqCst), 0);
+    wire::close_package_fds(&package);
+}
+
+#[test]
+fn test_session_forwards_packages_without_importing() {
+    let (a, b) = LoopbackTransport::pair();
+    let formats = vec![TextureFormat::Bgra8Unorm];
+    let (producer, consumer) = connect_sessions(a, b, session_config(1, formats.clone()), session_config(1, formats));
+    let (mut producer, mut consumer) = (producer.unwrap(), consumer.unwrap());
+    let file = File::open("/dev/null").unwrap();
+    let package = dma_buf_package(&file);
+
+    producer.share(&descriptor(1280), &package).unwrap();
+    let mut forwarded = None;
+    let event = consumer
+        .poll_forwarding(Duration::from_secs(1), |package, descriptor| {
+            forwarded = Some((package, descriptor.clone()));
+            Ok(())
+        })
+        .unwrap();
+    assert_eq!(
+        event,
+        Some(SessionEvent::TextureChanged {
+            generation: 0,
+            descriptor: descriptor(1280),
+        })
+    );
+    let (forwarded, forwarded_descriptor) = forwarded.unwrap();
+    assert_eq!(forwarded_descriptor, descriptor(1280));
+    assert_eq!(package_inode(&forwarded), package_inode(&package));
+    assert!(consumer.texture().is_none());
+    assert_eq!(consumer.generation(), Some(0));
+    assert_eq!(producer.poll(Duration::from_secs(1)).unwrap(), Some(SessionEvent::Imported { generation: 0 }));
+
+    wire::close_package_fds(&forwarded);
+    wire::close_package_fds(&package);
+}
+
+#[test]
+fn test_session_frame_timestamps() {
+    let (a, b) = LoopbackTransport::pair();
+    let formats = vec![TextureFormat::Bgra8Unorm];
+    let (producer, consumer) = connect_sessions(a, b, session_config(1, formats.clone()), session_config(1, formats));
+    let (mut producer, mut consumer) = (producer.unwrap(), consumer.unwrap());
+    let manager = counting_manager();
+    let file = File::open("/proc/self/exe").unwrap();
+    let package = dma_buf_package(&file);
+
+    producer.share(&descriptor(1280), &package).unwrap();
+    consumer.poll(&manager, Duration::from_secs(1)).unwrap();
+    producer.frame_ready_at(3, Duration::from_millis(1500)).unwrap();
+    assert_eq!(
+        consumer.poll(&manager, Duration::from_secs(1)).unwrap(),
+        Some(SessionEvent::Frame {
+            generation: 0,
+            frame: 3,
+            timestamp: Some(Duration::from_millis(1500))
+        })
+    );
+    drop(consumer);
+    wire::close_package_fds(&package);
+}
+
+#[test]
+fn test_session_omits_timestamps_for_version_1_consumers() {
+    let (producer_transport, mut consumer_transport) = LoopbackTransport::pair();
+    let hello = session::SessionMessage::Hello {
+        version: 1,
+        device: Default::default(),
+        formats: vec![TextureFormat::Bgra8Unorm],
+    };
+    let (bytes, fds) = hello.encode().unwrap().finish();
+    consumer_transport.send(&bytes, &fds).unwrap();
+
+    let mut producer =
+        ProducerSession::accept(producer_transport, session_config(1, vec![TextureFormat::Bgra8Unorm])).unwrap();
+    assert_eq!(producer.negotiated().version, 1);
+    let file = File::open("/proc/self/exe").unwrap();
+    let package = dma_buf_package(&file);
+    producer.share(&descriptor(64), &package).unwrap();
+    producer.frame_ready_at(1, Duration::from_secs(1)).unwrap();
+
+    // Welcome and Texture, then a frame in the version 1 encoding
+    let mut messages = Vec::new();
+    while let Some((bytes, fds)) = consumer_transport.recv(Duration::from_millis(100)).unwrap() {
+        let message = session::SessionMessage::decode(&bytes, fds).unwrap();
+        if let session::SessionMessage::Texture { package, .. } = &message {
+            wire::close_package_fds(package);
+        }
+        messages.push(message);
+    }
+    assert!(matches!(
+        messages.last(),
+        Some(session::SessionMessage::Frame {
+            frame: 1,
+            timestamp: None,
+            ..
+        })
+    ));
+    wire::close_package_fds(&package);
+}
+
+#[test]
+fn test_session_heartbeats_keep_idle_peers_alive() {
+    let (a, b) = LoopbackTransport::pair();
+    let formats = vec![TextureFormat::Bgra8Unorm];
+    let (producer, consumer) = connect_sessions(a, b, session_config(1, formats.clone()), session_config(1, formats));
+    let (producer, mut consumer) = (producer.unwrap(), consumer.unwrap());
+
+    // Both sides idle for longer than the peer timeout, polling in turn
+    let producer = std::thread::spawn(move || {
+        let mut producer = producer;
+        assert_eq!(producer.poll(Duration::from_millis(400)).unwrap(), None);
+        producer
+    });
+    assert_eq!(consumer.poll(&counting_manager(), Duration::from_millis(400)).unwrap(), None);
+    let mut producer = producer.join().unwrap();
+    assert_eq!(producer.state(), SessionState::Active);
+
+    consumer.close().unwrap();
+    assert_eq!(producer.poll(Duration::from_secs(1)).unwrap(), Some(SessionEvent::Closed));
+    assert_eq!(producer.state(), SessionState::Closed);
+}
+
+#[test]
+fn test_session_detects_dead_peer() {
+    let (a, b) = LoopbackTransport::pair();
+    let formats = vec![TextureFormat::Bgra8Unorm];
+    let (producer, consumer) = connect_sessions(a, b, session_config(1, formats.clone()), session_config(1, formats));
+    let (mut producer, consumer) = (producer.unwrap(), consumer.unwrap());
+
+    // A consumer that never polls sends no heartbeats
+    assert!(producer.poll(Duration::from_secs(1)).is_err());
+    assert_eq!(producer.state(), SessionState::PeerLost);
+    assert!(producer.frame_ready(0).is_err());
+    drop(consumer);
+
+    // A consumer whose transport goes away is lost immediately
+    let (a, b) = LoopbackTransport::pair();
+    let formats = vec![TextureFormat::Bgra8Unorm];
+    let (producer, consumer) = connect_sessions(a, b, session_config(1, formats.clone()), session_config(1, formats));
+    let (mut producer, mut consumer) = (producer.unwrap(), consumer.unwrap());
+    let manager = counting_manager();
+    let file = File::open("/dev/null").unwrap();
+    let package = dma_buf_package(&file);
+    producer.share(&descriptor(640), &package).unwrap();
+    consumer.poll(&manager, Duration::from_secs(1)).unwrap();
+    drop(producer);
+    // Dropping the producer says goodbye, which releases the import
+    assert_eq!(consumer.poll(&manager, Duration::from_secs(1)).unwrap(), Some(SessionEvent::Closed));
+    assert_eq!(manager.live.load(Ordering::SeqCst), 0);
+    wire::close_package_fds(&package);
+}
+
+#[cfg(feature = "vulkan")]
+#[test]
+fn test_session_releases_imported_handles() {
+    let (a, b) = LoopbackTransport::pair();
+    let formats = vec![TextureFormat::Bgra8Unorm];
+    let (producer, consumer) = connect_sessions(a, b, session_config(1, formats.clone()), session_config(1, formats));
+    let (mut producer, mut consumer) = (producer.unwrap(), consumer.unwrap());
+    let manager = counting_manager();
+    let file = File::open("/dev/null").unwrap();
+    let package = TextureSharePackage {
+        texture: ApiTextureHandle::Vulkan(crate::vulkan::VulkanTextureShareHandle {
+            raw_handle: file.try_clone().unwrap().into_raw_fd() as u64,
+            memory_type_index: 0,
+            size: 4096,
+            handle_type: ash::vk::ExternalMemoryHandleTypeFlags::OPAQUE_FD,
+            dedicated_allocation: true,
+            device_identity: Default::default(),
+            plane_layouts: Vec::new(),
+        }),
+        sync: SyncPrimitives::default(),
+    };
+
+    producer.share(&descriptor(640), &package).unwrap();
+    consumer.poll(&manager, Duration::from_secs(1)).unwrap();
+    assert_eq!(manager.released.load(Ordering::SeqCst), 0);
+
+    // Replacing the texture releases the first handle
+    producer.share(&descriptor(1280), &package).unwrap();
+    consumer.poll(&manager, Duration::from_secs(1)).unwrap();
+    assert_eq!(manager.released.load(Ordering::SeqCst), 1);
+
+    // So does dropping the session
+    drop(consumer);
+    assert_eq!(manager.live.load(Ordering::SeqCst), 0);
+    assert_eq!(manager.released.load(Ordering::SeqCst), 2);
+    wire::close_package_fds(&package);
+}
+
+// Open descriptors of this process that refer to the memfd called `name`
+fn open_memfds(name: &str) -> usize {
+    std::fs::read_dir("/proc/self/fd")
+        .unwrap()
+        .filter_map(|entry| std::fs::read_link(entry.unwrap().path()).ok())
+        .filter(|target| target.to_string_lossy().contains(name))
+        .count()
+}
+
+#[test]
+fn test_session_closes_fds_of_unexpected_textures() {
+    let (producer_transport, mut consumer_transport) = LoopbackTransport::pair();
+    let name = format!("geyser-unexpected-{}", std::process::id());
+    let name = std::ffi::CString::new(name).unwrap();
+    let fd = unsafe { OwnedFd::from_raw_fd(libc::memfd_create(name.as_ptr(), libc::MFD_CLOEXEC)) };
+    let file = File::from(fd);
+
+    // A texture where the producer expects `Hello`
+    let texture = session::SessionMessage::Texture {
+        generation: 0,
+        descriptor: descriptor(64),
+        package: dma_buf_package(&file),
+    };
+    let (bytes, fds) = texture.encode().unwrap().finish();
+    consumer_transport.send(&bytes, &fds).unwrap();
+    if let session::SessionMessage::Texture { package, .. } = &texture {
+        wire::close_package_fds(package);
+    }
+    // `file` and the copy in flight
+    assert_eq!(open_memfds(name.to_str().unwrap()), 2);
+
+    let producer = ProducerSession::accept(producer_transport, session_config(1, vec![TextureFormat::Bgra8Unorm]));
+    assert!(producer.is_err());
+    // Only `file` is left open
+    assert_eq!(open_memfds(name.to_str().unwrap()), 1);
+}
+
+#[test]
+fn test_session_reconnects_after_peer_loss() {
+    let formats = vec![TextureFormat::Bgra8Unorm];
+    let (a, b) = LoopbackTransport::pair();
+    let (producer, consumer) =
+        connect_sessions(a, b, session_config(1, formats.clone()), session_config(1, formats.clone()));
+    let (mut producer, mut consumer) = (producer.unwrap(), consumer.unwrap());
+    let manager = counting_manager();
+    let file = File::open("/dev/null").unwrap();
+    let package = dma_buf_package(&file);
+    producer.share(&descriptor(640), &package).unwrap();
+    consumer.poll(&manager, Duration::from_secs(1)).unwrap();
+
+    // The producer goes away without saying goodbye
+    std::mem::forget(producer);
+    assert!(consumer.poll(&manager, Duration::from_secs(1)).is_err());
+    assert_eq!(consumer.state(), SessionState::PeerLost);
+    assert_eq!(manager.live.load(Ordering::SeqCst), 0);
+
+    // A restarted producer accepts the same consumer on a new transport
+    let (a, b) = LoopbackTransport::pair();
+    let config = session_config(1, formats.clone());
+    let producer = std::thread::spawn(move || ProducerSession::accept(a, config));
+    consumer.reconnect(b).unwrap();
+    let mut producer = producer.join().unwrap().unwrap();
+    assert_eq!(consumer.state(), SessionState::Active);
+    assert_eq!(consumer.generation(), None);
+
+    assert_eq!(producer.share(&descriptor(1280), &package).unwrap(), 0);
+    consumer.poll(&manager, Duration::from_secs(1)).unwrap();
+    assert_eq!(consumer.texture().unwrap().width(), 1280);
+
+    // And the producer can take a reconnecting consumer back
+    let (a, b) = LoopbackTransport::pair();
+    let producer = std::thread::spawn(move || {
+        producer.reaccept(a).unwrap();
+        producer
+    });
+    let other = ConsumerSession::connect(b, session_config(1, formats)).unwrap();
+    let producer = producer.join().unwrap();
+    assert_eq!(producer.generation(), None);
+    assert_eq!(producer.state(), SessionState::Active);
+    drop(other);
+    drop(consumer);
+    wire::close_package_fds(&package);
+}
+
+#[test]
+fn test_session_over_unix_socket() {
+    let (a, b) = UnixStream::pair().unwrap();
+    let formats = vec![TextureFormat::Bgra8Unorm];
+    let (producer, consumer) = connect_sessions(
+        UnixTransport::new(a),
+        UnixTransport::new(b),
+        session_config(1, formats.clone()),
+        session_config(1, formats),
+    );
+    let (mut producer, mut consumer) = (producer.unwrap(), consumer.unwrap());
+    let manager = counting_manager();
+    let file = File::open("/proc/self/exe").unwrap();
+    let package = dma_buf_package(&file);
+
+    producer.share(&descriptor(1280), &package).unwrap();
+    consumer.poll(&manager, Duration::from_secs(1)).unwrap();
+    let texture = consumer.texture().unwrap().as_any().downcast_ref::<CountingTexture>().unwrap();
+    assert_ne!(package_inode(&texture.package), 0);
+    assert_eq!(package_inode(&texture.package), file.metadata().unwrap().ino());
+
+    drop(producer);
+    assert_eq!(consumer.poll(&manager, Duration::from_secs(1)).unwrap(), Some(SessionEvent::Closed));
+    // Polling after the session ended fails
+    assert!(consumer.poll(&manager, Duration::ZERO).is_err());
+    wire::close_package_fds(&package);
+}
+
+#[test]
+fn test_unix_transport_resumes_split_messages() {
+    let (a, b) = UnixStream::pair().unwrap();
+    let mut transport = UnixTransport::new(b);
+    let writer = std::thread::spawn(move || {
+        let body = b"split message";
+        let header = (body.len() as u32).to_le_bytes();
+        // Pauses inside the header and inside the body, longer than the receive timeout
+        for chunk in [&header[..2], &header[2..], &body[..5], &body[5..]] {
+            std::io::Write::write_all(&mut &a, chunk).unwrap();
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        let file = File::open("/proc/self/exe").unwrap();
+        socket::send_message(&a, b"next", &[file.as_raw_fd()]).unwrap();
+        a
+    });
+
+    let mut messages = Vec::new();
+    let mut timeouts = 0;
+    while messages.len() < 2 {
+        match transport.recv(Duration::from_millis(10)).unwrap() {
+            Some(message) => messages.push(message),
+            None => timeouts += 1,
+        }
+        assert!(timeouts < 500, "Messages did not arrive");
+    }
+    let _a = writer.join().unwrap();
+
+    assert!(timeouts > 0);
+    assert_eq!(messages[0].0, b"split message");
+    assert!(messages[0].1.is_empty());
+    // The stream stayed in step with the sender
+    assert_eq!(messages[1].0, b"next");
+    assert_eq!(messages[1].1.len(), 1);
+}
+
+// A memfd laid out like a linear dma-buf: `offset` bytes, then rows `stride` apart
+fn memfd_image(offset: u32, stride: u32, rows: &[&[u8]]) -> OwnedFd {
+    let fd = unsafe { libc::memfd_create(c"geyser-test".as_ptr(), libc::MFD_CLOEXEC) };
+    assert!(fd >= 0);
+    let mut file = unsafe { File::from_raw_fd(fd) };
+    let mut bytes = vec![0xee; offset as usize + stride as usize * rows.len()];
+    for (y, row) in rows.iter().enumerate() {
+        let start = offset as usize + y * stride as usize;
+        bytes[start..start + row.len()].copy_from_slice(row);
+    }
+    std::io::Write::write_all(&mut file, &bytes).unwrap();
+    file.into()
+}
+
+fn linear_handle(fd: OwnedFd, offset: u32, stride: u32) -> DmaBufHandle {
+    DmaBufHandle {
+        fd: fd.into_raw_fd(),
+        fourcc: crate::common::to_drm_fourcc(TextureFormat::R8Unorm).unwrap(),
+        modifier: DRM_FORMAT_MOD_LINEAR,
+        offset,
+        stride,
+    }
+}
+
+fn gray_descriptor(width: u32, height: u32) -> TextureDescriptor {
+    TextureDescriptor {
+        format: TextureFormat::R8Unorm,
+        height,
+        ..descriptor(width)
+    }
+}
+
+#[test]
+fn test_dma_buf_manager_maps_rows() {
+    use crate::TextureShareManager;
+
+    let manager = DmaBufShareManager::new();
+    let fd = memfd_image(16, 8, &[b"abcd", b"efgh", b"ijkl"]);
+    let texture = manager
+        .import_texture(ApiTextureHandle::DmaBuf(linear_handle(fd, 16, 8)), &gray_descriptor(4, 3))
+        .unwrap();
+    let texture = texture.as_any().downcast_ref::<DmaBufTexture>().unwrap();
+    assert_eq!(texture.size(), 16 + 8 * 2 + 4);
+
+    let mapping = texture.map().unwrap();
+    assert_eq!(mapping.row_pitch(), 8);
+    assert_eq!(mapping.row(0), b"abcd");
+    assert_eq!(mapping.row(2), b"ijkl");
+    drop(mapping);
+
+    // Exports hand out a duplicate of the same buffer
+    let exported = match manager.export_texture(texture).unwrap() {
+        ApiTextureHandle::DmaBuf(handle) => handle,
+        #[allow(unreachable_patterns)]
+        _ => unreachable!(),
+    };
+    assert_ne!(exported.fd, texture.handle().fd);
+    assert_eq!(exported.stride, 8);
+    let inode = |fd: RawFd| std::fs::metadata(format!("/proc/self/fd/{}", fd)).unwrap().ino();
+    assert_eq!(inode(exported.fd), inode(texture.handle().fd));
+    manager.release_texture_handle(ApiTextureHandle::DmaBuf(exported)).unwrap();
+
+    // Clones own a duplicate of the descriptor
+    let clone = texture.try_clone().unwrap();
+    assert_ne!(clone.handle().fd, texture.handle().fd);
+    assert_eq!(inode(clone.handle().fd), inode(texture.handle().fd));
+    assert_eq!(clone.descriptor(), texture.descriptor());
+    assert_eq!(clone.map().unwrap().row(1), b"efgh");
+}
+
+#[test]
+fn test_dma_buf_manager_rejects_unreadable_layouts() {
+    use crate::TextureShareManager;
+
+    let manager = DmaBufShareManager::new();
+    let tiled = DmaBufHandle {
+        modifier: 0x0100_0000_0000_0001,
+        ..linear_handle(memfd_image(0, 4, &[b"abcd"]), 0, 4)
+    };
+    assert!(manager.import_texture(ApiTextureHandle::DmaBuf(tiled), &gray_descriptor(4, 1)).is_err());
+
+    let narrow = linear_handle(memfd_image(0, 2, &[b"ab"]), 0, 2);
+    assert!(manager.import_texture(ApiTextureHandle::DmaBuf(narrow), &gray_descriptor(4, 1)).is_err());
+
+    let wrong_format = linear_handle(memfd_image(0, 16, &[b"abcd"]), 0, 16);
+    assert!(manager.import_texture(ApiTextureHandle::DmaBuf(wrong_format), &descriptor(4)).is_err());
+}
@@ -0,0 +1,475 @@
+//! Binary encoding of descriptors and share packages for other processes.
+//!
+//! Values are little-endian. File descriptors are not part of the bytes: they are
+//! collected by `Writer` and sent alongside (see `socket::send_message`), and the
+//! encoding refers to them by index.
+
+use std::os::fd::{IntoRawFd, OwnedFd, RawFd};
+
+use crate::common::{
+    ApiTextureHandle, DeviceIdentity, DmaBufHandle, SyncHandle, SyncPrimitives, TextureDescriptor, TextureFormat,
+    TextureMemoryLocation, TextureSharePackage, TextureTiling, TextureUsage,
+};
+use crate::error::{GeyserError, Result};
+
+// Formats by wire code; new formats are only ever appended
+const FORMATS: [TextureFormat; 21] = [
+    TextureFormat::Rgba8Unorm,
+    TextureFormat::Bgra8Unorm,
+    TextureFormat::Rgba8Srgb,
+    TextureFormat::Bgra8Srgb,
+    TextureFormat::R8Unorm,
+    TextureFormat::Rg8Unorm,
+    TextureFormat::R16Float,
+    TextureFormat::Rg16Float,
+    TextureFormat::Rgba16Float,
+    TextureFormat::R16Uint,
+    TextureFormat::R16Sint,
+    TextureFormat::R32Float,
+    TextureFormat::Rg32Float,
+    TextureFormat::Rgba32Float,
+    TextureFormat::R32Uint,
+    TextureFormat::R32Sint,
+    TextureFormat::Depth32Float,
+    TextureFormat::Depth24Plus,
+    TextureFormat::Depth24PlusStencil8,
+    TextureFormat::Rgb10a2Unorm,
+    TextureFormat::Rg11b10Float,
+];
+
+const USAGES: [TextureUsage; 5] = [
+    TextureUsage::CopySrc,
+    TextureUsage::CopyDst,
+    TextureUsage::TextureBinding,
+    TextureUsage::RenderAttachment,
+    TextureUsage::StorageBinding,
+];
+
+// Tags for `ApiTextureHandle` variants and `SyncHandle` variants; 0 means "none"
+#[cfg(feature = "vulkan")]
+const HANDLE_VULKAN: u8 = 1;
+const HANDLE_DMA_BUF: u8 = 2;
+#[cfg(feature = "vulkan")]
+const SYNC_VULKAN_SEMAPHORE: u8 = 1;
+#[cfg(feature = "vulkan")]
+const SYNC_VULKAN_FENCE: u8 = 2;
+
+// Images with a DRM format modifier have at most four memory planes
+#[cfg(feature = "vulkan")]
+const MAX_MEMORY_PLANES: u8 = 4;
+
+fn malformed(what: &str) -> GeyserError {
+    GeyserError::IpcError(format!("Malformed message: {}", what))
+}
+
+/// Accumulates an encoded message and the descriptors it refers to.
+#[derive(Debug, Default)]
+pub struct Writer {
+    bytes: Vec<u8>,
+    fds: Vec<RawFd>,
+}
+
+impl Writer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    pub fn u16(&mut self, value: u16) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn u32(&mut self, value: u32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn u64(&mut self, value: u64) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn bool(&mut self, value: bool) {
+        self.u8(value as u8);
+    }
+
+    /// Writes a length-prefixed byte string.
+    pub fn bytes(&mut self, value: &[u8]) {
+        self.u32(value.len() as u32);
+        self.bytes.extend_from_slice(value);
+    }
+
+    pub fn str(&mut self, value: &str) {
+        self.bytes(value.as_bytes());
+    }
+
+    /// Attaches a descriptor to the message and writes its index.
+    pub fn fd(&mut self, fd: RawFd) {
+        self.u8(self.fds.len() as u8);
+        self.fds.push(fd);
+    }
+
+    /// Returns the encoded bytes and the descriptors to send with them.
+    pub fn finish(self) -> (Vec<u8>, Vec<RawFd>) {
+        (self.bytes, self.fds)
+    }
+}
+
+/// Decodes a message produced by `Writer`.
+///
+/// Descriptors that are never read are closed when the reader is dropped.
+#[derive(Debug)]
+pub struct Reader<'a> {
+    bytes: &'a [u8],
+    fds: Vec<Option<OwnedFd>>,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(bytes: &'a [u8], fds: Vec<OwnedFd>) -> Self {
+        Self {
+            bytes,
+            fds: fds.into_iter().map(Some).collect(),
+        }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.bytes.len() < len {
+            return Err(malformed("unexpected end of data"));
+        }
+        let (head, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(head)
+    }
+
+    pub fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub fn u16(&mut self) -> Result<u16> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    pub fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    pub fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    pub fn bool(&mut self) -> Result<bool> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(malformed("invalid boolean")),
+        }
+    }
+
+    pub fn bytes(&mut self) -> Result<&'a [u8]> {
+        let len = self.u32()? as usize;
+        self.take(len)
+    }
+
+    pub fn str(&mut self) -> Result<&'a str> {
+        std::str::from_utf8(self.bytes()?).map_err(|_| malformed("invalid UTF-8"))
+    }
+
+    /// Reads a descriptor index and takes ownership of the descriptor.
+    pub fn fd(&mut self) -> Result<OwnedFd> {
+        let index = self.u8()? as usize;
+        self.fds
+            .get_mut(index)
+            .and_then(Option::take)
+            .ok_or_else(|| malformed("missing file descriptor"))
+    }
+
+    /// Fails if any bytes are left over.
+    pub fn finish(self) -> Result<()> {
+        if self.bytes.is_empty() {
+            Ok(())
+        } else {
+            Err(malformed("trailing data"))
+        }
+    }
+}
+
+pub fn write_format(writer: &mut Writer, format: TextureFormat) {
+    writer.u8(FORMATS.iter().position(|&f| f == format).unwrap() as u8);
+}
+
+pub fn read_format(reader: &mut Reader) -> Result<TextureFormat> {
+    FORMATS.get(reader.u8()? as usize).copied().ok_or_else(|| malformed("unknown texture format"))
+}
+
+pub fn write_descriptor(writer: &mut Writer, descriptor: &TextureDescriptor) {
+    writer.u32(descriptor.width);
+    writer.u32(descriptor.height);
+    write_format(writer, descriptor.format);
+    writer.u8(descriptor.usage.len() as u8);
+    for usage in &descriptor.usage {
+        writer.u8(USAGES.iter().position(|u| u == usage).unwrap() as u8);
+    }
+    match &descriptor.label {
+        Some(label) => {
+            writer.bool(true);
+            writer.str(label);
+        }
+        None => writer.bool(false),
+    }
+    match descriptor.tiling {
+        TextureTiling::Optimal => writer.u8(0),
+        TextureTiling::Linear => writer.u8(1),
+        TextureTiling::DrmModifier(modifier) => {
+            writer.u8(2);
+            writer.u64(modifier);
+        }
+    }
+    writer.u8(match descriptor.memory_location {
+        TextureMemoryLocation::GpuOnly => 0,
+        TextureMemoryLocation::CpuToGpu => 1,
+        TextureMemoryLocation::GpuToCpu => 2,
+    });
+}
+
+pub fn read_descriptor(reader: &mut Reader) -> Result<TextureDescriptor> {
+    let width = reader.u32()?;
+    let height = reader.u32()?;
+    let format = read_format(reader)?;
+    let usage = (0..reader.u8()?)
+        .map(|_| USAGES.get(reader.u8()? as usize).copied().ok_or_else(|| malformed("unknown texture usage")))
+        .collect::<Result<Vec<_>>>()?;
+    let label = if reader.bool()? { Some(reader.str()?.to_string()) } else { None };
+    let tiling = match reader.u8()? {
+        0 => TextureTiling::Optimal,
+        1 => TextureTiling::Linear,
+        2 => TextureTiling::DrmModifier(reader.u64()?),
+        _ => return Err(malformed("unknown tiling")),
+    };
+    let memory_location = match reader.u8()? {
+        0 => TextureMemoryLocation::GpuOnly,
+        1 => TextureMemoryLocation::CpuToGpu,
+        2 => TextureMemoryLocation::GpuToCpu,
+        _ => return Err(malformed("unknown memory location")),
+    };
+
+    Ok(TextureDescriptor {
+        width,
+        height,
+        format,
+        usage,
+        label,
+        tiling,
+        memory_location,
+    })
+}
+
+pub fn write_device_identity(writer: &mut Writer, identity: &DeviceIdentity) {
+    writer.bytes(&identity.device_uuid);
+    writer.bytes(&identity.driver_uuid);
+    writer.u32(identity.driver_version);
+}
+
+pub fn read_device_identity(reader: &mut Reader) -> Result<DeviceIdentity> {
+    Ok(DeviceIdentity {
+        device_uuid: read_uuid(reader)?,
+        driver_uuid: read_uuid(reader)?,
+        driver_version: reader.u32()?,
+    })
+}
+
+/// Encodes a package, attaching its memory and sync descriptors to the message.
+/// Only handles backed by file descriptors (Vulkan and dma-buf) can be sent.
+pub fn write_package(writer: &mut Writer, package: &TextureSharePackage) -> Result<()> {
+    match &package.texture {
+        #[cfg(feature = "vulkan")]
+        ApiTextureHandle::Vulkan(handle) => {
+            writer.u8(HANDLE_VULKAN);
+            writer.fd(handle.raw_handle as RawFd);
+            writer.u32(handle.memory_type_index);
+            writer.u64(handle.size);
+            writer.u32(handle.handle_type.as_raw());
+            writer.bool(handle.dedicated_allocation);
+            write_device_identity(writer, &handle.device_identity);
+            writer.u8(handle.plane_layouts.len() as u8);
+            for layout in &handle.plane_layouts {
+                writer.u64(layout.offset);
+                writer.u64(layout.row_pitch);
+            }
+        }
+        ApiTextureHandle::DmaBuf(handle) => {
+            writer.u8(HANDLE_DMA_BUF);
+            writer.fd(handle.fd);
+            writer.u32(handle.fourcc);
+            writer.u64(handle.modifier);
+            writer.u32(handle.offset);
+            writer.u32(handle.stride);
+        }
+        #[cfg(feature = "webgpu")]
+        ApiTextureHandle::WebGpu(_) => {
+            return Err(GeyserError::IpcError(
+                "WebGPU staging handles only refer to memory in the exporting process and cannot be sent".to_string(),
+            ))
+        }
+        #[allow(unreachable_patterns)]
+        _ => return Err(GeyserError::OperationNotSupported),
+    }
+    write_sync(writer, package.sync.semaphore.as_ref())?;
+    write_sync(writer, package.sync.fence.as_ref())
+}
+
+fn write_sync(writer: &mut Writer, sync: Option<&SyncHandle>) -> Result<()> {
+    match sync {
+        None => writer.u8(0),
+        #[cfg(feature = "vulkan")]
+        Some(SyncHandle::VulkanSemaphore(handle)) => {
+            writer.u8(SYNC_VULKAN_SEMAPHORE);
+            writer.fd(handle.raw_handle as RawFd);
+            writer.u32(handle.handle_type.as_raw());
+            writer.bool(handle.is_timeline);
+        }
+        #[cfg(feature = "vulkan")]
+        Some(SyncHandle::VulkanFence(handle)) => {
+            writer.u8(SYNC_VULKAN_FENCE);
+            writer.fd(handle.raw_handle as RawFd);
+            writer.u32(handle.handle_type.as_raw());
+        }
+        #[allow(unreachable_patterns)]
+        Some(_) => return Err(GeyserError::OperationNotSupported),
+    }
+    Ok(())
+}
+
+/// Decodes a package written by `write_package`. The returned handles own the
+/// received descriptors; importing them transfers ownership to the importer.
+pub fn read_package(reader: &mut Reader) -> Result<TextureSharePackage> {
+    let texture = match reader.u8()? {
+        #[cfg(feature = "vulkan")]
+        HANDLE_VULKAN => {
+            use ash::vk;
+            let fd = reader.fd()?;
+            let memory_type_index = reader.u32()?;
+            let size = reader.u64()?;
+            let handle_type = vk::ExternalMemoryHandleTypeFlags::from_raw(reader.u32()?);
+            let dedicated_allocation = reader.bool()?;
+            let device_identity = read_device_identity(reader)?;
+            let plane_count = reader.u8()?;
+            if plane_count > MAX_MEMORY_PLANES {
+                return Err(malformed("too many memory planes"));
+            }
+            let mut plane_layouts = Vec::with_capacity(plane_count as usize);
+            for _ in 0..plane_count {
+                plane_layouts.push(vk::SubresourceLayout {
+                    offset: reader.u64()?,
+                    row_pitch: reader.u64()?,
+                    ..Default::default()
+                });
+            }
+            ApiTextureHandle::Vulkan(crate::vulkan::VulkanTextureShareHandle {
+                raw_handle: fd.into_raw_fd() as u64,
+                memory_type_index,
+                size,
+                handle_type,
+                dedicated_allocation,
+                device_identity,
+                plane_layouts,
+            })
+        }
+        HANDLE_DMA_BUF => {
+            let fd = reader.fd()?;
+            let fourcc = reader.u32()?;
+            let modifier = reader.u64()?;
+            let offset = reader.u32()?;
+            let stride = reader.u32()?;
+            ApiTextureHandle::DmaBuf(DmaBufHandle {
+                fd: fd.into_raw_fd(),
+                fourcc,
+                modifier,
+                offset,
+                stride,
+            })
+        }
+        _ => return Err(malformed("unsupported texture handle")),
+    };
+    let package = TextureSharePackage {
+        texture,
+        sync: SyncPrimitives::default(),
+    };
+    // Close the texture descriptor if the sync primitives turn out malformed
+    let semaphore = read_sync(reader).inspect_err(|_| close_package_fds(&package))?;
+    let fence = read_sync(reader).inspect_err(|_| {
+        close_package_fds(&package);
+        if let Some(semaphore) = &semaphore {
+            close_sync_fd(semaphore);
+        }
+    })?;
+
+    Ok(TextureSharePackage {
+        sync: SyncPrimitives { semaphore, fence },
+        ..package
+    })
+}
+
+fn read_uuid(reader: &mut Reader) -> Result<[u8; 16]> {
+    reader.bytes()?.try_into().map_err(|_| malformed("invalid UUID"))
+}
+
+fn read_sync(reader: &mut Reader) -> Result<Option<SyncHandle>> {
+    match reader.u8()? {
+        0 => Ok(None),
+        #[cfg(feature = "vulkan")]
+        SYNC_VULKAN_SEMAPHORE => {
+            use ash::vk;
+            let fd = reader.fd()?;
+            let handle_type = vk::ExternalSemaphoreHandleTypeFlags::from_raw(reader.u32()?);
+            let is_timeline = reader.bool()?;
+            Ok(Some(SyncHandle::VulkanSemaphore(crate::vulkan::VulkanSemaphoreHandle {
+                raw_handle: fd.into_raw_fd() as u64,
+                handle_type,
+                is_timeline,
+            })))
+        }
+        #[cfg(feature = "vulkan")]
+        SYNC_VULKAN_FENCE => {
+            use ash::vk;
+            let fd = reader.fd()?;
+            let handle_type = vk::ExternalFenceHandleTypeFlags::from_raw(reader.u32()?);
+            Ok(Some(SyncHandle::VulkanFence(crate::vulkan::VulkanFenceHandle {
+                raw_handle: fd.into_raw_fd() as u64,
+                handle_type,
+            })))
+        }
+        _ => Err(malformed("unsupported sync handle")),
+    }
+}
+
+/// Closes every descriptor held by a package that will not be imported.
+pub fn close_package_fds(package: &TextureSharePackage) {
+    let fd = match &package.texture {
+        #[cfg(feature = "vulkan")]
+        ApiTextureHandle::Vulkan(handle) => Some(handle.raw_handle as RawFd),
+        ApiTextureHandle::DmaBuf(handle) => Some(handle.fd),
+        #[allow(unreachable_patterns)]
+        _ => None,
+    };
+    if let Some(fd) = fd {
+        unsafe { libc::close(fd) };
+    }
+    for sync in [&package.sync.semaphore, &package.sync.fence].into_iter().flatten() {
+        close_sync_fd(sync);
+    }
+}
+
+fn close_sync_fd(sync: &SyncHandle) {
+    #[allow(unreachable_patterns)]
+    let fd = match sync {
+        #[cfg(feature = "vulkan")]
+        SyncHandle::VulkanSemaphore(handle) => Some(handle.raw_handle as RawFd),
+        #[cfg(feature = "vulkan")]
+        SyncHandle::VulkanFence(handle) => Some(handle.raw_handle as RawFd),
+        _ => None,
+    };
+    if let Some(fd) = fd {
+        unsafe { libc::close(fd) };
+    }
+}
@@ -10,14 +10,44 @@ pub mod vulkan;
 pub mod metal;
 
 #[cfg(feature = "webgpu")]
-pub mod webgpu; // Placeholder for future WebGPU implementation
+pub mod webgpu;
+
+#[cfg(all(feature = "opengl", unix))]
+pub mod opengl;
+
+// Cross-process publish/subscribe (optional)
+#[cfg(all(feature = "ipc", target_os = "linux"))]
+pub mod ipc;
+
+// PipeWire video nodes (optional)
+#[cfg(all(feature = "pipewire", target_os = "linux"))]
+pub mod pipewire;
+
+// GStreamer elements (optional)
+#[cfg(all(feature = "gst", target_os = "linux"))]
+pub mod gst;
+
+// Wayland presentation of dma-buf textures (optional)
+#[cfg(all(feature = "wayland", target_os = "linux"))]
+pub mod wayland;
+
+// CUDA external memory interop (optional)
+#[cfg(feature = "cuda")]
+pub mod cuda;
+
+// wgpu interop (optional)
+#[cfg(feature = "wgpu")]
+pub mod wgpu_bridge;
 
 // Bevy integration (optional)
 #[cfg(all(feature = "vulkan", feature = "bevy"))]
 pub mod bevy_plugin;
 
 pub use error::{GeyserError, Result};
-pub use common::{ApiTextureHandle, TextureDescriptor, TextureFormat, TextureUsage};
+pub use common::{
+    ApiTextureHandle, DeviceIdentity, DmaBufHandle, SharingStrategy, SyncPrimitives, TextureDescriptor, TextureFormat,
+    TextureMemoryLocation, TextureSharePackage, TextureTiling, TextureUsage,
+};
 
 use std::any::Any;
 
@@ -33,6 +63,11 @@ pub trait SharedTexture {
     /// Returns the usage flags of the texture.
     fn usage(&self) -> &[TextureUsage];
 
+    /// Returns whether the texture aliases the exported memory or holds a copy of it.
+    fn sharing_strategy(&self) -> SharingStrategy {
+        SharingStrategy::ZeroCopy
+    }
+
     /// Helper for downcasting to concrete types.
     fn as_any(&self) -> &dyn Any;
     
@@ -51,6 +86,10 @@ pub trait TextureShareManager {
     /// Exports an existing shareable texture, returning an `ApiTextureHandle` that can be
     /// serialized and sent to another process or API context.
     /// The manager is responsible for keeping the underlying resource alive until explicitly released.
+    ///
+    /// Some handles only work within the exporting process, such as the
+    /// `ApiTextureHandle::WebGpu` staging handles of a `WgpuTextureShareManager` without
+    /// external memory; `ipc` refuses to send those.
     fn export_texture(&self, texture: &dyn SharedTexture) -> Result<ApiTextureHandle>;
 
     /// Imports a texture using a provided `ApiTextureHandle`, making it usable within
@@ -62,4 +101,29 @@ pub trait TextureShareManager {
     /// Releases any resources associated with a previously exported or imported texture handle.
     /// This should be called when the shared texture is no longer needed in this context.
     fn release_texture_handle(&self, handle: ApiTextureHandle) -> Result<()>;
+
+    /// Creates a new shareable texture together with a ready semaphore and fence.
+    /// The synchronization primitives are owned by the returned texture.
+    /// Backends without synchronization support return `OperationNotSupported`.
+    fn create_shareable_texture_with_sync(&self, _descriptor: &TextureDescriptor) -> Result<Box<dyn SharedTexture>> {
+        Err(GeyserError::OperationNotSupported)
+    }
+
+    /// Exports a texture along with any synchronization primitives attached to it.
+    /// The default implementation exports the memory only and leaves `sync` empty.
+    fn export_texture_with_sync(&self, texture: &dyn SharedTexture) -> Result<TextureSharePackage> {
+        Ok(TextureSharePackage {
+            texture: self.export_texture(texture)?,
+            sync: SyncPrimitives::default(),
+        })
+    }
+
+    /// Imports a texture and its synchronization primitives from a single package.
+    /// The default implementation only accepts packages without synchronization handles.
+    fn import_texture_with_sync(&self, package: TextureSharePackage, descriptor: &TextureDescriptor) -> Result<Box<dyn SharedTexture>> {
+        if package.sync.semaphore.is_some() || package.sync.fence.is_some() {
+            return Err(GeyserError::OperationNotSupported);
+        }
+        self.import_texture(package.texture, descriptor)
+    }
 }
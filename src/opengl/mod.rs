@@ -0,0 +1,801 @@
+//! OpenGL / EGL backend for texture sharing.
+//!
+//! Imports textures into the OpenGL context current on the calling thread:
+//! - Geyser Vulkan handles (opaque fds) through `GL_EXT_memory_object_fd`, together
+//!   with binary ready semaphores through `GL_EXT_semaphore_fd`
+//! - dma-bufs through `EGL_EXT_image_dma_buf_import`
+//!
+//! Textures created by the manager are exported as dma-bufs through
+//! `EGL_MESA_image_dma_buf_export`.
+//!
+//! libEGL is loaded at runtime. The manager works with any EGL context (e.g. the one
+//! mpv or OBS renders with); `SurfacelessContext` creates a headless one, which is
+//! how the backend is tested with Mesa's llvmpipe.
+//!
+//! All calls, including dropping textures, must be made with the manager's context
+//! current.
+
+use crate::common::{
+    ApiTextureHandle, DeviceIdentity, DmaBufHandle, SyncHandle, TextureDescriptor, TextureFormat,
+    TextureSharePackage, TextureUsage, DRM_FORMAT_MOD_INVALID,
+};
+pub use crate::common::to_drm_fourcc;
+use crate::error::{GeyserError, Result};
+use crate::vulkan::{VulkanSemaphoreHandle, VulkanTextureShareHandle};
+use crate::{SharedTexture, TextureShareManager};
+use ash::vk;
+use glow::HasContext;
+use khronos_egl as egl;
+use std::any::Any;
+use std::collections::HashSet;
+use std::ffi::c_void;
+use std::num::NonZeroU32;
+use std::os::fd::{FromRawFd, OwnedFd};
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// EGL entry points, loaded from libEGL at runtime
+pub type Egl = egl::DynamicInstance<egl::EGL1_5>;
+
+// GL_EXT_memory_object / GL_EXT_memory_object_fd
+const GL_DEDICATED_MEMORY_OBJECT_EXT: u32 = 0x9581;
+const GL_HANDLE_TYPE_OPAQUE_FD_EXT: u32 = 0x9586;
+const GL_DEVICE_UUID_EXT: u32 = 0x9597;
+const GL_DRIVER_UUID_EXT: u32 = 0x9598;
+
+// EGL_EXT_image_dma_buf_import(_modifiers)
+const EGL_LINUX_DMA_BUF_EXT: egl::Enum = 0x3270;
+const EGL_LINUX_DRM_FOURCC_EXT: egl::Attrib = 0x3271;
+const EGL_DMA_BUF_PLANE0_FD_EXT: egl::Attrib = 0x3272;
+const EGL_DMA_BUF_PLANE0_OFFSET_EXT: egl::Attrib = 0x3273;
+const EGL_DMA_BUF_PLANE0_PITCH_EXT: egl::Attrib = 0x3274;
+const EGL_DMA_BUF_PLANE0_MODIFIER_LO_EXT: egl::Attrib = 0x3443;
+const EGL_DMA_BUF_PLANE0_MODIFIER_HI_EXT: egl::Attrib = 0x3444;
+
+// EGL_MESA_platform_surfaceless
+const EGL_PLATFORM_SURFACELESS_MESA: egl::Enum = 0x31DD;
+
+/// Image layout a texture is handed over in when waiting on or signalling its
+/// semaphore (`GL_EXT_semaphore`); must match the layout used on the Vulkan side.
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GlImageLayout {
+    General = 0x958D,
+    ColorAttachment = 0x958E,
+    DepthStencilAttachment = 0x958F,
+    DepthStencilReadOnly = 0x9590,
+    ShaderReadOnly = 0x9591,
+    TransferSrc = 0x9592,
+    TransferDst = 0x9593,
+}
+
+/// Interop extensions available on a GL context and its EGL display
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GlInteropCapabilities {
+    /// `GL_EXT_memory_object_fd`: import Vulkan memory
+    pub memory_object_fd: bool,
+    /// `GL_EXT_semaphore_fd`: import Vulkan binary semaphores
+    pub semaphore_fd: bool,
+    /// `EGL_EXT_image_dma_buf_import` with `GL_OES_EGL_image`: import dma-bufs
+    pub dma_buf_import: bool,
+    /// `EGL_EXT_image_dma_buf_import_modifiers`: import dma-bufs with explicit modifiers
+    pub dma_buf_modifiers: bool,
+    /// `EGL_MESA_image_dma_buf_export` with `EGL_KHR_gl_texture_2D_image`: export textures
+    pub dma_buf_export: bool,
+}
+
+impl GlInteropCapabilities {
+    /// Derives the capabilities from the context's GL extensions and the display's
+    /// EGL extension string.
+    pub fn from_extensions(gl_extensions: &HashSet<String>, egl_extensions: &str) -> Self {
+        let gl = |name: &str| gl_extensions.contains(name);
+        let egl = |name: &str| egl_extensions.split_whitespace().any(|ext| ext == name);
+
+        let dma_buf_import = egl("EGL_EXT_image_dma_buf_import") && gl("GL_OES_EGL_image");
+        Self {
+            memory_object_fd: gl("GL_EXT_memory_object") && gl("GL_EXT_memory_object_fd"),
+            semaphore_fd: gl("GL_EXT_semaphore") && gl("GL_EXT_semaphore_fd"),
+            dma_buf_import,
+            dma_buf_modifiers: dma_buf_import && egl("EGL_EXT_image_dma_buf_import_modifiers"),
+            dma_buf_export: egl("EGL_MESA_image_dma_buf_export") && egl("EGL_KHR_gl_texture_2D_image"),
+        }
+    }
+}
+
+/// Convert a Geyser format to the sized GL internal format with the same memory layout
+/// as the Vulkan image Geyser creates for it. BGRA formats and `Rgb10a2Unorm` (stored
+/// as `A2R10G10B10`) have no GL equivalent.
+pub fn to_gl_internal_format(format: TextureFormat) -> Result<u32> {
+    match format {
+        TextureFormat::Rgba8Unorm => Ok(glow::RGBA8),
+        TextureFormat::Rgba8Srgb => Ok(glow::SRGB8_ALPHA8),
+        TextureFormat::R8Unorm => Ok(glow::R8),
+        TextureFormat::Rg8Unorm => Ok(glow::RG8),
+        TextureFormat::R16Float => Ok(glow::R16F),
+        TextureFormat::Rg16Float => Ok(glow::RG16F),
+        TextureFormat::Rgba16Float => Ok(glow::RGBA16F),
+        TextureFormat::R16Uint => Ok(glow::R16UI),
+        TextureFormat::R16Sint => Ok(glow::R16I),
+        TextureFormat::R32Float => Ok(glow::R32F),
+        TextureFormat::Rg32Float => Ok(glow::RG32F),
+        TextureFormat::Rgba32Float => Ok(glow::RGBA32F),
+        TextureFormat::R32Uint => Ok(glow::R32UI),
+        TextureFormat::R32Sint => Ok(glow::R32I),
+        TextureFormat::Depth32Float => Ok(glow::DEPTH_COMPONENT32F),
+        TextureFormat::Depth24Plus | TextureFormat::Depth24PlusStencil8 => Ok(glow::DEPTH24_STENCIL8),
+        TextureFormat::Rg11b10Float => Ok(glow::R11F_G11F_B10F),
+        TextureFormat::Bgra8Unorm | TextureFormat::Bgra8Srgb | TextureFormat::Rgb10a2Unorm => Err(
+            GeyserError::UnsupportedFormat(format!("{} has no matching OpenGL internal format", format)),
+        ),
+    }
+}
+
+// Entry points from the GL_EXT_memory_object/semaphore families and GL_OES_EGL_image;
+// only loaded when the matching extensions are supported
+#[derive(Default)]
+struct GlExtFunctions {
+    create_memory_objects: Option<unsafe extern "system" fn(i32, *mut u32)>,
+    delete_memory_objects: Option<unsafe extern "system" fn(i32, *const u32)>,
+    memory_object_parameteriv: Option<unsafe extern "system" fn(u32, u32, *const i32)>,
+    import_memory_fd: Option<unsafe extern "system" fn(u32, u64, u32, i32)>,
+    tex_storage_mem_2d: Option<unsafe extern "system" fn(u32, i32, u32, i32, i32, u32, u64)>,
+    get_unsigned_bytev: Option<unsafe extern "system" fn(u32, *mut u8)>,
+    get_unsigned_bytei_v: Option<unsafe extern "system" fn(u32, u32, *mut u8)>,
+    gen_semaphores: Option<unsafe extern "system" fn(i32, *mut u32)>,
+    delete_semaphores: Option<unsafe extern "system" fn(i32, *const u32)>,
+    import_semaphore_fd: Option<unsafe extern "system" fn(u32, u32, i32)>,
+    wait_semaphore: Option<unsafe extern "system" fn(u32, u32, *const u32, u32, *const u32, *const u32)>,
+    signal_semaphore: Option<unsafe extern "system" fn(u32, u32, *const u32, u32, *const u32, *const u32)>,
+    egl_image_target_texture_2d: Option<unsafe extern "system" fn(u32, *const c_void)>,
+    query_dma_buf_image: Option<unsafe extern "system" fn(*mut c_void, *mut c_void, *mut i32, *mut i32, *mut u64) -> u32>,
+    export_dma_buf_image: Option<unsafe extern "system" fn(*mut c_void, *mut c_void, *mut i32, *mut i32, *mut i32) -> u32>,
+}
+
+impl GlExtFunctions {
+    fn load(egl: &Egl, capabilities: &GlInteropCapabilities) -> Self {
+        let mut functions = Self::default();
+        unsafe {
+            if capabilities.memory_object_fd {
+                functions.create_memory_objects = load_proc(egl, "glCreateMemoryObjectsEXT");
+                functions.delete_memory_objects = load_proc(egl, "glDeleteMemoryObjectsEXT");
+                functions.memory_object_parameteriv = load_proc(egl, "glMemoryObjectParameterivEXT");
+                functions.import_memory_fd = load_proc(egl, "glImportMemoryFdEXT");
+                functions.tex_storage_mem_2d = load_proc(egl, "glTexStorageMem2DEXT");
+                functions.get_unsigned_bytev = load_proc(egl, "glGetUnsignedBytevEXT");
+                functions.get_unsigned_bytei_v = load_proc(egl, "glGetUnsignedBytei_vEXT");
+            }
+            if capabilities.semaphore_fd {
+                functions.gen_semaphores = load_proc(egl, "glGenSemaphoresEXT");
+                functions.delete_semaphores = load_proc(egl, "glDeleteSemaphoresEXT");
+                functions.import_semaphore_fd = load_proc(egl, "glImportSemaphoreFdEXT");
+                functions.wait_semaphore = load_proc(egl, "glWaitSemaphoreEXT");
+                functions.signal_semaphore = load_proc(egl, "glSignalSemaphoreEXT");
+            }
+            if capabilities.dma_buf_import {
+                functions.egl_image_target_texture_2d = load_proc(egl, "glEGLImageTargetTexture2DOES");
+            }
+            if capabilities.dma_buf_export {
+                functions.query_dma_buf_image = load_proc(egl, "eglExportDMABUFImageQueryMESA");
+                functions.export_dma_buf_image = load_proc(egl, "eglExportDMABUFImageMESA");
+            }
+        }
+        functions
+    }
+}
+
+// Safety: `F` must be the `extern "system"` function pointer type of `name`
+unsafe fn load_proc<F: Copy>(egl: &Egl, name: &str) -> Option<F> {
+    debug_assert_eq!(std::mem::size_of::<F>(), std::mem::size_of::<extern "system" fn()>());
+    egl.get_proc_address(name).map(|f| std::mem::transmute_copy(&f))
+}
+
+// GL and EGL state shared by a manager and its textures; bound to the context's thread
+struct GlContext {
+    gl: glow::Context,
+    ext: GlExtFunctions,
+    egl: Arc<Egl>,
+    display: egl::Display,
+    capabilities: GlInteropCapabilities,
+    device_identity: Option<DeviceIdentity>,
+}
+
+impl GlContext {
+    // Clears errors left by earlier GL calls so the next check reports ours
+    fn clear_errors(&self) {
+        for _ in 0..16 {
+            if unsafe { self.gl.get_error() } == glow::NO_ERROR {
+                break;
+            }
+        }
+    }
+
+    fn check_error(&self, operation: &str) -> Result<()> {
+        match unsafe { self.gl.get_error() } {
+            glow::NO_ERROR => Ok(()),
+            error => Err(GeyserError::OpenGlError(format!("Failed to {}: GL error {:#x}", operation, error))),
+        }
+    }
+
+    // Creates a texture and runs `f` with it bound to GL_TEXTURE_2D, restoring the
+    // previous binding afterwards
+    fn create_texture(&self, f: impl FnOnce(&Self)) -> Result<glow::NativeTexture> {
+        unsafe {
+            let previous = self.gl.get_parameter_i32(glow::TEXTURE_BINDING_2D);
+            let texture = self.gl.create_texture().map_err(GeyserError::OpenGlError)?;
+            self.gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+            f(self);
+            self.gl.bind_texture(
+                glow::TEXTURE_2D,
+                NonZeroU32::new(previous as u32).map(glow::NativeTexture),
+            );
+            Ok(texture)
+        }
+    }
+}
+
+/// An OpenGL texture created or imported by a `GlTextureShareManager`
+pub struct GlSharedTexture {
+    context: Rc<GlContext>,
+    texture: glow::NativeTexture,
+    memory_object: Option<u32>,
+    semaphore: Option<u32>,
+    egl_image: Option<egl::Image>,
+    descriptor: TextureDescriptor,
+}
+
+impl GlSharedTexture {
+    /// Returns the GL texture name, bindable to `GL_TEXTURE_2D`.
+    pub fn raw(&self) -> u32 {
+        self.texture.0.get()
+    }
+
+    /// Returns true if a ready semaphore was imported with the texture.
+    pub fn has_semaphore(&self) -> bool {
+        self.semaphore.is_some()
+    }
+}
+
+impl SharedTexture for GlSharedTexture {
+    fn width(&self) -> u32 {
+        self.descriptor.width
+    }
+
+    fn height(&self) -> u32 {
+        self.descriptor.height
+    }
+
+    fn format(&self) -> TextureFormat {
+        self.descriptor.format
+    }
+
+    fn usage(&self) -> &[TextureUsage] {
+        &self.descriptor.usage
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Drop for GlSharedTexture {
+    fn drop(&mut self) {
+        let context = &self.context;
+        unsafe {
+            context.gl.delete_texture(self.texture);
+            if let (Some(memory), Some(delete)) = (self.memory_object, context.ext.delete_memory_objects) {
+                delete(1, &memory);
+            }
+            if let (Some(semaphore), Some(delete)) = (self.semaphore, context.ext.delete_semaphores) {
+                delete(1, &semaphore);
+            }
+        }
+        if let Some(image) = self.egl_image.take() {
+            let _ = context.egl.destroy_image(context.display, image);
+        }
+    }
+}
+
+/// Texture share manager for an OpenGL context created through EGL.
+pub struct GlTextureShareManager {
+    context: Rc<GlContext>,
+}
+
+impl GlTextureShareManager {
+    /// Creates a manager for the GL context current on this thread, which must belong
+    /// to `display`. Fails if no context is current.
+    pub fn new(egl: Arc<Egl>, display: egl::Display) -> Result<Self> {
+        if egl.get_current_context().is_none() {
+            return Err(GeyserError::OpenGlError("No EGL context is current".to_string()));
+        }
+
+        let gl = unsafe {
+            glow::Context::from_loader_function(|name| {
+                egl.get_proc_address(name)
+                    .map_or(std::ptr::null(), |f| f as *const c_void)
+            })
+        };
+        let egl_extensions = egl
+            .query_string(Some(display), egl::EXTENSIONS)
+            .map_err(|e| GeyserError::OpenGlError(format!("Failed to query EGL extensions: {}", e)))?
+            .to_string_lossy();
+        let capabilities = GlInteropCapabilities::from_extensions(gl.supported_extensions(), &egl_extensions);
+        let ext = GlExtFunctions::load(&egl, &capabilities);
+
+        // Memory objects can only be imported on the device and driver that exported them
+        let device_identity = match (ext.get_unsigned_bytev, ext.get_unsigned_bytei_v) {
+            (Some(get_bytes), Some(get_indexed_bytes)) => {
+                let mut identity = DeviceIdentity::default();
+                unsafe {
+                    get_indexed_bytes(GL_DEVICE_UUID_EXT, 0, identity.device_uuid.as_mut_ptr());
+                    get_bytes(GL_DRIVER_UUID_EXT, identity.driver_uuid.as_mut_ptr());
+                }
+                Some(identity)
+            }
+            _ => None,
+        };
+
+        Ok(Self {
+            context: Rc::new(GlContext {
+                gl,
+                ext,
+                egl,
+                display,
+                capabilities,
+                device_identity,
+            }),
+        })
+    }
+
+    /// Returns the interop extensions supported by the context.
+    pub fn capabilities(&self) -> GlInteropCapabilities {
+        self.context.capabilities
+    }
+
+    /// Returns the device and driver UUIDs reported through `GL_EXT_memory_object`.
+    pub fn device_identity(&self) -> Option<DeviceIdentity> {
+        self.context.device_identity
+    }
+
+    /// Creates a texture that can be exported as a dma-buf.
+    pub fn create_gl_texture(&self, descriptor: &TextureDescriptor) -> Result<GlSharedTexture> {
+        let internal_format = to_gl_internal_format(descriptor.format)?;
+        let context = &self.context;
+        context.clear_errors();
+        let texture = context.create_texture(|context| unsafe {
+            context.gl.tex_storage_2d(
+                glow::TEXTURE_2D,
+                1,
+                internal_format,
+                descriptor.width as i32,
+                descriptor.height as i32,
+            );
+        })?;
+
+        let texture = self.wrap_texture(texture, descriptor);
+        context.check_error("allocate shareable texture")?;
+        Ok(texture)
+    }
+
+    /// Imports a Vulkan memory handle or a dma-buf. Ownership of the file descriptor
+    /// passes to GL once the import has been attempted.
+    pub fn import_gl_texture(&self, handle: ApiTextureHandle, descriptor: &TextureDescriptor) -> Result<GlSharedTexture> {
+        match handle {
+            ApiTextureHandle::Vulkan(handle) => self.import_memory_object(handle, descriptor),
+            ApiTextureHandle::DmaBuf(handle) => self.import_dma_buf(handle, descriptor),
+            #[allow(unreachable_patterns)]
+            _ => Err(GeyserError::InvalidTextureHandle),
+        }
+    }
+
+    /// Imports a texture and its binary ready semaphore. Timeline semaphores cannot be
+    /// imported by GL and are rejected; fence handles have no GL equivalent and are closed.
+    pub fn import_gl_texture_with_sync(
+        &self,
+        package: TextureSharePackage,
+        descriptor: &TextureDescriptor,
+    ) -> Result<GlSharedTexture> {
+        // Fences have no GL equivalent; owning the fd closes it on every return path
+        let _fence = package.sync.fence.as_ref().and_then(sync_fd);
+        let semaphore = match package.sync.semaphore {
+            Some(SyncHandle::VulkanSemaphore(semaphore)) => Some(semaphore),
+            Some(other) => {
+                drop(sync_fd(&other));
+                let _ = self.release_texture_handle(package.texture);
+                return Err(GeyserError::InvalidTextureHandle);
+            }
+            None => None,
+        };
+        if let Some(semaphore) = &semaphore {
+            if let Err(e) = check_semaphore_handle(semaphore, &self.context.capabilities) {
+                drop(unsafe { OwnedFd::from_raw_fd(semaphore.raw_handle as i32) });
+                let _ = self.release_texture_handle(package.texture);
+                return Err(e);
+            }
+        }
+
+        let mut texture = match self.import_gl_texture(package.texture, descriptor) {
+            Ok(texture) => texture,
+            Err(e) => {
+                if let Some(semaphore) = &semaphore {
+                    drop(unsafe { OwnedFd::from_raw_fd(semaphore.raw_handle as i32) });
+                }
+                return Err(e);
+            }
+        };
+        if let Some(semaphore) = semaphore {
+            texture.semaphore = Some(self.import_semaphore(&semaphore)?);
+        }
+        Ok(texture)
+    }
+
+    /// Exports `texture` as a dma-buf. The texture must have been rendered to (and
+    /// the work flushed) before the consumer reads it.
+    pub fn export_dma_buf(&self, texture: &GlSharedTexture) -> Result<DmaBufHandle> {
+        let context = &self.context;
+        let (Some(query), Some(export)) = (context.ext.query_dma_buf_image, context.ext.export_dma_buf_image) else {
+            return Err(GeyserError::OperationNotSupported);
+        };
+        let current = context
+            .egl
+            .get_current_context()
+            .ok_or_else(|| GeyserError::OpenGlError("No EGL context is current".to_string()))?;
+
+        let image = context
+            .egl
+            .create_image(
+                context.display,
+                current,
+                egl::GL_TEXTURE_2D as egl::Enum,
+                unsafe { egl::ClientBuffer::from_ptr(texture.raw() as usize as *mut c_void) },
+                &[egl::ATTRIB_NONE],
+            )
+            .map_err(|e| GeyserError::OpenGlError(format!("Failed to create EGL image from texture: {}", e)))?;
+
+        let result = unsafe {
+            let (mut fourcc, mut planes, mut modifier) = (0, 0, 0);
+            let (mut fd, mut stride, mut offset) = (-1, 0, 0);
+            if query(context.display.as_ptr(), image.as_ptr(), &mut fourcc, &mut planes, &mut modifier) != egl::TRUE {
+                Err(GeyserError::OpenGlError("Failed to query dma-buf layout".to_string()))
+            } else if planes != 1 {
+                Err(GeyserError::UnsupportedFormat(format!("{}-plane dma-buf export", planes)))
+            } else if export(context.display.as_ptr(), image.as_ptr(), &mut fd, &mut stride, &mut offset) != egl::TRUE {
+                Err(GeyserError::OpenGlError("Failed to export dma-buf".to_string()))
+            } else {
+                Ok(DmaBufHandle {
+                    fd,
+                    fourcc: fourcc as u32,
+                    modifier,
+                    offset: offset as u32,
+                    stride: stride as u32,
+                })
+            }
+        };
+        // The dma-buf keeps the memory alive without the image
+        let _ = context.egl.destroy_image(context.display, image);
+        result
+    }
+
+    /// Makes subsequent GL commands wait for `texture`'s ready semaphore, which the
+    /// producer signalled after leaving the image in `layout`.
+    pub fn wait_texture_semaphore(&self, texture: &GlSharedTexture, layout: GlImageLayout) -> Result<()> {
+        let context = &self.context;
+        let (Some(semaphore), Some(wait)) = (texture.semaphore, context.ext.wait_semaphore) else {
+            return Err(GeyserError::OperationNotSupported);
+        };
+        context.clear_errors();
+        unsafe {
+            wait(semaphore, 0, std::ptr::null(), 1, &texture.raw(), &(layout as u32));
+        }
+        context.check_error("wait for texture semaphore")
+    }
+
+    /// Signals `texture`'s semaphore once previously submitted GL commands complete,
+    /// handing the image back in `layout`. Flushes the context so the signal is submitted.
+    pub fn signal_texture_semaphore(&self, texture: &GlSharedTexture, layout: GlImageLayout) -> Result<()> {
+        let context = &self.context;
+        let (Some(semaphore), Some(signal)) = (texture.semaphore, context.ext.signal_semaphore) else {
+            return Err(GeyserError::OperationNotSupported);
+        };
+        context.clear_errors();
+        unsafe {
+            signal(semaphore, 0, std::ptr::null(), 1, &texture.raw(), &(layout as u32));
+            context.gl.flush();
+        }
+        context.check_error("signal texture semaphore")
+    }
+
+    fn import_memory_object(&self, handle: VulkanTextureShareHandle, descriptor: &TextureDescriptor) -> Result<GlSharedTexture> {
+        let context = &self.context;
+        let ext = &context.ext;
+        let (Some(create), Some(set_parameter), Some(import), Some(storage)) = (
+            ext.create_memory_objects,
+            ext.memory_object_parameteriv,
+            ext.import_memory_fd,
+            ext.tex_storage_mem_2d,
+        ) else {
+            return Err(GeyserError::OperationNotSupported);
+        };
+        if handle.handle_type != vk::ExternalMemoryHandleTypeFlags::OPAQUE_FD {
+            return Err(GeyserError::InvalidTextureHandle);
+        }
+        if let Some(identity) = context.device_identity {
+            if !handle.device_identity.is_compatible_with(&identity) {
+                return Err(GeyserError::IncompatibleDevice {
+                    exporter: handle.device_identity,
+                    importer: identity,
+                });
+            }
+        }
+        let internal_format = to_gl_internal_format(descriptor.format)?;
+
+        context.clear_errors();
+        let mut memory = 0;
+        unsafe {
+            create(1, &mut memory);
+            if handle.dedicated_allocation {
+                set_parameter(memory, GL_DEDICATED_MEMORY_OBJECT_EXT, &(glow::TRUE as i32));
+            }
+            import(memory, handle.size, GL_HANDLE_TYPE_OPAQUE_FD_EXT, handle.raw_handle as i32);
+        }
+        let texture = context.create_texture(|_| unsafe {
+            storage(
+                glow::TEXTURE_2D,
+                1,
+                internal_format,
+                descriptor.width as i32,
+                descriptor.height as i32,
+                memory,
+                0,
+            );
+        });
+        let texture = match texture {
+            Ok(texture) => texture,
+            Err(e) => {
+                if let Some(delete) = ext.delete_memory_objects {
+                    unsafe { delete(1, &memory) };
+                }
+                return Err(e);
+            }
+        };
+
+        let mut texture = self.wrap_texture(texture, descriptor);
+        texture.memory_object = Some(memory);
+        context.check_error("import Vulkan memory")?;
+        Ok(texture)
+    }
+
+    fn import_dma_buf(&self, handle: DmaBufHandle, descriptor: &TextureDescriptor) -> Result<GlSharedTexture> {
+        let context = &self.context;
+        let Some(target_texture) = context.ext.egl_image_target_texture_2d else {
+            return Err(GeyserError::OperationNotSupported);
+        };
+        if handle.fourcc != to_drm_fourcc(descriptor.format)? {
+            return Err(GeyserError::InvalidTextureHandle);
+        }
+        if handle.modifier != DRM_FORMAT_MOD_INVALID && !context.capabilities.dma_buf_modifiers {
+            return Err(GeyserError::OperationNotSupported);
+        }
+
+        let image = context.egl.create_image(
+            context.display,
+            unsafe { egl::Context::from_ptr(egl::NO_CONTEXT) },
+            EGL_LINUX_DMA_BUF_EXT,
+            unsafe { egl::ClientBuffer::from_ptr(std::ptr::null_mut()) },
+            &dma_buf_attributes(&handle, descriptor),
+        );
+        // EGL does not take ownership of the fd; the image keeps the buffer alive
+        drop(unsafe { OwnedFd::from_raw_fd(handle.fd) });
+        let image = image.map_err(|e| GeyserError::OpenGlError(format!("Failed to import dma-buf: {}", e)))?;
+
+        context.clear_errors();
+        let texture = match context.create_texture(|_| unsafe {
+            target_texture(glow::TEXTURE_2D, image.as_ptr());
+        }) {
+            Ok(texture) => texture,
+            Err(e) => {
+                let _ = context.egl.destroy_image(context.display, image);
+                return Err(e);
+            }
+        };
+
+        let mut texture = self.wrap_texture(texture, descriptor);
+        texture.egl_image = Some(image);
+        context.check_error("bind dma-buf image")?;
+        Ok(texture)
+    }
+
+    // Consumes the fd: GL owns it after a successful import, otherwise it is closed here
+    fn import_semaphore(&self, handle: &VulkanSemaphoreHandle) -> Result<u32> {
+        let ext = &self.context.ext;
+        let (Some(generate), Some(import)) = (ext.gen_semaphores, ext.import_semaphore_fd) else {
+            drop(unsafe { OwnedFd::from_raw_fd(handle.raw_handle as i32) });
+            return Err(GeyserError::OperationNotSupported);
+        };
+
+        self.context.clear_errors();
+        let mut semaphore = 0;
+        unsafe {
+            generate(1, &mut semaphore);
+            import(semaphore, GL_HANDLE_TYPE_OPAQUE_FD_EXT, handle.raw_handle as i32);
+        }
+        if let Err(e) = self.context.check_error("import semaphore") {
+            if let Some(delete) = ext.delete_semaphores {
+                unsafe { delete(1, &semaphore) };
+            }
+            // A failed import leaves the fd with the caller
+            drop(unsafe { OwnedFd::from_raw_fd(handle.raw_handle as i32) });
+            return Err(e);
+        }
+        Ok(semaphore)
+    }
+
+    fn wrap_texture(&self, texture: glow::NativeTexture, descriptor: &TextureDescriptor) -> GlSharedTexture {
+        GlSharedTexture {
+            context: self.context.clone(),
+            texture,
+            memory_object: None,
+            semaphore: None,
+            egl_image: None,
+            descriptor: descriptor.clone(),
+        }
+    }
+}
+
+impl TextureShareManager for GlTextureShareManager {
+    fn create_shareable_texture(&self, descriptor: &TextureDescriptor) -> Result<Box<dyn SharedTexture>> {
+        Ok(Box::new(self.create_gl_texture(descriptor)?))
+    }
+
+    fn export_texture(&self, texture: &dyn SharedTexture) -> Result<ApiTextureHandle> {
+        let texture = texture
+            .as_any()
+            .downcast_ref::<GlSharedTexture>()
+            .ok_or(GeyserError::InvalidTextureHandle)?;
+        Ok(ApiTextureHandle::DmaBuf(self.export_dma_buf(texture)?))
+    }
+
+    fn import_texture(&self, handle: ApiTextureHandle, descriptor: &TextureDescriptor) -> Result<Box<dyn SharedTexture>> {
+        Ok(Box::new(self.import_gl_texture(handle, descriptor)?))
+    }
+
+    fn release_texture_handle(&self, handle: ApiTextureHandle) -> Result<()> {
+        let fd = match handle {
+            ApiTextureHandle::Vulkan(handle) => handle.raw_handle as i32,
+            ApiTextureHandle::DmaBuf(handle) => handle.fd,
+            #[allow(unreachable_patterns)]
+            _ => return Err(GeyserError::InvalidTextureHandle),
+        };
+        drop(unsafe { OwnedFd::from_raw_fd(fd) });
+        Ok(())
+    }
+
+    fn import_texture_with_sync(&self, package: TextureSharePackage, descriptor: &TextureDescriptor) -> Result<Box<dyn SharedTexture>> {
+        Ok(Box::new(self.import_gl_texture_with_sync(package, descriptor)?))
+    }
+}
+
+fn sync_fd(handle: &SyncHandle) -> Option<OwnedFd> {
+    #[allow(unreachable_patterns)]
+    let fd = match handle {
+        SyncHandle::VulkanSemaphore(handle) => handle.raw_handle as i32,
+        SyncHandle::VulkanFence(handle) => handle.raw_handle as i32,
+        _ => return None,
+    };
+    Some(unsafe { OwnedFd::from_raw_fd(fd) })
+}
+
+fn check_semaphore_handle(handle: &VulkanSemaphoreHandle, capabilities: &GlInteropCapabilities) -> Result<()> {
+    if !capabilities.semaphore_fd || handle.is_timeline {
+        return Err(GeyserError::OperationNotSupported);
+    }
+    if handle.handle_type != vk::ExternalSemaphoreHandleTypeFlags::OPAQUE_FD {
+        return Err(GeyserError::InvalidTextureHandle);
+    }
+    Ok(())
+}
+
+fn dma_buf_attributes(handle: &DmaBufHandle, descriptor: &TextureDescriptor) -> Vec<egl::Attrib> {
+    let mut attributes = vec![
+        egl::WIDTH as egl::Attrib,
+        descriptor.width as egl::Attrib,
+        egl::HEIGHT as egl::Attrib,
+        descriptor.height as egl::Attrib,
+        EGL_LINUX_DRM_FOURCC_EXT,
+        handle.fourcc as egl::Attrib,
+        EGL_DMA_BUF_PLANE0_FD_EXT,
+        handle.fd as egl::Attrib,
+        EGL_DMA_BUF_PLANE0_OFFSET_EXT,
+        handle.offset as egl::Attrib,
+        EGL_DMA_BUF_PLANE0_PITCH_EXT,
+        handle.stride as egl::Attrib,
+    ];
+    if handle.modifier != DRM_FORMAT_MOD_INVALID {
+        attributes.extend([
+            EGL_DMA_BUF_PLANE0_MODIFIER_LO_EXT,
+            (handle.modifier & 0xffff_ffff) as egl::Attrib,
+            EGL_DMA_BUF_PLANE0_MODIFIER_HI_EXT,
+            (handle.modifier >> 32) as egl::Attrib,
+        ]);
+    }
+    attributes.push(egl::ATTRIB_NONE);
+    attributes
+}
+
+/// Headless EGL display and desktop GL context on Mesa's surfaceless platform
+/// (`EGL_MESA_platform_surfaceless`), made current on the creating thread.
+/// Managers and textures created for it must be dropped first.
+pub struct SurfacelessContext {
+    egl: Arc<Egl>,
+    display: egl::Display,
+    context: egl::Context,
+}
+
+impl SurfacelessContext {
+    /// Loads libEGL and creates the context. Fails if libEGL or the surfaceless
+    /// platform is unavailable.
+    pub fn new() -> Result<Self> {
+        let egl = unsafe { Egl::load_required() }
+            .map_err(|e| GeyserError::OpenGlError(format!("Failed to load libEGL: {}", e)))?;
+        let egl_error = |what: &str, e: egl::Error| GeyserError::OpenGlError(format!("Failed to {}: {}", what, e));
+
+        let display = unsafe {
+            egl.get_platform_display(EGL_PLATFORM_SURFACELESS_MESA, egl::DEFAULT_DISPLAY, &[egl::ATTRIB_NONE])
+        }
+        .map_err(|e| egl_error("get surfaceless display", e))?;
+        egl.initialize(display).map_err(|e| egl_error("initialize EGL", e))?;
+
+        let context = egl
+            .bind_api(egl::OPENGL_API)
+            .and_then(|_| egl.choose_first_config(display, &[egl::RENDERABLE_TYPE, egl::OPENGL_BIT, egl::NONE]))
+            .map_err(|e| egl_error("choose EGL config", e))
+            .and_then(|config| config.ok_or_else(|| GeyserError::OpenGlError("No OpenGL EGL config".to_string())))
+            .and_then(|config| {
+                egl.create_context(display, config, None, &[egl::NONE])
+                    .map_err(|e| egl_error("create GL context", e))
+            })
+            .and_then(|context| {
+                egl.make_current(display, None, None, Some(context))
+                    .map(|_| context)
+                    .map_err(|e| egl_error("make GL context current", e))
+            });
+        let context = match context {
+            Ok(context) => context,
+            Err(e) => {
+                let _ = egl.terminate(display);
+                return Err(e);
+            }
+        };
+
+        Ok(Self {
+            egl: Arc::new(egl),
+            display,
+            context,
+        })
+    }
+
+    /// Returns the loaded EGL entry points.
+    pub fn egl(&self) -> &Arc<Egl> {
+        &self.egl
+    }
+
+    /// Returns the surfaceless display.
+    pub fn display(&self) -> egl::Display {
+        self.display
+    }
+
+    /// Creates a manager for this context.
+    pub fn manager(&self) -> Result<GlTextureShareManager> {
+        GlTextureShareManager::new(self.egl.clone(), self.display)
+    }
+}
+
+impl Drop for SurfacelessContext {
+    fn drop(&mut self) {
+        let _ = self.egl.make_current(self.display, None, None, None);
+        let _ = self.egl.destroy_context(self.display, self.context);
+        let _ = self.egl.terminate(self.display);
+    }
+}
+
+#[cfg(test)]
+mod tests;
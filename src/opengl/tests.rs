@@ -0,0 +1,137 @@
+//! Unit tests for the OpenGL / EGL backend
+
+use super::*;
+
+fn extensions(names: &[&str]) -> HashSet<String> {
+    names.iter().map(|name| name.to_string()).collect()
+}
+
+fn descriptor(format: TextureFormat) -> TextureDescriptor {
+    TextureDescriptor::new(64, 32, format, vec![TextureUsage::TextureBinding, TextureUsage::RenderAttachment])
+        .with_label("GlTest")
+}
+
+#[test]
+fn test_capabilities_from_extensions() {
+    let gl = extensions(&[
+        "GL_EXT_memory_object",
+        "GL_EXT_memory_object_fd",
+        "GL_EXT_semaphore",
+        "GL_OES_EGL_image",
+    ]);
+    let caps = GlInteropCapabilities::from_extensions(
+        &gl,
+        "EGL_KHR_gl_texture_2D_image EGL_EXT_image_dma_buf_import EGL_MESA_image_dma_buf_export",
+    );
+
+    assert!(caps.memory_object_fd);
+    // GL_EXT_semaphore_fd is missing
+    assert!(!caps.semaphore_fd);
+    assert!(caps.dma_buf_import);
+    assert!(!caps.dma_buf_modifiers);
+    assert!(caps.dma_buf_export);
+
+    // dma-buf import needs GL_OES_EGL_image as well
+    let caps = GlInteropCapabilities::from_extensions(
+        &extensions(&[]),
+        "EGL_EXT_image_dma_buf_import EGL_EXT_image_dma_buf_import_modifiers",
+    );
+    assert_eq!(caps, GlInteropCapabilities::default());
+}
+
+#[test]
+fn test_gl_internal_formats_match_vulkan_layout() {
+    assert_eq!(to_gl_internal_format(TextureFormat::Rgba8Unorm).unwrap(), glow::RGBA8);
+    assert_eq!(to_gl_internal_format(TextureFormat::Rgba16Float).unwrap(), glow::RGBA16F);
+    assert_eq!(to_gl_internal_format(TextureFormat::Depth24Plus).unwrap(), glow::DEPTH24_STENCIL8);
+
+    for format in [
+        TextureFormat::Bgra8Unorm,
+        TextureFormat::Bgra8Srgb,
+        TextureFormat::Rgb10a2Unorm,
+    ] {
+        assert!(matches!(
+            to_gl_internal_format(format),
+            Err(GeyserError::UnsupportedFormat(_))
+        ));
+    }
+}
+
+#[test]
+fn test_drm_fourcc_codes() {
+    // DRM_FORMAT_ABGR8888 and DRM_FORMAT_ARGB8888 from drm_fourcc.h
+    assert_eq!(to_drm_fourcc(TextureFormat::Rgba8Unorm).unwrap(), 0x3432_4241);
+    assert_eq!(to_drm_fourcc(TextureFormat::Bgra8Unorm).unwrap(), 0x3432_5241);
+    assert!(to_drm_fourcc(TextureFormat::Rgba8Srgb).is_err());
+}
+
+#[test]
+fn test_dma_buf_attributes() {
+    let mut handle = DmaBufHandle {
+        fd: 7,
+        fourcc: to_drm_fourcc(TextureFormat::Rgba8Unorm).unwrap(),
+        modifier: DRM_FORMAT_MOD_INVALID,
+        offset: 0,
+        stride: 256,
+    };
+    let attributes = dma_buf_attributes(&handle, &descriptor(TextureFormat::Rgba8Unorm));
+    assert_eq!(attributes.len(), 13);
+    assert_eq!(attributes[7], 7);
+    assert_eq!(attributes[11], 256);
+    assert_eq!(*attributes.last().unwrap(), egl::ATTRIB_NONE);
+
+    // Explicit modifiers are split into 32-bit halves
+    handle.modifier = 0x0100_0000_0000_0001;
+    let attributes = dma_buf_attributes(&handle, &descriptor(TextureFormat::Rgba8Unorm));
+    assert_eq!(&attributes[12..16], &[EGL_DMA_BUF_PLANE0_MODIFIER_LO_EXT, 1, EGL_DMA_BUF_PLANE0_MODIFIER_HI_EXT, 0x0100_0000]);
+}
+
+#[test]
+fn test_timeline_semaphores_are_rejected() {
+    let caps = GlInteropCapabilities {
+        semaphore_fd: true,
+        ..Default::default()
+    };
+    let mut handle = VulkanSemaphoreHandle {
+        raw_handle: 3,
+        handle_type: vk::ExternalSemaphoreHandleTypeFlags::OPAQUE_FD,
+        is_timeline: true,
+    };
+    assert!(matches!(
+        check_semaphore_handle(&handle, &caps),
+        Err(GeyserError::OperationNotSupported)
+    ));
+
+    handle.is_timeline = false;
+    assert!(check_semaphore_handle(&handle, &caps).is_ok());
+    assert!(check_semaphore_handle(&handle, &GlInteropCapabilities::default()).is_err());
+}
+
+// Runs where Mesa provides a surfaceless EGL platform (e.g. llvmpipe with
+// EGL_PLATFORM=surfaceless); skipped elsewhere
+#[test]
+fn test_surfaceless_dma_buf_roundtrip() {
+    let context = match SurfacelessContext::new() {
+        Ok(context) => context,
+        Err(e) => {
+            eprintln!("Skipping: {}", e);
+            return;
+        }
+    };
+    let manager = context.manager().unwrap();
+    let caps = manager.capabilities();
+    if !caps.dma_buf_export || !caps.dma_buf_import {
+        eprintln!("Skipping: dma-buf import/export unsupported ({:?})", caps);
+        return;
+    }
+
+    let desc = descriptor(TextureFormat::Rgba8Unorm);
+    let texture = manager.create_gl_texture(&desc).unwrap();
+    let handle = manager.export_texture(&texture).unwrap();
+    let imported = manager.import_texture(handle, &desc).unwrap();
+
+    assert_eq!(imported.width(), 64);
+    assert_eq!(imported.format(), TextureFormat::Rgba8Unorm);
+    drop(imported);
+    drop(texture);
+}
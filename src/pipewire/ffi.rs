@@ -0,0 +1,181 @@
+//! The parts of the libpipewire-0.3 ABI used by the stream wrappers, loaded at runtime.
+
+#![allow(non_camel_case_types)]
+
+use std::ffi::{c_char, c_int, c_void};
+
+use libloading::Library;
+
+use crate::error::{GeyserError, Result};
+
+pub const SPA_DIRECTION_INPUT: u32 = 0;
+pub const SPA_DIRECTION_OUTPUT: u32 = 1;
+
+pub const PW_ID_ANY: u32 = 0xffff_ffff;
+
+pub const PW_STREAM_FLAG_AUTOCONNECT: u32 = 1 << 0;
+pub const PW_STREAM_FLAG_DRIVER: u32 = 1 << 3;
+pub const PW_STREAM_FLAG_ALLOC_BUFFERS: u32 = 1 << 8;
+
+pub const PW_VERSION_STREAM_EVENTS: u32 = 2;
+
+pub const SPA_DATA_DMA_BUF: u32 = 3;
+pub const SPA_DATA_FLAG_READABLE: u32 = 1 << 0;
+
+// `enum pw_stream_state`
+pub const PW_STREAM_STATE_ERROR: c_int = -1;
+pub const PW_STREAM_STATE_STREAMING: c_int = 3;
+
+#[repr(C)]
+pub struct pw_thread_loop {
+    _private: [u8; 0],
+}
+
+#[repr(C)]
+pub struct pw_loop {
+    _private: [u8; 0],
+}
+
+#[repr(C)]
+pub struct pw_stream {
+    _private: [u8; 0],
+}
+
+#[repr(C)]
+pub struct pw_properties {
+    _private: [u8; 0],
+}
+
+#[repr(C)]
+pub struct spa_pod {
+    pub size: u32,
+    pub type_: u32,
+}
+
+#[repr(C)]
+pub struct spa_chunk {
+    pub offset: u32,
+    pub size: u32,
+    pub stride: i32,
+    pub flags: i32,
+}
+
+#[repr(C)]
+pub struct spa_data {
+    pub type_: u32,
+    pub flags: u32,
+    pub fd: i64,
+    pub mapoffset: u32,
+    pub maxsize: u32,
+    pub data: *mut c_void,
+    pub chunk: *mut spa_chunk,
+}
+
+#[repr(C)]
+pub struct spa_buffer {
+    pub n_metas: u32,
+    pub n_datas: u32,
+    pub metas: *mut c_void,
+    pub datas: *mut spa_data,
+}
+
+#[repr(C)]
+pub struct pw_buffer {
+    pub buffer: *mut spa_buffer,
+    pub user_data: *mut c_void,
+    pub size: u64,
+    pub requested: u64,
+}
+
+#[repr(C)]
+pub struct pw_stream_events {
+    pub version: u32,
+    pub destroy: Option<unsafe extern "C" fn(data: *mut c_void)>,
+    pub state_changed: Option<unsafe extern "C" fn(data: *mut c_void, old: c_int, state: c_int, error: *const c_char)>,
+    pub control_info: Option<unsafe extern "C" fn(data: *mut c_void, id: u32, control: *const c_void)>,
+    pub io_changed: Option<unsafe extern "C" fn(data: *mut c_void, id: u32, area: *mut c_void, size: u32)>,
+    pub param_changed: Option<unsafe extern "C" fn(data: *mut c_void, id: u32, param: *const spa_pod)>,
+    pub add_buffer: Option<unsafe extern "C" fn(data: *mut c_void, buffer: *mut pw_buffer)>,
+    pub remove_buffer: Option<unsafe extern "C" fn(data: *mut c_void, buffer: *mut pw_buffer)>,
+    pub process: Option<unsafe extern "C" fn(data: *mut c_void)>,
+    pub drained: Option<unsafe extern "C" fn(data: *mut c_void)>,
+    pub command: Option<unsafe extern "C" fn(data: *mut c_void, command: *const c_void)>,
+    pub trigger_done: Option<unsafe extern "C" fn(data: *mut c_void)>,
+}
+
+/// Entry points of libpipewire-0.3.
+pub struct PipeWireLibrary {
+    pub init: unsafe extern "C" fn(argc: *mut c_int, argv: *mut *mut *mut c_char),
+    pub thread_loop_new: unsafe extern "C" fn(name: *const c_char, props: *const c_void) -> *mut pw_thread_loop,
+    pub thread_loop_get_loop: unsafe extern "C" fn(thread_loop: *mut pw_thread_loop) -> *mut pw_loop,
+    pub thread_loop_start: unsafe extern "C" fn(thread_loop: *mut pw_thread_loop) -> c_int,
+    pub thread_loop_stop: unsafe extern "C" fn(thread_loop: *mut pw_thread_loop),
+    pub thread_loop_lock: unsafe extern "C" fn(thread_loop: *mut pw_thread_loop),
+    pub thread_loop_unlock: unsafe extern "C" fn(thread_loop: *mut pw_thread_loop),
+    pub thread_loop_destroy: unsafe extern "C" fn(thread_loop: *mut pw_thread_loop),
+    pub properties_new: unsafe extern "C" fn(key: *const c_char, ...) -> *mut pw_properties,
+    pub properties_set: unsafe extern "C" fn(properties: *mut pw_properties, key: *const c_char, value: *const c_char) -> c_int,
+    pub stream_new_simple: unsafe extern "C" fn(
+        loop_: *mut pw_loop,
+        name: *const c_char,
+        props: *mut pw_properties,
+        events: *const pw_stream_events,
+        data: *mut c_void,
+    ) -> *mut pw_stream,
+    pub stream_connect: unsafe extern "C" fn(
+        stream: *mut pw_stream,
+        direction: u32,
+        target_id: u32,
+        flags: u32,
+        params: *mut *const spa_pod,
+        n_params: u32,
+    ) -> c_int,
+    pub stream_update_params: unsafe extern "C" fn(stream: *mut pw_stream, params: *mut *const spa_pod, n_params: u32) -> c_int,
+    pub stream_get_node_id: unsafe extern "C" fn(stream: *mut pw_stream) -> u32,
+    pub stream_dequeue_buffer: unsafe extern "C" fn(stream: *mut pw_stream) -> *mut pw_buffer,
+    pub stream_queue_buffer: unsafe extern "C" fn(stream: *mut pw_stream, buffer: *mut pw_buffer) -> c_int,
+    pub stream_trigger_process: unsafe extern "C" fn(stream: *mut pw_stream) -> c_int,
+    pub stream_destroy: unsafe extern "C" fn(stream: *mut pw_stream),
+    // Keeps the function pointers above valid
+    _library: Library,
+}
+
+impl PipeWireLibrary {
+    /// Loads libpipewire-0.3 and calls `pw_init`.
+    pub fn load() -> Result<Self> {
+        unsafe {
+            let library = Library::new("libpipewire-0.3.so.0")
+                .map_err(|e| GeyserError::PipeWireError(format!("Failed to load libpipewire-0.3: {}", e)))?;
+            macro_rules! symbol {
+                ($name:literal) => {
+                    *library
+                        .get(concat!($name, "\0").as_bytes())
+                        .map_err(|e| GeyserError::PipeWireError(format!("Missing {}: {}", $name, e)))?
+                };
+            }
+            let pipewire = Self {
+                init: symbol!("pw_init"),
+                thread_loop_new: symbol!("pw_thread_loop_new"),
+                thread_loop_get_loop: symbol!("pw_thread_loop_get_loop"),
+                thread_loop_start: symbol!("pw_thread_loop_start"),
+                thread_loop_stop: symbol!("pw_thread_loop_stop"),
+                thread_loop_lock: symbol!("pw_thread_loop_lock"),
+                thread_loop_unlock: symbol!("pw_thread_loop_unlock"),
+                thread_loop_destroy: symbol!("pw_thread_loop_destroy"),
+                properties_new: symbol!("pw_properties_new"),
+                properties_set: symbol!("pw_properties_set"),
+                stream_new_simple: symbol!("pw_stream_new_simple"),
+                stream_connect: symbol!("pw_stream_connect"),
+                stream_update_params: symbol!("pw_stream_update_params"),
+                stream_get_node_id: symbol!("pw_stream_get_node_id"),
+                stream_dequeue_buffer: symbol!("pw_stream_dequeue_buffer"),
+                stream_queue_buffer: symbol!("pw_stream_queue_buffer"),
+                stream_trigger_process: symbol!("pw_stream_trigger_process"),
+                stream_destroy: symbol!("pw_stream_destroy"),
+                _library: library,
+            };
+            (pipewire.init)(std::ptr::null_mut(), std::ptr::null_mut());
+            Ok(pipewire)
+        }
+    }
+}
@@ -0,0 +1,896 @@
+//! PipeWire video nodes backed by Geyser textures (Linux).
+//!
+//! - `PipeWireSource` publishes a pool of Vulkan textures as a PipeWire video source.
+//!   Each pool texture is exported as a dma-buf; the format and DRM modifier are
+//!   negotiated through `SPA_PARAM_EnumFormat`, so OBS, browsers and screen-capture
+//!   tools can read the frames without copies.
+//! - `PipeWireSink` connects to a PipeWire video stream and imports its dma-bufs as
+//!   `VulkanSharedTexture`s.
+//!
+//! libpipewire-0.3 is loaded at runtime, so the feature builds without PipeWire
+//! headers; creating a node fails if the library or daemon is missing. Each node runs
+//! its own PipeWire thread loop; the stream callbacks run there, and a sink copies
+//! frames on a worker thread of its own.
+
+mod ffi;
+pub mod pod;
+
+pub use pod::VideoFormat;
+
+use std::{
+    ffi::{c_char, c_int, c_void, CStr, CString},
+    os::fd::RawFd,
+    sync::{Arc, Condvar, Mutex},
+};
+
+use ash::vk;
+
+use crate::common::{
+    ApiTextureHandle, DmaBufHandle, TextureDescriptor, TextureFormat, TextureMemoryLocation, TextureTiling,
+    TextureUsage, DRM_FORMAT_MOD_LINEAR,
+};
+use crate::error::{GeyserError, Result};
+use crate::vulkan::{VulkanSharedTexture, VulkanTextureShareManager};
+use ffi::{pw_buffer, pw_stream, pw_stream_events, pw_thread_loop, spa_pod, PipeWireLibrary};
+use pod::FormatOffer;
+
+/// Lifecycle of a PipeWire node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StreamState {
+    /// Connecting or negotiating
+    Connecting,
+    /// Frames are flowing
+    Streaming,
+    /// PipeWire reported an error, or buffers could not be shared
+    Error(String),
+}
+
+// A stream on its own thread loop. Dropping it destroys the stream, which removes
+// its buffers through the callbacks, and then stops the loop.
+struct Stream {
+    library: Arc<PipeWireLibrary>,
+    thread_loop: *mut pw_thread_loop,
+    stream: *mut pw_stream,
+    // Read by PipeWire for the lifetime of the stream
+    _events: Box<pw_stream_events>,
+}
+
+// Holds the thread loop lock, keeping callbacks from running
+struct LoopGuard<'a>(&'a Stream);
+
+impl Drop for LoopGuard<'_> {
+    fn drop(&mut self) {
+        unsafe { (self.0.library.thread_loop_unlock)(self.0.thread_loop) };
+    }
+}
+
+impl Stream {
+    fn new(
+        library: Arc<PipeWireLibrary>,
+        name: &str,
+        properties: &[(&str, &str)],
+        events: pw_stream_events,
+        data: *mut c_void,
+    ) -> Result<Self> {
+        let name = CString::new(name).map_err(|_| GeyserError::PipeWireError("Node name contains NUL".to_string()))?;
+        let events = Box::new(events);
+        unsafe {
+            let thread_loop = (library.thread_loop_new)(name.as_ptr(), std::ptr::null());
+            if thread_loop.is_null() {
+                return Err(GeyserError::PipeWireError("Failed to create thread loop".to_string()));
+            }
+
+            let props = (library.properties_new)(std::ptr::null::<c_char>());
+            for (key, value) in properties {
+                let key = CString::new(*key).unwrap();
+                let value = CString::new(*value)
+                    .map_err(|_| GeyserError::PipeWireError("Property contains NUL".to_string()))?;
+                (library.properties_set)(props, key.as_ptr(), value.as_ptr());
+            }
+            let stream = (library.stream_new_simple)(
+                (library.thread_loop_get_loop)(thread_loop),
+                name.as_ptr(),
+                props,
+                &*events,
+                data,
+            );
+            if stream.is_null() {
+                (library.thread_loop_destroy)(thread_loop);
+                return Err(GeyserError::PipeWireError("Failed to create stream".to_string()));
+            }
+            Ok(Self {
+                library,
+                thread_loop,
+                stream,
+                _events: events,
+            })
+        }
+    }
+
+    fn lock(&self) -> LoopGuard<'_> {
+        unsafe { (self.library.thread_loop_lock)(self.thread_loop) };
+        LoopGuard(self)
+    }
+
+    // Connects the stream and starts the thread loop
+    fn connect(&self, direction: u32, flags: u32, params: &[Vec<u8>]) -> Result<()> {
+        let mut pointers: Vec<*const spa_pod> = params.iter().map(|p| p.as_ptr() as *const spa_pod).collect();
+        let result = unsafe {
+            (self.library.stream_connect)(
+                self.stream,
+                direction,
+                ffi::PW_ID_ANY,
+                flags,
+                pointers.as_mut_ptr(),
+                pointers.len() as u32,
+            )
+        };
+        if result < 0 {
+            return Err(GeyserError::PipeWireError(format!("Failed to connect stream: error {}", result)));
+        }
+        if unsafe { (self.library.thread_loop_start)(self.thread_loop) } < 0 {
+            return Err(GeyserError::PipeWireError("Failed to start thread loop".to_string()));
+        }
+        Ok(())
+    }
+
+    // Called from callbacks, which already hold the loop lock
+    fn update_params(library: &PipeWireLibrary, stream: *mut pw_stream, params: &[Vec<u8>]) {
+        let mut pointers: Vec<*const spa_pod> = params.iter().map(|p| p.as_ptr() as *const spa_pod).collect();
+        unsafe { (library.stream_update_params)(stream, pointers.as_mut_ptr(), pointers.len() as u32) };
+    }
+}
+
+impl Drop for Stream {
+    fn drop(&mut self) {
+        unsafe {
+            (self.library.thread_loop_lock)(self.thread_loop);
+            (self.library.stream_destroy)(self.stream);
+            (self.library.thread_loop_unlock)(self.thread_loop);
+            (self.library.thread_loop_stop)(self.thread_loop);
+            (self.library.thread_loop_destroy)(self.thread_loop);
+        }
+    }
+}
+
+fn empty_events() -> pw_stream_events {
+    pw_stream_events {
+        version: ffi::PW_VERSION_STREAM_EVENTS,
+        destroy: None,
+        state_changed: None,
+        control_info: None,
+        io_changed: None,
+        param_changed: None,
+        add_buffer: None,
+        remove_buffer: None,
+        process: None,
+        drained: None,
+        command: None,
+        trigger_done: None,
+    }
+}
+
+// Tiling a texture needs to be shared with `modifier`
+fn tiling_for_modifier(modifier: u64) -> TextureTiling {
+    if modifier == DRM_FORMAT_MOD_LINEAR {
+        TextureTiling::Linear
+    } else {
+        TextureTiling::DrmModifier(modifier)
+    }
+}
+
+fn stream_state(state: c_int, error: *const c_char) -> Option<StreamState> {
+    match state {
+        ffi::PW_STREAM_STATE_ERROR => {
+            let message = if error.is_null() {
+                "Unknown error".to_string()
+            } else {
+                unsafe { CStr::from_ptr(error) }.to_string_lossy().into_owned()
+            };
+            Some(StreamState::Error(message))
+        }
+        ffi::PW_STREAM_STATE_STREAMING => Some(StreamState::Streaming),
+        _ => None,
+    }
+}
+
+/// Picks the modifier for a texture pool: the first of ours, in order of preference,
+/// that the peer also supports.
+pub fn choose_modifier(ours: &[u64], theirs: &[u64]) -> Option<u64> {
+    ours.iter().copied().find(|modifier| theirs.contains(modifier))
+}
+
+// --- Source ---
+
+// A pool texture lent to PipeWire as one buffer
+struct SourceBuffer {
+    pw_buffer: *mut pw_buffer,
+    texture: Option<VulkanSharedTexture>,
+    dma_buf: DmaBufHandle,
+}
+
+struct SourceState {
+    library: Arc<PipeWireLibrary>,
+    stream: *mut pw_stream,
+    manager: Arc<VulkanTextureShareManager>,
+    descriptor: TextureDescriptor,
+    modifiers: Vec<u64>,
+    buffer_count: u32,
+    negotiated: Option<VideoFormat>,
+    buffers: Vec<SourceBuffer>,
+    state: StreamState,
+}
+
+impl SourceState {
+    fn offer(&self, modifiers: Vec<u64>) -> FormatOffer {
+        FormatOffer::Producer {
+            format: self.descriptor.format,
+            modifiers,
+            width: self.descriptor.width,
+            height: self.descriptor.height,
+        }
+    }
+
+    fn param_changed(&mut self, format: VideoFormat) -> Result<()> {
+        match format.modifier() {
+            Some(modifier) => {
+                if !self.modifiers.contains(&modifier) {
+                    return Err(GeyserError::PipeWireError(format!("Unsupported modifier {:#x}", modifier)));
+                }
+                self.negotiated = Some(format);
+                Stream::update_params(&self.library, self.stream, &[pod::dma_buf_buffers(self.buffer_count)]);
+            }
+            None => {
+                // The consumer listed what it can import; fixate on one of them
+                let modifier = choose_modifier(&self.modifiers, &format.modifiers)
+                    .ok_or_else(|| GeyserError::PipeWireError("No common DRM modifier".to_string()))?;
+                let fixated = pod::enum_format(&self.offer(vec![modifier]))?;
+                let all = pod::enum_format(&self.offer(self.modifiers.clone()))?;
+                Stream::update_params(&self.library, self.stream, &[fixated, all]);
+            }
+        }
+        Ok(())
+    }
+
+    fn add_buffer(&mut self, buffer: *mut pw_buffer) -> Result<()> {
+        let modifier = self.negotiated.as_ref().and_then(VideoFormat::modifier).ok_or_else(|| {
+            GeyserError::PipeWireError("Buffers were added before the format was negotiated".to_string())
+        })?;
+        let descriptor = TextureDescriptor {
+            tiling: tiling_for_modifier(modifier),
+            memory_location: TextureMemoryLocation::GpuOnly,
+            ..self.descriptor.clone()
+        };
+        let texture = self.manager.create_vulkan_texture(&descriptor)?;
+        let dma_buf = match self.manager.export_dma_buf(&texture) {
+            Ok(dma_buf) => dma_buf,
+            Err(e) => {
+                let unexported = DmaBufHandle { fd: -1, fourcc: 0, modifier, offset: 0, stride: 0 };
+                let _ = self.manager.destroy_texture(texture, ApiTextureHandle::DmaBuf(unexported));
+                return Err(e);
+            }
+        };
+
+        unsafe {
+            let spa_buffer = &mut *(*buffer).buffer;
+            if spa_buffer.n_datas < 1 {
+                return Err(GeyserError::PipeWireError("Buffer has no data plane".to_string()));
+            }
+            let data = &mut *spa_buffer.datas;
+            let size = dma_buf.stride * descriptor.height;
+            data.type_ = ffi::SPA_DATA_DMA_BUF;
+            data.flags = ffi::SPA_DATA_FLAG_READABLE;
+            data.fd = dma_buf.fd as i64;
+            data.mapoffset = 0;
+            data.maxsize = dma_buf.offset + size;
+            data.data = std::ptr::null_mut();
+            let chunk = &mut *data.chunk;
+            chunk.offset = dma_buf.offset;
+            chunk.size = size;
+            chunk.stride = dma_buf.stride as i32;
+            chunk.flags = 0;
+        }
+        self.buffers.push(SourceBuffer {
+            pw_buffer: buffer,
+            texture: Some(texture),
+            dma_buf,
+        });
+        Ok(())
+    }
+
+    fn remove_buffer(&mut self, buffer: *mut pw_buffer) {
+        if let Some(index) = self.buffers.iter().position(|b| b.pw_buffer == buffer) {
+            let mut removed = self.buffers.swap_remove(index);
+            unsafe { libc::close(removed.dma_buf.fd) };
+            if let Some(texture) = removed.texture.take() {
+                let _ = self.manager.destroy_texture(texture, ApiTextureHandle::DmaBuf(removed.dma_buf));
+            }
+        }
+    }
+}
+
+unsafe extern "C" fn source_param_changed(data: *mut c_void, id: u32, param: *const spa_pod) {
+    if id != pod::SPA_PARAM_FORMAT || param.is_null() {
+        return;
+    }
+    let state = &*(data as *const Mutex<SourceState>);
+    let mut state = state.lock().unwrap();
+    let bytes = std::slice::from_raw_parts(param as *const u8, 8 + (*param).size as usize);
+    if let Err(e) = pod::parse_video_format(bytes).and_then(|format| state.param_changed(format)) {
+        state.state = StreamState::Error(e.to_string());
+    }
+}
+
+unsafe extern "C" fn source_add_buffer(data: *mut c_void, buffer: *mut pw_buffer) {
+    let state = &*(data as *const Mutex<SourceState>);
+    let mut state = state.lock().unwrap();
+    if let Err(e) = state.add_buffer(buffer) {
+        state.state = StreamState::Error(e.to_string());
+    }
+}
+
+unsafe extern "C" fn source_remove_buffer(data: *mut c_void, buffer: *mut pw_buffer) {
+    let state = &*(data as *const Mutex<SourceState>);
+    state.lock().unwrap().remove_buffer(buffer);
+}
+
+unsafe extern "C" fn source_state_changed(data: *mut c_void, _old: c_int, state: c_int, error: *const c_char) {
+    let source = &*(data as *const Mutex<SourceState>);
+    if let Some(state) = stream_state(state, error) {
+        source.lock().unwrap().state = state;
+    }
+}
+
+/// A frame dequeued from a `PipeWireSource`, to be rendered into and queued.
+#[derive(Debug)]
+pub struct SourceFrame {
+    buffer: *mut pw_buffer,
+    image: vk::Image,
+}
+
+impl SourceFrame {
+    /// The pool image to render into. Hand it over to the external queue family in
+    /// `SHARED_IMAGE_LAYOUT` before queueing the frame, as for any exported texture.
+    pub fn image(&self) -> vk::Image {
+        self.image
+    }
+}
+
+/// Publishes a pool of Vulkan textures as a PipeWire video source node.
+pub struct PipeWireSource {
+    // Declared first so the stream is destroyed while the state is still alive
+    stream: Stream,
+    state: Box<Mutex<SourceState>>,
+}
+
+impl PipeWireSource {
+    /// Creates a source node named `name` producing frames described by `descriptor`
+    /// and connects it. Pool textures are allocated with the first of `modifiers`
+    /// (in order of preference) that the consumer supports; `DRM_FORMAT_MOD_LINEAR`
+    /// works with every consumer. `buffer_count` pool textures are requested.
+    ///
+    /// Requires `VK_EXT_external_memory_dma_buf`, and
+    /// `VK_EXT_image_drm_format_modifier` for modifiers other than linear.
+    pub fn new(
+        manager: Arc<VulkanTextureShareManager>,
+        name: &str,
+        descriptor: &TextureDescriptor,
+        modifiers: Vec<u64>,
+        buffer_count: u32,
+    ) -> Result<Self> {
+        if !manager.dma_buf_import_supported() {
+            return Err(GeyserError::MissingExtensions(vec![
+                ash::ext::external_memory_dma_buf::NAME.to_string_lossy().into_owned(),
+            ]));
+        }
+        let offer = FormatOffer::Producer {
+            format: descriptor.format,
+            modifiers: modifiers.clone(),
+            width: descriptor.width,
+            height: descriptor.height,
+        };
+        let enum_format = pod::enum_format(&offer)?;
+
+        let mut descriptor = descriptor.clone();
+        if !descriptor.usage.contains(&TextureUsage::CopySrc) {
+            descriptor.usage.push(TextureUsage::CopySrc);
+        }
+        let library = Arc::new(PipeWireLibrary::load()?);
+        let state = Box::new(Mutex::new(SourceState {
+            library: library.clone(),
+            stream: std::ptr::null_mut(),
+            manager,
+            descriptor,
+            modifiers,
+            buffer_count,
+            negotiated: None,
+            buffers: Vec::new(),
+            state: StreamState::Connecting,
+        }));
+
+        let events = pw_stream_events {
+            state_changed: Some(source_state_changed),
+            param_changed: Some(source_param_changed),
+            add_buffer: Some(source_add_buffer),
+            remove_buffer: Some(source_remove_buffer),
+            ..empty_events()
+        };
+        let stream = Stream::new(
+            library,
+            name,
+            &[("media.type", "Video"), ("media.category", "Source"), ("media.class", "Video/Source"), ("node.name", name)],
+            events,
+            &*state as *const Mutex<SourceState> as *mut c_void,
+        )?;
+        state.lock().unwrap().stream = stream.stream;
+        stream.connect(
+            ffi::SPA_DIRECTION_OUTPUT,
+            ffi::PW_STREAM_FLAG_AUTOCONNECT | ffi::PW_STREAM_FLAG_DRIVER | ffi::PW_STREAM_FLAG_ALLOC_BUFFERS,
+            &[enum_format],
+        )?;
+        Ok(Self { stream, state })
+    }
+
+    /// Returns the PipeWire node id consumers connect to.
+    pub fn node_id(&self) -> u32 {
+        let _guard = self.stream.lock();
+        unsafe { (self.stream.library.stream_get_node_id)(self.stream.stream) }
+    }
+
+    pub fn state(&self) -> StreamState {
+        self.state.lock().unwrap().state.clone()
+    }
+
+    /// Returns the negotiated format once a consumer is connected.
+    pub fn format(&self) -> Option<VideoFormat> {
+        self.state.lock().unwrap().negotiated.clone()
+    }
+
+    /// Takes a pool texture no consumer is reading, or `None` if all are in use.
+    pub fn dequeue(&self) -> Option<SourceFrame> {
+        let _guard = self.stream.lock();
+        let buffer = unsafe { (self.stream.library.stream_dequeue_buffer)(self.stream.stream) };
+        if buffer.is_null() {
+            return None;
+        }
+        let state = self.state.lock().unwrap();
+        match state.buffers.iter().find(|b| b.pw_buffer == buffer) {
+            Some(source_buffer) => Some(SourceFrame {
+                buffer,
+                image: source_buffer.texture.as_ref().unwrap().image(),
+            }),
+            None => {
+                // Not one of ours; hand it straight back
+                unsafe { (self.stream.library.stream_queue_buffer)(self.stream.stream, buffer) };
+                None
+            }
+        }
+    }
+
+    /// Publishes a rendered frame to the consumers.
+    pub fn queue(&self, frame: SourceFrame) -> Result<()> {
+        let _guard = self.stream.lock();
+        let result = unsafe { (self.stream.library.stream_queue_buffer)(self.stream.stream, frame.buffer) };
+        if result < 0 {
+            return Err(GeyserError::PipeWireError(format!("Failed to queue buffer: error {}", result)));
+        }
+        unsafe { (self.stream.library.stream_trigger_process)(self.stream.stream) };
+        Ok(())
+    }
+}
+
+// --- Sink ---
+
+// A PipeWire buffer imported as a local texture: a copy for linear dma-bufs, or the
+// buffer's own memory for other modifiers
+struct SinkBuffer {
+    pw_buffer: *mut pw_buffer,
+    texture: Arc<VulkanSharedTexture>,
+    // The handle the manager tracks the texture under
+    handle: ApiTextureHandle,
+    // Whether the texture reads the buffer's memory, so the buffer is kept from the
+    // producer for as long as it is the latest frame
+    zero_copy: bool,
+}
+
+struct SinkState {
+    library: Arc<PipeWireLibrary>,
+    thread_loop: *mut pw_thread_loop,
+    stream: *mut pw_stream,
+    manager: Arc<VulkanTextureShareManager>,
+    negotiated: Option<VideoFormat>,
+    buffers: Vec<SinkBuffer>,
+    // The newest dequeued buffer, waiting for the worker
+    pending: Option<*mut pw_buffer>,
+    // The buffer the worker took, until it goes back to PipeWire
+    working: Option<*mut pw_buffer>,
+    // Whether the worker is reading the memory of `working` or writing its texture
+    copying: bool,
+    // The latest frame's buffer when it was imported without a copy
+    held: Option<*mut pw_buffer>,
+    latest: Option<*mut pw_buffer>,
+    frame: u64,
+    state: StreamState,
+    stopped: bool,
+}
+
+// The pointers belong to the stream. The worker only uses them under the thread loop
+// lock, or reads a buffer while it is dequeued and marked as `working`.
+unsafe impl Send for SinkState {}
+
+// Sink state shared by the PipeWire thread loop and the copy worker
+struct SinkShared {
+    state: Mutex<SinkState>,
+    // Signalled when a frame is pending, a copy finishes or the sink stops
+    changed: Condvar,
+}
+
+impl SinkState {
+    // Takes the newest frame from the stream, returning older ones. Returns whether a
+    // frame is now pending.
+    fn process(&mut self) -> bool {
+        let mut newest = self.pending.take().unwrap_or(std::ptr::null_mut());
+        loop {
+            let buffer = unsafe { (self.library.stream_dequeue_buffer)(self.stream) };
+            if buffer.is_null() {
+                break;
+            }
+            if !newest.is_null() {
+                unsafe { (self.library.stream_queue_buffer)(self.stream, newest) };
+            }
+            newest = buffer;
+        }
+        self.pending = (!newest.is_null()).then_some(newest);
+        self.pending.is_some()
+    }
+
+    // Called with the loop lock held once the worker is done with `buffer`, so it can
+    // go back to PipeWire. `imported` is the buffer's texture if this was its first frame.
+    fn finish(&mut self, buffer: *mut pw_buffer, result: Result<Option<SinkBuffer>>) {
+        if self.working != Some(buffer) {
+            // Removed while the worker had it
+            if let Ok(Some(imported)) = result {
+                self.destroy(imported);
+            }
+            return;
+        }
+        self.working = None;
+
+        match result {
+            Ok(imported) => {
+                let zero_copy = match imported {
+                    Some(imported) => {
+                        let zero_copy = imported.zero_copy;
+                        self.buffers.push(imported);
+                        zero_copy
+                    }
+                    None => self.buffers.iter().any(|b| b.pw_buffer == buffer && b.zero_copy),
+                };
+                // The previous frame is no longer read
+                if let Some(previous) = self.held.take() {
+                    unsafe { (self.library.stream_queue_buffer)(self.stream, previous) };
+                }
+                if zero_copy {
+                    self.held = Some(buffer);
+                } else {
+                    unsafe { (self.library.stream_queue_buffer)(self.stream, buffer) };
+                }
+                self.latest = Some(buffer);
+                self.frame += 1;
+            }
+            Err(e) => {
+                unsafe { (self.library.stream_queue_buffer)(self.stream, buffer) };
+                self.state = StreamState::Error(e.to_string());
+            }
+        }
+    }
+
+    fn remove_buffer(&mut self, buffer: *mut pw_buffer) {
+        for slot in [&mut self.pending, &mut self.working, &mut self.held, &mut self.latest] {
+            if *slot == Some(buffer) {
+                *slot = None;
+            }
+        }
+        if let Some(index) = self.buffers.iter().position(|b| b.pw_buffer == buffer) {
+            let removed = self.buffers.swap_remove(index);
+            self.destroy(removed);
+        }
+    }
+
+    // Frees a texture nothing else refers to: the worker is not copying into it, and
+    // `with_latest_frame` holds the loop lock.
+    fn destroy(&self, buffer: SinkBuffer) {
+        if let Ok(texture) = Arc::try_unwrap(buffer.texture) {
+            let _ = self.manager.destroy_texture(texture, buffer.handle);
+        }
+    }
+}
+
+// Imports the dma-buf of a dequeued buffer: linear ones by copy, which works with
+// producers on any GPU, and others without a copy
+fn import_sink_buffer(
+    manager: &VulkanTextureShareManager,
+    format: Option<VideoFormat>,
+    buffer: *mut pw_buffer,
+) -> Result<SinkBuffer> {
+    let format =
+        format.ok_or_else(|| GeyserError::PipeWireError("Frame arrived before the format was negotiated".to_string()))?;
+    let data = unsafe {
+        let spa_buffer = &*(*buffer).buffer;
+        if spa_buffer.n_datas < 1 || (*spa_buffer.datas).type_ != ffi::SPA_DATA_DMA_BUF {
+            return Err(GeyserError::PipeWireError("Stream buffer is not a dma-buf".to_string()));
+        }
+        &*spa_buffer.datas
+    };
+    let chunk = unsafe { &*data.chunk };
+
+    // PipeWire keeps its descriptor; the import takes ownership of a duplicate
+    let fd: RawFd = unsafe { libc::fcntl(data.fd as RawFd, libc::F_DUPFD_CLOEXEC, 0) };
+    if fd < 0 {
+        return Err(GeyserError::PipeWireError("Failed to duplicate dma-buf descriptor".to_string()));
+    }
+    let modifier = format.modifier().unwrap_or(DRM_FORMAT_MOD_LINEAR);
+    let handle = DmaBufHandle {
+        fd,
+        fourcc: crate::common::to_drm_fourcc(format.format)?,
+        modifier,
+        offset: chunk.offset,
+        stride: chunk.stride as u32,
+    };
+    let descriptor = TextureDescriptor {
+        width: format.width,
+        height: format.height,
+        format: format.format,
+        usage: SINK_USAGE.to_vec(),
+        label: Some("PipeWire frame".to_string()),
+        tiling: TextureTiling::Optimal,
+        memory_location: TextureMemoryLocation::GpuOnly,
+    };
+
+    if modifier == DRM_FORMAT_MOD_LINEAR {
+        let texture = manager.import_vulkan_texture(ApiTextureHandle::DmaBuf(handle.clone()), &descriptor)?;
+        return Ok(SinkBuffer {
+            pw_buffer: buffer,
+            texture: Arc::new(texture),
+            handle: ApiTextureHandle::DmaBuf(handle),
+            zero_copy: false,
+        });
+    }
+    let texture = manager.import_dma_buf(handle, &descriptor)?;
+    // Imported dma-bufs are always tracked under a Vulkan handle
+    let handle = ApiTextureHandle::Vulkan(texture.exported_handle.clone().unwrap());
+    Ok(SinkBuffer {
+        pw_buffer: buffer,
+        texture: Arc::new(texture),
+        handle,
+        zero_copy: true,
+    })
+}
+
+// Imports and copies frames away from the PipeWire thread, so a slow copy never
+// stalls the graph. Runs until the sink stops.
+fn sink_worker(shared: Arc<SinkShared>) {
+    let mut state = shared.state.lock().unwrap();
+    loop {
+        if state.stopped {
+            return;
+        }
+        let Some(buffer) = state.pending.take() else {
+            state = shared.changed.wait(state).unwrap();
+            continue;
+        };
+        state.working = Some(buffer);
+        state.copying = true;
+        let known = state.buffers.iter().find(|b| b.pw_buffer == buffer).map(|b| (b.texture.clone(), b.zero_copy));
+        let format = state.negotiated.clone();
+        let manager = state.manager.clone();
+        drop(state);
+
+        let result = match known {
+            Some((_, true)) => Ok(None),
+            Some((texture, false)) => manager.refresh_copied_texture(&texture).map(|_| None),
+            // A first import already holds the frame's contents
+            None => import_sink_buffer(&manager, format, buffer).map(Some),
+        };
+
+        state = shared.state.lock().unwrap();
+        state.copying = false;
+        shared.changed.notify_all();
+        if state.stopped {
+            return;
+        }
+        let (library, thread_loop) = (state.library.clone(), state.thread_loop);
+        drop(state);
+
+        // Same lock order as the callbacks: the loop first, then the state
+        unsafe { (library.thread_loop_lock)(thread_loop) };
+        shared.state.lock().unwrap().finish(buffer, result);
+        unsafe { (library.thread_loop_unlock)(thread_loop) };
+        state = shared.state.lock().unwrap();
+    }
+}
+
+// Usage of the textures frames are imported into
+const SINK_USAGE: [TextureUsage; 2] = [TextureUsage::TextureBinding, TextureUsage::CopySrc];
+
+// Modifiers offered to producers: those this device imports without a copy for every
+// format, in the driver's order, then linear, which is copied
+fn sink_modifiers(manager: &VulkanTextureShareManager, formats: &[TextureFormat]) -> Result<Vec<u64>> {
+    let mut modifiers: Option<Vec<u64>> = None;
+    for format in formats {
+        let supported = manager.supported_drm_modifiers(*format, &SINK_USAGE)?;
+        match &mut modifiers {
+            Some(modifiers) => modifiers.retain(|modifier| supported.contains(modifier)),
+            None => modifiers = Some(supported),
+        }
+    }
+    let mut modifiers = modifiers.unwrap_or_default();
+    modifiers.retain(|&modifier| modifier != DRM_FORMAT_MOD_LINEAR);
+    modifiers.push(DRM_FORMAT_MOD_LINEAR);
+    Ok(modifiers)
+}
+
+unsafe extern "C" fn sink_param_changed(data: *mut c_void, id: u32, param: *const spa_pod) {
+    if id != pod::SPA_PARAM_FORMAT || param.is_null() {
+        return;
+    }
+    let shared = &*(data as *const SinkShared);
+    let mut state = shared.state.lock().unwrap();
+    let bytes = std::slice::from_raw_parts(param as *const u8, 8 + (*param).size as usize);
+    match pod::parse_video_format(bytes) {
+        Ok(format) => {
+            state.negotiated = Some(format);
+            Stream::update_params(&state.library, state.stream, &[pod::dma_buf_buffers(0)]);
+        }
+        Err(e) => state.state = StreamState::Error(e.to_string()),
+    }
+}
+
+unsafe extern "C" fn sink_process(data: *mut c_void) {
+    let shared = &*(data as *const SinkShared);
+    if shared.state.lock().unwrap().process() {
+        shared.changed.notify_all();
+    }
+}
+
+unsafe extern "C" fn sink_remove_buffer(data: *mut c_void, buffer: *mut pw_buffer) {
+    let shared = &*(data as *const SinkShared);
+    let mut state = shared.state.lock().unwrap();
+    // The worker may be reading the buffer or writing its texture
+    while state.copying && state.working == Some(buffer) {
+        state = shared.changed.wait(state).unwrap();
+    }
+    state.remove_buffer(buffer);
+}
+
+unsafe extern "C" fn sink_state_changed(data: *mut c_void, _old: c_int, state: c_int, error: *const c_char) {
+    let shared = &*(data as *const SinkShared);
+    if let Some(state) = stream_state(state, error) {
+        shared.state.lock().unwrap().state = state;
+    }
+}
+
+/// Receives a PipeWire video stream into Vulkan textures.
+///
+/// Dma-bufs with a DRM modifier this device supports (see
+/// `VulkanTextureShareManager::supported_drm_modifiers`) are imported without a copy;
+/// linear ones are copied (see `SharingStrategy::Copy`), which works with producers on
+/// any GPU. Copies run on a worker thread, not on the PipeWire thread loop.
+pub struct PipeWireSink {
+    // Declared first so the stream is destroyed while the state is still alive
+    stream: Stream,
+    shared: Arc<SinkShared>,
+    worker: Option<std::thread::JoinHandle<()>>,
+}
+
+impl PipeWireSink {
+    /// Creates a sink accepting `formats` and connects it to the node named `target`,
+    /// or to any video source if `None`.
+    ///
+    /// Requires `VK_EXT_external_memory_dma_buf`, and
+    /// `VK_EXT_image_drm_format_modifier` to accept modifiers other than linear.
+    pub fn new(
+        manager: Arc<VulkanTextureShareManager>,
+        name: &str,
+        target: Option<&str>,
+        formats: Vec<TextureFormat>,
+    ) -> Result<Self> {
+        if !manager.dma_buf_import_supported() {
+            return Err(GeyserError::MissingExtensions(vec![
+                ash::ext::external_memory_dma_buf::NAME.to_string_lossy().into_owned(),
+            ]));
+        }
+        let modifiers = sink_modifiers(&manager, &formats)?;
+        let enum_format = pod::enum_format(&FormatOffer::Consumer { formats, modifiers })?;
+
+        let library = Arc::new(PipeWireLibrary::load()?);
+        let shared = Arc::new(SinkShared {
+            state: Mutex::new(SinkState {
+                library: library.clone(),
+                thread_loop: std::ptr::null_mut(),
+                stream: std::ptr::null_mut(),
+                manager,
+                negotiated: None,
+                buffers: Vec::new(),
+                pending: None,
+                working: None,
+                copying: false,
+                held: None,
+                latest: None,
+                frame: 0,
+                state: StreamState::Connecting,
+                stopped: false,
+            }),
+            changed: Condvar::new(),
+        });
+
+        let events = pw_stream_events {
+            state_changed: Some(sink_state_changed),
+            param_changed: Some(sink_param_changed),
+            remove_buffer: Some(sink_remove_buffer),
+            process: Some(sink_process),
+            ..empty_events()
+        };
+        let mut properties = vec![("media.type", "Video"), ("media.category", "Capture"), ("node.name", name)];
+        if let Some(target) = target {
+            properties.push(("target.object", target));
+        }
+        let stream = Stream::new(library, name, &properties, events, Arc::as_ptr(&shared) as *mut c_void)?;
+        {
+            let mut state = shared.state.lock().unwrap();
+            state.thread_loop = stream.thread_loop;
+            state.stream = stream.stream;
+        }
+        let worker = {
+            let shared = shared.clone();
+            std::thread::Builder::new()
+                .name("geyser-pipewire-sink".to_string())
+                .spawn(move || sink_worker(shared))
+                .map_err(|e| GeyserError::PipeWireError(format!("Failed to start sink worker: {}", e)))?
+        };
+        let sink = Self {
+            stream,
+            shared,
+            worker: Some(worker),
+        };
+        sink.stream.connect(ffi::SPA_DIRECTION_INPUT, ffi::PW_STREAM_FLAG_AUTOCONNECT, &[enum_format])?;
+        Ok(sink)
+    }
+
+    pub fn state(&self) -> StreamState {
+        self.shared.state.lock().unwrap().state.clone()
+    }
+
+    /// Returns the negotiated format once connected to a producer.
+    pub fn format(&self) -> Option<VideoFormat> {
+        self.shared.state.lock().unwrap().negotiated.clone()
+    }
+
+    /// Runs `f` with the most recent frame and its sequence number, or returns `None`
+    /// if no frame has arrived yet. New frames are not received while `f` runs.
+    pub fn with_latest_frame<R>(&self, f: impl FnOnce(&VulkanSharedTexture, u64) -> R) -> Option<R> {
+        let _guard = self.stream.lock();
+        let mut state = self.shared.state.lock().unwrap();
+        // A new frame may be being copied into the latest frame's texture
+        while state.copying && state.working.is_some() && state.working == state.latest {
+            state = self.shared.changed.wait(state).unwrap();
+        }
+        let latest = state.latest?;
+        let buffer = state.buffers.iter().find(|b| b.pw_buffer == latest)?;
+        Some(f(&buffer.texture, state.frame))
+    }
+}
+
+impl Drop for PipeWireSink {
+    fn drop(&mut self) {
+        // Stop the worker while the stream it returns buffers to is still alive
+        self.shared.state.lock().unwrap().stopped = true;
+        self.shared.changed.notify_all();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
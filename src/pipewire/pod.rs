@@ -0,0 +1,414 @@
+//! Encoding and decoding of the SPA POD parameters used for video negotiation.
+//!
+//! libspa's POD builder is header-only, so the handful of layouts needed here are
+//! written directly. Every POD is a `u32` body size and a `u32` type followed by the
+//! body, padded to 8 bytes.
+
+use crate::common::TextureFormat;
+use crate::error::{GeyserError, Result};
+
+const SPA_TYPE_ID: u32 = 3;
+const SPA_TYPE_INT: u32 = 4;
+const SPA_TYPE_LONG: u32 = 5;
+const SPA_TYPE_RECTANGLE: u32 = 10;
+const SPA_TYPE_FRACTION: u32 = 11;
+const SPA_TYPE_OBJECT: u32 = 15;
+const SPA_TYPE_CHOICE: u32 = 19;
+
+const SPA_TYPE_OBJECT_FORMAT: u32 = 0x40003;
+const SPA_TYPE_OBJECT_PARAM_BUFFERS: u32 = 0x40004;
+
+pub const SPA_PARAM_ENUM_FORMAT: u32 = 3;
+pub const SPA_PARAM_FORMAT: u32 = 4;
+pub const SPA_PARAM_BUFFERS: u32 = 5;
+
+const SPA_CHOICE_NONE: u32 = 0;
+const SPA_CHOICE_RANGE: u32 = 1;
+const SPA_CHOICE_ENUM: u32 = 3;
+const SPA_CHOICE_FLAGS: u32 = 4;
+
+const SPA_POD_PROP_FLAG_MANDATORY: u32 = 1 << 3;
+const SPA_POD_PROP_FLAG_DONT_FIXATE: u32 = 1 << 4;
+
+const SPA_FORMAT_MEDIA_TYPE: u32 = 1;
+const SPA_FORMAT_MEDIA_SUBTYPE: u32 = 2;
+const SPA_FORMAT_VIDEO_FORMAT: u32 = 0x20001;
+const SPA_FORMAT_VIDEO_MODIFIER: u32 = 0x20002;
+const SPA_FORMAT_VIDEO_SIZE: u32 = 0x20003;
+const SPA_FORMAT_VIDEO_FRAMERATE: u32 = 0x20004;
+
+const SPA_MEDIA_TYPE_VIDEO: u32 = 2;
+const SPA_MEDIA_SUBTYPE_RAW: u32 = 1;
+
+const SPA_PARAM_BUFFERS_BUFFERS: u32 = 1;
+const SPA_PARAM_BUFFERS_BLOCKS: u32 = 2;
+const SPA_PARAM_BUFFERS_DATA_TYPE: u32 = 6;
+
+// `enum spa_video_format`
+const SPA_VIDEO_FORMAT_RGBX: u32 = 7;
+const SPA_VIDEO_FORMAT_BGRX: u32 = 8;
+const SPA_VIDEO_FORMAT_RGBA: u32 = 11;
+const SPA_VIDEO_FORMAT_BGRA: u32 = 12;
+const SPA_VIDEO_FORMAT_GRAY8: u32 = 25;
+
+/// Largest frame size a consumer offers to accept.
+pub const MAX_VIDEO_SIZE: u32 = 16384;
+
+/// The SPA video format a texture format is offered as.
+pub fn spa_video_format(format: TextureFormat) -> Option<u32> {
+    match format {
+        TextureFormat::Rgba8Unorm | TextureFormat::Rgba8Srgb => Some(SPA_VIDEO_FORMAT_RGBA),
+        TextureFormat::Bgra8Unorm | TextureFormat::Bgra8Srgb => Some(SPA_VIDEO_FORMAT_BGRA),
+        TextureFormat::R8Unorm => Some(SPA_VIDEO_FORMAT_GRAY8),
+        _ => None,
+    }
+}
+
+/// The texture format a SPA video format is imported as. Formats without alpha are
+/// read as their alpha counterpart, with the padding byte in the alpha channel.
+pub fn texture_format(spa_format: u32) -> Option<TextureFormat> {
+    match spa_format {
+        SPA_VIDEO_FORMAT_RGBA | SPA_VIDEO_FORMAT_RGBX => Some(TextureFormat::Rgba8Unorm),
+        SPA_VIDEO_FORMAT_BGRA | SPA_VIDEO_FORMAT_BGRX => Some(TextureFormat::Bgra8Unorm),
+        SPA_VIDEO_FORMAT_GRAY8 => Some(TextureFormat::R8Unorm),
+        _ => None,
+    }
+}
+
+// SPA video formats accepted as `format` by a consumer
+fn accepted_spa_formats(format: TextureFormat) -> Vec<u32> {
+    match format {
+        TextureFormat::Rgba8Unorm | TextureFormat::Rgba8Srgb => vec![SPA_VIDEO_FORMAT_RGBA, SPA_VIDEO_FORMAT_RGBX],
+        TextureFormat::Bgra8Unorm | TextureFormat::Bgra8Srgb => vec![SPA_VIDEO_FORMAT_BGRA, SPA_VIDEO_FORMAT_BGRX],
+        format => spa_video_format(format).into_iter().collect(),
+    }
+}
+
+/// Appends PODs to a buffer, 8-byte aligned.
+#[derive(Debug, Default)]
+pub struct PodBuilder {
+    data: Vec<u8>,
+}
+
+impl PodBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn finish(self) -> Vec<u8> {
+        self.data
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.data.extend_from_slice(&value.to_ne_bytes());
+    }
+
+    fn pad(&mut self) {
+        while !self.data.len().is_multiple_of(8) {
+            self.data.push(0);
+        }
+    }
+
+    fn primitive(&mut self, type_: u32, body: &[u8]) {
+        self.u32(body.len() as u32);
+        self.u32(type_);
+        self.data.extend_from_slice(body);
+        self.pad();
+    }
+
+    // Writes a header whose size is patched by `end` once the body is complete
+    fn begin(&mut self, type_: u32) -> usize {
+        let start = self.data.len();
+        self.u32(0);
+        self.u32(type_);
+        start
+    }
+
+    fn end(&mut self, start: usize) {
+        let size = (self.data.len() - start - 8) as u32;
+        self.data[start..start + 4].copy_from_slice(&size.to_ne_bytes());
+        self.pad();
+    }
+
+    pub fn id(&mut self, value: u32) {
+        self.primitive(SPA_TYPE_ID, &value.to_ne_bytes());
+    }
+
+    pub fn int(&mut self, value: i32) {
+        self.primitive(SPA_TYPE_INT, &value.to_ne_bytes());
+    }
+
+    pub fn rectangle(&mut self, width: u32, height: u32) {
+        self.primitive(SPA_TYPE_RECTANGLE, &pair(width, height));
+    }
+
+    pub fn fraction(&mut self, num: u32, denom: u32) {
+        self.primitive(SPA_TYPE_FRACTION, &pair(num, denom));
+    }
+
+    /// Writes a choice of `values` of one type, each `child_size` bytes. For enums the
+    /// first value is the default.
+    fn choice(&mut self, choice_type: u32, child_type: u32, child_size: u32, values: &[&[u8]]) {
+        let start = self.begin(SPA_TYPE_CHOICE);
+        self.u32(choice_type);
+        self.u32(0);
+        self.u32(child_size);
+        self.u32(child_type);
+        for value in values {
+            self.data.extend_from_slice(value);
+        }
+        self.end(start);
+    }
+
+    pub fn choice_enum_id(&mut self, values: &[u32]) {
+        let bytes: Vec<_> = values.iter().map(|v| v.to_ne_bytes()).collect();
+        let choice_type = if values.len() == 1 { SPA_CHOICE_NONE } else { SPA_CHOICE_ENUM };
+        let mut refs: Vec<&[u8]> = bytes.iter().map(|b| b.as_slice()).collect();
+        // An enum repeats its default before the alternatives
+        if choice_type == SPA_CHOICE_ENUM {
+            refs.insert(0, &bytes[0]);
+        }
+        self.choice(choice_type, SPA_TYPE_ID, 4, &refs);
+    }
+
+    pub fn choice_enum_long(&mut self, values: &[u64]) {
+        let bytes: Vec<_> = values.iter().map(|v| v.to_ne_bytes()).collect();
+        let choice_type = if values.len() == 1 { SPA_CHOICE_NONE } else { SPA_CHOICE_ENUM };
+        let mut refs: Vec<&[u8]> = bytes.iter().map(|b| b.as_slice()).collect();
+        if choice_type == SPA_CHOICE_ENUM {
+            refs.insert(0, &bytes[0]);
+        }
+        self.choice(choice_type, SPA_TYPE_LONG, 8, &refs);
+    }
+
+    pub fn choice_range_rectangle(&mut self, default: (u32, u32), min: (u32, u32), max: (u32, u32)) {
+        let values = [pair(default.0, default.1), pair(min.0, min.1), pair(max.0, max.1)];
+        self.choice(SPA_CHOICE_RANGE, SPA_TYPE_RECTANGLE, 8, &[&values[0], &values[1], &values[2]]);
+    }
+
+    pub fn choice_range_fraction(&mut self, default: (u32, u32), min: (u32, u32), max: (u32, u32)) {
+        let values = [pair(default.0, default.1), pair(min.0, min.1), pair(max.0, max.1)];
+        self.choice(SPA_CHOICE_RANGE, SPA_TYPE_FRACTION, 8, &[&values[0], &values[1], &values[2]]);
+    }
+
+    pub fn choice_flags_int(&mut self, flags: i32) {
+        self.choice(SPA_CHOICE_FLAGS, SPA_TYPE_INT, 4, &[&flags.to_ne_bytes()]);
+    }
+
+    /// Writes an object of `object_type` for param `id`; `properties` adds its
+    /// properties with `property`.
+    pub fn object(&mut self, object_type: u32, id: u32, properties: impl FnOnce(&mut Self)) {
+        let start = self.begin(SPA_TYPE_OBJECT);
+        self.u32(object_type);
+        self.u32(id);
+        properties(self);
+        self.end(start);
+    }
+
+    /// Writes a property key and flags; the value POD must follow.
+    pub fn property(&mut self, key: u32, flags: u32) {
+        self.u32(key);
+        self.u32(flags);
+    }
+}
+
+fn pair(a: u32, b: u32) -> [u8; 8] {
+    let mut bytes = [0u8; 8];
+    bytes[..4].copy_from_slice(&a.to_ne_bytes());
+    bytes[4..].copy_from_slice(&b.to_ne_bytes());
+    bytes
+}
+
+/// What an `EnumFormat` offers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormatOffer {
+    /// A producer's fixed frame size in one format
+    Producer {
+        format: TextureFormat,
+        modifiers: Vec<u64>,
+        width: u32,
+        height: u32,
+    },
+    /// A consumer accepting any size in any of `formats`
+    Consumer {
+        formats: Vec<TextureFormat>,
+        modifiers: Vec<u64>,
+    },
+}
+
+/// Builds an `EnumFormat` param for raw video in dma-bufs with the given modifiers.
+pub fn enum_format(offer: &FormatOffer) -> Result<Vec<u8>> {
+    let (spa_formats, modifiers) = match offer {
+        FormatOffer::Producer { format, modifiers, .. } => (spa_video_format(*format).into_iter().collect(), modifiers),
+        FormatOffer::Consumer { formats, modifiers } => {
+            (formats.iter().flat_map(|format| accepted_spa_formats(*format)).collect::<Vec<_>>(), modifiers)
+        }
+    };
+    if spa_formats.is_empty() {
+        return Err(GeyserError::UnsupportedFormat(format!("No PipeWire video format for {:?}", offer)));
+    }
+    if modifiers.is_empty() {
+        return Err(GeyserError::PipeWireError("At least one DRM modifier must be offered".to_string()));
+    }
+
+    let mut builder = PodBuilder::new();
+    builder.object(SPA_TYPE_OBJECT_FORMAT, SPA_PARAM_ENUM_FORMAT, |b| {
+        b.property(SPA_FORMAT_MEDIA_TYPE, 0);
+        b.id(SPA_MEDIA_TYPE_VIDEO);
+        b.property(SPA_FORMAT_MEDIA_SUBTYPE, 0);
+        b.id(SPA_MEDIA_SUBTYPE_RAW);
+        b.property(SPA_FORMAT_VIDEO_FORMAT, 0);
+        b.choice_enum_id(&spa_formats);
+        // Left unfixated so the producer can pick among the modifiers both sides support
+        b.property(SPA_FORMAT_VIDEO_MODIFIER, SPA_POD_PROP_FLAG_MANDATORY | SPA_POD_PROP_FLAG_DONT_FIXATE);
+        b.choice_enum_long(modifiers);
+        b.property(SPA_FORMAT_VIDEO_SIZE, 0);
+        match offer {
+            FormatOffer::Producer { width, height, .. } => b.rectangle(*width, *height),
+            FormatOffer::Consumer { .. } => {
+                b.choice_range_rectangle((1920, 1080), (1, 1), (MAX_VIDEO_SIZE, MAX_VIDEO_SIZE))
+            }
+        }
+        b.property(SPA_FORMAT_VIDEO_FRAMERATE, 0);
+        match offer {
+            // Frames are pushed as they are rendered
+            FormatOffer::Producer { .. } => b.fraction(0, 1),
+            FormatOffer::Consumer { .. } => b.choice_range_fraction((0, 1), (0, 1), (1000, 1)),
+        }
+    });
+    Ok(builder.finish())
+}
+
+/// Builds a `Buffers` param requesting `count` single-plane dma-buf buffers.
+pub fn dma_buf_buffers(count: u32) -> Vec<u8> {
+    let mut builder = PodBuilder::new();
+    builder.object(SPA_TYPE_OBJECT_PARAM_BUFFERS, SPA_PARAM_BUFFERS, |b| {
+        b.property(SPA_PARAM_BUFFERS_BUFFERS, 0);
+        b.int(count as i32);
+        b.property(SPA_PARAM_BUFFERS_BLOCKS, 0);
+        b.int(1);
+        b.property(SPA_PARAM_BUFFERS_DATA_TYPE, 0);
+        b.choice_flags_int(1 << super::ffi::SPA_DATA_DMA_BUF);
+    });
+    builder.finish()
+}
+
+/// A `Format` param as negotiated by PipeWire.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VideoFormat {
+    pub format: TextureFormat,
+    pub width: u32,
+    pub height: u32,
+    /// A single modifier once fixated; before that, the modifiers both sides support,
+    /// from which the producer picks one
+    pub modifiers: Vec<u64>,
+}
+
+impl VideoFormat {
+    /// Returns the modifier once the format is fixated.
+    pub fn modifier(&self) -> Option<u64> {
+        match self.modifiers.as_slice() {
+            [modifier] => Some(*modifier),
+            _ => None,
+        }
+    }
+}
+
+fn malformed(what: &str) -> GeyserError {
+    GeyserError::PipeWireError(format!("Malformed format param: {}", what))
+}
+
+// Splits one POD off the front of `bytes`, returning its type, body and the rest
+fn split_pod(bytes: &[u8]) -> Result<(u32, &[u8], &[u8])> {
+    if bytes.len() < 8 {
+        return Err(malformed("truncated POD header"));
+    }
+    let size = read_u32(bytes, 0) as usize;
+    let type_ = read_u32(bytes, 4);
+    let padded = (8 + size).next_multiple_of(8);
+    if bytes.len() < 8 + size {
+        return Err(malformed("truncated POD body"));
+    }
+    Ok((type_, &bytes[8..8 + size], &bytes[padded.min(bytes.len())..]))
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_ne_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> u64 {
+    u64::from_ne_bytes(bytes[offset..offset + 8].try_into().unwrap())
+}
+
+// The values of a property: a plain POD yields one value, a choice yields its
+// alternatives (the default first, without repeating it for enums)
+fn values(type_: u32, body: &[u8]) -> Result<(u32, Vec<&[u8]>)> {
+    if type_ != SPA_TYPE_CHOICE {
+        return Ok((type_, vec![body]));
+    }
+    if body.len() < 16 {
+        return Err(malformed("truncated choice"));
+    }
+    let choice_type = read_u32(body, 0);
+    let child_size = read_u32(body, 8) as usize;
+    let child_type = read_u32(body, 12);
+    if child_size == 0 {
+        return Err(malformed("empty choice value"));
+    }
+    let mut children: Vec<&[u8]> = body[16..].chunks_exact(child_size).collect();
+    if choice_type == SPA_CHOICE_ENUM && children.len() > 1 {
+        // Drop the default, which is repeated among the alternatives
+        children.remove(0);
+    } else if choice_type != SPA_CHOICE_ENUM {
+        children.truncate(1);
+    }
+    Ok((child_type, children))
+}
+
+/// Parses a `Format` (or `EnumFormat`) object describing raw video.
+pub fn parse_video_format(bytes: &[u8]) -> Result<VideoFormat> {
+    let (type_, body, _) = split_pod(bytes)?;
+    if type_ != SPA_TYPE_OBJECT || body.len() < 8 || read_u32(body, 0) != SPA_TYPE_OBJECT_FORMAT {
+        return Err(malformed("not a format object"));
+    }
+
+    let mut media = (None, None);
+    let mut format = None;
+    let mut size = None;
+    let mut modifiers = Vec::new();
+    let mut rest = &body[8..];
+    while rest.len() >= 8 {
+        let key = read_u32(rest, 0);
+        let (type_, value, next) = split_pod(&rest[8..])?;
+        rest = next;
+        let (child_type, values) = values(type_, value)?;
+        let first = values.first().ok_or_else(|| malformed("property without a value"))?;
+        match (key, child_type) {
+            (SPA_FORMAT_MEDIA_TYPE, SPA_TYPE_ID) => media.0 = Some(read_u32(first, 0)),
+            (SPA_FORMAT_MEDIA_SUBTYPE, SPA_TYPE_ID) => media.1 = Some(read_u32(first, 0)),
+            (SPA_FORMAT_VIDEO_FORMAT, SPA_TYPE_ID) => format = Some(read_u32(first, 0)),
+            (SPA_FORMAT_VIDEO_SIZE, SPA_TYPE_RECTANGLE) => size = Some((read_u32(first, 0), read_u32(first, 4))),
+            (SPA_FORMAT_VIDEO_MODIFIER, SPA_TYPE_LONG) => {
+                modifiers = values.iter().map(|value| read_u64(value, 0)).collect();
+            }
+            _ => {}
+        }
+    }
+
+    if media != (Some(SPA_MEDIA_TYPE_VIDEO), Some(SPA_MEDIA_SUBTYPE_RAW)) {
+        return Err(malformed("not raw video"));
+    }
+    let spa_format = format.ok_or_else(|| malformed("missing video format"))?;
+    let format = texture_format(spa_format)
+        .ok_or_else(|| GeyserError::UnsupportedFormat(format!("SPA video format {}", spa_format)))?;
+    let (width, height) = size.ok_or_else(|| malformed("missing size"))?;
+    if modifiers.is_empty() {
+        return Err(GeyserError::PipeWireError("Only dma-buf streams with a DRM modifier are supported".to_string()));
+    }
+    Ok(VideoFormat {
+        format,
+        width,
+        height,
+        modifiers,
+    })
+}
@@ -0,0 +1,152 @@
+//! Unit tests for PipeWire format negotiation and POD encoding
+
+use std::time::{Duration, Instant};
+
+use super::*;
+use super::pod::{dma_buf_buffers, enum_format, parse_video_format, spa_video_format, texture_format};
+use crate::SharedTexture;
+
+const MOD_TILED: u64 = 0x0100_0000_0000_0001;
+
+fn producer(modifiers: Vec<u64>) -> FormatOffer {
+    FormatOffer::Producer {
+        format: TextureFormat::Bgra8Unorm,
+        modifiers,
+        width: 1280,
+        height: 720,
+    }
+}
+
+#[test]
+fn test_video_formats_roundtrip() {
+    for format in [TextureFormat::Rgba8Unorm, TextureFormat::Bgra8Unorm, TextureFormat::R8Unorm] {
+        let spa = spa_video_format(format).unwrap();
+        assert_eq!(texture_format(spa), Some(format));
+    }
+    // Video formats carry no color space, so sRGB textures share the Unorm layout
+    assert_eq!(spa_video_format(TextureFormat::Rgba8Srgb), spa_video_format(TextureFormat::Rgba8Unorm));
+    assert_eq!(texture_format(0), None);
+}
+
+#[test]
+fn test_producer_offer_parses_back() {
+    let bytes = enum_format(&producer(vec![MOD_TILED, DRM_FORMAT_MOD_LINEAR])).unwrap();
+    assert_eq!(bytes.len() % 8, 0);
+
+    let format = parse_video_format(&bytes).unwrap();
+    assert_eq!(format.format, TextureFormat::Bgra8Unorm);
+    assert_eq!((format.width, format.height), (1280, 720));
+    assert_eq!(format.modifiers, vec![MOD_TILED, DRM_FORMAT_MOD_LINEAR]);
+    // Several modifiers on offer: the producer still has to fixate one
+    assert_eq!(format.modifier(), None);
+}
+
+#[test]
+fn test_single_modifier_is_fixated() {
+    let bytes = enum_format(&producer(vec![DRM_FORMAT_MOD_LINEAR])).unwrap();
+    let format = parse_video_format(&bytes).unwrap();
+    assert_eq!(format.modifier(), Some(DRM_FORMAT_MOD_LINEAR));
+    assert_eq!(tiling_for_modifier(DRM_FORMAT_MOD_LINEAR), TextureTiling::Linear);
+    assert_eq!(tiling_for_modifier(MOD_TILED), TextureTiling::DrmModifier(MOD_TILED));
+}
+
+#[test]
+fn test_consumer_offer_takes_first_format_as_default() {
+    let offer = FormatOffer::Consumer {
+        formats: vec![TextureFormat::Rgba8Unorm, TextureFormat::Bgra8Unorm],
+        modifiers: vec![DRM_FORMAT_MOD_LINEAR],
+    };
+    let format = parse_video_format(&enum_format(&offer).unwrap()).unwrap();
+    assert_eq!(format.format, TextureFormat::Rgba8Unorm);
+    assert_eq!(format.modifier(), Some(DRM_FORMAT_MOD_LINEAR));
+}
+
+#[test]
+fn test_invalid_offers_are_rejected() {
+    assert!(enum_format(&producer(Vec::new())).is_err());
+    let float = FormatOffer::Producer {
+        format: TextureFormat::Rgba16Float,
+        modifiers: vec![DRM_FORMAT_MOD_LINEAR],
+        width: 16,
+        height: 16,
+    };
+    assert!(matches!(enum_format(&float), Err(GeyserError::UnsupportedFormat(_))));
+    // Truncated and non-format PODs
+    let bytes = enum_format(&producer(vec![DRM_FORMAT_MOD_LINEAR])).unwrap();
+    assert!(parse_video_format(&bytes[..bytes.len() / 2]).is_err());
+    assert!(parse_video_format(&dma_buf_buffers(4)).is_err());
+}
+
+#[test]
+fn test_buffers_param_requests_dma_bufs() {
+    let bytes = dma_buf_buffers(3);
+    let word = |i: usize| u32::from_ne_bytes(bytes[i * 4..i * 4 + 4].try_into().unwrap());
+    assert_eq!(bytes.len() % 8, 0);
+    // Object header: size, SPA_TYPE_Object, object type, param id
+    assert_eq!(word(0) as usize, bytes.len() - 8);
+    assert_eq!(word(1), 15);
+    assert_eq!(word(3), pod::SPA_PARAM_BUFFERS);
+    // First property: buffer count as an Int
+    assert_eq!(word(6), 4);
+    assert_eq!(word(8) as i32, 3);
+}
+
+#[test]
+fn test_modifier_choice_prefers_ours() {
+    assert_eq!(choose_modifier(&[MOD_TILED, DRM_FORMAT_MOD_LINEAR], &[DRM_FORMAT_MOD_LINEAR, MOD_TILED]), Some(MOD_TILED));
+    assert_eq!(choose_modifier(&[MOD_TILED, DRM_FORMAT_MOD_LINEAR], &[DRM_FORMAT_MOD_LINEAR]), Some(DRM_FORMAT_MOD_LINEAR));
+    assert_eq!(choose_modifier(&[MOD_TILED], &[DRM_FORMAT_MOD_LINEAR]), None);
+}
+
+// Runs against a PipeWire daemon on a machine with a Vulkan device that can share
+// dma-bufs; skipped elsewhere
+#[test]
+fn test_sink_receives_frames_from_source() {
+    let socket = std::env::var_os("PIPEWIRE_REMOTE").map(std::path::PathBuf::from).or_else(|| {
+        std::env::var_os("XDG_RUNTIME_DIR").map(|dir| std::path::Path::new(&dir).join("pipewire-0"))
+    });
+    if !socket.is_some_and(|socket| socket.exists()) {
+        eprintln!("Skipping: no PipeWire daemon is running");
+        return;
+    }
+    if let Err(e) = PipeWireLibrary::load() {
+        eprintln!("Skipping: {}", e);
+        return;
+    }
+    let manager = match VulkanTextureShareManager::builder().application_name("geyser pipewire test").build() {
+        Ok(manager) if manager.dma_buf_import_supported() => Arc::new(manager),
+        Ok(_) => {
+            eprintln!("Skipping: VK_EXT_external_memory_dma_buf is unsupported");
+            return;
+        }
+        Err(e) => {
+            eprintln!("Skipping: {}", e);
+            return;
+        }
+    };
+
+    let descriptor = TextureDescriptor::new(64, 48, TextureFormat::Bgra8Unorm, vec![TextureUsage::CopyDst])
+        .with_tiling(TextureTiling::Linear);
+    let source = PipeWireSource::new(manager.clone(), "geyser-test-source", &descriptor, vec![DRM_FORMAT_MOD_LINEAR], 3)
+        .unwrap();
+    let sink =
+        PipeWireSink::new(manager, "geyser-test-sink", Some("geyser-test-source"), vec![TextureFormat::Bgra8Unorm]).unwrap();
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let mut received = None;
+    while received.is_none() && Instant::now() < deadline {
+        assert!(!matches!(source.state(), StreamState::Error(_)), "{:?}", source.state());
+        assert!(!matches!(sink.state(), StreamState::Error(_)), "{:?}", sink.state());
+        if let Some(frame) = source.dequeue() {
+            source.queue(frame).unwrap();
+        }
+        received = sink.with_latest_frame(|texture, frame| (texture.width(), texture.height(), frame));
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    let (width, height, frame) = received.expect("no frame reached the sink");
+    assert_eq!((width, height), (64, 48));
+    assert!(frame >= 1);
+    let format = sink.format().unwrap();
+    assert_eq!(format.format, TextureFormat::Bgra8Unorm);
+    assert_eq!(format.modifier(), Some(DRM_FORMAT_MOD_LINEAR));
+}
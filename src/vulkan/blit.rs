@@ -0,0 +1,920 @@
+//! Blits and copies between shared textures on the manager's queue.
+
+use ash::vk;
+use gpu_allocator::{
+    vulkan::{Allocation, AllocationCreateDesc, AllocationScheme},
+    MemoryLocation,
+};
+use crate::{
+    common::{bytes_per_texel, TextureDescriptor, TextureFormat, TextureTiling, TextureUsage},
+    error::{GeyserError, Result},
+};
+use super::{aspect_mask_for_format, VulkanSharedTexture, VulkanTextureShareManager, SHARED_IMAGE_LAYOUT};
+
+// SPIR-V of `shaders/convert.wgsl`, the compute fallback of `blit`
+const CONVERT_SPV: &[u8] = include_bytes!("shaders/convert.spv");
+
+// Push constant flags of the convert shader; must match the constants in convert.wgsl
+pub(super) const CONVERT_SRC_SRGB: u32 = 1;
+pub(super) const CONVERT_SRC_BGRA: u32 = 2;
+pub(super) const CONVERT_DST_SRGB: u32 = 4;
+pub(super) const CONVERT_DST_BGRA: u32 = 8;
+pub(super) const CONVERT_LINEAR_FILTER: u32 = 16;
+
+// Size of the convert shader's push constants: source size, destination size and flags
+const CONVERT_PUSH_CONSTANTS_SIZE: u32 = 5 * 4;
+const CONVERT_WORKGROUP_SIZE: u32 = 8;
+
+/// A rectangle copied by `VulkanTextureShareManager::copy`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CopyRegion {
+    /// Top-left texel read from the source texture
+    pub src_origin: [u32; 2],
+    /// Top-left texel written in the destination texture
+    pub dst_origin: [u32; 2],
+    /// Width and height of the rectangle in texels
+    pub extent: [u32; 2],
+}
+
+impl CopyRegion {
+    /// Copies a `width` x `height` rectangle at the top-left corner of both textures.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            src_origin: [0, 0],
+            dst_origin: [0, 0],
+            extent: [width, height],
+        }
+    }
+
+    /// Reads the rectangle starting at texel (`x`, `y`) of the source.
+    pub fn with_src_origin(mut self, x: u32, y: u32) -> Self {
+        self.src_origin = [x, y];
+        self
+    }
+
+    /// Writes the rectangle starting at texel (`x`, `y`) of the destination.
+    pub fn with_dst_origin(mut self, x: u32, y: u32) -> Self {
+        self.dst_origin = [x, y];
+        self
+    }
+}
+
+// Command pool and fence of `blit` and `copy`, plus the compute fallback pipeline once
+// a blit has needed it. Created on first use.
+pub(super) struct BlitState {
+    command_pool: vk::CommandPool,
+    fence: vk::Fence,
+    converter: Option<Converter>,
+}
+
+// Compute pipeline running `shaders/convert.wgsl` over two storage buffers. Its single
+// descriptor set is rewritten by every fallback blit, which are serialized by the state lock.
+struct Converter {
+    descriptor_set_layout: vk::DescriptorSetLayout,
+    pipeline_layout: vk::PipelineLayout,
+    pipeline: vk::Pipeline,
+    descriptor_pool: vk::DescriptorPool,
+    descriptor_set: vk::DescriptorSet,
+}
+
+// Whether Vulkan allows blitting between the formats, before checking format features.
+// Depth/stencil texels can only be blitted unfiltered to the same format, and integer
+// texels only unfiltered to integers of the same signedness.
+pub(super) fn blit_formats_compatible(src: TextureFormat, dst: TextureFormat, filter: vk::Filter) -> bool {
+    let is_depth = |format| aspect_mask_for_format(format) != vk::ImageAspectFlags::COLOR;
+    if is_depth(src) || is_depth(dst) {
+        return src == dst && filter == vk::Filter::NEAREST;
+    }
+
+    let integer_signedness = |format| match format {
+        TextureFormat::R16Uint | TextureFormat::R32Uint => Some(false),
+        TextureFormat::R16Sint | TextureFormat::R32Sint => Some(true),
+        _ => None,
+    };
+    let signedness = integer_signedness(src);
+    signedness == integer_signedness(dst) && (signedness.is_none() || filter == vk::Filter::NEAREST)
+}
+
+// Push constant flags converting `src` texels to `dst` texels in the compute fallback,
+// which handles the 8-bit four-channel formats only
+pub(super) fn convert_flags(src: TextureFormat, dst: TextureFormat, filter: vk::Filter) -> Option<u32> {
+    let format_flags = |format| match format {
+        TextureFormat::Rgba8Unorm => Some((false, false)),
+        TextureFormat::Rgba8Srgb => Some((true, false)),
+        TextureFormat::Bgra8Unorm => Some((false, true)),
+        TextureFormat::Bgra8Srgb => Some((true, true)),
+        _ => None,
+    };
+    let (src_srgb, src_bgra) = format_flags(src)?;
+    let (dst_srgb, dst_bgra) = format_flags(dst)?;
+
+    let mut flags = 0;
+    for (set, flag) in [
+        (src_srgb, CONVERT_SRC_SRGB),
+        (src_bgra, CONVERT_SRC_BGRA),
+        (dst_srgb, CONVERT_DST_SRGB),
+        (dst_bgra, CONVERT_DST_BGRA),
+        (filter == vk::Filter::LINEAR, CONVERT_LINEAR_FILTER),
+    ] {
+        if set {
+            flags |= flag;
+        }
+    }
+    Some(flags)
+}
+
+// Copies reinterpret texels bit for bit, so colour formats only need matching texel
+// sizes; depth/stencil texels can only be copied to the same format
+pub(super) fn copy_formats_compatible(src: TextureFormat, dst: TextureFormat) -> bool {
+    match (bytes_per_texel(src), bytes_per_texel(dst)) {
+        (Some(src_size), Some(dst_size)) => src_size == dst_size,
+        (None, None) => src == dst,
+        _ => false,
+    }
+}
+
+// Checks that `region` is non-empty and lies inside both textures
+pub(super) fn validate_copy_region(region: &CopyRegion, src: &TextureDescriptor, dst: &TextureDescriptor) -> Result<()> {
+    let [width, height] = region.extent;
+    if width == 0 || height == 0 {
+        return Err(GeyserError::Other(format!("Copy region {:?} is empty", region)));
+    }
+    let fits = |origin: [u32; 2], descriptor: &TextureDescriptor| {
+        origin[0].checked_add(width).is_some_and(|right| right <= descriptor.width)
+            && origin[1].checked_add(height).is_some_and(|bottom| bottom <= descriptor.height)
+    };
+    for (origin, descriptor, role) in [(region.src_origin, src, "source"), (region.dst_origin, dst, "destination")] {
+        if !fits(origin, descriptor) {
+            return Err(GeyserError::Other(format!(
+                "Copy region {:?} exceeds the {}x{} {} texture",
+                region, descriptor.width, descriptor.height, role
+            )));
+        }
+    }
+    Ok(())
+}
+
+// Rejects textures created without the usage a transfer command needs
+fn require_usage(texture: &VulkanSharedTexture, usage: TextureUsage) -> Result<()> {
+    if texture.descriptor.usage.contains(&usage) {
+        Ok(())
+    } else {
+        Err(GeyserError::Other(format!(
+            "Texture {} was created without {:?} usage",
+            texture.descriptor.label.as_deref().unwrap_or("(unlabelled)"),
+            usage
+        )))
+    }
+}
+
+// Checks the format features for copying texels out of `src` and into `dst`
+fn require_transfer_features(
+    src: &VulkanSharedTexture,
+    src_features: vk::FormatFeatureFlags,
+    dst: &VulkanSharedTexture,
+    dst_features: vk::FormatFeatureFlags,
+) -> Result<()> {
+    if !src_features.contains(vk::FormatFeatureFlags::TRANSFER_SRC) || !dst_features.contains(vk::FormatFeatureFlags::TRANSFER_DST) {
+        return Err(GeyserError::UnsupportedFormat(format!(
+            "Cannot copy {} texels to {} with these tilings on this device",
+            src.descriptor.format, dst.descriptor.format
+        )));
+    }
+    Ok(())
+}
+
+fn subresource_layers(texture: &VulkanSharedTexture) -> vk::ImageSubresourceLayers {
+    vk::ImageSubresourceLayers {
+        aspect_mask: aspect_mask_for_format(texture.descriptor.format),
+        mip_level: 0,
+        base_array_layer: 0,
+        layer_count: 1,
+    }
+}
+
+// Moves `texture` between layouts around transfer commands. Leaving `SHARED_IMAGE_LAYOUT`
+// waits for all earlier work on the queue; returning to it makes the transfer writes
+// available to all later work.
+fn transfer_barrier(texture: &VulkanSharedTexture, old_layout: vk::ImageLayout, new_layout: vk::ImageLayout) -> vk::ImageMemoryBarrier2<'static> {
+    let (src_stage_mask, src_access_mask, dst_stage_mask, dst_access_mask) = if new_layout == SHARED_IMAGE_LAYOUT {
+        (
+            vk::PipelineStageFlags2::ALL_TRANSFER,
+            vk::AccessFlags2::TRANSFER_WRITE,
+            vk::PipelineStageFlags2::ALL_COMMANDS,
+            vk::AccessFlags2::MEMORY_READ | vk::AccessFlags2::MEMORY_WRITE,
+        )
+    } else {
+        (
+            vk::PipelineStageFlags2::ALL_COMMANDS,
+            vk::AccessFlags2::MEMORY_WRITE,
+            vk::PipelineStageFlags2::ALL_TRANSFER,
+            vk::AccessFlags2::TRANSFER_READ | vk::AccessFlags2::TRANSFER_WRITE,
+        )
+    };
+
+    vk::ImageMemoryBarrier2 {
+        s_type: vk::StructureType::IMAGE_MEMORY_BARRIER_2,
+        p_next: std::ptr::null(),
+        src_stage_mask,
+        src_access_mask,
+        dst_stage_mask,
+        dst_access_mask,
+        old_layout,
+        new_layout,
+        src_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+        dst_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+        image: texture.image,
+        subresource_range: vk::ImageSubresourceRange {
+            aspect_mask: aspect_mask_for_format(texture.descriptor.format),
+            base_mip_level: 0,
+            level_count: 1,
+            base_array_layer: 0,
+            layer_count: 1,
+        },
+        _marker: std::marker::PhantomData,
+    }
+}
+
+fn memory_barrier(
+    src_stage_mask: vk::PipelineStageFlags2,
+    src_access_mask: vk::AccessFlags2,
+    dst_stage_mask: vk::PipelineStageFlags2,
+    dst_access_mask: vk::AccessFlags2,
+) -> vk::MemoryBarrier2<'static> {
+    vk::MemoryBarrier2 {
+        s_type: vk::StructureType::MEMORY_BARRIER_2,
+        p_next: std::ptr::null(),
+        src_stage_mask,
+        src_access_mask,
+        dst_stage_mask,
+        dst_access_mask,
+        _marker: std::marker::PhantomData,
+    }
+}
+
+// Region covering the whole of a texture, for buffer copies of the compute fallback
+fn whole_image_copy(texture: &VulkanSharedTexture) -> vk::BufferImageCopy {
+    vk::BufferImageCopy {
+        buffer_offset: 0,
+        buffer_row_length: 0,
+        buffer_image_height: 0,
+        image_subresource: subresource_layers(texture),
+        image_offset: vk::Offset3D { x: 0, y: 0, z: 0 },
+        image_extent: vk::Extent3D {
+            width: texture.descriptor.width,
+            height: texture.descriptor.height,
+            depth: 1,
+        },
+    }
+}
+
+impl VulkanTextureShareManager {
+    /// Blits the whole of `src` onto the whole of `dst`, scaling with `filter`
+    /// (`NEAREST` or `LINEAR`) and converting between their formats.
+    ///
+    /// Uses `vkCmdBlitImage2`, which also converts between sRGB and linear encodings and
+    /// between RGBA and BGRA orders. Where the formats or tilings cannot be blitted, e.g.
+    /// for many linear or DRM-modifier images, 8-bit RGBA/BGRA textures are converted by a
+    /// compute shader through staging buffers instead; other formats fail with
+    /// `UnsupportedFormat`.
+    ///
+    /// `src` needs `TextureUsage::CopySrc` and `dst` needs `TextureUsage::CopyDst`. Both must
+    /// be in `SHARED_IMAGE_LAYOUT`, where they are left; the previous contents of `dst` are
+    /// discarded. Like `refresh_copied_texture`, this runs on the manager's queue and blocks
+    /// until it completes, so wait on the textures' sync primitives first.
+    ///
+    /// Requires a Vulkan 1.3 instance and device with `synchronization2` enabled.
+    pub fn blit(&self, src: &VulkanSharedTexture, dst: &VulkanSharedTexture, filter: vk::Filter) -> Result<()> {
+        self.check_transfer(src, dst)?;
+        if filter != vk::Filter::NEAREST && filter != vk::Filter::LINEAR {
+            return Err(GeyserError::Other(format!("Unsupported blit filter {:?}", filter)));
+        }
+
+        let src_format = self.map_texture_format_to_vk(src.descriptor.format)?;
+        let dst_format = self.map_texture_format_to_vk(dst.descriptor.format)?;
+        let src_features = self.format_features(src_format, src.descriptor.tiling);
+        let dst_features = self.format_features(dst_format, dst.descriptor.tiling);
+        let blittable = blit_formats_compatible(src.descriptor.format, dst.descriptor.format, filter)
+            && src_features.contains(vk::FormatFeatureFlags::BLIT_SRC)
+            && dst_features.contains(vk::FormatFeatureFlags::BLIT_DST)
+            && (filter == vk::Filter::NEAREST || src_features.contains(vk::FormatFeatureFlags::SAMPLED_IMAGE_FILTER_LINEAR));
+
+        let mut state_guard = self.blit_state.lock().unwrap();
+        if state_guard.is_none() {
+            *state_guard = Some(self.create_blit_state()?);
+        }
+        let state = state_guard.as_mut().unwrap();
+
+        if blittable {
+            return self.submit_transfer(state, |command_buffer| self.record_blit(command_buffer, src, dst, filter));
+        }
+        let flags = convert_flags(src.descriptor.format, dst.descriptor.format, filter).ok_or_else(|| {
+            GeyserError::UnsupportedFormat(format!(
+                "Cannot blit {} to {} on this device",
+                src.descriptor.format, dst.descriptor.format
+            ))
+        })?;
+        // The fallback copies both textures through staging buffers
+        require_transfer_features(src, src_features, dst, dst_features)?;
+        self.blit_with_compute(state, src, dst, flags)
+    }
+
+    /// Copies `regions` of `src` into `dst` without scaling or conversion.
+    ///
+    /// Texels are copied bit for bit, so the formats only need the same texel size (or must
+    /// be equal for depth/stencil formats); use `blit` to convert between formats. Each
+    /// region must lie inside both textures.
+    ///
+    /// `src` needs `TextureUsage::CopySrc` and `dst` needs `TextureUsage::CopyDst`. Both must
+    /// be in `SHARED_IMAGE_LAYOUT`, where they are left. Runs on the manager's queue and
+    /// blocks until it completes, so wait on the textures' sync primitives first.
+    ///
+    /// Requires a Vulkan 1.3 instance and device with `synchronization2` enabled, and
+    /// formats whose tilings support transfers.
+    pub fn copy(&self, src: &VulkanSharedTexture, dst: &VulkanSharedTexture, regions: &[CopyRegion]) -> Result<()> {
+        self.check_transfer(src, dst)?;
+        if !copy_formats_compatible(src.descriptor.format, dst.descriptor.format) {
+            return Err(GeyserError::UnsupportedFormat(format!(
+                "Cannot copy {} texels to {}; use blit to convert",
+                src.descriptor.format, dst.descriptor.format
+            )));
+        }
+        let src_features = self.format_features(self.map_texture_format_to_vk(src.descriptor.format)?, src.descriptor.tiling);
+        let dst_features = self.format_features(self.map_texture_format_to_vk(dst.descriptor.format)?, dst.descriptor.tiling);
+        require_transfer_features(src, src_features, dst, dst_features)?;
+        for region in regions {
+            validate_copy_region(region, &src.descriptor, &dst.descriptor)?;
+        }
+        if regions.is_empty() {
+            return Ok(());
+        }
+
+        let mut state_guard = self.blit_state.lock().unwrap();
+        if state_guard.is_none() {
+            *state_guard = Some(self.create_blit_state()?);
+        }
+        let state = state_guard.as_ref().unwrap();
+        self.submit_transfer(state, |command_buffer| self.record_copy(command_buffer, src, dst, regions))
+    }
+
+    // Checks what `blit` and `copy` need from the device and both textures
+    fn check_transfer(&self, src: &VulkanSharedTexture, dst: &VulkanSharedTexture) -> Result<()> {
+        // `vkCmdBlitImage2`, `vkCmdCopyImage2` and `vkCmdPipelineBarrier2` are Vulkan 1.3
+        // commands, recorded only with `synchronization2` enabled
+        if self.api_version < vk::API_VERSION_1_3 || !self.sync_capabilities.synchronization2 {
+            return Err(GeyserError::OperationNotSupported);
+        }
+        if src.image == dst.image {
+            return Err(GeyserError::Other("Cannot transfer between a texture and itself".to_string()));
+        }
+        require_usage(src, TextureUsage::CopySrc)?;
+        require_usage(dst, TextureUsage::CopyDst)
+    }
+
+    // Format features of images with `format` and `tiling`
+    fn format_features(&self, format: vk::Format, tiling: TextureTiling) -> vk::FormatFeatureFlags {
+        let modifier = match tiling {
+            TextureTiling::Optimal => {
+                return unsafe { self.instance.get_physical_device_format_properties(self.physical_device, format) }
+                    .optimal_tiling_features;
+            }
+            TextureTiling::Linear => {
+                return unsafe { self.instance.get_physical_device_format_properties(self.physical_device, format) }
+                    .linear_tiling_features;
+            }
+            TextureTiling::DrmModifier(modifier) => modifier,
+        };
+
+        // First call counts the format's modifiers, the second fills them in
+        let mut modifier_list = vk::DrmFormatModifierPropertiesListEXT {
+            s_type: vk::StructureType::DRM_FORMAT_MODIFIER_PROPERTIES_LIST_EXT,
+            p_next: std::ptr::null_mut(),
+            drm_format_modifier_count: 0,
+            p_drm_format_modifier_properties: std::ptr::null_mut(),
+            _marker: std::marker::PhantomData,
+        };
+        let mut modifiers = Vec::new();
+        for _ in 0..2 {
+            modifiers.resize(modifier_list.drm_format_modifier_count as usize, vk::DrmFormatModifierPropertiesEXT::default());
+            modifier_list.p_drm_format_modifier_properties = if modifiers.is_empty() {
+                std::ptr::null_mut()
+            } else {
+                modifiers.as_mut_ptr()
+            };
+            let mut properties = vk::FormatProperties2 {
+                s_type: vk::StructureType::FORMAT_PROPERTIES_2,
+                p_next: &mut modifier_list as *mut _ as *mut std::ffi::c_void,
+                format_properties: vk::FormatProperties::default(),
+                _marker: std::marker::PhantomData,
+            };
+            unsafe { self.instance.get_physical_device_format_properties2(self.physical_device, format, &mut properties) };
+        }
+        modifiers.truncate(modifier_list.drm_format_modifier_count as usize);
+
+        modifiers
+            .iter()
+            .find(|properties| properties.drm_format_modifier == modifier)
+            .map_or(vk::FormatFeatureFlags::empty(), |properties| properties.drm_format_modifier_tiling_features)
+    }
+
+    fn create_blit_state(&self) -> Result<BlitState> {
+        let pool_info = vk::CommandPoolCreateInfo {
+            s_type: vk::StructureType::COMMAND_POOL_CREATE_INFO,
+            p_next: std::ptr::null(),
+            flags: vk::CommandPoolCreateFlags::TRANSIENT,
+            queue_family_index: self.queue_family_index,
+            _marker: std::marker::PhantomData,
+        };
+        let command_pool = unsafe { self.device.create_command_pool(&pool_info, None) }
+            .map_err(|e| GeyserError::VulkanApiError(format!("Failed to create command pool: {:?}", e)))?;
+
+        let fence_info = vk::FenceCreateInfo {
+            s_type: vk::StructureType::FENCE_CREATE_INFO,
+            p_next: std::ptr::null(),
+            flags: vk::FenceCreateFlags::empty(),
+            _marker: std::marker::PhantomData,
+        };
+        let fence = match unsafe { self.device.create_fence(&fence_info, None) } {
+            Ok(fence) => fence,
+            Err(e) => {
+                unsafe { self.device.destroy_command_pool(command_pool, None) };
+                return Err(GeyserError::VulkanApiError(format!("Failed to create blit fence: {:?}", e)));
+            }
+        };
+
+        Ok(BlitState {
+            command_pool,
+            fence,
+            converter: None,
+        })
+    }
+
+    // Destroys the objects of `create_blit_state`. Nothing is pending, since every
+    // transfer is waited for before the state lock is released.
+    pub(super) fn destroy_blit_state(&self, state: BlitState) {
+        if let Some(converter) = &state.converter {
+            self.destroy_converter(converter);
+        }
+        unsafe {
+            self.device.destroy_fence(state.fence, None);
+            self.device.destroy_command_pool(state.command_pool, None);
+        }
+    }
+
+    fn create_converter(&self) -> Result<Converter> {
+        let mut converter = Converter {
+            descriptor_set_layout: vk::DescriptorSetLayout::null(),
+            pipeline_layout: vk::PipelineLayout::null(),
+            pipeline: vk::Pipeline::null(),
+            descriptor_pool: vk::DescriptorPool::null(),
+            descriptor_set: vk::DescriptorSet::null(),
+        };
+        // Destroying the null handles of objects not yet created is a no-op
+        if let Err(e) = self.build_converter(&mut converter) {
+            self.destroy_converter(&converter);
+            return Err(e);
+        }
+        Ok(converter)
+    }
+
+    fn build_converter(&self, converter: &mut Converter) -> Result<()> {
+        let bindings: Vec<_> = (0..2)
+            .map(|binding| vk::DescriptorSetLayoutBinding {
+                binding,
+                descriptor_type: vk::DescriptorType::STORAGE_BUFFER,
+                descriptor_count: 1,
+                stage_flags: vk::ShaderStageFlags::COMPUTE,
+                p_immutable_samplers: std::ptr::null(),
+                _marker: std::marker::PhantomData,
+            })
+            .collect();
+        let set_layout_info = vk::DescriptorSetLayoutCreateInfo {
+            s_type: vk::StructureType::DESCRIPTOR_SET_LAYOUT_CREATE_INFO,
+            p_next: std::ptr::null(),
+            flags: vk::DescriptorSetLayoutCreateFlags::empty(),
+            binding_count: bindings.len() as u32,
+            p_bindings: bindings.as_ptr(),
+            _marker: std::marker::PhantomData,
+        };
+        converter.descriptor_set_layout = unsafe { self.device.create_descriptor_set_layout(&set_layout_info, None) }
+            .map_err(|e| GeyserError::VulkanApiError(format!("Failed to create descriptor set layout: {:?}", e)))?;
+
+        let push_constant_range = vk::PushConstantRange {
+            stage_flags: vk::ShaderStageFlags::COMPUTE,
+            offset: 0,
+            size: CONVERT_PUSH_CONSTANTS_SIZE,
+        };
+        let pipeline_layout_info = vk::PipelineLayoutCreateInfo {
+            s_type: vk::StructureType::PIPELINE_LAYOUT_CREATE_INFO,
+            p_next: std::ptr::null(),
+            flags: vk::PipelineLayoutCreateFlags::empty(),
+            set_layout_count: 1,
+            p_set_layouts: &converter.descriptor_set_layout,
+            push_constant_range_count: 1,
+            p_push_constant_ranges: &push_constant_range,
+            _marker: std::marker::PhantomData,
+        };
+        converter.pipeline_layout = unsafe { self.device.create_pipeline_layout(&pipeline_layout_info, None) }
+            .map_err(|e| GeyserError::VulkanApiError(format!("Failed to create pipeline layout: {:?}", e)))?;
+
+        let code = ash::util::read_spv(&mut std::io::Cursor::new(CONVERT_SPV))
+            .map_err(|e| GeyserError::Other(format!("Invalid convert shader: {}", e)))?;
+        let module_info = vk::ShaderModuleCreateInfo {
+            s_type: vk::StructureType::SHADER_MODULE_CREATE_INFO,
+            p_next: std::ptr::null(),
+            flags: vk::ShaderModuleCreateFlags::empty(),
+            code_size: code.len() * 4,
+            p_code: code.as_ptr(),
+            _marker: std::marker::PhantomData,
+        };
+        let module = unsafe { self.device.create_shader_module(&module_info, None) }
+            .map_err(|e| GeyserError::VulkanApiError(format!("Failed to create shader module: {:?}", e)))?;
+
+        let pipeline_info = vk::ComputePipelineCreateInfo {
+            s_type: vk::StructureType::COMPUTE_PIPELINE_CREATE_INFO,
+            p_next: std::ptr::null(),
+            flags: vk::PipelineCreateFlags::empty(),
+            stage: vk::PipelineShaderStageCreateInfo {
+                s_type: vk::StructureType::PIPELINE_SHADER_STAGE_CREATE_INFO,
+                p_next: std::ptr::null(),
+                flags: vk::PipelineShaderStageCreateFlags::empty(),
+                stage: vk::ShaderStageFlags::COMPUTE,
+                module,
+                p_name: c"main".as_ptr(),
+                p_specialization_info: std::ptr::null(),
+                _marker: std::marker::PhantomData,
+            },
+            layout: converter.pipeline_layout,
+            base_pipeline_handle: vk::Pipeline::null(),
+            base_pipeline_index: -1,
+            _marker: std::marker::PhantomData,
+        };
+        let pipelines = unsafe { self.device.create_compute_pipelines(vk::PipelineCache::null(), &[pipeline_info], None) };
+        unsafe { self.device.destroy_shader_module(module, None) };
+        converter.pipeline = pipelines
+            .map_err(|(_, e)| GeyserError::VulkanApiError(format!("Failed to create convert pipeline: {:?}", e)))?[0];
+
+        let pool_size = vk::DescriptorPoolSize {
+            ty: vk::DescriptorType::STORAGE_BUFFER,
+            descriptor_count: 2,
+        };
+        let pool_info = vk::DescriptorPoolCreateInfo {
+            s_type: vk::StructureType::DESCRIPTOR_POOL_CREATE_INFO,
+            p_next: std::ptr::null(),
+            flags: vk::DescriptorPoolCreateFlags::empty(),
+            max_sets: 1,
+            pool_size_count: 1,
+            p_pool_sizes: &pool_size,
+            _marker: std::marker::PhantomData,
+        };
+        converter.descriptor_pool = unsafe { self.device.create_descriptor_pool(&pool_info, None) }
+            .map_err(|e| GeyserError::VulkanApiError(format!("Failed to create descriptor pool: {:?}", e)))?;
+
+        let allocate_info = vk::DescriptorSetAllocateInfo {
+            s_type: vk::StructureType::DESCRIPTOR_SET_ALLOCATE_INFO,
+            p_next: std::ptr::null(),
+            descriptor_pool: converter.descriptor_pool,
+            descriptor_set_count: 1,
+            p_set_layouts: &converter.descriptor_set_layout,
+            _marker: std::marker::PhantomData,
+        };
+        converter.descriptor_set = unsafe { self.device.allocate_descriptor_sets(&allocate_info) }
+            .map_err(|e| GeyserError::VulkanApiError(format!("Failed to allocate descriptor set: {:?}", e)))?[0];
+        Ok(())
+    }
+
+    fn destroy_converter(&self, converter: &Converter) {
+        unsafe {
+            self.device.destroy_descriptor_pool(converter.descriptor_pool, None);
+            self.device.destroy_pipeline(converter.pipeline, None);
+            self.device.destroy_pipeline_layout(converter.pipeline_layout, None);
+            self.device.destroy_descriptor_set_layout(converter.descriptor_set_layout, None);
+        }
+    }
+
+    // Records a one-time command buffer with `record`, submits it on the manager's queue and
+    // waits for it to complete
+    fn submit_transfer(&self, state: &BlitState, record: impl FnOnce(vk::CommandBuffer)) -> Result<()> {
+        let allocate_info = vk::CommandBufferAllocateInfo {
+            s_type: vk::StructureType::COMMAND_BUFFER_ALLOCATE_INFO,
+            p_next: std::ptr::null(),
+            command_pool: state.command_pool,
+            level: vk::CommandBufferLevel::PRIMARY,
+            command_buffer_count: 1,
+            _marker: std::marker::PhantomData,
+        };
+        let command_buffer = unsafe { self.device.allocate_command_buffers(&allocate_info) }
+            .map_err(|e| GeyserError::VulkanApiError(format!("Failed to allocate command buffers: {:?}", e)))?[0];
+
+        let result = self.record_and_wait(command_buffer, state.fence, record);
+        unsafe { self.device.free_command_buffers(state.command_pool, &[command_buffer]) };
+        result
+    }
+
+    fn record_and_wait(&self, command_buffer: vk::CommandBuffer, fence: vk::Fence, record: impl FnOnce(vk::CommandBuffer)) -> Result<()> {
+        let begin_info = vk::CommandBufferBeginInfo {
+            s_type: vk::StructureType::COMMAND_BUFFER_BEGIN_INFO,
+            p_next: std::ptr::null(),
+            flags: vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT,
+            p_inheritance_info: std::ptr::null(),
+            _marker: std::marker::PhantomData,
+        };
+        unsafe { self.device.begin_command_buffer(command_buffer, &begin_info)? };
+        record(command_buffer);
+        unsafe { self.device.end_command_buffer(command_buffer)? };
+
+        let submit_info = vk::SubmitInfo {
+            s_type: vk::StructureType::SUBMIT_INFO,
+            p_next: std::ptr::null(),
+            wait_semaphore_count: 0,
+            p_wait_semaphores: std::ptr::null(),
+            p_wait_dst_stage_mask: std::ptr::null(),
+            command_buffer_count: 1,
+            p_command_buffers: &command_buffer,
+            signal_semaphore_count: 0,
+            p_signal_semaphores: std::ptr::null(),
+            _marker: std::marker::PhantomData,
+        };
+        unsafe {
+            self.device.reset_fences(&[fence])
+                .map_err(|e| GeyserError::VulkanApiError(format!("Failed to reset blit fence: {:?}", e)))?;
+        }
+        self.submit_to_queue(&[submit_info], fence)
+            .map_err(|e| GeyserError::VulkanApiError(format!("Failed to submit texture transfer: {:?}", e)))?;
+        self.wait_for_transfer(fence)
+            .map_err(|e| GeyserError::VulkanApiError(format!("Failed to wait for texture transfer: {:?}", e)))
+    }
+
+    fn cmd_barriers(&self, command_buffer: vk::CommandBuffer, memory_barriers: &[vk::MemoryBarrier2], image_barriers: &[vk::ImageMemoryBarrier2]) {
+        let dependency_info = vk::DependencyInfo {
+            s_type: vk::StructureType::DEPENDENCY_INFO,
+            p_next: std::ptr::null(),
+            dependency_flags: vk::DependencyFlags::empty(),
+            memory_barrier_count: memory_barriers.len() as u32,
+            p_memory_barriers: memory_barriers.as_ptr(),
+            buffer_memory_barrier_count: 0,
+            p_buffer_memory_barriers: std::ptr::null(),
+            image_memory_barrier_count: image_barriers.len() as u32,
+            p_image_memory_barriers: image_barriers.as_ptr(),
+            _marker: std::marker::PhantomData,
+        };
+        unsafe { self.device.cmd_pipeline_barrier2(command_buffer, &dependency_info) };
+    }
+
+    // Moves both textures into transfer layouts; the whole of `dst` is overwritten by blits,
+    // so its contents are only kept for copies
+    fn cmd_acquire_for_transfer(&self, command_buffer: vk::CommandBuffer, src: &VulkanSharedTexture, dst: &VulkanSharedTexture, keep_dst: bool) {
+        let dst_layout = if keep_dst { SHARED_IMAGE_LAYOUT } else { vk::ImageLayout::UNDEFINED };
+        self.cmd_barriers(command_buffer, &[], &[
+            transfer_barrier(src, SHARED_IMAGE_LAYOUT, vk::ImageLayout::TRANSFER_SRC_OPTIMAL),
+            transfer_barrier(dst, dst_layout, vk::ImageLayout::TRANSFER_DST_OPTIMAL),
+        ]);
+    }
+
+    fn cmd_release_after_transfer(&self, command_buffer: vk::CommandBuffer, src: &VulkanSharedTexture, dst: &VulkanSharedTexture) {
+        self.cmd_barriers(command_buffer, &[], &[
+            transfer_barrier(src, vk::ImageLayout::TRANSFER_SRC_OPTIMAL, SHARED_IMAGE_LAYOUT),
+            transfer_barrier(dst, vk::ImageLayout::TRANSFER_DST_OPTIMAL, SHARED_IMAGE_LAYOUT),
+        ]);
+    }
+
+    fn record_blit(&self, command_buffer: vk::CommandBuffer, src: &VulkanSharedTexture, dst: &VulkanSharedTexture, filter: vk::Filter) {
+        let far_corner = |texture: &VulkanSharedTexture| vk::Offset3D {
+            x: texture.descriptor.width as i32,
+            y: texture.descriptor.height as i32,
+            z: 1,
+        };
+        let region = vk::ImageBlit2 {
+            s_type: vk::StructureType::IMAGE_BLIT_2,
+            p_next: std::ptr::null(),
+            src_subresource: subresource_layers(src),
+            src_offsets: [vk::Offset3D { x: 0, y: 0, z: 0 }, far_corner(src)],
+            dst_subresource: subresource_layers(dst),
+            dst_offsets: [vk::Offset3D { x: 0, y: 0, z: 0 }, far_corner(dst)],
+            _marker: std::marker::PhantomData,
+        };
+        let blit_info = vk::BlitImageInfo2 {
+            s_type: vk::StructureType::BLIT_IMAGE_INFO_2,
+            p_next: std::ptr::null(),
+            src_image: src.image,
+            src_image_layout: vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+            dst_image: dst.image,
+            dst_image_layout: vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+            region_count: 1,
+            p_regions: &region,
+            filter,
+            _marker: std::marker::PhantomData,
+        };
+
+        self.cmd_acquire_for_transfer(command_buffer, src, dst, false);
+        unsafe { self.device.cmd_blit_image2(command_buffer, &blit_info) };
+        self.cmd_release_after_transfer(command_buffer, src, dst);
+    }
+
+    fn record_copy(&self, command_buffer: vk::CommandBuffer, src: &VulkanSharedTexture, dst: &VulkanSharedTexture, regions: &[CopyRegion]) {
+        let copies: Vec<_> = regions
+            .iter()
+            .map(|region| vk::ImageCopy2 {
+                s_type: vk::StructureType::IMAGE_COPY_2,
+                p_next: std::ptr::null(),
+                src_subresource: subresource_layers(src),
+                src_offset: vk::Offset3D {
+                    x: region.src_origin[0] as i32,
+                    y: region.src_origin[1] as i32,
+                    z: 0,
+                },
+                dst_subresource: subresource_layers(dst),
+                dst_offset: vk::Offset3D {
+                    x: region.dst_origin[0] as i32,
+                    y: region.dst_origin[1] as i32,
+                    z: 0,
+                },
+                extent: vk::Extent3D {
+                    width: region.extent[0],
+                    height: region.extent[1],
+                    depth: 1,
+                },
+                _marker: std::marker::PhantomData,
+            })
+            .collect();
+        let copy_info = vk::CopyImageInfo2 {
+            s_type: vk::StructureType::COPY_IMAGE_INFO_2,
+            p_next: std::ptr::null(),
+            src_image: src.image,
+            src_image_layout: vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+            dst_image: dst.image,
+            dst_image_layout: vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+            region_count: copies.len() as u32,
+            p_regions: copies.as_ptr(),
+            _marker: std::marker::PhantomData,
+        };
+
+        self.cmd_acquire_for_transfer(command_buffer, src, dst, true);
+        unsafe { self.device.cmd_copy_image2(command_buffer, &copy_info) };
+        self.cmd_release_after_transfer(command_buffer, src, dst);
+    }
+
+    // Blits through the convert shader: `src` is copied into a staging buffer, converted
+    // into a second one and copied from there into `dst`
+    fn blit_with_compute(&self, state: &mut BlitState, src: &VulkanSharedTexture, dst: &VulkanSharedTexture, flags: u32) -> Result<()> {
+        if state.converter.is_none() {
+            state.converter = Some(self.create_converter()?);
+        }
+        let converter = state.converter.as_ref().unwrap();
+
+        let texels_size = |texture: &VulkanSharedTexture| texture.descriptor.width as u64 * texture.descriptor.height as u64 * 4;
+        let (src_buffer, src_allocation) = self.create_staging_buffer(texels_size(src))?;
+        let (dst_buffer, dst_allocation) = match self.create_staging_buffer(texels_size(dst)) {
+            Ok(staging) => staging,
+            Err(e) => {
+                self.free_staging_buffer(src_buffer, src_allocation);
+                return Err(e);
+            }
+        };
+
+        let buffer_infos = [src_buffer, dst_buffer].map(|buffer| vk::DescriptorBufferInfo {
+            buffer,
+            offset: 0,
+            range: vk::WHOLE_SIZE,
+        });
+        let writes: Vec<_> = buffer_infos
+            .iter()
+            .enumerate()
+            .map(|(binding, buffer_info)| vk::WriteDescriptorSet {
+                s_type: vk::StructureType::WRITE_DESCRIPTOR_SET,
+                p_next: std::ptr::null(),
+                dst_set: converter.descriptor_set,
+                dst_binding: binding as u32,
+                dst_array_element: 0,
+                descriptor_count: 1,
+                descriptor_type: vk::DescriptorType::STORAGE_BUFFER,
+                p_image_info: std::ptr::null(),
+                p_buffer_info: buffer_info,
+                p_texel_buffer_view: std::ptr::null(),
+                _marker: std::marker::PhantomData,
+            })
+            .collect();
+        unsafe { self.device.update_descriptor_sets(&writes, &[]) };
+
+        let result = self.submit_transfer(state, |command_buffer| {
+            self.record_convert(command_buffer, converter, src, dst, [src_buffer, dst_buffer], flags)
+        });
+        self.free_staging_buffer(src_buffer, src_allocation);
+        self.free_staging_buffer(dst_buffer, dst_allocation);
+        result
+    }
+
+    fn record_convert(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        converter: &Converter,
+        src: &VulkanSharedTexture,
+        dst: &VulkanSharedTexture,
+        [src_buffer, dst_buffer]: [vk::Buffer; 2],
+        flags: u32,
+    ) {
+        let push_constants: Vec<u8> = [src.descriptor.width, src.descriptor.height, dst.descriptor.width, dst.descriptor.height, flags]
+            .iter()
+            .flat_map(|value| value.to_ne_bytes())
+            .collect();
+        let transfer_to_compute = memory_barrier(
+            vk::PipelineStageFlags2::ALL_TRANSFER,
+            vk::AccessFlags2::TRANSFER_WRITE,
+            vk::PipelineStageFlags2::COMPUTE_SHADER,
+            vk::AccessFlags2::SHADER_STORAGE_READ,
+        );
+        let compute_to_transfer = memory_barrier(
+            vk::PipelineStageFlags2::COMPUTE_SHADER,
+            vk::AccessFlags2::SHADER_STORAGE_WRITE,
+            vk::PipelineStageFlags2::ALL_TRANSFER,
+            vk::AccessFlags2::TRANSFER_READ,
+        );
+
+        self.cmd_acquire_for_transfer(command_buffer, src, dst, false);
+        unsafe {
+            self.device.cmd_copy_image_to_buffer(
+                command_buffer,
+                src.image,
+                vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                src_buffer,
+                &[whole_image_copy(src)],
+            );
+            self.cmd_barriers(command_buffer, &[transfer_to_compute], &[]);
+            self.device.cmd_bind_pipeline(command_buffer, vk::PipelineBindPoint::COMPUTE, converter.pipeline);
+            self.device.cmd_bind_descriptor_sets(
+                command_buffer,
+                vk::PipelineBindPoint::COMPUTE,
+                converter.pipeline_layout,
+                0,
+                &[converter.descriptor_set],
+                &[],
+            );
+            self.device.cmd_push_constants(
+                command_buffer,
+                converter.pipeline_layout,
+                vk::ShaderStageFlags::COMPUTE,
+                0,
+                &push_constants,
+            );
+            self.device.cmd_dispatch(
+                command_buffer,
+                dst.descriptor.width.div_ceil(CONVERT_WORKGROUP_SIZE),
+                dst.descriptor.height.div_ceil(CONVERT_WORKGROUP_SIZE),
+                1,
+            );
+            self.cmd_barriers(command_buffer, &[compute_to_transfer], &[]);
+            self.device.cmd_copy_buffer_to_image(
+                command_buffer,
+                dst_buffer,
+                dst.image,
+                vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                &[whole_image_copy(dst)],
+            );
+        }
+        self.cmd_release_after_transfer(command_buffer, src, dst);
+    }
+
+    // Creates a device-local buffer holding `size` bytes of texels for the convert shader
+    fn create_staging_buffer(&self, size: u64) -> Result<(vk::Buffer, Allocation)> {
+        let buffer_info = vk::BufferCreateInfo {
+            s_type: vk::StructureType::BUFFER_CREATE_INFO,
+            p_next: std::ptr::null(),
+            flags: vk::BufferCreateFlags::empty(),
+            size,
+            usage: vk::BufferUsageFlags::STORAGE_BUFFER | vk::BufferUsageFlags::TRANSFER_SRC | vk::BufferUsageFlags::TRANSFER_DST,
+            sharing_mode: vk::SharingMode::EXCLUSIVE,
+            queue_family_index_count: 0,
+            p_queue_family_indices: std::ptr::null(),
+            _marker: std::marker::PhantomData,
+        };
+        let buffer = unsafe { self.device.create_buffer(&buffer_info, None) }
+            .map_err(|e| GeyserError::VulkanApiError(format!("Failed to create staging buffer: {:?}", e)))?;
+
+        let requirements = unsafe { self.device.get_buffer_memory_requirements(buffer) };
+        let allocation = self.allocator.lock().unwrap().allocate(&AllocationCreateDesc {
+            name: "geyser-blit-staging",
+            requirements,
+            location: MemoryLocation::GpuOnly,
+            linear: true,
+            allocation_scheme: AllocationScheme::GpuAllocatorManaged,
+        });
+        let allocation = match allocation {
+            Ok(allocation) => allocation,
+            Err(e) => {
+                unsafe { self.device.destroy_buffer(buffer, None) };
+                return Err(GeyserError::VulkanApiError(format!("Failed to allocate staging buffer memory: {}", e)));
+            }
+        };
+
+        if let Err(e) = unsafe { self.device.bind_buffer_memory(buffer, allocation.memory(), allocation.offset()) } {
+            self.free_staging_buffer(buffer, allocation);
+            return Err(GeyserError::VulkanApiError(format!("Failed to bind staging buffer memory: {:?}", e)));
+        }
+        Ok((buffer, allocation))
+    }
+
+    fn free_staging_buffer(&self, buffer: vk::Buffer, allocation: Allocation) {
+        unsafe { self.device.destroy_buffer(buffer, None) };
+        let _ = self.allocator.lock().unwrap().free(allocation);
+    }
+}
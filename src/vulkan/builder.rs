@@ -0,0 +1,374 @@
+//! Builder that creates a Vulkan instance and device configured for texture sharing.
+
+use ash::{vk, Device, Entry, Instance};
+use std::{
+    ffi::{CStr, CString},
+    sync::Arc,
+};
+use crate::error::{GeyserError, Result};
+use super::{SyncCapabilities, VulkanTextureShareManager};
+
+/// Selects which physical device `VulkanTextureShareManagerBuilder` creates its device on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DeviceSelector {
+    /// The first device that supports all required extensions
+    #[default]
+    Any,
+    /// Index into `vkEnumeratePhysicalDevices`
+    Index(usize),
+    /// `deviceUUID` from `PhysicalDeviceIDProperties`
+    Uuid([u8; vk::UUID_SIZE]),
+    /// `deviceLUID` from `PhysicalDeviceIDProperties` (Windows)
+    Luid([u8; vk::LUID_SIZE]),
+    /// PCI vendor ID, e.g. `0x10DE` for NVIDIA
+    Vendor(u32),
+}
+
+/// Device extensions the manager relies on for external memory, semaphores and fences.
+pub fn required_device_extensions() -> Vec<&'static CStr> {
+    #[cfg(target_os = "linux")]
+    {
+        vec![
+            ash::khr::external_memory_fd::NAME,
+            ash::khr::external_semaphore_fd::NAME,
+            ash::khr::external_fence_fd::NAME,
+        ]
+    }
+    #[cfg(target_os = "windows")]
+    {
+        vec![
+            ash::khr::external_memory_win32::NAME,
+            ash::khr::external_semaphore_win32::NAME,
+            ash::khr::external_fence_win32::NAME,
+        ]
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+    {
+        Vec::new()
+    }
+}
+
+// Returns the names from `required` that are not present in `available`
+pub(crate) fn missing_extensions(required: &[&CStr], available: &[vk::ExtensionProperties]) -> Vec<String> {
+    required
+        .iter()
+        .filter(|name| {
+            !available
+                .iter()
+                .any(|ext| ext.extension_name_as_c_str().is_ok_and(|ext_name| ext_name == **name))
+        })
+        .map(|name| name.to_string_lossy().into_owned())
+        .collect()
+}
+
+// Queries `PhysicalDeviceIDProperties` (device/driver UUID and LUID)
+pub(crate) fn query_id_properties(instance: &Instance, physical_device: vk::PhysicalDevice) -> vk::PhysicalDeviceIDProperties<'static> {
+    let mut id_properties = vk::PhysicalDeviceIDProperties::default();
+    let mut properties2 = vk::PhysicalDeviceProperties2 {
+        s_type: vk::StructureType::PHYSICAL_DEVICE_PROPERTIES_2,
+        p_next: &mut id_properties as *mut _ as *mut std::ffi::c_void,
+        properties: vk::PhysicalDeviceProperties::default(),
+        _marker: std::marker::PhantomData,
+    };
+    unsafe { instance.get_physical_device_properties2(physical_device, &mut properties2) };
+    id_properties.p_next = std::ptr::null_mut();
+    id_properties
+}
+
+/// Instance and device created by the builder. Destroyed when the manager is dropped,
+/// provided no textures still hold a reference to the device.
+pub(crate) struct OwnedVulkanContext {
+    // Keeps the Vulkan loader alive for the lifetime of the instance
+    _entry: Entry,
+    instance: Arc<Instance>,
+    device: Arc<Device>,
+}
+
+impl Drop for OwnedVulkanContext {
+    fn drop(&mut self) {
+        // Textures created by the manager keep their own `Arc<Device>`; destroying the
+        // device underneath them would be undefined behaviour, so leak instead.
+        if Arc::strong_count(&self.device) == 1 {
+            unsafe {
+                let _ = self.device.device_wait_idle();
+                self.device.destroy_device(None);
+            }
+            if Arc::strong_count(&self.instance) == 1 {
+                unsafe { self.instance.destroy_instance(None) };
+            }
+        }
+    }
+}
+
+/// Builds a `VulkanTextureShareManager` together with its own Vulkan instance and device.
+///
+/// The device is created with the external memory, semaphore and fence extensions
+/// required for sharing, and with timeline semaphores, `synchronization2`,
+/// `VK_EXT_external_memory_dma_buf` and `VK_EXT_image_drm_format_modifier` enabled when
+/// supported.
+///
+/// ```ignore
+/// let manager = VulkanTextureShareManager::builder()
+///     .application_name("MyProducer")
+///     .device(DeviceSelector::Vendor(0x10DE))
+///     .build()?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct VulkanTextureShareManagerBuilder {
+    application_name: String,
+    api_version: u32,
+    device: DeviceSelector,
+    extra_device_extensions: Vec<&'static CStr>,
+}
+
+impl Default for VulkanTextureShareManagerBuilder {
+    fn default() -> Self {
+        Self {
+            application_name: "Geyser".to_string(),
+            api_version: vk::API_VERSION_1_3,
+            device: DeviceSelector::Any,
+            extra_device_extensions: Vec::new(),
+        }
+    }
+}
+
+impl VulkanTextureShareManagerBuilder {
+    /// Sets the application name reported to the driver.
+    pub fn application_name(mut self, name: impl Into<String>) -> Self {
+        self.application_name = name.into();
+        self
+    }
+
+    /// Sets the Vulkan API version requested for the instance (at least 1.2).
+    pub fn api_version(mut self, api_version: u32) -> Self {
+        self.api_version = api_version;
+        self
+    }
+
+    /// Selects the physical device to create the logical device on.
+    pub fn device(mut self, device: DeviceSelector) -> Self {
+        self.device = device;
+        self
+    }
+
+    /// Enables an additional device extension on top of the required ones.
+    pub fn device_extension(mut self, name: &'static CStr) -> Self {
+        if !self.extra_device_extensions.contains(&name) {
+            self.extra_device_extensions.push(name);
+        }
+        self
+    }
+
+    /// Creates the instance, picks a physical device, creates the device and the manager.
+    pub fn build(self) -> Result<VulkanTextureShareManager> {
+        if self.api_version < vk::API_VERSION_1_2 {
+            return Err(GeyserError::VulkanInitializationError(
+                "Texture sharing requires Vulkan 1.2 or newer".to_string(),
+            ));
+        }
+
+        let entry = Entry::linked();
+        let app_name = CString::new(self.application_name.as_str())
+            .map_err(|e| GeyserError::VulkanInitializationError(format!("Invalid application name: {}", e)))?;
+        let engine_name = c"Geyser";
+
+        let app_info = vk::ApplicationInfo {
+            s_type: vk::StructureType::APPLICATION_INFO,
+            p_next: std::ptr::null(),
+            p_application_name: app_name.as_ptr(),
+            application_version: 0,
+            p_engine_name: engine_name.as_ptr(),
+            engine_version: 0,
+            api_version: self.api_version,
+            _marker: std::marker::PhantomData,
+        };
+
+        let instance_create_info = vk::InstanceCreateInfo {
+            s_type: vk::StructureType::INSTANCE_CREATE_INFO,
+            p_next: std::ptr::null(),
+            flags: vk::InstanceCreateFlags::empty(),
+            p_application_info: &app_info,
+            enabled_layer_count: 0,
+            pp_enabled_layer_names: std::ptr::null(),
+            enabled_extension_count: 0,
+            pp_enabled_extension_names: std::ptr::null(),
+            _marker: std::marker::PhantomData,
+        };
+
+        let instance = unsafe { entry.create_instance(&instance_create_info, None) }
+            .map_err(|e| GeyserError::VulkanInitializationError(format!("Failed to create instance: {:?}", e)))?;
+
+        match self.create_device(&instance) {
+            Ok((device, physical_device, queue_family_index, dma_buf_import, drm_format_modifiers, sync_capabilities)) => {
+                let instance = Arc::new(instance);
+                let device = Arc::new(device);
+                let manager = VulkanTextureShareManager::new(
+                    instance.clone(),
+                    device.clone(),
+                    physical_device,
+                    queue_family_index,
+                );
+                // On failure the context is dropped here, destroying the device and instance
+                let owned_context = OwnedVulkanContext {
+                    _entry: entry,
+                    instance,
+                    device,
+                };
+                let mut manager = manager?
+                    .with_dma_buf_import(dma_buf_import)
+                    .with_drm_format_modifiers(drm_format_modifiers)
+                    .with_api_version(self.api_version)
+                    .with_sync_features(sync_capabilities.timeline_semaphore, sync_capabilities.synchronization2);
+                manager.owned_context = Some(owned_context);
+                Ok(manager)
+            }
+            Err(e) => {
+                unsafe { instance.destroy_instance(None) };
+                Err(e)
+            }
+        }
+    }
+
+    // Returns the device, its physical device and queue family, and whether dma-buf
+    // import and DRM format modifiers were enabled, and the synchronization features enabled
+    fn create_device(
+        &self,
+        instance: &Instance,
+    ) -> Result<(Device, vk::PhysicalDevice, u32, bool, bool, SyncCapabilities)> {
+        let physical_devices = unsafe { instance.enumerate_physical_devices() }?;
+        if physical_devices.is_empty() {
+            return Err(GeyserError::VulkanInitializationError("No Vulkan physical devices found".to_string()));
+        }
+
+        let mut extensions = required_device_extensions();
+        for name in &self.extra_device_extensions {
+            if !extensions.contains(name) {
+                extensions.push(name);
+            }
+        }
+
+        // Candidates matching the selector, in enumeration order
+        let candidates: Vec<(usize, vk::PhysicalDevice)> = physical_devices
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|&(index, physical_device)| match self.device {
+                DeviceSelector::Any => true,
+                DeviceSelector::Index(wanted) => index == wanted,
+                DeviceSelector::Uuid(uuid) => query_id_properties(instance, physical_device).device_uuid == uuid,
+                DeviceSelector::Luid(luid) => {
+                    let id_properties = query_id_properties(instance, physical_device);
+                    id_properties.device_luid_valid == vk::TRUE && id_properties.device_luid == luid
+                }
+                DeviceSelector::Vendor(vendor_id) => {
+                    unsafe { instance.get_physical_device_properties(physical_device) }.vendor_id == vendor_id
+                }
+            })
+            .collect();
+
+        if candidates.is_empty() {
+            return Err(GeyserError::VulkanInitializationError(format!(
+                "No physical device matches {:?}",
+                self.device
+            )));
+        }
+
+        // Pick the first candidate with every required extension, remembering what the
+        // first candidate lacked for the error message
+        let mut first_missing = None;
+        let mut selected = None;
+        for &(_, physical_device) in &candidates {
+            let available = unsafe { instance.enumerate_device_extension_properties(physical_device) }?;
+            let missing = missing_extensions(&extensions, &available);
+            if missing.is_empty() {
+                selected = Some((physical_device, available));
+                break;
+            }
+            first_missing.get_or_insert(missing);
+        }
+        let (physical_device, available) = match selected {
+            Some(selected) => selected,
+            None => return Err(GeyserError::MissingExtensions(first_missing.unwrap_or_default())),
+        };
+
+        // Lets `import_texture` fall back to copying dma-bufs from other GPUs
+        let dma_buf_import = cfg!(target_os = "linux")
+            && missing_extensions(&[ash::ext::external_memory_dma_buf::NAME], &available).is_empty();
+        if dma_buf_import && !extensions.contains(&ash::ext::external_memory_dma_buf::NAME) {
+            extensions.push(ash::ext::external_memory_dma_buf::NAME);
+        }
+        // Lets textures use `TextureTiling::DrmModifier`
+        let drm_format_modifiers =
+            missing_extensions(&[ash::ext::image_drm_format_modifier::NAME], &available).is_empty();
+        if drm_format_modifiers && !extensions.contains(&ash::ext::image_drm_format_modifier::NAME) {
+            extensions.push(ash::ext::image_drm_format_modifier::NAME);
+        }
+
+        let queue_family_index = unsafe { instance.get_physical_device_queue_family_properties(physical_device) }
+            .iter()
+            .position(|props| props.queue_flags.contains(vk::QueueFlags::GRAPHICS))
+            .or_else(|| {
+                unsafe { instance.get_physical_device_queue_family_properties(physical_device) }
+                    .iter()
+                    .position(|props| props.queue_flags.contains(vk::QueueFlags::COMPUTE))
+            })
+            .ok_or_else(|| GeyserError::VulkanInitializationError("No graphics or compute queue family".to_string()))?
+            as u32;
+
+        let capabilities = SyncCapabilities::query_with_api_version(instance, physical_device, self.api_version);
+        // `synchronization2` is only chained when it will be enabled, since drivers older
+        // than Vulkan 1.3 may not recognise the structure
+        let enable_synchronization2 = capabilities.synchronization2 && self.api_version >= vk::API_VERSION_1_3;
+
+        let mut synchronization2_features = vk::PhysicalDeviceSynchronization2Features {
+            s_type: vk::StructureType::PHYSICAL_DEVICE_SYNCHRONIZATION_2_FEATURES,
+            p_next: std::ptr::null_mut(),
+            synchronization2: vk::TRUE,
+            _marker: std::marker::PhantomData,
+        };
+        let mut timeline_features = vk::PhysicalDeviceTimelineSemaphoreFeatures {
+            s_type: vk::StructureType::PHYSICAL_DEVICE_TIMELINE_SEMAPHORE_FEATURES,
+            p_next: if enable_synchronization2 {
+                &mut synchronization2_features as *mut _ as *mut std::ffi::c_void
+            } else {
+                std::ptr::null_mut()
+            },
+            timeline_semaphore: capabilities.timeline_semaphore.into(),
+            _marker: std::marker::PhantomData,
+        };
+
+        let queue_priority = 1.0f32;
+        let queue_create_info = vk::DeviceQueueCreateInfo {
+            s_type: vk::StructureType::DEVICE_QUEUE_CREATE_INFO,
+            p_next: std::ptr::null(),
+            flags: vk::DeviceQueueCreateFlags::empty(),
+            queue_family_index,
+            queue_count: 1,
+            p_queue_priorities: &queue_priority,
+            _marker: std::marker::PhantomData,
+        };
+
+        let extension_names: Vec<_> = extensions.iter().map(|name| name.as_ptr()).collect();
+
+        #[allow(deprecated)]
+        let device_create_info = vk::DeviceCreateInfo {
+            s_type: vk::StructureType::DEVICE_CREATE_INFO,
+            p_next: &mut timeline_features as *mut _ as *const std::ffi::c_void,
+            flags: vk::DeviceCreateFlags::empty(),
+            queue_create_info_count: 1,
+            p_queue_create_infos: &queue_create_info,
+            enabled_layer_count: 0,
+            pp_enabled_layer_names: std::ptr::null(),
+            enabled_extension_count: extension_names.len() as u32,
+            pp_enabled_extension_names: extension_names.as_ptr(),
+            p_enabled_features: std::ptr::null(),
+            _marker: std::marker::PhantomData,
+        };
+
+        let device = unsafe { instance.create_device(physical_device, &device_create_info, None) }
+            .map_err(|e| GeyserError::VulkanInitializationError(format!("Failed to create device: {:?}", e)))?;
+
+        let enabled = capabilities.with_enabled_features(true, enable_synchronization2);
+        Ok((device, physical_device, queue_family_index, dma_buf_import, drm_format_modifiers, enabled))
+    }
+}
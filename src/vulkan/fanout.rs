@@ -0,0 +1,314 @@
+//! Fan-out of one producer's textures to several consumers.
+
+use ash::vk;
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::{Duration, Instant},
+};
+use crate::{
+    common::{TextureDescriptor, TextureSharePackage},
+    error::{GeyserError, Result},
+    TextureShareManager,
+};
+use super::{VulkanSemaphoreHandle, VulkanSharedTexture, VulkanTextureShareManager};
+
+/// Identifies a consumer attached to a `FanOutPublisher` or `ReleaseTracker`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ConsumerId(u64);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SlotState {
+    Free,
+    InFlight { frame: u64, published_at: Instant },
+}
+
+/// Decides when pool slots can be reused, independent of the GPU.
+///
+/// Frames are numbered from 1 in publish order. Each consumer reports the newest frame
+/// it has finished reading (its release value); a slot holding frame `n` is free again
+/// once every attached consumer has released `n`. A consumer that has not released a
+/// frame within the stall timeout after it was published is evicted, so one stuck
+/// reader cannot starve the pool.
+#[derive(Debug)]
+pub struct ReleaseTracker {
+    slots: Vec<SlotState>,
+    // Release value of each attached consumer
+    consumers: BTreeMap<ConsumerId, u64>,
+    next_consumer: u64,
+    last_frame: u64,
+    stall_timeout: Duration,
+}
+
+impl ReleaseTracker {
+    pub fn new(slot_count: usize, stall_timeout: Duration) -> Self {
+        Self {
+            slots: vec![SlotState::Free; slot_count],
+            consumers: BTreeMap::new(),
+            next_consumer: 0,
+            last_frame: 0,
+            stall_timeout,
+        }
+    }
+
+    /// Returns the number of the most recently published frame, or 0.
+    pub fn last_frame(&self) -> u64 {
+        self.last_frame
+    }
+
+    /// Attaches a consumer. It is only waited on for frames published from now on.
+    pub fn attach(&mut self) -> ConsumerId {
+        let id = ConsumerId(self.next_consumer);
+        self.next_consumer += 1;
+        self.consumers.insert(id, self.last_frame);
+        id
+    }
+
+    /// Detaches a consumer; returns false if it was not attached.
+    pub fn detach(&mut self, id: ConsumerId) -> bool {
+        self.consumers.remove(&id).is_some()
+    }
+
+    /// Returns the attached consumers.
+    pub fn consumers(&self) -> Vec<ConsumerId> {
+        self.consumers.keys().copied().collect()
+    }
+
+    /// Returns the release value of a consumer, or `None` if it is not attached.
+    pub fn released(&self, id: ConsumerId) -> Option<u64> {
+        self.consumers.get(&id).copied()
+    }
+
+    /// Records that `id` finished reading every frame up to `value`. Release values
+    /// only move forward; returns false if the consumer is not attached.
+    pub fn release(&mut self, id: ConsumerId, value: u64) -> bool {
+        match self.consumers.get_mut(&id) {
+            Some(released) => {
+                *released = (*released).max(value);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns true if `slot` can be written.
+    pub fn is_free(&self, slot: usize) -> bool {
+        self.slots.get(slot) == Some(&SlotState::Free)
+    }
+
+    /// Returns a slot that can be written, if any.
+    pub fn free_slot(&self) -> Option<usize> {
+        (0..self.slots.len()).find(|&slot| self.is_free(slot))
+    }
+
+    /// Marks `slot` as holding a new frame and returns the frame's number.
+    pub fn publish(&mut self, slot: usize, now: Instant) -> Result<u64> {
+        if !self.is_free(slot) {
+            return Err(GeyserError::ResourceInUse);
+        }
+        self.last_frame += 1;
+        self.slots[slot] = SlotState::InFlight {
+            frame: self.last_frame,
+            published_at: now,
+        };
+        Ok(self.last_frame)
+    }
+
+    /// Evicts consumers that stalled on a frame for longer than the stall timeout,
+    /// then frees every slot all remaining consumers have released. Returns the
+    /// evicted consumers.
+    pub fn reclaim(&mut self, now: Instant) -> Vec<ConsumerId> {
+        let mut evicted = Vec::new();
+        for slot in &self.slots {
+            if let SlotState::InFlight { frame, published_at } = *slot {
+                if now.saturating_duration_since(published_at) > self.stall_timeout {
+                    evicted.extend(self.consumers.iter().filter(|(_, &released)| released < frame).map(|(&id, _)| id));
+                }
+            }
+        }
+        evicted.sort();
+        evicted.dedup();
+        for id in &evicted {
+            self.consumers.remove(id);
+        }
+
+        let oldest_release = self.consumers.values().copied().min().unwrap_or(u64::MAX);
+        for slot in &mut self.slots {
+            if let SlotState::InFlight { frame, .. } = *slot {
+                if frame <= oldest_release {
+                    *slot = SlotState::Free;
+                }
+            }
+        }
+        evicted
+    }
+}
+
+// A pool texture and the package every consumer imports it from
+struct FanOutSlot {
+    texture: Option<VulkanSharedTexture>,
+    package: TextureSharePackage,
+}
+
+// The release semaphore a consumer signals, kept with its exported handle
+struct FanOutConsumer {
+    semaphore: vk::Semaphore,
+    handle: VulkanSemaphoreHandle,
+}
+
+/// Publishes a pool of textures to any number of consumers.
+///
+/// Every slot is a shareable texture with a timeline ready semaphore; consumers import
+/// all slots once from `package`. Each consumer also gets its own exported timeline
+/// release semaphore. Per frame:
+///
+/// 1. The producer takes a slot from `acquire`, renders into it and signals the slot's
+///    ready semaphore with the value returned by `publish`.
+/// 2. Consumers wait for that value on the ready semaphore, read the slot, then signal
+///    their release semaphore with the same value.
+/// 3. The slot comes back from `acquire` once every consumer has released it. Consumers
+///    that stall for longer than the stall timeout are evicted and listed by
+///    `take_evicted`.
+///
+/// Dropping the publisher destroys the pool, which must no longer be in use on the GPU.
+pub struct FanOutPublisher<'a> {
+    manager: &'a VulkanTextureShareManager,
+    slots: Vec<FanOutSlot>,
+    consumers: HashMap<ConsumerId, FanOutConsumer>,
+    tracker: ReleaseTracker,
+    evicted: Vec<ConsumerId>,
+}
+
+impl<'a> FanOutPublisher<'a> {
+    /// Creates and exports `slot_count` textures described by `descriptor`.
+    ///
+    /// Requires exportable timeline semaphores.
+    pub fn new(
+        manager: &'a VulkanTextureShareManager,
+        descriptor: &TextureDescriptor,
+        slot_count: usize,
+        stall_timeout: Duration,
+    ) -> Result<Self> {
+        if !manager.sync_capabilities.timeline_semaphore_export {
+            return Err(GeyserError::OperationNotSupported);
+        }
+
+        // Slots created so far are destroyed by `Drop` if a later one fails
+        let mut publisher = Self {
+            manager,
+            slots: Vec::with_capacity(slot_count),
+            consumers: HashMap::new(),
+            tracker: ReleaseTracker::new(slot_count, stall_timeout),
+            evicted: Vec::new(),
+        };
+        for _ in 0..slot_count {
+            let texture = manager.create_vulkan_texture_with_sync(descriptor)?;
+            let package = manager.export_texture_with_sync(&texture)?;
+            publisher.slots.push(FanOutSlot {
+                texture: Some(texture),
+                package,
+            });
+        }
+        Ok(publisher)
+    }
+
+    /// Returns the number of textures in the pool.
+    pub fn slot_count(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Returns the texture of `slot`, e.g. to render into it.
+    pub fn texture(&self, slot: usize) -> &VulkanSharedTexture {
+        self.slots[slot].texture.as_ref().unwrap()
+    }
+
+    /// Returns the exported texture and ready semaphore of `slot`, to be sent to every
+    /// consumer.
+    pub fn package(&self, slot: usize) -> &TextureSharePackage {
+        &self.slots[slot].package
+    }
+
+    /// Attaches a consumer and returns its release semaphore, to be sent to it along
+    /// with the slot packages. The consumer is only waited on for frames published
+    /// from now on.
+    pub fn attach_consumer(&mut self) -> Result<(ConsumerId, VulkanSemaphoreHandle)> {
+        let semaphore = self.manager.create_exportable_timeline_semaphore(self.tracker.last_frame())?;
+        let handle = {
+            #[cfg(target_os = "linux")]
+            { self.manager.export_timeline_semaphore_fd(semaphore) }
+            #[cfg(target_os = "windows")]
+            { self.manager.export_timeline_semaphore_win32(semaphore) }
+            #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+            { Err(GeyserError::OperationNotSupported) }
+        };
+        let handle = match handle {
+            Ok(handle) => handle,
+            Err(e) => {
+                unsafe { self.manager.device.destroy_semaphore(semaphore, None) };
+                return Err(e);
+            }
+        };
+
+        let id = self.tracker.attach();
+        self.consumers.insert(id, FanOutConsumer { semaphore, handle: handle.clone() });
+        Ok((id, handle))
+    }
+
+    /// Detaches a consumer and destroys its release semaphore.
+    pub fn detach_consumer(&mut self, id: ConsumerId) -> Result<()> {
+        self.tracker.detach(id);
+        match self.consumers.remove(&id) {
+            Some(consumer) => self.manager.release_semaphore(&consumer.handle),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the attached consumers.
+    pub fn consumers(&self) -> Vec<ConsumerId> {
+        self.tracker.consumers()
+    }
+
+    /// Returns the newest frame a consumer has released, as of the last `acquire`.
+    pub fn released(&self, id: ConsumerId) -> Option<u64> {
+        self.tracker.released(id)
+    }
+
+    /// Reads every consumer's release semaphore, evicts stalled consumers and returns
+    /// a slot no consumer is reading, or `None` if all slots are still in flight.
+    pub fn acquire(&mut self) -> Result<Option<usize>> {
+        for (&id, consumer) in &self.consumers {
+            let value = self.manager.get_timeline_semaphore_value(consumer.semaphore)?;
+            self.tracker.release(id, value);
+        }
+        for id in self.tracker.reclaim(Instant::now()) {
+            if let Some(consumer) = self.consumers.remove(&id) {
+                self.manager.release_semaphore(&consumer.handle)?;
+            }
+            self.evicted.push(id);
+        }
+        Ok(self.tracker.free_slot())
+    }
+
+    /// Records that `slot` holds a new frame and returns its value. The producer must
+    /// signal the slot's ready semaphore with this value once rendering completes.
+    pub fn publish(&mut self, slot: usize) -> Result<u64> {
+        self.tracker.publish(slot, Instant::now())
+    }
+
+    /// Returns the consumers evicted since the last call.
+    pub fn take_evicted(&mut self) -> Vec<ConsumerId> {
+        std::mem::take(&mut self.evicted)
+    }
+}
+
+impl Drop for FanOutPublisher<'_> {
+    fn drop(&mut self) {
+        for (_, consumer) in self.consumers.drain() {
+            let _ = self.manager.release_semaphore(&consumer.handle);
+        }
+        for slot in &mut self.slots {
+            if let Some(texture) = slot.texture.take() {
+                let _ = self.manager.destroy_texture(texture, slot.package.texture.clone());
+            }
+        }
+    }
+}
@@ -11,15 +11,33 @@ use gpu_allocator::{
 };
 use std::{
     any::Any,
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
     collections::HashMap,
 };
 use crate::{
-    common::{ApiTextureHandle, TextureDescriptor, TextureFormat, TextureUsage},
+    common::{
+        bytes_per_texel, to_drm_fourcc, ApiTextureHandle, DeviceIdentity, DmaBufHandle, SharingStrategy, SyncHandle,
+        SyncPrimitives, TextureDescriptor, TextureFormat, TextureMemoryLocation, TextureSharePackage, TextureTiling,
+        TextureUsage, DRM_FORMAT_MOD_INVALID, DRM_FORMAT_MOD_LINEAR,
+    },
     error::{GeyserError, Result},
     SharedTexture, TextureShareManager,
 };
 
+mod blit;
+pub use blit::CopyRegion;
+use blit::BlitState;
+
+mod builder;
+pub use builder::{required_device_extensions, DeviceSelector, VulkanTextureShareManagerBuilder};
+use builder::{query_id_properties, OwnedVulkanContext};
+
+mod fanout;
+pub use fanout::{ConsumerId, FanOutPublisher, ReleaseTracker};
+
 // --- API-Specific Handle for Vulkan ---
 // This struct will contain the necessary information to re-create/import a Vulkan image
 // from an external memory handle (e.g., a file descriptor on Linux, or a Windows handle).
@@ -33,6 +51,11 @@ pub struct VulkanTextureShareHandle {
     pub size: u64, // Size of the external memory allocation
     pub handle_type: vk::ExternalMemoryHandleTypeFlags,
     pub dedicated_allocation: bool,
+    /// Device and driver that allocated the memory; checked on import
+    pub device_identity: DeviceIdentity,
+    /// Offset and row pitch of each memory plane when the texture uses
+    /// `TextureTiling::DrmModifier`; empty for other tilings
+    pub plane_layouts: Vec<vk::SubresourceLayout>,
 }
 
 /// Vulkan semaphore handle for synchronization.
@@ -51,6 +74,163 @@ pub struct VulkanFenceHandle {
     pub handle_type: vk::ExternalFenceHandleTypeFlags,
 }
 
+/// Synchronization features available to a manager. `query` reports what the physical
+/// device supports; a manager only uses the subset its device was created with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SyncCapabilities {
+    /// `timelineSemaphore` feature from `PhysicalDeviceTimelineSemaphoreFeatures`
+    pub timeline_semaphore: bool,
+    /// Binary semaphores can be exported with the platform handle type
+    pub binary_semaphore_export: bool,
+    /// Binary semaphores can be imported from the platform handle type
+    pub binary_semaphore_import: bool,
+    /// Timeline semaphores can be exported with the platform handle type
+    pub timeline_semaphore_export: bool,
+    /// Timeline semaphores can be imported from the platform handle type
+    pub timeline_semaphore_import: bool,
+    /// Fences can be exported with the platform handle type
+    pub fence_export: bool,
+    /// Fences can be imported from the platform handle type
+    pub fence_import: bool,
+    /// `synchronization2` feature, required by `submit_shared_frame`
+    pub synchronization2: bool,
+}
+
+impl SyncCapabilities {
+    /// Builds capabilities from raw Vulkan query results.
+    pub fn from_properties(
+        timeline_semaphore: bool,
+        binary_semaphore: vk::ExternalSemaphoreFeatureFlags,
+        timeline_semaphore_features: vk::ExternalSemaphoreFeatureFlags,
+        fence: vk::ExternalFenceFeatureFlags,
+        synchronization2: bool,
+    ) -> Self {
+        Self {
+            timeline_semaphore,
+            binary_semaphore_export: binary_semaphore.contains(vk::ExternalSemaphoreFeatureFlags::EXPORTABLE),
+            binary_semaphore_import: binary_semaphore.contains(vk::ExternalSemaphoreFeatureFlags::IMPORTABLE),
+            timeline_semaphore_export: timeline_semaphore
+                && timeline_semaphore_features.contains(vk::ExternalSemaphoreFeatureFlags::EXPORTABLE),
+            timeline_semaphore_import: timeline_semaphore
+                && timeline_semaphore_features.contains(vk::ExternalSemaphoreFeatureFlags::IMPORTABLE),
+            fence_export: fence.contains(vk::ExternalFenceFeatureFlags::EXPORTABLE),
+            fence_import: fence.contains(vk::ExternalFenceFeatureFlags::IMPORTABLE),
+            synchronization2,
+        }
+    }
+
+    /// Masks out the features that were not enabled on the logical device. Timeline
+    /// export/import depends on the timeline feature itself.
+    pub fn with_enabled_features(self, timeline_semaphore: bool, synchronization2: bool) -> Self {
+        let timeline_semaphore = self.timeline_semaphore && timeline_semaphore;
+        Self {
+            timeline_semaphore,
+            timeline_semaphore_export: self.timeline_semaphore_export && timeline_semaphore,
+            timeline_semaphore_import: self.timeline_semaphore_import && timeline_semaphore,
+            synchronization2: self.synchronization2 && synchronization2,
+            ..self
+        }
+    }
+
+    /// Queries the timeline feature and external semaphore/fence properties of a physical device.
+    ///
+    /// Assumes the instance was created with an `apiVersion` at least as new as the
+    /// device's; use `query_with_api_version` otherwise.
+    pub fn query(instance: &Instance, physical_device: vk::PhysicalDevice) -> Self {
+        let api_version = unsafe { instance.get_physical_device_properties(physical_device) }.api_version;
+        Self::query_with_api_version(instance, physical_device, api_version)
+    }
+
+    /// Like `query`, for an instance created with `api_version`. The queries are Vulkan 1.1
+    /// commands, so nothing is reported when the instance or the device is older.
+    pub fn query_with_api_version(instance: &Instance, physical_device: vk::PhysicalDevice, api_version: u32) -> Self {
+        // `queue_submit2` is loaded from the Vulkan 1.3 function table
+        let api_version = api_version.min(unsafe { instance.get_physical_device_properties(physical_device) }.api_version);
+        if api_version < vk::API_VERSION_1_1 {
+            return Self::default();
+        }
+
+        let (semaphore_handle_type, fence_handle_type) = {
+            #[cfg(target_os = "linux")]
+            { (vk::ExternalSemaphoreHandleTypeFlags::OPAQUE_FD, vk::ExternalFenceHandleTypeFlags::OPAQUE_FD) }
+            #[cfg(target_os = "windows")]
+            { (vk::ExternalSemaphoreHandleTypeFlags::OPAQUE_WIN32, vk::ExternalFenceHandleTypeFlags::OPAQUE_WIN32) }
+            #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+            { (vk::ExternalSemaphoreHandleTypeFlags::empty(), vk::ExternalFenceHandleTypeFlags::empty()) }
+        };
+
+        let mut synchronization2_features = vk::PhysicalDeviceSynchronization2Features {
+            s_type: vk::StructureType::PHYSICAL_DEVICE_SYNCHRONIZATION_2_FEATURES,
+            p_next: std::ptr::null_mut(),
+            synchronization2: vk::FALSE,
+            _marker: std::marker::PhantomData,
+        };
+        let mut timeline_features = vk::PhysicalDeviceTimelineSemaphoreFeatures {
+            s_type: vk::StructureType::PHYSICAL_DEVICE_TIMELINE_SEMAPHORE_FEATURES,
+            p_next: &mut synchronization2_features as *mut _ as *mut std::ffi::c_void,
+            timeline_semaphore: vk::FALSE,
+            _marker: std::marker::PhantomData,
+        };
+        let mut features2 = vk::PhysicalDeviceFeatures2 {
+            s_type: vk::StructureType::PHYSICAL_DEVICE_FEATURES_2,
+            p_next: &mut timeline_features as *mut _ as *mut std::ffi::c_void,
+            features: vk::PhysicalDeviceFeatures::default(),
+            _marker: std::marker::PhantomData,
+        };
+        unsafe { instance.get_physical_device_features2(physical_device, &mut features2) };
+        let timeline_semaphore = timeline_features.timeline_semaphore == vk::TRUE;
+        let synchronization2 = synchronization2_features.synchronization2 == vk::TRUE
+            && api_version >= vk::API_VERSION_1_3;
+
+        let query_semaphore = |semaphore_type: vk::SemaphoreType| {
+            let type_info = vk::SemaphoreTypeCreateInfo {
+                s_type: vk::StructureType::SEMAPHORE_TYPE_CREATE_INFO,
+                p_next: std::ptr::null(),
+                semaphore_type,
+                initial_value: 0,
+                _marker: std::marker::PhantomData,
+            };
+            let info = vk::PhysicalDeviceExternalSemaphoreInfo {
+                s_type: vk::StructureType::PHYSICAL_DEVICE_EXTERNAL_SEMAPHORE_INFO,
+                p_next: &type_info as *const _ as *const std::ffi::c_void,
+                handle_type: semaphore_handle_type,
+                _marker: std::marker::PhantomData,
+            };
+            let mut properties = vk::ExternalSemaphoreProperties::default();
+            unsafe {
+                instance.get_physical_device_external_semaphore_properties(physical_device, &info, &mut properties)
+            };
+            properties.external_semaphore_features
+        };
+
+        let binary_semaphore = query_semaphore(vk::SemaphoreType::BINARY);
+        let timeline_semaphore_features = if timeline_semaphore {
+            query_semaphore(vk::SemaphoreType::TIMELINE)
+        } else {
+            vk::ExternalSemaphoreFeatureFlags::empty()
+        };
+
+        let fence_info = vk::PhysicalDeviceExternalFenceInfo {
+            s_type: vk::StructureType::PHYSICAL_DEVICE_EXTERNAL_FENCE_INFO,
+            p_next: std::ptr::null(),
+            handle_type: fence_handle_type,
+            _marker: std::marker::PhantomData,
+        };
+        let mut fence_properties = vk::ExternalFenceProperties::default();
+        unsafe {
+            instance.get_physical_device_external_fence_properties(physical_device, &fence_info, &mut fence_properties)
+        };
+
+        Self::from_properties(
+            timeline_semaphore,
+            binary_semaphore,
+            timeline_semaphore_features,
+            fence_properties.external_fence_features,
+            synchronization2,
+        )
+    }
+}
+
 // --- Vulkan Specific SharedTexture Implementation ---
 pub struct VulkanSharedTexture {
     device: Arc<Device>,
@@ -60,6 +240,263 @@ pub struct VulkanSharedTexture {
     descriptor: TextureDescriptor,
     // Potentially store the native handle if exported
     pub(crate) exported_handle: Option<VulkanTextureShareHandle>,
+    // Synchronization primitives owned by this texture (see `create_shareable_texture_with_sync`)
+    ready_semaphore: Option<vk::Semaphore>,
+    ready_semaphore_is_timeline: bool,
+    ready_fence: Option<vk::Fence>,
+    // Set when the exported memory could not be bound directly (see `SharingStrategy::Copy`)
+    transfer: Option<CopyTransfer>,
+    // Offset and row pitch of the image's memory; only defined for non-optimal tiling
+    subresource_layout: Option<vk::SubresourceLayout>,
+    // Set once the image holds contents in `SHARED_IMAGE_LAYOUT`: it was imported, copied
+    // into, or released by `submit_shared_frame`. Until then it is still `UNDEFINED` and
+    // owned by no queue family, so its first acquire transitions from there
+    initialized: AtomicBool,
+}
+
+/// CPU view of a host-visible texture, returned by `VulkanSharedTexture::map`.
+/// Rows are `row_pitch` bytes apart and may be followed by padding.
+pub struct MappedTexture<'a> {
+    data: &'a mut [u8],
+    row_pitch: usize,
+    row_size: usize,
+    height: u32,
+}
+
+impl<'a> MappedTexture<'a> {
+    fn new(data: &'a mut [u8], row_pitch: usize, row_size: usize, height: u32) -> Self {
+        Self {
+            data,
+            row_pitch,
+            row_size,
+            height,
+        }
+    }
+
+    /// Returns the number of bytes between the starts of consecutive rows.
+    pub fn row_pitch(&self) -> usize {
+        self.row_pitch
+    }
+
+    /// Returns the number of rows.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns the texels of row `y`, without the padding after them.
+    pub fn row(&self, y: u32) -> &[u8] {
+        let start = y as usize * self.row_pitch;
+        &self.data[start..start + self.row_size]
+    }
+
+    /// Returns the texels of row `y` for writing, without the padding after them.
+    pub fn row_mut(&mut self, y: u32) -> &mut [u8] {
+        let start = y as usize * self.row_pitch;
+        &mut self.data[start..start + self.row_size]
+    }
+
+    /// Returns the whole mapping, padding included.
+    pub fn as_bytes(&self) -> &[u8] {
+        self.data
+    }
+
+    /// Returns the whole mapping for writing, padding included.
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+        self.data
+    }
+}
+
+// Staging buffer bound to memory imported from another device, copied into the
+// texture's image by `VulkanTextureShareManager::refresh_copied_texture`
+struct CopyTransfer {
+    buffer: vk::Buffer,
+    memory: vk::DeviceMemory,
+    // Records the copy once; resubmitted on every refresh
+    command_pool: vk::CommandPool,
+    command_buffer: vk::CommandBuffer,
+    fence: vk::Fence,
+}
+
+// Placement of a linear dma-buf's texels inside its memory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct LinearLayout {
+    offset: u64,
+    // Row pitch in texels, as expected by `vkCmdCopyBufferToImage`
+    row_length: u32,
+    // Bytes the buffer must cover, from the start of the memory
+    size: u64,
+}
+
+// Validates a dma-buf for the copy fallback and computes where its texels are.
+// Only linear layouts can be read through a buffer.
+fn linear_dma_buf_layout(handle: &DmaBufHandle, descriptor: &TextureDescriptor) -> Result<LinearLayout> {
+    if handle.modifier != DRM_FORMAT_MOD_LINEAR {
+        return Err(GeyserError::UnsupportedFormat(format!(
+            "dma-buf modifier {:#x} cannot be copied across devices; only linear dma-bufs can",
+            handle.modifier
+        )));
+    }
+    let texel_size = bytes_per_texel(descriptor.format).ok_or_else(|| {
+        GeyserError::UnsupportedTextureFormat(format!("{} cannot be copied from a dma-buf", descriptor.format))
+    })?;
+    let row_size = descriptor.width as u64 * texel_size as u64;
+    if (handle.stride as u64) < row_size || !handle.stride.is_multiple_of(texel_size) {
+        return Err(GeyserError::Other(format!(
+            "dma-buf stride {} does not fit {} texels of {} bytes",
+            handle.stride, descriptor.width, texel_size
+        )));
+    }
+
+    Ok(LinearLayout {
+        offset: handle.offset as u64,
+        row_length: handle.stride / texel_size,
+        size: handle.offset as u64 + handle.stride as u64 * descriptor.height.saturating_sub(1) as u64 + row_size,
+    })
+}
+
+impl VulkanSharedTexture {
+    /// Returns the underlying Vulkan image.
+    pub fn image(&self) -> vk::Image {
+        self.image
+    }
+
+    /// Returns the semaphore signalled when the texture contents are ready, if one is attached.
+    pub fn ready_semaphore(&self) -> Option<vk::Semaphore> {
+        self.ready_semaphore
+    }
+
+    /// Returns true if the attached ready semaphore is a timeline semaphore.
+    pub fn ready_semaphore_is_timeline(&self) -> bool {
+        self.ready_semaphore_is_timeline
+    }
+
+    /// Returns the fence used for CPU-side synchronization, if one is attached.
+    pub fn ready_fence(&self) -> Option<vk::Fence> {
+        self.ready_fence
+    }
+
+    /// Returns the number of bytes between rows of a linear or DRM-modifier texture.
+    /// Optimal-tiling textures have no defined row pitch.
+    pub fn row_pitch(&self) -> Option<u64> {
+        self.subresource_layout.map(|layout| layout.row_pitch)
+    }
+
+    /// Maps the texture's memory for CPU access.
+    ///
+    /// Requires a texture created by this process with `TextureTiling::Linear` and a
+    /// host-visible `TextureMemoryLocation`. The memory is host-coherent, so writes are
+    /// visible to the GPU once submitted work that reads the texture starts.
+    pub fn map(&mut self) -> Result<MappedTexture<'_>> {
+        let layout = match (self.descriptor.tiling, self.subresource_layout) {
+            (TextureTiling::Linear, Some(layout)) => layout,
+            _ => return Err(GeyserError::Other("Only linear textures can be mapped".to_string())),
+        };
+        let row_size = bytes_per_texel(self.descriptor.format)
+            .map_or(layout.row_pitch as usize, |texel_size| self.descriptor.width as usize * texel_size as usize);
+        let height = self.descriptor.height;
+
+        let data = self.allocation.as_mut()
+            .and_then(|allocation| allocation.mapped_slice_mut())
+            .ok_or_else(|| GeyserError::Other("Texture memory is not host-visible".to_string()))?;
+        let start = layout.offset as usize;
+        let data = &mut data[start..start + layout.size as usize];
+
+        Ok(MappedTexture::new(data, layout.row_pitch as usize, row_size, height))
+    }
+}
+
+/// Layout shared textures rest in between queue family ownership transfers.
+/// `submit_shared_frame` acquires from and releases back to this layout.
+pub const SHARED_IMAGE_LAYOUT: vk::ImageLayout = vk::ImageLayout::GENERAL;
+
+// Upper bound on the blocking waits for copies and blits, so a lost device surfaces as
+// an error instead of hanging the caller
+const TRANSFER_TIMEOUT_NS: u64 = 5_000_000_000;
+
+/// Describes how a shared texture takes part in a `submit_shared_frame` call.
+#[derive(Clone, Copy)]
+pub struct SharedTextureAccess<'a> {
+    /// Texture whose ready semaphore is waited on and/or signalled
+    pub texture: &'a VulkanSharedTexture,
+    /// Layout the submitted command buffers expect the image to be in
+    pub layout: vk::ImageLayout,
+    /// Value to wait for before the work starts (must be 0 for binary semaphores)
+    pub wait_value: Option<u64>,
+    /// Value to signal once the work completes (must be 0 for binary semaphores)
+    pub signal_value: Option<u64>,
+}
+
+impl<'a> SharedTextureAccess<'a> {
+    /// Accesses `texture` in `layout` without waiting on or signalling its semaphore.
+    pub fn new(texture: &'a VulkanSharedTexture, layout: vk::ImageLayout) -> Self {
+        Self {
+            texture,
+            layout,
+            wait_value: None,
+            signal_value: None,
+        }
+    }
+
+    /// Waits on the texture's ready semaphore reaching `value` before the work starts.
+    pub fn wait(mut self, value: u64) -> Self {
+        self.wait_value = Some(value);
+        self
+    }
+
+    /// Signals the texture's ready semaphore to `value` once the work completes.
+    pub fn signal(mut self, value: u64) -> Self {
+        self.signal_value = Some(value);
+        self
+    }
+}
+
+// Command pool and completion tracking for the barrier command buffers recorded by
+// `submit_shared_frame`. Created on first use.
+struct SubmitState {
+    command_pool: vk::CommandPool,
+    // Internal timeline signalled by every submission; used to recycle command buffers
+    timeline: vk::Semaphore,
+    next_value: u64,
+    pending: Vec<(u64, Vec<vk::CommandBuffer>)>,
+    // Full execution barrier submitted by `queue_wait_semaphore`; recorded once and reused
+    wait_barrier: vk::CommandBuffer,
+}
+
+// Reads the UUIDs and driver version identifying `physical_device`
+fn query_device_identity(instance: &Instance, physical_device: vk::PhysicalDevice) -> DeviceIdentity {
+    let id_properties = query_id_properties(instance, physical_device);
+    let properties = unsafe { instance.get_physical_device_properties(physical_device) };
+    DeviceIdentity {
+        device_uuid: id_properties.device_uuid,
+        driver_uuid: id_properties.driver_uuid,
+        driver_version: properties.driver_version,
+    }
+}
+
+// Image aspects touched by barriers on a texture of the given format
+fn aspect_mask_for_format(format: TextureFormat) -> vk::ImageAspectFlags {
+    match format {
+        TextureFormat::Depth32Float => vk::ImageAspectFlags::DEPTH,
+        TextureFormat::Depth24Plus | TextureFormat::Depth24PlusStencil8 => {
+            vk::ImageAspectFlags::DEPTH | vk::ImageAspectFlags::STENCIL
+        }
+        _ => vk::ImageAspectFlags::COLOR,
+    }
+}
+
+// Format features an image needs to be created with `usage`
+fn format_features_for_usage(usage: vk::ImageUsageFlags) -> vk::FormatFeatureFlags {
+    [
+        (vk::ImageUsageFlags::TRANSFER_SRC, vk::FormatFeatureFlags::TRANSFER_SRC),
+        (vk::ImageUsageFlags::TRANSFER_DST, vk::FormatFeatureFlags::TRANSFER_DST),
+        (vk::ImageUsageFlags::SAMPLED, vk::FormatFeatureFlags::SAMPLED_IMAGE),
+        (vk::ImageUsageFlags::STORAGE, vk::FormatFeatureFlags::STORAGE_IMAGE),
+        (vk::ImageUsageFlags::COLOR_ATTACHMENT, vk::FormatFeatureFlags::COLOR_ATTACHMENT),
+        (vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT, vk::FormatFeatureFlags::DEPTH_STENCIL_ATTACHMENT),
+    ]
+    .into_iter()
+    .filter(|(image_usage, _)| usage.contains(*image_usage))
+    .fold(vk::FormatFeatureFlags::empty(), |features, (_, feature)| features | feature)
 }
 
 impl SharedTexture for VulkanSharedTexture {
@@ -67,16 +504,31 @@ impl SharedTexture for VulkanSharedTexture {
     fn height(&self) -> u32 { self.descriptor.height }
     fn format(&self) -> TextureFormat { self.descriptor.format }
     fn usage(&self) -> &[TextureUsage] { &self.descriptor.usage }
+    fn sharing_strategy(&self) -> SharingStrategy {
+        if self.transfer.is_some() { SharingStrategy::Copy } else { SharingStrategy::ZeroCopy }
+    }
     fn as_any(&self) -> &dyn Any { self }
 }
 
 impl Drop for VulkanSharedTexture {
     fn drop(&mut self) {
         unsafe {
+            if let Some(transfer) = self.transfer.take() {
+                self.device.destroy_command_pool(transfer.command_pool, None);
+                self.device.destroy_fence(transfer.fence, None);
+                self.device.destroy_buffer(transfer.buffer, None);
+                self.device.free_memory(transfer.memory, None);
+            }
             if let Some(view) = self.image_view.take() {
                 self.device.destroy_image_view(view, None);
             }
             self.device.destroy_image(self.image, None);
+            if let Some(semaphore) = self.ready_semaphore.take() {
+                self.device.destroy_semaphore(semaphore, None);
+            }
+            if let Some(fence) = self.ready_fence.take() {
+                self.device.destroy_fence(fence, None);
+            }
             // Don't free allocation here if it was imported or exported
             // Allocation should be handled by the allocator or `TextureShareManager`'s release.
         }
@@ -86,6 +538,9 @@ impl Drop for VulkanSharedTexture {
 // --- Vulkan Specific TextureShareManager Implementation ---
 
 /// Represents the Vulkan context needed for sharing operations.
+///
+/// Dropping the manager destroys the semaphores, fences and imported memory it still
+/// tracks, so textures imported through it must be dropped first.
 pub struct VulkanTextureShareManager {
     instance: Arc<Instance>,
     device: Arc<Device>,
@@ -95,11 +550,30 @@ pub struct VulkanTextureShareManager {
     // Store exported resources to manage their lifetime
     // (e.g., `vk::DeviceMemory` and associated external handles)
     exported_resources: Mutex<HashMap<u64, vk::DeviceMemory>>,
+    // Memory allocated by texture imports, keyed by the imported handle. Unlike exported
+    // memory it is owned by the manager rather than the allocator.
+    imported_resources: Mutex<HashMap<u64, vk::DeviceMemory>>,
     // Store exported sync primitives
     exported_semaphores: Mutex<HashMap<u64, vk::Semaphore>>,
     exported_fences: Mutex<HashMap<u64, vk::Fence>>,
-    // Store timeline semaphore extension if supported
-    timeline_semaphore_supported: bool,
+    // Timeline semaphore and external sync support, queried at construction
+    sync_capabilities: SyncCapabilities,
+    // Stamped on exported handles and compared on import
+    device_identity: DeviceIdentity,
+    // `VK_EXT_external_memory_dma_buf` is enabled, allowing the copy fallback on import
+    dma_buf_import: bool,
+    // `VK_EXT_image_drm_format_modifier` is enabled, allowing `TextureTiling::DrmModifier`
+    drm_format_modifiers: bool,
+    // Version the device's core commands are available at: the lower of the instance's
+    // `apiVersion` and the physical device's, see `with_api_version`
+    api_version: u32,
+    queue: vk::Queue,
+    // Held around every submission to `queue`, which Vulkan requires to be externally
+    // synchronized; shared with other users of the queue through `with_queue_lock`
+    queue_lock: Arc<Mutex<()>>,
+    submit_state: Mutex<Option<SubmitState>>,
+    // Command pool, fence and compute fallback of `blit` and `copy`; created on first use
+    blit_state: Mutex<Option<BlitState>>,
     #[cfg(target_os = "windows")]
     external_memory_win32: ash::khr::external_memory_win32::Device,
     #[cfg(target_os = "linux")]
@@ -112,6 +586,9 @@ pub struct VulkanTextureShareManager {
     external_fence_win32: ash::khr::external_fence_win32::Device,
     #[cfg(target_os = "linux")]
     external_fence_fd: ash::khr::external_fence_fd::Device,
+    // Instance and device created by `VulkanTextureShareManagerBuilder`.
+    // Declared last so the allocator is dropped before the device is destroyed.
+    owned_context: Option<OwnedVulkanContext>,
 }
 
 impl VulkanTextureShareManager {
@@ -148,9 +625,16 @@ impl VulkanTextureShareManager {
         #[cfg(target_os = "linux")]
         let external_fence_fd = ash::khr::external_fence_fd::Device::new(&*instance, &*device);
 
-        // Check if timeline semaphores are supported
-        // Timeline semaphores were promoted to core in Vulkan 1.2
-        let timeline_semaphore_supported = true; // Assume support for now, could query features
+        // The device was created by the caller, so no optional feature is assumed enabled
+        // until `with_sync_features` declares it
+        let sync_capabilities = SyncCapabilities::query(&instance, physical_device).with_enabled_features(false, false);
+
+        let device_identity = query_device_identity(&instance, physical_device);
+
+        // Assumes an instance as new as the device until `with_api_version` says otherwise
+        let api_version = unsafe { instance.get_physical_device_properties(physical_device) }.api_version;
+
+        let queue = unsafe { device.get_device_queue(queue_family_index, 0) };
 
         Ok(Self {
             instance,
@@ -159,9 +643,18 @@ impl VulkanTextureShareManager {
             queue_family_index,
             allocator: Mutex::new(allocator),
             exported_resources: Mutex::new(HashMap::new()),
+            imported_resources: Mutex::new(HashMap::new()),
             exported_semaphores: Mutex::new(HashMap::new()),
             exported_fences: Mutex::new(HashMap::new()),
-            timeline_semaphore_supported,
+            sync_capabilities,
+            device_identity,
+            dma_buf_import: false,
+            drm_format_modifiers: false,
+            api_version,
+            queue,
+            queue_lock: Arc::new(Mutex::new(())),
+            submit_state: Mutex::new(None),
+            blit_state: Mutex::new(None),
             #[cfg(target_os = "windows")]
             external_memory_win32,
             #[cfg(target_os = "linux")]
@@ -174,9 +667,111 @@ impl VulkanTextureShareManager {
             external_fence_win32,
             #[cfg(target_os = "linux")]
             external_fence_fd,
+            owned_context: None,
         })
     }
 
+    /// Returns a builder that creates the Vulkan instance and device itself, with the
+    /// extensions and features required for sharing enabled.
+    pub fn builder() -> VulkanTextureShareManagerBuilder {
+        VulkanTextureShareManagerBuilder::default()
+    }
+
+    /// Returns the Vulkan instance this manager was created with.
+    pub fn instance(&self) -> &Arc<Instance> {
+        &self.instance
+    }
+
+    /// Returns the logical device this manager was created with.
+    pub fn device(&self) -> &Arc<Device> {
+        &self.device
+    }
+
+    /// Returns the physical device backing this manager.
+    pub fn physical_device(&self) -> vk::PhysicalDevice {
+        self.physical_device
+    }
+
+    /// Returns the synchronization features enabled on this manager's device.
+    pub fn sync_capabilities(&self) -> SyncCapabilities {
+        self.sync_capabilities
+    }
+
+    /// Returns the device/driver identity stamped on textures exported by this manager.
+    pub fn device_identity(&self) -> DeviceIdentity {
+        self.device_identity
+    }
+
+    /// Declares whether the device was created with `VK_EXT_external_memory_dma_buf`
+    /// enabled. Without it, textures from other devices cannot be imported by copy.
+    ///
+    /// `VulkanTextureShareManager::builder()` detects and enables the extension itself.
+    pub fn with_dma_buf_import(mut self, enabled: bool) -> Self {
+        self.dma_buf_import = enabled && cfg!(target_os = "linux");
+        self
+    }
+
+    /// Returns true if dma-bufs can be imported through the copy fallback.
+    pub fn dma_buf_import_supported(&self) -> bool {
+        self.dma_buf_import
+    }
+
+    /// Declares whether the device was created with `VK_EXT_image_drm_format_modifier`
+    /// enabled, which `TextureTiling::DrmModifier` requires.
+    ///
+    /// `VulkanTextureShareManager::builder()` detects and enables the extension itself.
+    pub fn with_drm_format_modifiers(mut self, enabled: bool) -> Self {
+        self.drm_format_modifiers = enabled;
+        self
+    }
+
+    /// Declares whether the device was created with the `timelineSemaphore` and
+    /// `synchronization2` features enabled. Features the physical device does not support
+    /// are ignored. `synchronization2` also requires a Vulkan 1.3 device.
+    ///
+    /// `VulkanTextureShareManager::builder()` declares what it enabled itself.
+    pub fn with_sync_features(mut self, timeline_semaphore: bool, synchronization2: bool) -> Self {
+        self.sync_capabilities = SyncCapabilities::query_with_api_version(&self.instance, self.physical_device, self.api_version)
+            .with_enabled_features(timeline_semaphore, synchronization2);
+        self
+    }
+
+    /// Returns true if textures can be created with `TextureTiling::DrmModifier`.
+    pub fn drm_format_modifiers_supported(&self) -> bool {
+        self.drm_format_modifiers
+    }
+
+    /// Declares the `apiVersion` the instance was created with. Core device commands are
+    /// only available up to the lower of it and the physical device's version; without
+    /// this, the physical device's version is assumed.
+    ///
+    /// Call it before `with_sync_features`. Below Vulkan 1.1 the sync capabilities cannot
+    /// be queried, so none are reported.
+    ///
+    /// `VulkanTextureShareManager::builder()` declares the version it requested itself.
+    pub fn with_api_version(mut self, api_version: u32) -> Self {
+        let device_version = unsafe { self.instance.get_physical_device_properties(self.physical_device) }.api_version;
+        self.api_version = api_version.min(device_version);
+        if self.api_version < vk::API_VERSION_1_1 {
+            self.sync_capabilities = SyncCapabilities::default();
+        }
+        self
+    }
+
+    /// Returns the Vulkan version the device is used at.
+    pub fn api_version(&self) -> u32 {
+        self.api_version
+    }
+
+    // Returns `OperationNotSupported` unless the device supports timeline semaphores
+    fn ensure_timeline_semaphores(&self) -> Result<()> {
+        if self.sync_capabilities.timeline_semaphore {
+            Ok(())
+        } else {
+            Err(GeyserError::OperationNotSupported)
+        }
+    }
+
     // Helper to convert `TextureFormat` to `vk::Format`
     fn map_texture_format_to_vk(&self, format: TextureFormat) -> Result<vk::Format> {
         match format {
@@ -554,9 +1149,7 @@ impl VulkanTextureShareManager {
 
     /// Create an exportable timeline semaphore with an initial counter value
     pub fn create_exportable_timeline_semaphore(&self, initial_value: u64) -> Result<vk::Semaphore> {
-        if !self.timeline_semaphore_supported {
-            return Err(GeyserError::OperationNotSupported);
-        }
+        self.ensure_timeline_semaphores()?;
 
         let handle_types = {
             #[cfg(target_os = "linux")]
@@ -598,6 +1191,10 @@ impl VulkanTextureShareManager {
     /// Export a timeline semaphore handle for sharing (Windows)
     #[cfg(target_os = "windows")]
     pub fn export_timeline_semaphore_win32(&self, semaphore: vk::Semaphore) -> Result<VulkanSemaphoreHandle> {
+        if !self.sync_capabilities.timeline_semaphore_export {
+            return Err(GeyserError::OperationNotSupported);
+        }
+
         let get_handle_info = vk::SemaphoreGetWin32HandleInfoKHR {
             s_type: vk::StructureType::SEMAPHORE_GET_WIN32_HANDLE_INFO_KHR,
             p_next: std::ptr::null(),
@@ -625,6 +1222,10 @@ impl VulkanTextureShareManager {
     /// Export a timeline semaphore handle for sharing (Linux)
     #[cfg(target_os = "linux")]
     pub fn export_timeline_semaphore_fd(&self, semaphore: vk::Semaphore) -> Result<VulkanSemaphoreHandle> {
+        if !self.sync_capabilities.timeline_semaphore_export {
+            return Err(GeyserError::OperationNotSupported);
+        }
+
         let get_fd_info = vk::SemaphoreGetFdInfoKHR {
             s_type: vk::StructureType::SEMAPHORE_GET_FD_INFO_KHR,
             p_next: std::ptr::null(),
@@ -652,6 +1253,10 @@ impl VulkanTextureShareManager {
     /// Import a timeline semaphore from an external handle (Windows)
     #[cfg(target_os = "windows")]
     pub fn import_timeline_semaphore_win32(&self, handle: &VulkanSemaphoreHandle, initial_value: u64) -> Result<vk::Semaphore> {
+        if !self.sync_capabilities.timeline_semaphore_import {
+            return Err(GeyserError::OperationNotSupported);
+        }
+
         let mut import_info = vk::ImportSemaphoreWin32HandleInfoKHR {
             s_type: vk::StructureType::IMPORT_SEMAPHORE_WIN32_HANDLE_INFO_KHR,
             p_next: std::ptr::null(),
@@ -679,6 +1284,10 @@ impl VulkanTextureShareManager {
     /// Import a timeline semaphore from an external handle (Linux)
     #[cfg(target_os = "linux")]
     pub fn import_timeline_semaphore_fd(&self, handle: &VulkanSemaphoreHandle, initial_value: u64) -> Result<vk::Semaphore> {
+        if !self.sync_capabilities.timeline_semaphore_import {
+            return Err(GeyserError::OperationNotSupported);
+        }
+
         let mut import_info = vk::ImportSemaphoreFdInfoKHR {
             s_type: vk::StructureType::IMPORT_SEMAPHORE_FD_INFO_KHR,
             p_next: std::ptr::null(),
@@ -704,6 +1313,8 @@ impl VulkanTextureShareManager {
 
     /// Signal a timeline semaphore to a specific value from the host
     pub fn signal_timeline_semaphore(&self, semaphore: vk::Semaphore, value: u64) -> Result<()> {
+        self.ensure_timeline_semaphores()?;
+
         let signal_info = vk::SemaphoreSignalInfo {
             s_type: vk::StructureType::SEMAPHORE_SIGNAL_INFO,
             p_next: std::ptr::null(),
@@ -720,6 +1331,8 @@ impl VulkanTextureShareManager {
 
     /// Wait for a timeline semaphore to reach a specific value from the host
     pub fn wait_timeline_semaphore(&self, semaphore: vk::Semaphore, value: u64, timeout_ns: u64) -> Result<()> {
+        self.ensure_timeline_semaphores()?;
+
         let wait_info = vk::SemaphoreWaitInfo {
             s_type: vk::StructureType::SEMAPHORE_WAIT_INFO,
             p_next: std::ptr::null(),
@@ -738,55 +1351,79 @@ impl VulkanTextureShareManager {
 
     /// Get the current counter value of a timeline semaphore
     pub fn get_timeline_semaphore_value(&self, semaphore: vk::Semaphore) -> Result<u64> {
+        self.ensure_timeline_semaphores()?;
+
         unsafe {
             self.device.get_semaphore_counter_value(semaphore)
                 .map_err(|e| GeyserError::VulkanApiError(format!("Failed to get timeline semaphore value: {:?}", e)))
         }
     }
 
-    /// Cleanup exported semaphore
-    pub fn release_semaphore(&self, handle: &VulkanSemaphoreHandle) -> Result<()> {
-        if let Some(semaphore) = self.exported_semaphores.lock().unwrap().remove(&handle.raw_handle) {
-            unsafe {
-                self.device.destroy_semaphore(semaphore, None);
-            }
-        }
-        Ok(())
-    }
-
-    /// Cleanup exported fence
-    pub fn release_fence(&self, handle: &VulkanFenceHandle) -> Result<()> {
-        if let Some(fence) = self.exported_fences.lock().unwrap().remove(&handle.raw_handle) {
-            unsafe {
-                self.device.destroy_fence(fence, None);
-            }
-        }
-        Ok(())
-    }
-}
-
-impl TextureShareManager for VulkanTextureShareManager {
-    fn create_shareable_texture(&self, descriptor: &TextureDescriptor) -> Result<Box<dyn SharedTexture>> {
+    // Creates an image whose memory can be exported or imported with `handle_types`,
+    // using the tiling requested by `descriptor`. Images imported with a DRM modifier
+    // pass the exporter's `plane_layouts` so both sides agree on where the planes live.
+    fn create_external_image(
+        &self,
+        descriptor: &TextureDescriptor,
+        handle_types: vk::ExternalMemoryHandleTypeFlags,
+        plane_layouts: &[vk::SubresourceLayout],
+    ) -> Result<vk::Image> {
         let vk_format = self.map_texture_format_to_vk(descriptor.format)?;
         let (vk_usage, _) = self.map_texture_usage_to_vk(&descriptor.usage);
+        self.check_tiling_features(vk_format, vk_usage, descriptor.tiling)?;
 
-        // Required for external memory export
-        let handle_types = {
-            #[cfg(target_os = "linux")]
-            { vk::ExternalMemoryHandleTypeFlags::OPAQUE_FD }
-            #[cfg(target_os = "windows")]
-            { vk::ExternalMemoryHandleTypeFlags::OPAQUE_WIN32 }
-            #[cfg(not(any(target_os = "linux", target_os = "windows")))]
-            { vk::ExternalMemoryHandleTypeFlags::empty() }
+        let modifier = match descriptor.tiling {
+            TextureTiling::DrmModifier(modifier) => Some(modifier),
+            _ => None,
+        };
+        let mut modifier_list_info = vk::ImageDrmFormatModifierListCreateInfoEXT {
+            s_type: vk::StructureType::IMAGE_DRM_FORMAT_MODIFIER_LIST_CREATE_INFO_EXT,
+            p_next: std::ptr::null(),
+            drm_format_modifier_count: 1,
+            p_drm_format_modifiers: modifier.as_ref().map_or(std::ptr::null(), |modifier| modifier as *const u64),
+            _marker: std::marker::PhantomData,
+        };
+        // `size` must be zero, and the array and depth pitches are unused for single-layer 2D images
+        let explicit_layouts: Vec<vk::SubresourceLayout> = plane_layouts
+            .iter()
+            .map(|layout| vk::SubresourceLayout {
+                offset: layout.offset,
+                size: 0,
+                row_pitch: layout.row_pitch,
+                array_pitch: 0,
+                depth_pitch: 0,
+            })
+            .collect();
+        let mut modifier_explicit_info = vk::ImageDrmFormatModifierExplicitCreateInfoEXT {
+            s_type: vk::StructureType::IMAGE_DRM_FORMAT_MODIFIER_EXPLICIT_CREATE_INFO_EXT,
+            p_next: std::ptr::null(),
+            drm_format_modifier: modifier.unwrap_or(DRM_FORMAT_MOD_LINEAR),
+            drm_format_modifier_plane_count: explicit_layouts.len() as u32,
+            p_plane_layouts: explicit_layouts.as_ptr(),
+            _marker: std::marker::PhantomData,
+        };
+        let modifier_info = match modifier {
+            Some(_) if !explicit_layouts.is_empty() => {
+                &mut modifier_explicit_info as *mut _ as *const std::ffi::c_void
+            }
+            Some(_) => &mut modifier_list_info as *mut _ as *const std::ffi::c_void,
+            None => std::ptr::null(),
         };
 
         let mut external_memory_create_info = vk::ExternalMemoryImageCreateInfo {
             s_type: vk::StructureType::EXTERNAL_MEMORY_IMAGE_CREATE_INFO,
-            p_next: std::ptr::null(),
+            p_next: modifier_info,
             handle_types,
             _marker: std::marker::PhantomData,
         };
 
+        let (tiling, initial_layout) = match descriptor.tiling {
+            TextureTiling::Optimal => (vk::ImageTiling::OPTIMAL, vk::ImageLayout::UNDEFINED),
+            // Keeps texels written through `map` before the first layout transition
+            TextureTiling::Linear => (vk::ImageTiling::LINEAR, vk::ImageLayout::PREINITIALIZED),
+            TextureTiling::DrmModifier(_) => (vk::ImageTiling::DRM_FORMAT_MODIFIER_EXT, vk::ImageLayout::UNDEFINED),
+        };
+
         let image_create_info = vk::ImageCreateInfo {
             s_type: vk::StructureType::IMAGE_CREATE_INFO,
             p_next: &mut external_memory_create_info as *mut _ as *const std::ffi::c_void,
@@ -801,138 +1438,264 @@ impl TextureShareManager for VulkanTextureShareManager {
             mip_levels: 1,
             array_layers: 1,
             samples: vk::SampleCountFlags::TYPE_1,
-            tiling: vk::ImageTiling::OPTIMAL,
+            tiling,
             usage: vk_usage,
             sharing_mode: vk::SharingMode::EXCLUSIVE,
             queue_family_index_count: 0,
             p_queue_family_indices: std::ptr::null(),
-            initial_layout: vk::ImageLayout::UNDEFINED,
+            initial_layout,
+            _marker: std::marker::PhantomData,
+        };
+
+        Ok(unsafe { self.device.create_image(&image_create_info, None) }?)
+    }
+
+    // Queries where the texels of a non-optimal image live in its memory
+    fn query_subresource_layout(&self, image: vk::Image, tiling: TextureTiling) -> Option<vk::SubresourceLayout> {
+        let aspect_mask = match tiling {
+            TextureTiling::Optimal => return None,
+            TextureTiling::Linear => vk::ImageAspectFlags::COLOR,
+            TextureTiling::DrmModifier(_) => vk::ImageAspectFlags::MEMORY_PLANE_0_EXT,
+        };
+        let subresource = vk::ImageSubresource {
+            aspect_mask,
+            mip_level: 0,
+            array_layer: 0,
+        };
+        Some(unsafe { self.device.get_image_subresource_layout(image, subresource) })
+    }
+
+    // Returns an error unless images with `tiling` support every feature `usage` needs.
+    // Optimal tiling is left to `vkCreateImage`, which every format used here supports.
+    fn check_tiling_features(&self, vk_format: vk::Format, usage: vk::ImageUsageFlags, tiling: TextureTiling) -> Result<()> {
+        let supported = match tiling {
+            TextureTiling::Optimal => return Ok(()),
+            TextureTiling::Linear => unsafe {
+                self.instance.get_physical_device_format_properties(self.physical_device, vk_format)
+            }
+            .linear_tiling_features,
+            TextureTiling::DrmModifier(modifier) => {
+                self.drm_modifier_properties(vk_format, modifier)?.drm_format_modifier_tiling_features
+            }
+        };
+        let required = format_features_for_usage(usage);
+        if !supported.contains(required) {
+            return Err(GeyserError::UnsupportedFormat(format!(
+                "{:?} with {:?} tiling lacks {:?}",
+                vk_format,
+                tiling,
+                required & !supported
+            )));
+        }
+        Ok(())
+    }
+
+    // Looks up how the device supports `modifier` for `vk_format`
+    fn drm_modifier_properties(&self, vk_format: vk::Format, modifier: u64) -> Result<vk::DrmFormatModifierPropertiesEXT> {
+        self.drm_modifier_property_list(vk_format)?
+            .into_iter()
+            .find(|properties| properties.drm_format_modifier == modifier)
+            .ok_or_else(|| GeyserError::UnsupportedFormat(format!("{:?} does not support DRM modifier {:#x}", vk_format, modifier)))
+    }
+
+    // Lists every DRM modifier the device supports for `vk_format`
+    fn drm_modifier_property_list(&self, vk_format: vk::Format) -> Result<Vec<vk::DrmFormatModifierPropertiesEXT>> {
+        if !self.drm_format_modifiers {
+            return Err(GeyserError::MissingExtensions(vec![
+                ash::ext::image_drm_format_modifier::NAME.to_string_lossy().into_owned(),
+            ]));
+        }
+
+        // First call reports the number of modifiers, the second fills them in
+        let mut list = vk::DrmFormatModifierPropertiesListEXT {
+            s_type: vk::StructureType::DRM_FORMAT_MODIFIER_PROPERTIES_LIST_EXT,
+            p_next: std::ptr::null_mut(),
+            drm_format_modifier_count: 0,
+            p_drm_format_modifier_properties: std::ptr::null_mut(),
+            _marker: std::marker::PhantomData,
+        };
+        let mut properties = vk::FormatProperties2 {
+            s_type: vk::StructureType::FORMAT_PROPERTIES_2,
+            p_next: &mut list as *mut _ as *mut std::ffi::c_void,
+            format_properties: vk::FormatProperties::default(),
             _marker: std::marker::PhantomData,
         };
+        unsafe { self.instance.get_physical_device_format_properties2(self.physical_device, vk_format, &mut properties) };
+
+        let mut modifiers = vec![vk::DrmFormatModifierPropertiesEXT::default(); list.drm_format_modifier_count as usize];
+        list.p_drm_format_modifier_properties = modifiers.as_mut_ptr();
+        properties.p_next = &mut list as *mut _ as *mut std::ffi::c_void;
+        unsafe { self.instance.get_physical_device_format_properties2(self.physical_device, vk_format, &mut properties) };
+        modifiers.truncate(list.drm_format_modifier_count as usize);
+        Ok(modifiers)
+    }
+
+    /// Lists the DRM modifiers with which single-plane dma-bufs of `format` can be
+    /// imported without a copy (see `import_dma_buf`) for `usage`, including
+    /// `DRM_FORMAT_MOD_LINEAR` if the device supports it.
+    ///
+    /// Returns an empty list unless both `VK_EXT_external_memory_dma_buf` and
+    /// `VK_EXT_image_drm_format_modifier` are enabled.
+    pub fn supported_drm_modifiers(&self, format: TextureFormat, usage: &[TextureUsage]) -> Result<Vec<u64>> {
+        if !self.dma_buf_import || !self.drm_format_modifiers {
+            return Ok(Vec::new());
+        }
+        let vk_format = self.map_texture_format_to_vk(format)?;
+        let (vk_usage, _) = self.map_texture_usage_to_vk(usage);
+        let required = format_features_for_usage(vk_usage);
+        Ok(self
+            .drm_modifier_property_list(vk_format)?
+            .into_iter()
+            .filter(|properties| {
+                properties.drm_format_modifier_plane_count == 1
+                    && properties.drm_format_modifier_tiling_features.contains(required)
+            })
+            .map(|properties| properties.drm_format_modifier)
+            .collect())
+    }
+
+    // Queries the layout of every memory plane of an image created with a DRM modifier,
+    // which importers need to recreate the image on the same memory
+    fn query_plane_layouts(&self, texture: &VulkanSharedTexture) -> Result<Vec<vk::SubresourceLayout>> {
+        let TextureTiling::DrmModifier(modifier) = texture.descriptor.tiling else {
+            return Ok(Vec::new());
+        };
+        let vk_format = self.map_texture_format_to_vk(texture.descriptor.format)?;
+        let plane_count = self.drm_modifier_properties(vk_format, modifier)?.drm_format_modifier_plane_count;
+
+        const PLANE_ASPECTS: [vk::ImageAspectFlags; 4] = [
+            vk::ImageAspectFlags::MEMORY_PLANE_0_EXT,
+            vk::ImageAspectFlags::MEMORY_PLANE_1_EXT,
+            vk::ImageAspectFlags::MEMORY_PLANE_2_EXT,
+            vk::ImageAspectFlags::MEMORY_PLANE_3_EXT,
+        ];
+        Ok(PLANE_ASPECTS
+            .iter()
+            .take(plane_count as usize)
+            .map(|&aspect_mask| {
+                let subresource = vk::ImageSubresource {
+                    aspect_mask,
+                    mip_level: 0,
+                    array_layer: 0,
+                };
+                unsafe { self.device.get_image_subresource_layout(texture.image, subresource) }
+            })
+            .collect())
+    }
+
+    // Creates an exportable image backed by a dedicated allocation
+    pub(crate) fn create_vulkan_texture(&self, descriptor: &TextureDescriptor) -> Result<VulkanSharedTexture> {
+        // Required for external memory export. Non-optimal images can also be exported as
+        // dma-bufs, since their layout is meaningful to other devices.
+        let handle_types = {
+            #[cfg(target_os = "linux")]
+            {
+                if self.dma_buf_import && descriptor.tiling != TextureTiling::Optimal {
+                    vk::ExternalMemoryHandleTypeFlags::OPAQUE_FD | vk::ExternalMemoryHandleTypeFlags::DMA_BUF_EXT
+                } else {
+                    vk::ExternalMemoryHandleTypeFlags::OPAQUE_FD
+                }
+            }
+            #[cfg(target_os = "windows")]
+            { vk::ExternalMemoryHandleTypeFlags::OPAQUE_WIN32 }
+            #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+            { vk::ExternalMemoryHandleTypeFlags::empty() }
+        };
 
-        let image = unsafe { self.device.create_image(&image_create_info, None) }?;
+        let image = self.create_external_image(descriptor, handle_types, &[])?;
 
         let requirements = unsafe { self.device.get_image_memory_requirements(image) };
 
+        let location = match descriptor.memory_location {
+            TextureMemoryLocation::GpuOnly => MemoryLocation::GpuOnly,
+            TextureMemoryLocation::CpuToGpu => MemoryLocation::CpuToGpu,
+            TextureMemoryLocation::GpuToCpu => MemoryLocation::GpuToCpu,
+        };
         let allocation = self.allocator.lock().unwrap().allocate(&AllocationCreateDesc {
             name: descriptor.label.as_deref().unwrap_or("geyser-shared-texture"),
             requirements,
-            location: MemoryLocation::GpuOnly, // Or appropriate location for sharing
-            linear: false,
+            location,
+            linear: descriptor.tiling != TextureTiling::Optimal,
             allocation_scheme: AllocationScheme::DedicatedImage(image),
-        })
-        .map_err(|e| GeyserError::VulkanApiError(format!("Failed to allocate image memory: {}", e)))?;
+        });
+        let allocation = match allocation {
+            Ok(allocation) => allocation,
+            Err(e) => {
+                unsafe { self.device.destroy_image(image, None) };
+                return Err(GeyserError::VulkanApiError(format!("Failed to allocate image memory: {}", e)));
+            }
+        };
 
         unsafe {
             self.device.bind_image_memory(image, allocation.memory(), allocation.offset())?;
         }
 
-        Ok(Box::new(VulkanSharedTexture {
+        let subresource_layout = self.query_subresource_layout(image, descriptor.tiling);
+
+        Ok(VulkanSharedTexture {
             device: self.device.clone(),
             allocation: Some(allocation),
             image,
             image_view: None, // Can be created later if needed
             descriptor: descriptor.clone(),
             exported_handle: None,
-        }))
+            ready_semaphore: None,
+            ready_semaphore_is_timeline: false,
+            ready_fence: None,
+            transfer: None,
+            subresource_layout,
+            initialized: AtomicBool::new(false),
+        })
     }
 
-    fn export_texture(&self, texture: &dyn SharedTexture) -> Result<ApiTextureHandle> {
-        let vulkan_texture = texture
-            .as_any()
-            .downcast_ref::<VulkanSharedTexture>()
-            .ok_or(GeyserError::Other("Provided texture is not a VulkanSharedTexture".to_string()))?;
-
-        let allocation = vulkan_texture.allocation.as_ref()
-            .ok_or(GeyserError::Other("Texture has no allocation to export".to_string()))?;
-
-        let memory = unsafe { allocation.memory() };
-        let memory_requirements = unsafe { self.device.get_image_memory_requirements(vulkan_texture.image) };
-
-        // Export the external memory handle (platform-specific)
-        #[cfg(target_os = "windows")]
-        let raw_handle = self.get_external_memory_win32_info(memory)?;
-
-        #[cfg(target_os = "linux")]
-        let raw_handle = self.get_external_memory_fd_info(memory)? as u64;
+    // Creates a texture with an exportable ready semaphore (and fence, if supported)
+    pub(crate) fn create_vulkan_texture_with_sync(&self, descriptor: &TextureDescriptor) -> Result<VulkanSharedTexture> {
+        let mut texture = self.create_vulkan_texture(descriptor)?;
 
-        #[cfg(not(any(target_os = "linux", target_os = "windows")))]
-        return Err(GeyserError::OperationNotSupported);
-
-        // Query memory properties to get memory type index
-        let memory_properties = unsafe {
-            self.instance.get_physical_device_memory_properties(self.physical_device)
-        };
-        
-        // Find memory type index for the allocation
-        let memory_type_index = (0..memory_properties.memory_type_count)
-            .find(|&i| {
-                (memory_requirements.memory_type_bits & (1 << i)) != 0
-            })
-            .unwrap_or(0);
-
-        let handle = VulkanTextureShareHandle {
-            raw_handle,
-            memory_type_index,
-            size: memory_requirements.size,
-            handle_type: {
-                #[cfg(target_os = "windows")]
-                { vk::ExternalMemoryHandleTypeFlags::OPAQUE_WIN32 }
-                #[cfg(target_os = "linux")]
-                { vk::ExternalMemoryHandleTypeFlags::OPAQUE_FD }
-                #[cfg(not(any(target_os = "linux", target_os = "windows")))]
-                { vk::ExternalMemoryHandleTypeFlags::empty() }
-            },
-            dedicated_allocation: true,
+        // Prefer a timeline semaphore so consumers can wait on per-frame values
+        let (semaphore, is_timeline) = if self.sync_capabilities.timeline_semaphore_export {
+            (self.create_exportable_timeline_semaphore(0)?, true)
+        } else if self.sync_capabilities.binary_semaphore_export {
+            (self.create_exportable_semaphore()?, false)
+        } else {
+            return Err(GeyserError::OperationNotSupported);
         };
+        texture.ready_semaphore = Some(semaphore);
+        texture.ready_semaphore_is_timeline = is_timeline;
+        if self.sync_capabilities.fence_export {
+            texture.ready_fence = Some(self.create_exportable_fence()?);
+        }
 
-        // Store the vk::DeviceMemory to ensure it stays alive
-        self.exported_resources.lock().unwrap().insert(handle.raw_handle, memory);
-
-        Ok(ApiTextureHandle::Vulkan(handle))
+        Ok(texture)
     }
 
-    fn import_texture(&self, handle: ApiTextureHandle, descriptor: &TextureDescriptor) -> Result<Box<dyn SharedTexture>> {
+    // Imports external memory and binds it to a freshly created image, falling back to a
+    // copy when the memory comes from another device
+    pub(crate) fn import_vulkan_texture(&self, handle: ApiTextureHandle, descriptor: &TextureDescriptor) -> Result<VulkanSharedTexture> {
         let vulkan_handle = match handle {
             ApiTextureHandle::Vulkan(h) => h,
+            ApiTextureHandle::DmaBuf(h) => return self.import_dma_buf_copy(h, descriptor),
+            #[allow(unreachable_patterns)]
             _ => return Err(GeyserError::InvalidTextureHandle),
         };
 
-        let vk_format = self.map_texture_format_to_vk(descriptor.format)?;
-        let (vk_usage, _) = self.map_texture_usage_to_vk(&descriptor.usage);
-
-        // Create the image first with external memory info
-        let mut external_memory_create_info = vk::ExternalMemoryImageCreateInfo {
-            s_type: vk::StructureType::EXTERNAL_MEMORY_IMAGE_CREATE_INFO,
-            p_next: std::ptr::null(),
-            handle_types: vulkan_handle.handle_type,
-            _marker: std::marker::PhantomData,
-        };
-
-        let image_create_info = vk::ImageCreateInfo {
-            s_type: vk::StructureType::IMAGE_CREATE_INFO,
-            p_next: &mut external_memory_create_info as *mut _ as *const std::ffi::c_void,
-            flags: vk::ImageCreateFlags::empty(),
-            image_type: vk::ImageType::TYPE_2D,
-            format: vk_format,
-            extent: vk::Extent3D {
-                width: descriptor.width,
-                height: descriptor.height,
-                depth: 1,
-            },
-            mip_levels: 1,
-            array_layers: 1,
-            samples: vk::SampleCountFlags::TYPE_1,
-            tiling: vk::ImageTiling::OPTIMAL,
-            usage: vk_usage,
-            sharing_mode: vk::SharingMode::EXCLUSIVE,
-            queue_family_index_count: 0,
-            p_queue_family_indices: std::ptr::null(),
-            initial_layout: vk::ImageLayout::UNDEFINED,
-            _marker: std::marker::PhantomData,
-        };
+        // Opaque handles are only meaningful on the same device and driver. A dma-buf can
+        // still be copied by another device, but only `ApiTextureHandle::DmaBuf` (see
+        // `export_dma_buf`) carries the offset, stride and modifier needed to read it
+        if !vulkan_handle.device_identity.is_compatible_with(&self.device_identity) {
+            if vulkan_handle.handle_type == vk::ExternalMemoryHandleTypeFlags::DMA_BUF_EXT {
+                return Err(GeyserError::Other(
+                    "Dma-bufs from another device must be shared as ApiTextureHandle::DmaBuf".to_string(),
+                ));
+            }
+            return Err(GeyserError::IncompatibleDevice {
+                exporter: vulkan_handle.device_identity,
+                importer: self.device_identity,
+            });
+        }
 
-        let image = unsafe { self.device.create_image(&image_create_info, None) }?;
+        // Create the image first with external memory info; its tiling must match the exporter's
+        let image = self.create_external_image(descriptor, vulkan_handle.handle_type, &vulkan_handle.plane_layouts)?;
 
         // Platform-specific import of external memory
         #[cfg(target_os = "windows")]
@@ -973,7 +1736,7 @@ impl TextureShareManager for VulkanTextureShareManager {
             let mut import_fd_info = vk::ImportMemoryFdInfoKHR {
                 s_type: vk::StructureType::IMPORT_MEMORY_FD_INFO_KHR,
                 p_next: std::ptr::null(),
-                handle_type: vk::ExternalMemoryHandleTypeFlags::OPAQUE_FD,
+                handle_type: vulkan_handle.handle_type,
                 fd: vulkan_handle.raw_handle as i32,
                 _marker: std::marker::PhantomData,
             };
@@ -1006,31 +1769,1528 @@ impl TextureShareManager for VulkanTextureShareManager {
         unsafe { self.device.bind_image_memory(image, imported_memory, 0)?; }
 
         // Store the imported memory to ensure its lifetime
-        self.exported_resources.lock().unwrap().insert(vulkan_handle.raw_handle, imported_memory);
+        self.imported_resources.lock().unwrap().insert(vulkan_handle.raw_handle, imported_memory);
 
-        Ok(Box::new(VulkanSharedTexture {
+        let subresource_layout = self.query_subresource_layout(image, descriptor.tiling);
+
+        Ok(VulkanSharedTexture {
             device: self.device.clone(),
             allocation: None, // No allocation managed by `gpu_allocator` here, it's externally imported
             image,
             image_view: None,
             descriptor: descriptor.clone(),
             exported_handle: Some(vulkan_handle),
-        }))
+            ready_semaphore: None,
+            ready_semaphore_is_timeline: false,
+            ready_fence: None,
+            transfer: None,
+            subresource_layout,
+            // The exporter released it in `SHARED_IMAGE_LAYOUT`
+            initialized: AtomicBool::new(true),
+        })
     }
 
-    fn release_texture_handle(&self, handle: ApiTextureHandle) -> Result<()> {
-        let raw_handle_key = match handle {
-            ApiTextureHandle::Vulkan(h) => h.raw_handle,
-            _ => return Err(GeyserError::InvalidTextureHandle),
+    // Imports a linear dma-buf, typically allocated on another GPU, into a staging buffer
+    // and copies it into a new local image. On success the importer owns the fd.
+    #[cfg(target_os = "linux")]
+    fn import_dma_buf_copy(&self, handle: DmaBufHandle, descriptor: &TextureDescriptor) -> Result<VulkanSharedTexture> {
+        if !self.dma_buf_import {
+            return Err(GeyserError::MissingExtensions(vec![
+                ash::ext::external_memory_dma_buf::NAME.to_string_lossy().into_owned(),
+            ]));
+        }
+        let layout = linear_dma_buf_layout(&handle, descriptor)?;
+
+        let mut fd_properties = vk::MemoryFdPropertiesKHR::default();
+        unsafe {
+            self.external_memory_fd.get_memory_fd_properties(
+                vk::ExternalMemoryHandleTypeFlags::DMA_BUF_EXT,
+                handle.fd,
+                &mut fd_properties,
+            )
+        }
+        .map_err(|e| GeyserError::VulkanApiError(format!("Failed to query dma-buf memory properties: {:?}", e)))?;
+
+        let mut external_memory_buffer_info = vk::ExternalMemoryBufferCreateInfo {
+            s_type: vk::StructureType::EXTERNAL_MEMORY_BUFFER_CREATE_INFO,
+            p_next: std::ptr::null(),
+            handle_types: vk::ExternalMemoryHandleTypeFlags::DMA_BUF_EXT,
+            _marker: std::marker::PhantomData,
+        };
+        let buffer_create_info = vk::BufferCreateInfo {
+            s_type: vk::StructureType::BUFFER_CREATE_INFO,
+            p_next: &mut external_memory_buffer_info as *mut _ as *const std::ffi::c_void,
+            flags: vk::BufferCreateFlags::empty(),
+            size: layout.size,
+            usage: vk::BufferUsageFlags::TRANSFER_SRC,
+            sharing_mode: vk::SharingMode::EXCLUSIVE,
+            queue_family_index_count: 0,
+            p_queue_family_indices: std::ptr::null(),
+            _marker: std::marker::PhantomData,
+        };
+        let buffer = unsafe { self.device.create_buffer(&buffer_create_info, None) }
+            .map_err(|e| GeyserError::VulkanApiError(format!("Failed to create dma-buf staging buffer: {:?}", e)))?;
+
+        let requirements = unsafe { self.device.get_buffer_memory_requirements(buffer) };
+        let memory_type_bits = requirements.memory_type_bits & fd_properties.memory_type_bits;
+        if memory_type_bits == 0 {
+            unsafe { self.device.destroy_buffer(buffer, None) };
+            return Err(GeyserError::VulkanApiError("No memory type can import the dma-buf".to_string()));
+        }
+
+        let mut import_fd_info = vk::ImportMemoryFdInfoKHR {
+            s_type: vk::StructureType::IMPORT_MEMORY_FD_INFO_KHR,
+            p_next: std::ptr::null(),
+            handle_type: vk::ExternalMemoryHandleTypeFlags::DMA_BUF_EXT,
+            fd: handle.fd,
+            _marker: std::marker::PhantomData,
+        };
+        let mut dedicated_alloc_info = vk::MemoryDedicatedAllocateInfo {
+            s_type: vk::StructureType::MEMORY_DEDICATED_ALLOCATE_INFO,
+            p_next: &mut import_fd_info as *mut _ as *const std::ffi::c_void,
+            image: vk::Image::null(),
+            buffer,
+            _marker: std::marker::PhantomData,
+        };
+        let alloc_info = vk::MemoryAllocateInfo {
+            s_type: vk::StructureType::MEMORY_ALLOCATE_INFO,
+            p_next: &mut dedicated_alloc_info as *mut _ as *const std::ffi::c_void,
+            allocation_size: requirements.size,
+            memory_type_index: memory_type_bits.trailing_zeros(),
+            _marker: std::marker::PhantomData,
+        };
+        let memory = match unsafe { self.device.allocate_memory(&alloc_info, None) } {
+            Ok(memory) => memory,
+            Err(e) => {
+                unsafe { self.device.destroy_buffer(buffer, None) };
+                return Err(GeyserError::VulkanApiError(format!("Failed to import dma-buf memory: {:?}", e)));
+            }
+        };
+        let free_staging = || unsafe {
+            self.device.destroy_buffer(buffer, None);
+            self.device.free_memory(memory, None);
+        };
+        if let Err(e) = unsafe { self.device.bind_buffer_memory(buffer, memory, 0) } {
+            free_staging();
+            return Err(GeyserError::VulkanApiError(format!("Failed to bind dma-buf memory: {:?}", e)));
+        }
+
+        // The local copy is itself exportable, so it can be shared on this device. The
+        // requested tiling and memory describe the dma-buf, not the copy.
+        let mut local_descriptor = TextureDescriptor {
+            tiling: TextureTiling::Optimal,
+            memory_location: TextureMemoryLocation::GpuOnly,
+            ..descriptor.clone()
+        };
+        if !local_descriptor.usage.contains(&TextureUsage::CopyDst) {
+            local_descriptor.usage.push(TextureUsage::CopyDst);
+        }
+        let mut texture = match self.create_vulkan_texture(&local_descriptor) {
+            Ok(texture) => texture,
+            Err(e) => {
+                free_staging();
+                return Err(e);
+            }
+        };
+
+        let region = vk::BufferImageCopy {
+            buffer_offset: layout.offset,
+            buffer_row_length: layout.row_length,
+            buffer_image_height: 0,
+            image_subresource: vk::ImageSubresourceLayers {
+                aspect_mask: vk::ImageAspectFlags::COLOR,
+                mip_level: 0,
+                base_array_layer: 0,
+                layer_count: 1,
+            },
+            image_offset: vk::Offset3D::default(),
+            image_extent: vk::Extent3D {
+                width: descriptor.width,
+                height: descriptor.height,
+                depth: 1,
+            },
+        };
+        let transfer = match self.create_copy_transfer(buffer, memory, texture.image, region) {
+            Ok(transfer) => transfer,
+            Err(e) => {
+                free_staging();
+                self.free_texture_allocation(texture);
+                return Err(e);
+            }
         };
+        texture.transfer = Some(transfer);
 
-        if let Some(memory) = self.exported_resources.lock().unwrap().remove(&raw_handle_key) {
+        // Fill the image with the exporter's current contents
+        if let Err(e) = self.refresh_copied_texture(&texture) {
+            self.free_texture_allocation(texture);
+            return Err(e);
+        }
+        Ok(texture)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn import_dma_buf_copy(&self, _handle: DmaBufHandle, _descriptor: &TextureDescriptor) -> Result<VulkanSharedTexture> {
+        Err(GeyserError::OperationNotSupported)
+    }
+
+    /// Imports a single-plane dma-buf allocated on this device without copying it, as an
+    /// image with the handle's DRM modifier. The tiling in `descriptor` is ignored.
+    ///
+    /// The texture reads the exporter's memory directly, so the exporter must not write
+    /// to it while the texture is in use. On success the importer owns the fd; destroy the
+    /// texture with `destroy_texture` and the `ApiTextureHandle::Vulkan` it is tracked
+    /// under (`VulkanTextureShareHandle::raw_handle` is the fd). Requires
+    /// `VK_EXT_external_memory_dma_buf` and `VK_EXT_image_drm_format_modifier`; see
+    /// `supported_drm_modifiers` for the modifiers that can be imported.
+    #[cfg(target_os = "linux")]
+    pub fn import_dma_buf(&self, handle: DmaBufHandle, descriptor: &TextureDescriptor) -> Result<VulkanSharedTexture> {
+        if !self.dma_buf_import {
+            return Err(GeyserError::MissingExtensions(vec![
+                ash::ext::external_memory_dma_buf::NAME.to_string_lossy().into_owned(),
+            ]));
+        }
+        if handle.modifier == DRM_FORMAT_MOD_INVALID {
+            return Err(GeyserError::UnsupportedFormat(
+                "dma-bufs with an implicit layout cannot be imported by Vulkan".to_string(),
+            ));
+        }
+        let descriptor = TextureDescriptor {
+            tiling: TextureTiling::DrmModifier(handle.modifier),
+            ..descriptor.clone()
+        };
+        let plane_layout = vk::SubresourceLayout {
+            offset: handle.offset as u64,
+            size: 0,
+            row_pitch: handle.stride as u64,
+            array_pitch: 0,
+            depth_pitch: 0,
+        };
+
+        let mut fd_properties = vk::MemoryFdPropertiesKHR::default();
+        unsafe {
+            self.external_memory_fd.get_memory_fd_properties(
+                vk::ExternalMemoryHandleTypeFlags::DMA_BUF_EXT,
+                handle.fd,
+                &mut fd_properties,
+            )
+        }
+        .map_err(|e| GeyserError::VulkanApiError(format!("Failed to query dma-buf memory properties: {:?}", e)))?;
+
+        let image = self.create_external_image(
+            &descriptor,
+            vk::ExternalMemoryHandleTypeFlags::DMA_BUF_EXT,
+            std::slice::from_ref(&plane_layout),
+        )?;
+        let requirements = unsafe { self.device.get_image_memory_requirements(image) };
+        let memory_type_bits = requirements.memory_type_bits & fd_properties.memory_type_bits;
+        if memory_type_bits == 0 {
+            unsafe { self.device.destroy_image(image, None) };
+            return Err(GeyserError::VulkanApiError("No memory type can import the dma-buf".to_string()));
+        }
+
+        let mut import_fd_info = vk::ImportMemoryFdInfoKHR {
+            s_type: vk::StructureType::IMPORT_MEMORY_FD_INFO_KHR,
+            p_next: std::ptr::null(),
+            handle_type: vk::ExternalMemoryHandleTypeFlags::DMA_BUF_EXT,
+            fd: handle.fd,
+            _marker: std::marker::PhantomData,
+        };
+        let mut dedicated_alloc_info = vk::MemoryDedicatedAllocateInfo {
+            s_type: vk::StructureType::MEMORY_DEDICATED_ALLOCATE_INFO,
+            p_next: &mut import_fd_info as *mut _ as *const std::ffi::c_void,
+            image,
+            buffer: vk::Buffer::null(),
+            _marker: std::marker::PhantomData,
+        };
+        let alloc_info = vk::MemoryAllocateInfo {
+            s_type: vk::StructureType::MEMORY_ALLOCATE_INFO,
+            p_next: &mut dedicated_alloc_info as *mut _ as *const std::ffi::c_void,
+            allocation_size: requirements.size,
+            memory_type_index: memory_type_bits.trailing_zeros(),
+            _marker: std::marker::PhantomData,
+        };
+        let memory = match unsafe { self.device.allocate_memory(&alloc_info, None) } {
+            Ok(memory) => memory,
+            Err(e) => {
+                unsafe { self.device.destroy_image(image, None) };
+                return Err(GeyserError::VulkanApiError(format!("Failed to import dma-buf memory: {:?}", e)));
+            }
+        };
+        if let Err(e) = unsafe { self.device.bind_image_memory(image, memory, 0) } {
             unsafe {
+                self.device.destroy_image(image, None);
                 self.device.free_memory(memory, None);
             }
+            return Err(GeyserError::VulkanApiError(format!("Failed to bind dma-buf memory: {:?}", e)));
+        }
+
+        // Tracked like other imports, so `destroy_texture` frees the memory
+        let vulkan_handle = VulkanTextureShareHandle {
+            raw_handle: handle.fd as u64,
+            memory_type_index: alloc_info.memory_type_index,
+            size: requirements.size,
+            handle_type: vk::ExternalMemoryHandleTypeFlags::DMA_BUF_EXT,
+            dedicated_allocation: true,
+            device_identity: self.device_identity,
+            plane_layouts: vec![plane_layout],
+        };
+        self.imported_resources.lock().unwrap().insert(vulkan_handle.raw_handle, memory);
+
+        Ok(VulkanSharedTexture {
+            device: self.device.clone(),
+            allocation: None,
+            image,
+            image_view: None,
+            subresource_layout: self.query_subresource_layout(image, descriptor.tiling),
+            descriptor,
+            exported_handle: Some(vulkan_handle),
+            ready_semaphore: None,
+            ready_semaphore_is_timeline: false,
+            ready_fence: None,
+            transfer: None,
+            initialized: AtomicBool::new(true),
+        })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn import_dma_buf(&self, _handle: DmaBufHandle, _descriptor: &TextureDescriptor) -> Result<VulkanSharedTexture> {
+        Err(GeyserError::OperationNotSupported)
+    }
+
+    // Creates the command buffer and fence that copy `buffer` into `image`, recording the
+    // copy between a discard of the old contents and a transition to `SHARED_IMAGE_LAYOUT`
+    fn create_copy_transfer(
+        &self,
+        buffer: vk::Buffer,
+        memory: vk::DeviceMemory,
+        image: vk::Image,
+        region: vk::BufferImageCopy,
+    ) -> Result<CopyTransfer> {
+        let pool_info = vk::CommandPoolCreateInfo {
+            s_type: vk::StructureType::COMMAND_POOL_CREATE_INFO,
+            p_next: std::ptr::null(),
+            flags: vk::CommandPoolCreateFlags::empty(),
+            queue_family_index: self.queue_family_index,
+            _marker: std::marker::PhantomData,
+        };
+        let command_pool = unsafe { self.device.create_command_pool(&pool_info, None) }
+            .map_err(|e| GeyserError::VulkanApiError(format!("Failed to create command pool: {:?}", e)))?;
+
+        let fence_info = vk::FenceCreateInfo {
+            s_type: vk::StructureType::FENCE_CREATE_INFO,
+            p_next: std::ptr::null(),
+            flags: vk::FenceCreateFlags::empty(),
+            _marker: std::marker::PhantomData,
+        };
+        let fence = match unsafe { self.device.create_fence(&fence_info, None) } {
+            Ok(fence) => fence,
+            Err(e) => {
+                unsafe { self.device.destroy_command_pool(command_pool, None) };
+                return Err(GeyserError::VulkanApiError(format!("Failed to create copy fence: {:?}", e)));
+            }
+        };
+
+        let recorded = self.record_buffer_to_image_copy(command_pool, buffer, image, region);
+        match recorded {
+            Ok(command_buffer) => Ok(CopyTransfer {
+                buffer,
+                memory,
+                command_pool,
+                command_buffer,
+                fence,
+            }),
+            Err(e) => {
+                unsafe {
+                    self.device.destroy_fence(fence, None);
+                    self.device.destroy_command_pool(command_pool, None);
+                }
+                Err(e)
+            }
+        }
+    }
+
+    fn record_buffer_to_image_copy(
+        &self,
+        command_pool: vk::CommandPool,
+        buffer: vk::Buffer,
+        image: vk::Image,
+        region: vk::BufferImageCopy,
+    ) -> Result<vk::CommandBuffer> {
+        let allocate_info = vk::CommandBufferAllocateInfo {
+            s_type: vk::StructureType::COMMAND_BUFFER_ALLOCATE_INFO,
+            p_next: std::ptr::null(),
+            command_pool,
+            level: vk::CommandBufferLevel::PRIMARY,
+            command_buffer_count: 1,
+            _marker: std::marker::PhantomData,
+        };
+        let command_buffer = unsafe { self.device.allocate_command_buffers(&allocate_info) }
+            .map_err(|e| GeyserError::VulkanApiError(format!("Failed to allocate command buffers: {:?}", e)))?[0];
+
+        let begin_info = vk::CommandBufferBeginInfo {
+            s_type: vk::StructureType::COMMAND_BUFFER_BEGIN_INFO,
+            p_next: std::ptr::null(),
+            flags: vk::CommandBufferUsageFlags::empty(),
+            p_inheritance_info: std::ptr::null(),
+            _marker: std::marker::PhantomData,
+        };
+        let subresource_range = vk::ImageSubresourceRange {
+            aspect_mask: vk::ImageAspectFlags::COLOR,
+            base_mip_level: 0,
+            level_count: 1,
+            base_array_layer: 0,
+            layer_count: 1,
+        };
+        // The whole image is overwritten, so its previous contents are discarded
+        let to_transfer_dst = vk::ImageMemoryBarrier {
+            s_type: vk::StructureType::IMAGE_MEMORY_BARRIER,
+            p_next: std::ptr::null(),
+            src_access_mask: vk::AccessFlags::empty(),
+            dst_access_mask: vk::AccessFlags::TRANSFER_WRITE,
+            old_layout: vk::ImageLayout::UNDEFINED,
+            new_layout: vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+            src_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+            dst_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+            image,
+            subresource_range,
+            _marker: std::marker::PhantomData,
+        };
+        let to_shared = vk::ImageMemoryBarrier {
+            src_access_mask: vk::AccessFlags::TRANSFER_WRITE,
+            dst_access_mask: vk::AccessFlags::MEMORY_READ | vk::AccessFlags::MEMORY_WRITE,
+            old_layout: vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+            new_layout: SHARED_IMAGE_LAYOUT,
+            ..to_transfer_dst
+        };
+
+        unsafe {
+            self.device.begin_command_buffer(command_buffer, &begin_info)?;
+            self.device.cmd_pipeline_barrier(
+                command_buffer,
+                vk::PipelineStageFlags::ALL_COMMANDS,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[],
+                &[to_transfer_dst],
+            );
+            self.device.cmd_copy_buffer_to_image(
+                command_buffer,
+                buffer,
+                image,
+                vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                &[region],
+            );
+            self.device.cmd_pipeline_barrier(
+                command_buffer,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::PipelineStageFlags::ALL_COMMANDS,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[],
+                &[to_shared],
+            );
+            self.device.end_command_buffer(command_buffer)?;
+        }
+        Ok(command_buffer)
+    }
+
+    // Returns the memory of a texture created by `create_vulkan_texture` to the allocator
+    fn free_texture_allocation(&self, mut texture: VulkanSharedTexture) {
+        if let Some(allocation) = texture.allocation.take() {
+            drop(texture);
+            let _ = self.allocator.lock().unwrap().free(allocation);
+        }
+    }
+
+    /// Copies the exporter's current contents into a texture imported with
+    /// `SharingStrategy::Copy`. Does nothing for zero-copy textures.
+    ///
+    /// Call this once the exporter has finished writing a frame, e.g. after waiting on the
+    /// texture's sync primitives. The copy runs on the manager's queue and this call blocks
+    /// until it completes, leaving the image in `SHARED_IMAGE_LAYOUT`.
+    pub fn refresh_copied_texture(&self, texture: &VulkanSharedTexture) -> Result<()> {
+        let Some(transfer) = &texture.transfer else {
+            return Ok(());
+        };
+
+        let submit_info = vk::SubmitInfo {
+            s_type: vk::StructureType::SUBMIT_INFO,
+            p_next: std::ptr::null(),
+            wait_semaphore_count: 0,
+            p_wait_semaphores: std::ptr::null(),
+            p_wait_dst_stage_mask: std::ptr::null(),
+            command_buffer_count: 1,
+            p_command_buffers: &transfer.command_buffer,
+            signal_semaphore_count: 0,
+            p_signal_semaphores: std::ptr::null(),
+            _marker: std::marker::PhantomData,
+        };
+
+        unsafe {
+            self.device.reset_fences(&[transfer.fence])
+                .map_err(|e| GeyserError::VulkanApiError(format!("Failed to reset copy fence: {:?}", e)))?;
         }
+        self.submit_to_queue(&[submit_info], transfer.fence)
+            .map_err(|e| GeyserError::VulkanApiError(format!("Failed to submit texture copy: {:?}", e)))?;
+        self.wait_for_transfer(transfer.fence)
+            .map_err(|e| GeyserError::VulkanApiError(format!("Failed to wait for texture copy: {:?}", e)))?;
+        texture.initialized.store(true, Ordering::Release);
         Ok(())
     }
+
+    /// Exports a linear or DRM-modifier texture created by this manager as a dma-buf.
+    ///
+    /// Unlike `export_texture`, the result can be imported on other GPUs (by copy, see
+    /// `SharingStrategy::Copy`) and by other APIs such as EGL. Requires
+    /// `VK_EXT_external_memory_dma_buf`. The caller owns the returned fd.
+    #[cfg(target_os = "linux")]
+    pub fn export_dma_buf(&self, texture: &VulkanSharedTexture) -> Result<DmaBufHandle> {
+        let modifier = match texture.descriptor.tiling {
+            TextureTiling::Optimal => {
+                return Err(GeyserError::Other("Optimal-tiling textures cannot be exported as dma-bufs".to_string()));
+            }
+            TextureTiling::Linear => DRM_FORMAT_MOD_LINEAR,
+            TextureTiling::DrmModifier(modifier) => modifier,
+        };
+        if !self.dma_buf_import {
+            return Err(GeyserError::MissingExtensions(vec![
+                ash::ext::external_memory_dma_buf::NAME.to_string_lossy().into_owned(),
+            ]));
+        }
+        let fourcc = to_drm_fourcc(texture.descriptor.format)?;
+        let (allocation, layout) = match (&texture.allocation, texture.subresource_layout) {
+            (Some(allocation), Some(layout)) => (allocation, layout),
+            _ => return Err(GeyserError::Other("Texture has no allocation to export".to_string())),
+        };
+
+        let get_fd_info = vk::MemoryGetFdInfoKHR {
+            s_type: vk::StructureType::MEMORY_GET_FD_INFO_KHR,
+            p_next: std::ptr::null(),
+            memory: unsafe { allocation.memory() },
+            handle_type: vk::ExternalMemoryHandleTypeFlags::DMA_BUF_EXT,
+            _marker: std::marker::PhantomData,
+        };
+        let fd = unsafe { self.external_memory_fd.get_memory_fd(&get_fd_info) }
+            .map_err(|e| GeyserError::VulkanApiError(format!("Failed to get dma-buf FD: {:?}", e)))?;
+
+        Ok(DmaBufHandle {
+            fd,
+            fourcc,
+            modifier,
+            offset: (allocation.offset() + layout.offset) as u32,
+            stride: layout.row_pitch as u32,
+        })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn export_dma_buf(&self, _texture: &VulkanSharedTexture) -> Result<DmaBufHandle> {
+        Err(GeyserError::OperationNotSupported)
+    }
+
+    // --- Texture + Sync Bundling ---
+
+    // Exports a semaphore owned by a `VulkanSharedTexture`.
+    // Unlike `export_semaphore_*`, the semaphore is not tracked in `exported_semaphores`
+    // because the texture destroys it on drop.
+    fn export_texture_semaphore(&self, semaphore: vk::Semaphore, is_timeline: bool) -> Result<VulkanSemaphoreHandle> {
+        #[cfg(target_os = "linux")]
+        {
+            let get_fd_info = vk::SemaphoreGetFdInfoKHR {
+                s_type: vk::StructureType::SEMAPHORE_GET_FD_INFO_KHR,
+                p_next: std::ptr::null(),
+                semaphore,
+                handle_type: vk::ExternalSemaphoreHandleTypeFlags::OPAQUE_FD,
+                _marker: std::marker::PhantomData,
+            };
+
+            let raw_handle = unsafe {
+                self.external_semaphore_fd
+                    .get_semaphore_fd(&get_fd_info)
+                    .map(|fd| fd as u64)
+                    .map_err(|e| GeyserError::VulkanApiError(format!("Failed to export texture semaphore: {:?}", e)))?
+            };
+
+            Ok(VulkanSemaphoreHandle {
+                raw_handle,
+                handle_type: vk::ExternalSemaphoreHandleTypeFlags::OPAQUE_FD,
+                is_timeline,
+            })
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            let get_handle_info = vk::SemaphoreGetWin32HandleInfoKHR {
+                s_type: vk::StructureType::SEMAPHORE_GET_WIN32_HANDLE_INFO_KHR,
+                p_next: std::ptr::null(),
+                semaphore,
+                handle_type: vk::ExternalSemaphoreHandleTypeFlags::OPAQUE_WIN32,
+                _marker: std::marker::PhantomData,
+            };
+
+            let raw_handle = unsafe {
+                self.external_semaphore_win32
+                    .get_semaphore_win32_handle(&get_handle_info)
+                    .map(|h| h as u64)
+                    .map_err(|e| GeyserError::VulkanApiError(format!("Failed to export texture semaphore: {:?}", e)))?
+            };
+
+            Ok(VulkanSemaphoreHandle {
+                raw_handle,
+                handle_type: vk::ExternalSemaphoreHandleTypeFlags::OPAQUE_WIN32,
+                is_timeline,
+            })
+        }
+
+        #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+        {
+            let _ = (semaphore, is_timeline);
+            Err(GeyserError::OperationNotSupported)
+        }
+    }
+
+    // Exports a fence owned by a `VulkanSharedTexture` without tracking it in `exported_fences`.
+    fn export_texture_fence(&self, fence: vk::Fence) -> Result<VulkanFenceHandle> {
+        #[cfg(target_os = "linux")]
+        {
+            let get_fd_info = vk::FenceGetFdInfoKHR {
+                s_type: vk::StructureType::FENCE_GET_FD_INFO_KHR,
+                p_next: std::ptr::null(),
+                fence,
+                handle_type: vk::ExternalFenceHandleTypeFlags::OPAQUE_FD,
+                _marker: std::marker::PhantomData,
+            };
+
+            let raw_handle = unsafe {
+                self.external_fence_fd
+                    .get_fence_fd(&get_fd_info)
+                    .map(|fd| fd as u64)
+                    .map_err(|e| GeyserError::VulkanApiError(format!("Failed to export texture fence: {:?}", e)))?
+            };
+
+            Ok(VulkanFenceHandle {
+                raw_handle,
+                handle_type: vk::ExternalFenceHandleTypeFlags::OPAQUE_FD,
+            })
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            let get_handle_info = vk::FenceGetWin32HandleInfoKHR {
+                s_type: vk::StructureType::FENCE_GET_WIN32_HANDLE_INFO_KHR,
+                p_next: std::ptr::null(),
+                fence,
+                handle_type: vk::ExternalFenceHandleTypeFlags::OPAQUE_WIN32,
+                _marker: std::marker::PhantomData,
+            };
+
+            let raw_handle = unsafe {
+                self.external_fence_win32
+                    .get_fence_win32_handle(&get_handle_info)
+                    .map(|h| h as u64)
+                    .map_err(|e| GeyserError::VulkanApiError(format!("Failed to export texture fence: {:?}", e)))?
+            };
+
+            Ok(VulkanFenceHandle {
+                raw_handle,
+                handle_type: vk::ExternalFenceHandleTypeFlags::OPAQUE_WIN32,
+            })
+        }
+
+        #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+        {
+            let _ = fence;
+            Err(GeyserError::OperationNotSupported)
+        }
+    }
+
+    // Imports a semaphore handle from a `TextureSharePackage`, honouring its timeline flag
+    fn import_texture_semaphore(&self, handle: &VulkanSemaphoreHandle) -> Result<vk::Semaphore> {
+        #[cfg(target_os = "linux")]
+        {
+            if handle.is_timeline {
+                self.import_timeline_semaphore_fd(handle, 0)
+            } else {
+                self.import_semaphore_fd(handle)
+            }
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            if handle.is_timeline {
+                self.import_timeline_semaphore_win32(handle, 0)
+            } else {
+                self.import_semaphore_win32(handle)
+            }
+        }
+
+        #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+        {
+            let _ = handle;
+            Err(GeyserError::OperationNotSupported)
+        }
+    }
+
+    // Imports a fence handle from a `TextureSharePackage`
+    fn import_texture_fence(&self, handle: &VulkanFenceHandle) -> Result<vk::Fence> {
+        #[cfg(target_os = "linux")]
+        {
+            self.import_fence_fd(handle)
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            self.import_fence_win32(handle)
+        }
+
+        #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+        {
+            let _ = handle;
+            Err(GeyserError::OperationNotSupported)
+        }
+    }
+
+    // Imports external memory along with the package's semaphore and fence
+    pub(crate) fn import_vulkan_texture_with_sync(&self, package: TextureSharePackage, descriptor: &TextureDescriptor) -> Result<VulkanSharedTexture> {
+        // Import the sync primitives first so a failure doesn't leave imported memory behind
+        let semaphore = match &package.sync.semaphore {
+            Some(SyncHandle::VulkanSemaphore(handle)) => Some((self.import_texture_semaphore(handle)?, handle.is_timeline)),
+            Some(_) => return Err(GeyserError::InvalidTextureHandle),
+            None => None,
+        };
+        let fence = match &package.sync.fence {
+            Some(SyncHandle::VulkanFence(handle)) => match self.import_texture_fence(handle) {
+                Ok(fence) => Some(fence),
+                Err(e) => {
+                    if let Some((semaphore, _)) = semaphore {
+                        unsafe { self.device.destroy_semaphore(semaphore, None) };
+                    }
+                    return Err(e);
+                }
+            },
+            Some(_) => return Err(GeyserError::InvalidTextureHandle),
+            None => None,
+        };
+
+        let mut texture = match self.import_vulkan_texture(package.texture, descriptor) {
+            Ok(texture) => texture,
+            Err(e) => {
+                unsafe {
+                    if let Some((semaphore, _)) = semaphore {
+                        self.device.destroy_semaphore(semaphore, None);
+                    }
+                    if let Some(fence) = fence {
+                        self.device.destroy_fence(fence, None);
+                    }
+                }
+                return Err(e);
+            }
+        };
+
+        if let Some((semaphore, is_timeline)) = semaphore {
+            texture.ready_semaphore = Some(semaphore);
+            texture.ready_semaphore_is_timeline = is_timeline;
+        }
+        texture.ready_fence = fence;
+
+        Ok(texture)
+    }
+
+    /// Destroys a texture created or imported by this manager and frees its memory.
+    ///
+    /// `handle` is the handle the texture was exported as or imported from; the manager
+    /// stops tracking it, along with any other export of the same texture. The texture
+    /// must no longer be in use on the GPU.
+    pub fn destroy_texture(&self, texture: VulkanSharedTexture, handle: ApiTextureHandle) -> Result<()> {
+        if texture.allocation.is_some() {
+            return self.destroy_owned_texture(texture);
+        }
+        // Imported memory is tracked under the handle; copied textures free theirs on drop
+        drop(texture);
+        self.release_texture_handle(handle)
+    }
+
+    // Destroys a texture whose memory came from the allocator, untracking every export of
+    // that memory. Textures without an allocation are only dropped.
+    pub(crate) fn destroy_owned_texture(&self, mut texture: VulkanSharedTexture) -> Result<()> {
+        let Some(allocation) = texture.allocation.take() else {
+            return Ok(());
+        };
+        drop(texture);
+
+        // Exported memory is a dedicated allocation owned by the allocator, so it is only untracked
+        let memory = unsafe { allocation.memory() };
+        self.exported_resources.lock().unwrap().retain(|_, exported| *exported != memory);
+        self.allocator.lock().unwrap().free(allocation)
+            .map_err(|e| GeyserError::VulkanApiError(format!("Failed to free image memory: {}", e)))
+    }
+
+    // --- Device-Side Submission ---
+
+    /// Returns the queue used by `submit_shared_frame`.
+    pub fn queue(&self) -> vk::Queue {
+        self.queue
+    }
+
+    /// Returns the lock the manager holds while submitting to its queue. Code that submits
+    /// to the same `VkQueue` must hold it too.
+    pub fn queue_lock(&self) -> &Arc<Mutex<()>> {
+        &self.queue_lock
+    }
+
+    /// Makes the manager hold `lock` instead of its own while submitting, for devices
+    /// whose queue is already guarded by a lock elsewhere in the application.
+    pub fn with_queue_lock(mut self, lock: Arc<Mutex<()>>) -> Self {
+        self.queue_lock = lock;
+        self
+    }
+
+    // Every `vkQueueSubmit` of the manager goes through here so the queue lock is held
+    pub(crate) fn submit_to_queue(&self, submits: &[vk::SubmitInfo], fence: vk::Fence) -> ash::prelude::VkResult<()> {
+        let _queue = self.queue_lock.lock().unwrap();
+        unsafe { self.device.queue_submit(self.queue, submits, fence) }
+    }
+
+    // Waits for a copy or blit submitted with `fence`, without holding the queue lock
+    pub(crate) fn wait_for_transfer(&self, fence: vk::Fence) -> ash::prelude::VkResult<()> {
+        unsafe { self.device.wait_for_fences(&[fence], true, TRANSFER_TIMEOUT_NS) }
+    }
+
+    /// Returns the queue family index this manager submits on.
+    pub fn queue_family_index(&self) -> u32 {
+        self.queue_family_index
+    }
+
+    /// Signals a timeline semaphore to `value` once all work previously submitted to the
+    /// manager's queue has completed.
+    ///
+    /// Unlike `submit_shared_frame` this needs neither synchronization2 nor ownership
+    /// transfers, so it suits textures rendered by another API on the same queue.
+    pub fn queue_signal_semaphore(&self, semaphore: vk::Semaphore, value: u64) -> Result<()> {
+        self.ensure_timeline_semaphores()?;
+
+        let timeline_info = vk::TimelineSemaphoreSubmitInfo {
+            s_type: vk::StructureType::TIMELINE_SEMAPHORE_SUBMIT_INFO,
+            p_next: std::ptr::null(),
+            wait_semaphore_value_count: 0,
+            p_wait_semaphore_values: std::ptr::null(),
+            signal_semaphore_value_count: 1,
+            p_signal_semaphore_values: &value,
+            _marker: std::marker::PhantomData,
+        };
+        let submit_info = vk::SubmitInfo {
+            s_type: vk::StructureType::SUBMIT_INFO,
+            p_next: &timeline_info as *const _ as *const std::ffi::c_void,
+            wait_semaphore_count: 0,
+            p_wait_semaphores: std::ptr::null(),
+            p_wait_dst_stage_mask: std::ptr::null(),
+            command_buffer_count: 0,
+            p_command_buffers: std::ptr::null(),
+            signal_semaphore_count: 1,
+            p_signal_semaphores: &semaphore,
+            _marker: std::marker::PhantomData,
+        };
+
+        self.submit_to_queue(&[submit_info], vk::Fence::null())
+            .map_err(|e| GeyserError::VulkanApiError(format!("Failed to submit semaphore signal: {:?}", e)))
+    }
+
+    /// Makes all work submitted to the manager's queue after this call wait until a
+    /// timeline semaphore reaches `value`.
+    ///
+    /// The counterpart of `queue_signal_semaphore` for textures read by another API on
+    /// the same queue; like it, this needs neither synchronization2 nor ownership transfers.
+    pub fn queue_wait_semaphore(&self, semaphore: vk::Semaphore, value: u64) -> Result<()> {
+        self.ensure_timeline_semaphores()?;
+
+        let mut state_guard = self.submit_state.lock().unwrap();
+        if state_guard.is_none() {
+            *state_guard = Some(self.create_submit_state()?);
+        }
+        let state = state_guard.as_mut().unwrap();
+
+        // Also signals the internal timeline so dropping the manager waits for the barrier
+        let submit_value = state.next_value;
+        let signal_semaphores = [state.timeline];
+        let timeline_info = vk::TimelineSemaphoreSubmitInfo {
+            s_type: vk::StructureType::TIMELINE_SEMAPHORE_SUBMIT_INFO,
+            p_next: std::ptr::null(),
+            wait_semaphore_value_count: 1,
+            p_wait_semaphore_values: &value,
+            signal_semaphore_value_count: 1,
+            p_signal_semaphore_values: &submit_value,
+            _marker: std::marker::PhantomData,
+        };
+        let wait_stage = vk::PipelineStageFlags::ALL_COMMANDS;
+        let submit_info = vk::SubmitInfo {
+            s_type: vk::StructureType::SUBMIT_INFO,
+            p_next: &timeline_info as *const _ as *const std::ffi::c_void,
+            wait_semaphore_count: 1,
+            p_wait_semaphores: &semaphore,
+            p_wait_dst_stage_mask: &wait_stage,
+            command_buffer_count: 1,
+            p_command_buffers: &state.wait_barrier,
+            signal_semaphore_count: 1,
+            p_signal_semaphores: signal_semaphores.as_ptr(),
+            _marker: std::marker::PhantomData,
+        };
+
+        self.submit_to_queue(&[submit_info], vk::Fence::null())
+            .map_err(|e| GeyserError::VulkanApiError(format!("Failed to submit semaphore wait: {:?}", e)))?;
+        state.next_value += 1;
+        Ok(())
+    }
+
+    /// Releases a color image from `layout` on the manager's queue family to
+    /// `VK_QUEUE_FAMILY_EXTERNAL` in `SHARED_IMAGE_LAYOUT`, then signals a timeline
+    /// semaphore to `value`, once all work previously submitted to the queue has completed.
+    ///
+    /// Like `queue_signal_semaphore` this suits images rendered by another API on the same
+    /// queue, but also hands them over in the layout `submit_shared_frame` consumers expect.
+    pub fn queue_release_image(&self, image: vk::Image, layout: vk::ImageLayout, semaphore: vk::Semaphore, value: u64) -> Result<()> {
+        self.submit_ownership_transfer(image, layout, false, Some((semaphore, value)))
+    }
+
+    /// Acquires a color image released by `queue_release_image` (or by another process)
+    /// from `VK_QUEUE_FAMILY_EXTERNAL`, transitioning it from `SHARED_IMAGE_LAYOUT` to
+    /// `layout` before any work submitted to the manager's queue after this call.
+    pub fn queue_acquire_image(&self, image: vk::Image, layout: vk::ImageLayout) -> Result<()> {
+        self.submit_ownership_transfer(image, layout, true, None)
+    }
+
+    // Submits a single queue family ownership transfer of `image` between the manager's
+    // queue family and `VK_QUEUE_FAMILY_EXTERNAL`, optionally signalling `signal` after it
+    fn submit_ownership_transfer(
+        &self,
+        image: vk::Image,
+        layout: vk::ImageLayout,
+        acquire: bool,
+        signal: Option<(vk::Semaphore, u64)>,
+    ) -> Result<()> {
+        self.ensure_timeline_semaphores()?;
+
+        let (old_layout, new_layout, src_queue_family_index, dst_queue_family_index) = if acquire {
+            (SHARED_IMAGE_LAYOUT, layout, vk::QUEUE_FAMILY_EXTERNAL, self.queue_family_index)
+        } else {
+            (layout, SHARED_IMAGE_LAYOUT, self.queue_family_index, vk::QUEUE_FAMILY_EXTERNAL)
+        };
+        let barrier = vk::ImageMemoryBarrier {
+            s_type: vk::StructureType::IMAGE_MEMORY_BARRIER,
+            p_next: std::ptr::null(),
+            src_access_mask: if acquire { vk::AccessFlags::empty() } else { vk::AccessFlags::MEMORY_WRITE },
+            dst_access_mask: if acquire {
+                vk::AccessFlags::MEMORY_READ | vk::AccessFlags::MEMORY_WRITE
+            } else {
+                vk::AccessFlags::empty()
+            },
+            old_layout,
+            new_layout,
+            src_queue_family_index,
+            dst_queue_family_index,
+            image,
+            subresource_range: vk::ImageSubresourceRange {
+                aspect_mask: vk::ImageAspectFlags::COLOR,
+                base_mip_level: 0,
+                level_count: 1,
+                base_array_layer: 0,
+                layer_count: 1,
+            },
+            _marker: std::marker::PhantomData,
+        };
+        let (src_stage, dst_stage) = if acquire {
+            (vk::PipelineStageFlags::TOP_OF_PIPE, vk::PipelineStageFlags::ALL_COMMANDS)
+        } else {
+            (vk::PipelineStageFlags::ALL_COMMANDS, vk::PipelineStageFlags::BOTTOM_OF_PIPE)
+        };
+
+        let mut state_guard = self.submit_state.lock().unwrap();
+        if state_guard.is_none() {
+            *state_guard = Some(self.create_submit_state()?);
+        }
+        let state = state_guard.as_mut().unwrap();
+        self.recycle_submit_command_buffers(state)?;
+
+        let allocate_info = vk::CommandBufferAllocateInfo {
+            s_type: vk::StructureType::COMMAND_BUFFER_ALLOCATE_INFO,
+            p_next: std::ptr::null(),
+            command_pool: state.command_pool,
+            level: vk::CommandBufferLevel::PRIMARY,
+            command_buffer_count: 1,
+            _marker: std::marker::PhantomData,
+        };
+        let command_buffer = unsafe { self.device.allocate_command_buffers(&allocate_info) }
+            .map_err(|e| GeyserError::VulkanApiError(format!("Failed to allocate command buffers: {:?}", e)))?[0];
+
+        let begin_info = vk::CommandBufferBeginInfo {
+            s_type: vk::StructureType::COMMAND_BUFFER_BEGIN_INFO,
+            p_next: std::ptr::null(),
+            flags: vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT,
+            p_inheritance_info: std::ptr::null(),
+            _marker: std::marker::PhantomData,
+        };
+        let recorded = unsafe {
+            self.device.begin_command_buffer(command_buffer, &begin_info).and_then(|_| {
+                self.device.cmd_pipeline_barrier(
+                    command_buffer,
+                    src_stage,
+                    dst_stage,
+                    vk::DependencyFlags::empty(),
+                    &[],
+                    &[],
+                    &[barrier],
+                );
+                self.device.end_command_buffer(command_buffer)
+            })
+        };
+        if let Err(e) = recorded {
+            unsafe { self.device.free_command_buffers(state.command_pool, &[command_buffer]) };
+            return Err(GeyserError::VulkanApiError(format!("Failed to record ownership transfer: {:?}", e)));
+        }
+
+        // Also signals the internal timeline so the command buffer can be recycled
+        let submit_value = state.next_value;
+        let mut signal_semaphores = vec![state.timeline];
+        let mut signal_values = vec![submit_value];
+        if let Some((semaphore, value)) = signal {
+            signal_semaphores.push(semaphore);
+            signal_values.push(value);
+        }
+        let timeline_info = vk::TimelineSemaphoreSubmitInfo {
+            s_type: vk::StructureType::TIMELINE_SEMAPHORE_SUBMIT_INFO,
+            p_next: std::ptr::null(),
+            wait_semaphore_value_count: 0,
+            p_wait_semaphore_values: std::ptr::null(),
+            signal_semaphore_value_count: signal_values.len() as u32,
+            p_signal_semaphore_values: signal_values.as_ptr(),
+            _marker: std::marker::PhantomData,
+        };
+        let submit_info = vk::SubmitInfo {
+            s_type: vk::StructureType::SUBMIT_INFO,
+            p_next: &timeline_info as *const _ as *const std::ffi::c_void,
+            wait_semaphore_count: 0,
+            p_wait_semaphores: std::ptr::null(),
+            p_wait_dst_stage_mask: std::ptr::null(),
+            command_buffer_count: 1,
+            p_command_buffers: &command_buffer,
+            signal_semaphore_count: signal_semaphores.len() as u32,
+            p_signal_semaphores: signal_semaphores.as_ptr(),
+            _marker: std::marker::PhantomData,
+        };
+
+        if let Err(e) = self.submit_to_queue(&[submit_info], vk::Fence::null()) {
+            unsafe { self.device.free_command_buffers(state.command_pool, &[command_buffer]) };
+            return Err(GeyserError::VulkanApiError(format!("Failed to submit ownership transfer: {:?}", e)));
+        }
+        state.next_value += 1;
+        state.pending.push((submit_value, vec![command_buffer]));
+        Ok(())
+    }
+
+    /// Submits `command_buffers` on the manager's queue, waiting on and signalling the
+    /// ready semaphores of the given shared textures on the GPU.
+    ///
+    /// Each texture is acquired from `VK_QUEUE_FAMILY_EXTERNAL` in `SHARED_IMAGE_LAYOUT`
+    /// before the command buffers run and released back afterwards, so the whole frame
+    /// handoff is a single `vkQueueSubmit2`. A texture created by this manager that was
+    /// never released or written is instead transitioned from `UNDEFINED`, discarding
+    /// its contents. `fence` may be null.
+    ///
+    /// Wait and signal values only apply to timeline semaphores; binary semaphores
+    /// must be given 0.
+    ///
+    /// Requires timeline semaphores and a Vulkan 1.3 device with `synchronization2` enabled.
+    pub fn submit_shared_frame(
+        &self,
+        command_buffers: &[vk::CommandBuffer],
+        textures: &[SharedTextureAccess],
+        fence: vk::Fence,
+    ) -> Result<()> {
+        if !self.sync_capabilities.timeline_semaphore || !self.sync_capabilities.synchronization2 {
+            return Err(GeyserError::OperationNotSupported);
+        }
+
+        let mut waits = Vec::new();
+        let mut signals = Vec::new();
+        for access in textures {
+            let semaphore_info = |value: u64| vk::SemaphoreSubmitInfo {
+                s_type: vk::StructureType::SEMAPHORE_SUBMIT_INFO,
+                p_next: std::ptr::null(),
+                semaphore: access.texture.ready_semaphore.unwrap_or_default(),
+                value,
+                stage_mask: vk::PipelineStageFlags2::ALL_COMMANDS,
+                device_index: 0,
+                _marker: std::marker::PhantomData,
+            };
+            let values = [(access.wait_value, &mut waits), (access.signal_value, &mut signals)];
+            for (value, infos) in values {
+                if let Some(value) = value {
+                    if access.texture.ready_semaphore.is_none() {
+                        return Err(GeyserError::Other("Shared texture has no ready semaphore attached".to_string()));
+                    }
+                    if !access.texture.ready_semaphore_is_timeline && value != 0 {
+                        return Err(GeyserError::Other(format!(
+                            "Binary semaphores take no value, but {} was given",
+                            value
+                        )));
+                    }
+                    infos.push(semaphore_info(value));
+                }
+            }
+        }
+
+        let acquire_barriers: Vec<_> = textures
+            .iter()
+            .map(|access| self.ownership_barrier(access, vk::QUEUE_FAMILY_EXTERNAL, self.queue_family_index))
+            .collect();
+        let release_barriers: Vec<_> = textures
+            .iter()
+            .map(|access| self.ownership_barrier(access, self.queue_family_index, vk::QUEUE_FAMILY_EXTERNAL))
+            .collect();
+
+        let mut state_guard = self.submit_state.lock().unwrap();
+        if state_guard.is_none() {
+            *state_guard = Some(self.create_submit_state()?);
+        }
+        let state = state_guard.as_mut().unwrap();
+        self.recycle_submit_command_buffers(state)?;
+
+        let allocate_info = vk::CommandBufferAllocateInfo {
+            s_type: vk::StructureType::COMMAND_BUFFER_ALLOCATE_INFO,
+            p_next: std::ptr::null(),
+            command_pool: state.command_pool,
+            level: vk::CommandBufferLevel::PRIMARY,
+            command_buffer_count: 2,
+            _marker: std::marker::PhantomData,
+        };
+        let barrier_buffers = unsafe { self.device.allocate_command_buffers(&allocate_info) }
+            .map_err(|e| GeyserError::VulkanApiError(format!("Failed to allocate command buffers: {:?}", e)))?;
+        let barrier_buffers = [barrier_buffers[0], barrier_buffers[1]];
+
+        let recorded = self.record_barriers(barrier_buffers[0], &acquire_barriers)
+            .and_then(|_| self.record_barriers(barrier_buffers[1], &release_barriers));
+        if let Err(e) = recorded {
+            unsafe { self.device.free_command_buffers(state.command_pool, &barrier_buffers) };
+            return Err(e);
+        }
+
+        let command_buffer_infos: Vec<_> = std::iter::once(barrier_buffers[0])
+            .chain(command_buffers.iter().copied())
+            .chain(std::iter::once(barrier_buffers[1]))
+            .map(|command_buffer| vk::CommandBufferSubmitInfo {
+                s_type: vk::StructureType::COMMAND_BUFFER_SUBMIT_INFO,
+                p_next: std::ptr::null(),
+                command_buffer,
+                device_mask: 0,
+                _marker: std::marker::PhantomData,
+            })
+            .collect();
+
+        let submit_value = state.next_value;
+        signals.push(vk::SemaphoreSubmitInfo {
+            s_type: vk::StructureType::SEMAPHORE_SUBMIT_INFO,
+            p_next: std::ptr::null(),
+            semaphore: state.timeline,
+            value: submit_value,
+            stage_mask: vk::PipelineStageFlags2::ALL_COMMANDS,
+            device_index: 0,
+            _marker: std::marker::PhantomData,
+        });
+
+        let submit_info = vk::SubmitInfo2 {
+            s_type: vk::StructureType::SUBMIT_INFO_2,
+            p_next: std::ptr::null(),
+            flags: vk::SubmitFlags::empty(),
+            wait_semaphore_info_count: waits.len() as u32,
+            p_wait_semaphore_infos: waits.as_ptr(),
+            command_buffer_info_count: command_buffer_infos.len() as u32,
+            p_command_buffer_infos: command_buffer_infos.as_ptr(),
+            signal_semaphore_info_count: signals.len() as u32,
+            p_signal_semaphore_infos: signals.as_ptr(),
+            _marker: std::marker::PhantomData,
+        };
+
+        let submitted = {
+            let _queue = self.queue_lock.lock().unwrap();
+            unsafe { self.device.queue_submit2(self.queue, &[submit_info], fence) }
+        };
+        if let Err(e) = submitted {
+            unsafe { self.device.free_command_buffers(state.command_pool, &barrier_buffers) };
+            return Err(GeyserError::VulkanApiError(format!("Failed to submit shared frame: {:?}", e)));
+        }
+
+        state.next_value += 1;
+        state.pending.push((submit_value, barrier_buffers.to_vec()));
+        // Later submissions see the release recorded above
+        for access in textures {
+            access.texture.initialized.store(true, Ordering::Release);
+        }
+        Ok(())
+    }
+
+    // Builds a queue family ownership transfer barrier between `SHARED_IMAGE_LAYOUT`
+    // and the layout requested by `access`. An uninitialized texture is acquired from
+    // `UNDEFINED` without an ownership transfer, since nobody released it.
+    fn ownership_barrier(&self, access: &SharedTextureAccess, src_queue_family_index: u32, dst_queue_family_index: u32) -> vk::ImageMemoryBarrier2<'static> {
+        let acquire = src_queue_family_index == vk::QUEUE_FAMILY_EXTERNAL;
+        let (old_layout, new_layout) = if acquire {
+            (SHARED_IMAGE_LAYOUT, access.layout)
+        } else {
+            (access.layout, SHARED_IMAGE_LAYOUT)
+        };
+        let (old_layout, src_queue_family_index, dst_queue_family_index) =
+            if acquire && !access.texture.initialized.load(Ordering::Acquire) {
+                (vk::ImageLayout::UNDEFINED, vk::QUEUE_FAMILY_IGNORED, vk::QUEUE_FAMILY_IGNORED)
+            } else {
+                (old_layout, src_queue_family_index, dst_queue_family_index)
+            };
+
+        vk::ImageMemoryBarrier2 {
+            s_type: vk::StructureType::IMAGE_MEMORY_BARRIER_2,
+            p_next: std::ptr::null(),
+            src_stage_mask: if acquire { vk::PipelineStageFlags2::NONE } else { vk::PipelineStageFlags2::ALL_COMMANDS },
+            src_access_mask: if acquire { vk::AccessFlags2::NONE } else { vk::AccessFlags2::MEMORY_WRITE },
+            dst_stage_mask: if acquire { vk::PipelineStageFlags2::ALL_COMMANDS } else { vk::PipelineStageFlags2::NONE },
+            dst_access_mask: if acquire {
+                vk::AccessFlags2::MEMORY_READ | vk::AccessFlags2::MEMORY_WRITE
+            } else {
+                vk::AccessFlags2::NONE
+            },
+            old_layout,
+            new_layout,
+            src_queue_family_index,
+            dst_queue_family_index,
+            image: access.texture.image,
+            subresource_range: vk::ImageSubresourceRange {
+                aspect_mask: aspect_mask_for_format(access.texture.descriptor.format),
+                base_mip_level: 0,
+                level_count: 1,
+                base_array_layer: 0,
+                layer_count: 1,
+            },
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    // Records a one-time-submit command buffer containing only `barriers`
+    fn record_barriers(&self, command_buffer: vk::CommandBuffer, barriers: &[vk::ImageMemoryBarrier2]) -> Result<()> {
+        let begin_info = vk::CommandBufferBeginInfo {
+            s_type: vk::StructureType::COMMAND_BUFFER_BEGIN_INFO,
+            p_next: std::ptr::null(),
+            flags: vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT,
+            p_inheritance_info: std::ptr::null(),
+            _marker: std::marker::PhantomData,
+        };
+        let dependency_info = vk::DependencyInfo {
+            s_type: vk::StructureType::DEPENDENCY_INFO,
+            p_next: std::ptr::null(),
+            dependency_flags: vk::DependencyFlags::empty(),
+            memory_barrier_count: 0,
+            p_memory_barriers: std::ptr::null(),
+            buffer_memory_barrier_count: 0,
+            p_buffer_memory_barriers: std::ptr::null(),
+            image_memory_barrier_count: barriers.len() as u32,
+            p_image_memory_barriers: barriers.as_ptr(),
+            _marker: std::marker::PhantomData,
+        };
+
+        unsafe {
+            self.device.begin_command_buffer(command_buffer, &begin_info)?;
+            if !barriers.is_empty() {
+                self.device.cmd_pipeline_barrier2(command_buffer, &dependency_info);
+            }
+            self.device.end_command_buffer(command_buffer)?;
+        }
+        Ok(())
+    }
+
+    fn create_submit_state(&self) -> Result<SubmitState> {
+        let pool_info = vk::CommandPoolCreateInfo {
+            s_type: vk::StructureType::COMMAND_POOL_CREATE_INFO,
+            p_next: std::ptr::null(),
+            flags: vk::CommandPoolCreateFlags::TRANSIENT,
+            queue_family_index: self.queue_family_index,
+            _marker: std::marker::PhantomData,
+        };
+        let command_pool = unsafe { self.device.create_command_pool(&pool_info, None) }
+            .map_err(|e| GeyserError::VulkanApiError(format!("Failed to create command pool: {:?}", e)))?;
+
+        let mut timeline_create_info = vk::SemaphoreTypeCreateInfo {
+            s_type: vk::StructureType::SEMAPHORE_TYPE_CREATE_INFO,
+            p_next: std::ptr::null(),
+            semaphore_type: vk::SemaphoreType::TIMELINE,
+            initial_value: 0,
+            _marker: std::marker::PhantomData,
+        };
+        let semaphore_create_info = vk::SemaphoreCreateInfo {
+            s_type: vk::StructureType::SEMAPHORE_CREATE_INFO,
+            p_next: &mut timeline_create_info as *mut _ as *const std::ffi::c_void,
+            flags: vk::SemaphoreCreateFlags::empty(),
+            _marker: std::marker::PhantomData,
+        };
+        let timeline = match unsafe { self.device.create_semaphore(&semaphore_create_info, None) } {
+            Ok(timeline) => timeline,
+            Err(e) => {
+                unsafe { self.device.destroy_command_pool(command_pool, None) };
+                return Err(GeyserError::VulkanApiError(format!("Failed to create submit timeline: {:?}", e)));
+            }
+        };
+
+        let wait_barrier = match self.record_wait_barrier(command_pool) {
+            Ok(wait_barrier) => wait_barrier,
+            Err(e) => {
+                unsafe {
+                    self.device.destroy_semaphore(timeline, None);
+                    self.device.destroy_command_pool(command_pool, None);
+                }
+                return Err(e);
+            }
+        };
+
+        Ok(SubmitState {
+            command_pool,
+            timeline,
+            next_value: 1,
+            pending: Vec::new(),
+            wait_barrier,
+        })
+    }
+
+    // Records a reusable command buffer holding an all-commands execution and memory
+    // barrier, which extends a semaphore wait to everything submitted after it
+    fn record_wait_barrier(&self, command_pool: vk::CommandPool) -> Result<vk::CommandBuffer> {
+        let allocate_info = vk::CommandBufferAllocateInfo {
+            s_type: vk::StructureType::COMMAND_BUFFER_ALLOCATE_INFO,
+            p_next: std::ptr::null(),
+            command_pool,
+            level: vk::CommandBufferLevel::PRIMARY,
+            command_buffer_count: 1,
+            _marker: std::marker::PhantomData,
+        };
+        let command_buffer = unsafe { self.device.allocate_command_buffers(&allocate_info) }
+            .map_err(|e| GeyserError::VulkanApiError(format!("Failed to allocate command buffers: {:?}", e)))?[0];
+
+        let begin_info = vk::CommandBufferBeginInfo {
+            s_type: vk::StructureType::COMMAND_BUFFER_BEGIN_INFO,
+            p_next: std::ptr::null(),
+            flags: vk::CommandBufferUsageFlags::SIMULTANEOUS_USE,
+            p_inheritance_info: std::ptr::null(),
+            _marker: std::marker::PhantomData,
+        };
+        let memory_barrier = vk::MemoryBarrier {
+            s_type: vk::StructureType::MEMORY_BARRIER,
+            p_next: std::ptr::null(),
+            src_access_mask: vk::AccessFlags::MEMORY_WRITE,
+            dst_access_mask: vk::AccessFlags::MEMORY_READ | vk::AccessFlags::MEMORY_WRITE,
+            _marker: std::marker::PhantomData,
+        };
+
+        unsafe {
+            self.device.begin_command_buffer(command_buffer, &begin_info)?;
+            self.device.cmd_pipeline_barrier(
+                command_buffer,
+                vk::PipelineStageFlags::ALL_COMMANDS,
+                vk::PipelineStageFlags::ALL_COMMANDS,
+                vk::DependencyFlags::empty(),
+                &[memory_barrier],
+                &[],
+                &[],
+            );
+            self.device.end_command_buffer(command_buffer)?;
+        }
+        Ok(command_buffer)
+    }
+
+    // Frees barrier command buffers whose submissions have completed
+    fn recycle_submit_command_buffers(&self, state: &mut SubmitState) -> Result<()> {
+        let completed = unsafe { self.device.get_semaphore_counter_value(state.timeline) }
+            .map_err(|e| GeyserError::VulkanApiError(format!("Failed to query submit timeline: {:?}", e)))?;
+
+        let device = &self.device;
+        let command_pool = state.command_pool;
+        state.pending.retain(|(value, buffers)| {
+            if *value <= completed {
+                unsafe { device.free_command_buffers(command_pool, buffers) };
+                false
+            } else {
+                true
+            }
+        });
+        Ok(())
+    }
+
+    /// Cleanup exported semaphore
+    pub fn release_semaphore(&self, handle: &VulkanSemaphoreHandle) -> Result<()> {
+        if let Some(semaphore) = self.exported_semaphores.lock().unwrap().remove(&handle.raw_handle) {
+            unsafe {
+                self.device.destroy_semaphore(semaphore, None);
+            }
+        }
+        Ok(())
+    }
+
+    /// Cleanup exported fence
+    pub fn release_fence(&self, handle: &VulkanFenceHandle) -> Result<()> {
+        if let Some(fence) = self.exported_fences.lock().unwrap().remove(&handle.raw_handle) {
+            unsafe {
+                self.device.destroy_fence(fence, None);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for VulkanTextureShareManager {
+    fn drop(&mut self) {
+        // Everything tracked below is destroyed before `owned_context` destroys the device,
+        // so textures imported through this manager must already have been dropped.
+        unsafe {
+            let _ = self.device.device_wait_idle();
+            for (_, semaphore) in self.exported_semaphores.get_mut().unwrap().drain() {
+                self.device.destroy_semaphore(semaphore, None);
+            }
+            for (_, fence) in self.exported_fences.get_mut().unwrap().drain() {
+                self.device.destroy_fence(fence, None);
+            }
+            for (_, memory) in self.imported_resources.get_mut().unwrap().drain() {
+                self.device.free_memory(memory, None);
+            }
+        }
+        // Exported memory belongs to the allocator, which frees it when dropped
+        self.exported_resources.get_mut().unwrap().clear();
+        if let Some(state) = self.blit_state.get_mut().unwrap().take() {
+            self.destroy_blit_state(state);
+        }
+        if let Some(state) = self.submit_state.get_mut().unwrap().take() {
+            unsafe {
+                // Barrier command buffers must not be freed while still executing
+                let wait_info = vk::SemaphoreWaitInfo {
+                    s_type: vk::StructureType::SEMAPHORE_WAIT_INFO,
+                    p_next: std::ptr::null(),
+                    flags: vk::SemaphoreWaitFlags::empty(),
+                    semaphore_count: 1,
+                    p_semaphores: &state.timeline,
+                    p_values: &(state.next_value - 1),
+                    _marker: std::marker::PhantomData,
+                };
+                let _ = self.device.wait_semaphores(&wait_info, u64::MAX);
+                self.device.destroy_command_pool(state.command_pool, None);
+                self.device.destroy_semaphore(state.timeline, None);
+            }
+        }
+    }
+}
+
+impl TextureShareManager for VulkanTextureShareManager {
+    fn create_shareable_texture(&self, descriptor: &TextureDescriptor) -> Result<Box<dyn SharedTexture>> {
+        Ok(Box::new(self.create_vulkan_texture(descriptor)?))
+    }
+
+    fn export_texture(&self, texture: &dyn SharedTexture) -> Result<ApiTextureHandle> {
+        let vulkan_texture = texture
+            .as_any()
+            .downcast_ref::<VulkanSharedTexture>()
+            .ok_or(GeyserError::Other("Provided texture is not a VulkanSharedTexture".to_string()))?;
+
+        let allocation = vulkan_texture.allocation.as_ref()
+            .ok_or(GeyserError::Other("Texture has no allocation to export".to_string()))?;
+
+        let memory = unsafe { allocation.memory() };
+        let memory_requirements = unsafe { self.device.get_image_memory_requirements(vulkan_texture.image) };
+        let plane_layouts = self.query_plane_layouts(vulkan_texture)?;
+
+        // Export the external memory handle (platform-specific)
+        #[cfg(target_os = "windows")]
+        let raw_handle = self.get_external_memory_win32_info(memory)?;
+
+        #[cfg(target_os = "linux")]
+        let raw_handle = self.get_external_memory_fd_info(memory)? as u64;
+
+        #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+        return Err(GeyserError::OperationNotSupported);
+
+        // Query memory properties to get memory type index
+        let memory_properties = unsafe {
+            self.instance.get_physical_device_memory_properties(self.physical_device)
+        };
+        
+        // Find memory type index for the allocation
+        let memory_type_index = (0..memory_properties.memory_type_count)
+            .find(|&i| {
+                (memory_requirements.memory_type_bits & (1 << i)) != 0
+            })
+            .unwrap_or(0);
+
+        let handle = VulkanTextureShareHandle {
+            raw_handle,
+            memory_type_index,
+            size: memory_requirements.size,
+            handle_type: {
+                #[cfg(target_os = "windows")]
+                { vk::ExternalMemoryHandleTypeFlags::OPAQUE_WIN32 }
+                #[cfg(target_os = "linux")]
+                { vk::ExternalMemoryHandleTypeFlags::OPAQUE_FD }
+                #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+                { vk::ExternalMemoryHandleTypeFlags::empty() }
+            },
+            dedicated_allocation: true,
+            device_identity: self.device_identity,
+            plane_layouts,
+        };
+
+        // Store the vk::DeviceMemory to ensure it stays alive
+        self.exported_resources.lock().unwrap().insert(handle.raw_handle, memory);
+
+        Ok(ApiTextureHandle::Vulkan(handle))
+    }
+
+    fn import_texture(&self, handle: ApiTextureHandle, descriptor: &TextureDescriptor) -> Result<Box<dyn SharedTexture>> {
+        Ok(Box::new(self.import_vulkan_texture(handle, descriptor)?))
+    }
+
+    fn release_texture_handle(&self, handle: ApiTextureHandle) -> Result<()> {
+        let raw_handle_key = match handle {
+            ApiTextureHandle::Vulkan(h) => h.raw_handle,
+            _ => return Err(GeyserError::InvalidTextureHandle),
+        };
+
+        let memory = self.imported_resources.lock().unwrap().remove(&raw_handle_key)
+            .or_else(|| self.exported_resources.lock().unwrap().remove(&raw_handle_key));
+        if let Some(memory) = memory {
+            unsafe {
+                self.device.free_memory(memory, None);
+            }
+        }
+        Ok(())
+    }
+
+    fn create_shareable_texture_with_sync(&self, descriptor: &TextureDescriptor) -> Result<Box<dyn SharedTexture>> {
+        Ok(Box::new(self.create_vulkan_texture_with_sync(descriptor)?))
+    }
+
+    fn export_texture_with_sync(&self, texture: &dyn SharedTexture) -> Result<TextureSharePackage> {
+        let vulkan_texture = texture
+            .as_any()
+            .downcast_ref::<VulkanSharedTexture>()
+            .ok_or(GeyserError::Other("Provided texture is not a VulkanSharedTexture".to_string()))?;
+
+        let semaphore = vulkan_texture.ready_semaphore
+            .map(|semaphore| self.export_texture_semaphore(semaphore, vulkan_texture.ready_semaphore_is_timeline))
+            .transpose()?
+            .map(SyncHandle::VulkanSemaphore);
+        let fence = vulkan_texture.ready_fence
+            .map(|fence| self.export_texture_fence(fence))
+            .transpose()?
+            .map(SyncHandle::VulkanFence);
+
+        Ok(TextureSharePackage {
+            texture: self.export_texture(texture)?,
+            sync: SyncPrimitives { semaphore, fence },
+        })
+    }
+
+    fn import_texture_with_sync(&self, package: TextureSharePackage, descriptor: &TextureDescriptor) -> Result<Box<dyn SharedTexture>> {
+        Ok(Box::new(self.import_vulkan_texture_with_sync(package, descriptor)?))
+    }
 }
 
 #[cfg(test)]
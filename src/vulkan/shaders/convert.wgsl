@@ -0,0 +1,78 @@
+// Compute fallback of `VulkanTextureShareManager::blit` for 8-bit RGBA/BGRA textures
+// whose formats cannot be blitted. Texels are read from and written to tightly packed
+// buffers, converting between RGBA/BGRA order and sRGB/linear encoding and scaling with
+// nearest or bilinear filtering. Filtering happens on linear values, like a blit.
+//
+// `convert.spv` is compiled from this file with naga, as SPIR-V 1.3 without debug info.
+// After editing, rebuild it with `GEYSER_UPDATE_SHADERS=1 cargo test --features vulkan
+// convert_shader`; the test fails while the two disagree.
+
+struct Params {
+    src_size: vec2<u32>,
+    dst_size: vec2<u32>,
+    flags: u32,
+}
+
+// Must match the `CONVERT_*` flags in blit.rs
+const SRC_SRGB: u32 = 1u;
+const SRC_BGRA: u32 = 2u;
+const DST_SRGB: u32 = 4u;
+const DST_BGRA: u32 = 8u;
+const LINEAR_FILTER: u32 = 16u;
+
+var<push_constant> params: Params;
+@group(0) @binding(0) var<storage, read> src_texels: array<u32>;
+@group(0) @binding(1) var<storage, read_write> dst_texels: array<u32>;
+
+fn srgb_to_linear(c: vec3<f32>) -> vec3<f32> {
+    return select(pow((c + 0.055) / 1.055, vec3(2.4)), c / 12.92, c <= vec3(0.04045));
+}
+
+fn linear_to_srgb(c: vec3<f32>) -> vec3<f32> {
+    return select(1.055 * pow(c, vec3(1.0 / 2.4)) - 0.055, c * 12.92, c <= vec3(0.0031308));
+}
+
+// Loads a source texel as linear RGBA, clamping to the edge like a blit
+fn load(x: i32, y: i32) -> vec4<f32> {
+    let size = vec2<i32>(params.src_size);
+    let p = clamp(vec2(x, y), vec2(0), size - 1);
+    var texel = unpack4x8unorm(src_texels[p.y * size.x + p.x]);
+    if (params.flags & SRC_BGRA) != 0u {
+        texel = texel.bgra;
+    }
+    if (params.flags & SRC_SRGB) != 0u {
+        texel = vec4(srgb_to_linear(texel.rgb), texel.a);
+    }
+    return texel;
+}
+
+@compute @workgroup_size(8, 8)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    if any(id.xy >= params.dst_size) {
+        return;
+    }
+
+    // Centre of the destination texel in source texel coordinates
+    let pos = (vec2<f32>(id.xy) + 0.5) * vec2<f32>(params.src_size) / vec2<f32>(params.dst_size);
+    var color: vec4<f32>;
+    if (params.flags & LINEAR_FILTER) != 0u {
+        let p = pos - 0.5;
+        let base = floor(p);
+        let f = p - base;
+        let b = vec2<i32>(base);
+        let top = mix(load(b.x, b.y), load(b.x + 1, b.y), f.x);
+        let bottom = mix(load(b.x, b.y + 1), load(b.x + 1, b.y + 1), f.x);
+        color = mix(top, bottom, f.y);
+    } else {
+        let b = vec2<i32>(floor(pos));
+        color = load(b.x, b.y);
+    }
+
+    if (params.flags & DST_SRGB) != 0u {
+        color = vec4(linear_to_srgb(color.rgb), color.a);
+    }
+    if (params.flags & DST_BGRA) != 0u {
+        color = color.bgra;
+    }
+    dst_texels[id.y * params.dst_size.x + id.x] = pack4x8unorm(color);
+}
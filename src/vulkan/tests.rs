@@ -7,6 +7,7 @@ fn test_vulkan_semaphore_handle_creation() {
     let handle = VulkanSemaphoreHandle {
         raw_handle: 12345,
         handle_type: vk::ExternalSemaphoreHandleTypeFlags::OPAQUE_WIN32,
+        is_timeline: false,
     };
 
     assert_eq!(handle.raw_handle, 12345);
@@ -29,6 +30,7 @@ fn test_vulkan_semaphore_handle_clone() {
     let handle1 = VulkanSemaphoreHandle {
         raw_handle: 111,
         handle_type: vk::ExternalSemaphoreHandleTypeFlags::OPAQUE_FD,
+        is_timeline: false,
     };
 
     let handle2 = handle1.clone();
@@ -56,6 +58,8 @@ fn test_vulkan_texture_share_handle() {
         size: 1024 * 1024,
         handle_type: vk::ExternalMemoryHandleTypeFlags::OPAQUE_WIN32,
         dedicated_allocation: true,
+        device_identity: DeviceIdentity::default(),
+        plane_layouts: Vec::new(),
     };
 
     assert_eq!(handle.raw_handle, 999);
@@ -71,6 +75,7 @@ fn test_sync_handle_variants() {
     let sem_handle = VulkanSemaphoreHandle {
         raw_handle: 111,
         handle_type: vk::ExternalSemaphoreHandleTypeFlags::OPAQUE_WIN32,
+        is_timeline: false,
     };
 
     let fence_handle = VulkanFenceHandle {
@@ -108,3 +113,410 @@ fn test_linux_handle_types() {
     assert!(vk::ExternalFenceHandleTypeFlags::OPAQUE_FD.as_raw() != 0);
     assert!(vk::ExternalMemoryHandleTypeFlags::OPAQUE_FD.as_raw() != 0);
 }
+
+#[test]
+fn test_texture_share_package_with_sync() {
+    use crate::common::{SyncHandle, SyncPrimitives, TextureSharePackage};
+
+    let package = TextureSharePackage {
+        texture: ApiTextureHandle::Vulkan(VulkanTextureShareHandle {
+            raw_handle: 10,
+            memory_type_index: 1,
+            size: 4096,
+            handle_type: vk::ExternalMemoryHandleTypeFlags::OPAQUE_FD,
+            dedicated_allocation: true,
+            device_identity: DeviceIdentity::default(),
+            plane_layouts: Vec::new(),
+        }),
+        sync: SyncPrimitives {
+            semaphore: Some(SyncHandle::VulkanSemaphore(VulkanSemaphoreHandle {
+                raw_handle: 11,
+                handle_type: vk::ExternalSemaphoreHandleTypeFlags::OPAQUE_FD,
+                is_timeline: true,
+            })),
+            fence: Some(SyncHandle::VulkanFence(VulkanFenceHandle {
+                raw_handle: 12,
+                handle_type: vk::ExternalFenceHandleTypeFlags::OPAQUE_FD,
+            })),
+        },
+    };
+
+    let cloned = package.clone();
+
+    match cloned.texture {
+        ApiTextureHandle::Vulkan(texture) => assert_eq!(texture.raw_handle, 10),
+        #[allow(unreachable_patterns)]
+        _ => panic!("Wrong variant"),
+    }
+    match cloned.sync.semaphore {
+        Some(SyncHandle::VulkanSemaphore(semaphore)) => assert!(semaphore.is_timeline),
+        _ => panic!("Wrong variant"),
+    }
+    assert!(matches!(cloned.sync.fence, Some(SyncHandle::VulkanFence(_))));
+}
+
+#[test]
+fn test_sync_capabilities_from_properties() {
+    let both = vk::ExternalSemaphoreFeatureFlags::EXPORTABLE | vk::ExternalSemaphoreFeatureFlags::IMPORTABLE;
+    let caps = SyncCapabilities::from_properties(
+        true,
+        both,
+        vk::ExternalSemaphoreFeatureFlags::EXPORTABLE,
+        vk::ExternalFenceFeatureFlags::IMPORTABLE,
+        true,
+    );
+
+    assert!(caps.timeline_semaphore);
+    assert!(caps.binary_semaphore_export);
+    assert!(caps.binary_semaphore_import);
+    assert!(caps.timeline_semaphore_export);
+    assert!(!caps.timeline_semaphore_import);
+    assert!(!caps.fence_export);
+    assert!(caps.fence_import);
+    assert!(caps.synchronization2);
+}
+
+#[test]
+fn test_sync_capabilities_without_timeline_feature() {
+    let both = vk::ExternalSemaphoreFeatureFlags::EXPORTABLE | vk::ExternalSemaphoreFeatureFlags::IMPORTABLE;
+    let caps = SyncCapabilities::from_properties(false, both, both, vk::ExternalFenceFeatureFlags::empty(), false);
+
+    // Timeline export/import is meaningless without the feature itself
+    assert!(!caps.timeline_semaphore);
+    assert!(!caps.timeline_semaphore_export);
+    assert!(!caps.timeline_semaphore_import);
+    assert!(caps.binary_semaphore_export);
+    assert!(!SyncCapabilities::default().fence_export);
+}
+
+#[test]
+fn test_sync_capabilities_with_enabled_features() {
+    let both = vk::ExternalSemaphoreFeatureFlags::EXPORTABLE | vk::ExternalSemaphoreFeatureFlags::IMPORTABLE;
+    let supported = SyncCapabilities::from_properties(true, both, both, vk::ExternalFenceFeatureFlags::EXPORTABLE, true);
+
+    assert_eq!(supported.with_enabled_features(true, true), supported);
+
+    let caps = supported.with_enabled_features(false, false);
+    assert!(!caps.timeline_semaphore);
+    assert!(!caps.timeline_semaphore_export);
+    assert!(!caps.timeline_semaphore_import);
+    assert!(!caps.synchronization2);
+    // External binary semaphores and fences need no optional feature
+    assert!(caps.binary_semaphore_export);
+    assert!(caps.fence_export);
+
+    // Enabling a feature the device lacks has no effect
+    let unsupported = SyncCapabilities::from_properties(false, both, both, vk::ExternalFenceFeatureFlags::empty(), false);
+    assert_eq!(unsupported.with_enabled_features(true, true), unsupported);
+}
+
+#[test]
+fn test_aspect_mask_for_format() {
+    assert_eq!(aspect_mask_for_format(TextureFormat::Rgba8Unorm), vk::ImageAspectFlags::COLOR);
+    assert_eq!(aspect_mask_for_format(TextureFormat::Rgba16Float), vk::ImageAspectFlags::COLOR);
+    assert_eq!(aspect_mask_for_format(TextureFormat::Depth32Float), vk::ImageAspectFlags::DEPTH);
+    assert_eq!(
+        aspect_mask_for_format(TextureFormat::Depth24PlusStencil8),
+        vk::ImageAspectFlags::DEPTH | vk::ImageAspectFlags::STENCIL
+    );
+}
+
+#[test]
+fn test_missing_extensions_lists_only_absent_names() {
+    let available = [
+        vk::ExtensionProperties::default()
+            .extension_name(ash::khr::external_memory_fd::NAME)
+            .unwrap(),
+        vk::ExtensionProperties::default()
+            .extension_name(ash::khr::swapchain::NAME)
+            .unwrap(),
+    ];
+    let required = [
+        ash::khr::external_memory_fd::NAME,
+        ash::khr::external_semaphore_fd::NAME,
+        ash::khr::external_fence_fd::NAME,
+    ];
+
+    let missing = builder::missing_extensions(&required, &available);
+
+    assert_eq!(missing, vec!["VK_KHR_external_semaphore_fd", "VK_KHR_external_fence_fd"]);
+    assert!(builder::missing_extensions(&required[..1], &available).is_empty());
+}
+
+#[test]
+fn test_missing_extensions_error_message() {
+    let err = GeyserError::MissingExtensions(vec![
+        "VK_KHR_external_semaphore_fd".to_string(),
+        "VK_KHR_external_fence_fd".to_string(),
+    ]);
+
+    assert_eq!(
+        err.to_string(),
+        "Missing required Vulkan extensions: VK_KHR_external_semaphore_fd, VK_KHR_external_fence_fd"
+    );
+}
+
+#[test]
+fn test_incompatible_device_error_reports_both_identities() {
+    let exporter = DeviceIdentity {
+        device_uuid: [0xab; 16],
+        driver_uuid: [0x01; 16],
+        driver_version: 0x1000,
+    };
+    let importer = DeviceIdentity {
+        device_uuid: [0xcd; 16],
+        ..exporter
+    };
+
+    let message = GeyserError::IncompatibleDevice { exporter, importer }.to_string();
+    assert!(message.contains(&"ab".repeat(16)));
+    assert!(message.contains(&"cd".repeat(16)));
+}
+
+fn dma_buf(modifier: u64, offset: u32, stride: u32) -> DmaBufHandle {
+    DmaBufHandle {
+        fd: -1,
+        fourcc: 0,
+        modifier,
+        offset,
+        stride,
+    }
+}
+
+fn copy_descriptor(format: TextureFormat) -> TextureDescriptor {
+    TextureDescriptor {
+        width: 100,
+        height: 10,
+        format,
+        usage: vec![TextureUsage::TextureBinding],
+        label: None,
+        tiling: TextureTiling::Optimal,
+        memory_location: TextureMemoryLocation::GpuOnly,
+    }
+}
+
+#[test]
+fn test_linear_dma_buf_layout_uses_stride_and_offset() {
+    let layout = linear_dma_buf_layout(&dma_buf(DRM_FORMAT_MOD_LINEAR, 64, 512), &copy_descriptor(TextureFormat::Bgra8Unorm)).unwrap();
+
+    assert_eq!(layout.offset, 64);
+    assert_eq!(layout.row_length, 128);
+    // The last row only needs its texels, not the padding after them
+    assert_eq!(layout.size, 64 + 512 * 9 + 400);
+}
+
+#[test]
+fn test_linear_dma_buf_layout_rejects_tiled_modifiers() {
+    let descriptor = copy_descriptor(TextureFormat::Rgba8Unorm);
+
+    assert!(matches!(
+        linear_dma_buf_layout(&dma_buf(crate::common::DRM_FORMAT_MOD_INVALID, 0, 400), &descriptor),
+        Err(GeyserError::UnsupportedFormat(_))
+    ));
+}
+
+#[test]
+fn test_linear_dma_buf_layout_rejects_short_or_misaligned_stride() {
+    let descriptor = copy_descriptor(TextureFormat::Rgba16Float);
+
+    assert!(linear_dma_buf_layout(&dma_buf(DRM_FORMAT_MOD_LINEAR, 0, 799), &descriptor).is_err());
+    assert!(linear_dma_buf_layout(&dma_buf(DRM_FORMAT_MOD_LINEAR, 0, 804), &descriptor).is_err());
+    assert!(linear_dma_buf_layout(&dma_buf(DRM_FORMAT_MOD_LINEAR, 0, 808), &descriptor).is_ok());
+}
+
+#[test]
+fn test_linear_dma_buf_layout_rejects_depth_formats() {
+    assert!(matches!(
+        linear_dma_buf_layout(&dma_buf(DRM_FORMAT_MOD_LINEAR, 0, 400), &copy_descriptor(TextureFormat::Depth32Float)),
+        Err(GeyserError::UnsupportedTextureFormat(_))
+    ));
+}
+
+#[test]
+fn test_mapped_texture_rows_skip_padding() {
+    // 2 rows of 3 RGBA texels, 16 bytes apart
+    let mut data: Vec<u8> = (0..28).collect();
+    let mut mapped = MappedTexture::new(&mut data, 16, 12, 2);
+
+    assert_eq!(mapped.row_pitch(), 16);
+    assert_eq!(mapped.height(), 2);
+    assert_eq!(mapped.row(1), &(16..28).collect::<Vec<u8>>()[..]);
+
+    mapped.row_mut(0).fill(0xff);
+    assert_eq!(&mapped.as_bytes()[..12], &[0xff; 12]);
+    assert_eq!(mapped.as_bytes()[12], 12);
+}
+
+#[test]
+fn test_blit_formats_compatible() {
+    use blit::blit_formats_compatible;
+
+    // Blits convert encodings and component orders
+    assert!(blit_formats_compatible(TextureFormat::Rgba8Srgb, TextureFormat::Bgra8Unorm, vk::Filter::LINEAR));
+    assert!(blit_formats_compatible(TextureFormat::Rgba16Float, TextureFormat::Rgba8Unorm, vk::Filter::LINEAR));
+    // Integers only go unfiltered to integers of the same signedness
+    assert!(blit_formats_compatible(TextureFormat::R16Uint, TextureFormat::R32Uint, vk::Filter::NEAREST));
+    assert!(!blit_formats_compatible(TextureFormat::R16Uint, TextureFormat::R32Uint, vk::Filter::LINEAR));
+    assert!(!blit_formats_compatible(TextureFormat::R32Uint, TextureFormat::R32Sint, vk::Filter::NEAREST));
+    assert!(!blit_formats_compatible(TextureFormat::R32Float, TextureFormat::R32Uint, vk::Filter::NEAREST));
+    // Depth only goes unfiltered to the same format
+    assert!(blit_formats_compatible(TextureFormat::Depth32Float, TextureFormat::Depth32Float, vk::Filter::NEAREST));
+    assert!(!blit_formats_compatible(TextureFormat::Depth32Float, TextureFormat::Depth32Float, vk::Filter::LINEAR));
+    assert!(!blit_formats_compatible(TextureFormat::Depth32Float, TextureFormat::R32Float, vk::Filter::NEAREST));
+}
+
+#[test]
+fn test_convert_flags_cover_srgb_and_swizzles() {
+    use blit::*;
+
+    assert_eq!(convert_flags(TextureFormat::Rgba8Unorm, TextureFormat::Rgba8Unorm, vk::Filter::NEAREST), Some(0));
+    assert_eq!(
+        convert_flags(TextureFormat::Bgra8Srgb, TextureFormat::Rgba8Unorm, vk::Filter::LINEAR),
+        Some(CONVERT_SRC_SRGB | CONVERT_SRC_BGRA | CONVERT_LINEAR_FILTER)
+    );
+    assert_eq!(
+        convert_flags(TextureFormat::Rgba8Unorm, TextureFormat::Bgra8Srgb, vk::Filter::NEAREST),
+        Some(CONVERT_DST_SRGB | CONVERT_DST_BGRA)
+    );
+    // The shader only reads and writes 8-bit four-channel texels
+    assert_eq!(convert_flags(TextureFormat::Rgba16Float, TextureFormat::Rgba8Unorm, vk::Filter::NEAREST), None);
+    assert_eq!(convert_flags(TextureFormat::Rgba8Unorm, TextureFormat::R8Unorm, vk::Filter::NEAREST), None);
+}
+
+// Compiles `convert.wgsl` the way `convert.spv` is built: SPIR-V 1.3 without debug info
+fn compile_convert_shader() -> Vec<u32> {
+    let source = include_str!("shaders/convert.wgsl");
+    let module = naga::front::wgsl::parse_str(source).unwrap_or_else(|e| panic!("{}", e.emit_to_string(source)));
+    let info = naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::PUSH_CONSTANT)
+        .validate(&module)
+        .unwrap();
+    let mut options = naga::back::spv::Options::default();
+    options.flags.remove(naga::back::spv::WriterFlags::DEBUG);
+    options.lang_version = (1, 3);
+    naga::back::spv::write_vec(&module, &info, &options, None).unwrap()
+}
+
+#[test]
+fn test_convert_shader_matches_wgsl() {
+    let compiled = compile_convert_shader();
+    // `GEYSER_UPDATE_SHADERS=1 cargo test --features vulkan convert_shader` rebuilds it
+    if std::env::var_os("GEYSER_UPDATE_SHADERS").is_some() {
+        let bytes: Vec<u8> = compiled.iter().flat_map(|word| word.to_le_bytes()).collect();
+        std::fs::write(concat!(env!("CARGO_MANIFEST_DIR"), "/src/vulkan/shaders/convert.spv"), bytes).unwrap();
+        return;
+    }
+
+    let code = ash::util::read_spv(&mut std::io::Cursor::new(include_bytes!("shaders/convert.spv"))).unwrap();
+    assert!(code == compiled, "convert.spv is out of date with convert.wgsl; rebuild it with GEYSER_UPDATE_SHADERS=1");
+}
+
+#[test]
+fn test_copy_formats_compatible_by_texel_size() {
+    use blit::copy_formats_compatible;
+
+    assert!(copy_formats_compatible(TextureFormat::Rgba8Unorm, TextureFormat::Bgra8Srgb));
+    assert!(copy_formats_compatible(TextureFormat::R32Uint, TextureFormat::Rgba8Unorm));
+    assert!(!copy_formats_compatible(TextureFormat::Rgba16Float, TextureFormat::Rgba8Unorm));
+    assert!(copy_formats_compatible(TextureFormat::Depth32Float, TextureFormat::Depth32Float));
+    assert!(!copy_formats_compatible(TextureFormat::Depth32Float, TextureFormat::R32Float));
+}
+
+#[test]
+fn test_copy_regions_must_fit_both_textures() {
+    use blit::validate_copy_region;
+
+    let src = copy_descriptor(TextureFormat::Rgba8Unorm);
+    let dst = TextureDescriptor {
+        width: 50,
+        ..copy_descriptor(TextureFormat::Rgba8Unorm)
+    };
+
+    assert!(validate_copy_region(&CopyRegion::new(50, 10), &src, &dst).is_ok());
+    assert!(validate_copy_region(&CopyRegion::new(40, 4).with_src_origin(60, 6).with_dst_origin(10, 0), &src, &dst).is_ok());
+    // Past the edge of the smaller destination
+    assert!(validate_copy_region(&CopyRegion::new(51, 10), &src, &dst).is_err());
+    assert!(validate_copy_region(&CopyRegion::new(40, 4).with_src_origin(61, 0), &src, &dst).is_err());
+    assert!(validate_copy_region(&CopyRegion::new(1, 1).with_dst_origin(u32::MAX, 0), &src, &dst).is_err());
+    assert!(validate_copy_region(&CopyRegion::new(0, 10), &src, &dst).is_err());
+}
+
+#[test]
+fn test_release_tracker_recycles_after_every_consumer_releases() {
+    let start = std::time::Instant::now();
+    let mut tracker = ReleaseTracker::new(2, std::time::Duration::from_secs(1));
+    let preview = tracker.attach();
+    let recorder = tracker.attach();
+
+    assert_eq!(tracker.publish(0, start).unwrap(), 1);
+    assert_eq!(tracker.publish(1, start).unwrap(), 2);
+    assert!(matches!(tracker.publish(0, start), Err(GeyserError::ResourceInUse)));
+    assert_eq!(tracker.free_slot(), None);
+
+    // One consumer is not enough
+    assert!(tracker.release(preview, 2));
+    assert!(tracker.reclaim(start).is_empty());
+    assert_eq!(tracker.free_slot(), None);
+
+    // Frame 1 is released by both; frame 2 still held by the recorder
+    tracker.release(recorder, 1);
+    tracker.reclaim(start);
+    assert!(tracker.is_free(0));
+    assert!(!tracker.is_free(1));
+
+    // Release values never move backwards
+    tracker.release(preview, 0);
+    assert_eq!(tracker.released(preview), Some(2));
+}
+
+#[test]
+fn test_release_tracker_late_consumer_waits_only_for_new_frames() {
+    let start = std::time::Instant::now();
+    let mut tracker = ReleaseTracker::new(2, std::time::Duration::from_secs(1));
+    let first = tracker.attach();
+    tracker.publish(0, start).unwrap();
+
+    let late = tracker.attach();
+    assert_eq!(tracker.released(late), Some(1));
+    tracker.release(first, 1);
+    tracker.reclaim(start);
+    assert!(tracker.is_free(0));
+
+    tracker.publish(0, start).unwrap();
+    tracker.release(first, 2);
+    tracker.reclaim(start);
+    assert!(!tracker.is_free(0));
+    assert!(tracker.detach(late));
+    assert!(!tracker.detach(late));
+    tracker.reclaim(start);
+    assert!(tracker.is_free(0));
+}
+
+#[test]
+fn test_release_tracker_evicts_stalled_consumers() {
+    let start = std::time::Instant::now();
+    let timeout = std::time::Duration::from_millis(100);
+    let mut tracker = ReleaseTracker::new(1, timeout);
+    let streamer = tracker.attach();
+    let stalled = tracker.attach();
+
+    tracker.publish(0, start).unwrap();
+    tracker.release(streamer, 1);
+    // Within the timeout the stalled consumer still holds the slot
+    assert!(tracker.reclaim(start + timeout).is_empty());
+    assert!(!tracker.is_free(0));
+
+    assert_eq!(tracker.reclaim(start + timeout * 2), vec![stalled]);
+    assert_eq!(tracker.consumers(), vec![streamer]);
+    assert!(tracker.is_free(0));
+    assert!(!tracker.release(stalled, 1));
+}
+
+#[test]
+fn test_release_tracker_without_consumers_frees_immediately() {
+    let start = std::time::Instant::now();
+    let mut tracker = ReleaseTracker::new(1, std::time::Duration::from_secs(1));
+    tracker.publish(0, start).unwrap();
+    assert!(tracker.reclaim(start).is_empty());
+    assert_eq!(tracker.free_slot(), Some(0));
+    assert_eq!(tracker.last_frame(), 1);
+}
@@ -0,0 +1,459 @@
+//! Presenting shared textures in a Wayland window (Linux).
+//!
+//! `WaylandPresenter` opens an xdg-shell toplevel on the compositor named by
+//! `WAYLAND_DISPLAY` and turns exported `DmaBufHandle`s into `wl_buffer`s through
+//! `zwp_linux_dmabuf_v1`, so a small viewer process can show a Geyser texture without
+//! copying it. If the compositor offers `wp_linux_drm_syncobj_manager_v1`, frames can
+//! be presented with explicit acquire and release points on DRM syncobj timelines;
+//! otherwise the compositor relies on the dma-buf's implicit fences.
+//!
+//! ```ignore
+//! let mut presenter = WaylandPresenter::connect("viewer")?;
+//! let buffer = presenter.create_buffer(&manager.export_dma_buf(&texture)?, &descriptor)?;
+//! while presenter.dispatch(Duration::from_millis(16))? {
+//!     if !buffer.is_busy() {
+//!         presenter.present(&buffer, None)?;
+//!     }
+//! }
+//! ```
+//!
+//! The protocol code is pure Rust, so the feature needs no libwayland. It runs
+//! headless against a nested compositor such as `weston --backend=headless`.
+
+use std::{
+    os::fd::{AsRawFd, BorrowedFd},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use wayland_client::{
+    delegate_noop,
+    globals::{registry_queue_init, GlobalListContents},
+    protocol::{wl_buffer::WlBuffer, wl_compositor::WlCompositor, wl_registry::WlRegistry, wl_surface::WlSurface},
+    Connection, Dispatch, EventQueue, Proxy, QueueHandle,
+};
+use wayland_protocols::{
+    wp::{
+        linux_dmabuf::zv1::client::{
+            zwp_linux_buffer_params_v1::{self, ZwpLinuxBufferParamsV1},
+            zwp_linux_dmabuf_v1::{self, ZwpLinuxDmabufV1},
+        },
+        linux_drm_syncobj::v1::client::{
+            wp_linux_drm_syncobj_manager_v1::WpLinuxDrmSyncobjManagerV1,
+            wp_linux_drm_syncobj_surface_v1::WpLinuxDrmSyncobjSurfaceV1,
+            wp_linux_drm_syncobj_timeline_v1::WpLinuxDrmSyncobjTimelineV1,
+        },
+    },
+    xdg::shell::client::{
+        xdg_surface::{self, XdgSurface},
+        xdg_toplevel::{self, XdgToplevel},
+        xdg_wm_base::{self, XdgWmBase},
+    },
+};
+
+use crate::common::{to_drm_fourcc, DmaBufHandle, TextureDescriptor};
+use crate::error::{GeyserError, Result};
+
+/// A dma-buf wrapped as a `wl_buffer`. It holds its own duplicate of the dma-buf, so
+/// the handle it was created from can be released.
+#[derive(Debug)]
+pub struct WaylandBuffer {
+    buffer: WlBuffer,
+    busy: Arc<AtomicBool>,
+    width: u32,
+    height: u32,
+}
+
+impl WaylandBuffer {
+    /// Returns true while the compositor may still read the buffer, i.e. from
+    /// `present` until it sends `wl_buffer.release`. With explicit sync, wait on the
+    /// release point instead.
+    pub fn is_busy(&self) -> bool {
+        self.busy.load(Ordering::Acquire)
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+}
+
+impl Drop for WaylandBuffer {
+    fn drop(&mut self) {
+        self.buffer.destroy();
+    }
+}
+
+/// A DRM syncobj timeline imported into the compositor.
+#[derive(Debug)]
+pub struct WaylandTimeline {
+    timeline: WpLinuxDrmSyncobjTimelineV1,
+}
+
+impl Drop for WaylandTimeline {
+    fn drop(&mut self) {
+        self.timeline.destroy();
+    }
+}
+
+/// Timeline points guarding one presented frame.
+#[derive(Debug, Clone, Copy)]
+pub struct ExplicitSync<'a> {
+    /// The compositor waits for this point before reading the buffer
+    pub acquire: (&'a WaylandTimeline, u64),
+    /// The compositor signals this point once it no longer reads the buffer
+    pub release: (&'a WaylandTimeline, u64),
+}
+
+// Protocol state updated by the event handlers
+#[derive(Default)]
+struct PresenterState {
+    // (fourcc, modifier) pairs the compositor imports
+    formats: Vec<(u32, u64)>,
+    configured: bool,
+    closed: bool,
+}
+
+/// A toplevel window showing dma-buf textures. See the module docs.
+pub struct WaylandPresenter {
+    connection: Connection,
+    queue: EventQueue<PresenterState>,
+    state: PresenterState,
+    dmabuf: ZwpLinuxDmabufV1,
+    syncobj: Option<WpLinuxDrmSyncobjManagerV1>,
+    surface: WlSurface,
+    // Created on the first explicitly synchronized frame; from then on every frame
+    // has to carry timeline points
+    surface_sync: Option<WpLinuxDrmSyncobjSurfaceV1>,
+    toplevel: XdgToplevel,
+    xdg_surface: XdgSurface,
+    wm_base: XdgWmBase,
+}
+
+impl WaylandPresenter {
+    /// Connects to the compositor named by `WAYLAND_DISPLAY` and maps a toplevel
+    /// window titled `title`.
+    pub fn connect(title: &str) -> Result<Self> {
+        let connection = Connection::connect_to_env()
+            .map_err(|e| GeyserError::WaylandError(format!("Failed to connect to compositor: {}", e)))?;
+        Self::with_connection(connection, title)
+    }
+
+    /// Maps a toplevel window titled `title` on an existing connection.
+    pub fn with_connection(connection: Connection, title: &str) -> Result<Self> {
+        let (globals, mut queue) = registry_queue_init::<PresenterState>(&connection)
+            .map_err(|e| GeyserError::WaylandError(format!("Failed to list globals: {}", e)))?;
+        let qh = queue.handle();
+
+        let bind_error = |interface: &str, e| GeyserError::WaylandError(format!("Compositor lacks {}: {}", interface, e));
+        let compositor: WlCompositor =
+            globals.bind(&qh, 4..=6, ()).map_err(|e| bind_error("wl_compositor", e))?;
+        let wm_base: XdgWmBase = globals.bind(&qh, 1..=6, ()).map_err(|e| bind_error("xdg_wm_base", e))?;
+        // Version 3 announces formats with `modifier` events and adds `create_immed`
+        let dmabuf: ZwpLinuxDmabufV1 = globals.bind(&qh, 3..=3, ()).map_err(|e| bind_error("zwp_linux_dmabuf_v1", e))?;
+        let syncobj: Option<WpLinuxDrmSyncobjManagerV1> = globals.bind(&qh, 1..=1, ()).ok();
+
+        let surface = compositor.create_surface(&qh, ());
+        let xdg_surface = wm_base.get_xdg_surface(&surface, &qh, ());
+        let toplevel = xdg_surface.get_toplevel(&qh, ());
+        toplevel.set_title(title.to_string());
+        toplevel.set_app_id("geyser".to_string());
+        // The initial commit without a buffer asks for the first configure
+        surface.commit();
+
+        let mut state = PresenterState::default();
+        while !state.configured {
+            queue
+                .blocking_dispatch(&mut state)
+                .map_err(|e| GeyserError::WaylandError(format!("Failed to configure window: {}", e)))?;
+        }
+
+        Ok(Self {
+            connection,
+            queue,
+            state,
+            dmabuf,
+            syncobj,
+            surface,
+            surface_sync: None,
+            toplevel,
+            xdg_surface,
+            wm_base,
+        })
+    }
+
+    /// Returns the (DRM fourcc, modifier) pairs the compositor can import.
+    pub fn formats(&self) -> &[(u32, u64)] {
+        &self.state.formats
+    }
+
+    /// Returns true if frames can be presented with `ExplicitSync` timeline points.
+    pub fn explicit_sync_supported(&self) -> bool {
+        self.syncobj.is_some()
+    }
+
+    /// Returns true once the user asked to close the window.
+    pub fn is_closed(&self) -> bool {
+        self.state.closed
+    }
+
+    /// Wraps the dma-buf of `handle`, holding a texture described by `descriptor`, as
+    /// a `wl_buffer`. The handle keeps its file descriptor.
+    pub fn create_buffer(&mut self, handle: &DmaBufHandle, descriptor: &TextureDescriptor) -> Result<WaylandBuffer> {
+        check_buffer(handle, descriptor, &self.state.formats)?;
+        let qh = self.queue.handle();
+        let busy = Arc::new(AtomicBool::new(false));
+        let params = self.dmabuf.create_params(&qh, ());
+        let (modifier_hi, modifier_lo) = split_u64(handle.modifier);
+        // The request duplicates the descriptor onto the socket
+        let fd = unsafe { BorrowedFd::borrow_raw(handle.fd) };
+        params.add(fd, 0, handle.offset, handle.stride, modifier_hi, modifier_lo);
+        let buffer = params.create_immed(
+            descriptor.width as i32,
+            descriptor.height as i32,
+            handle.fourcc,
+            zwp_linux_buffer_params_v1::Flags::empty(),
+            &qh,
+            busy.clone(),
+        );
+        params.destroy();
+
+        // A rejected import is a protocol error, which ends the connection
+        self.queue
+            .roundtrip(&mut self.state)
+            .map_err(|e| GeyserError::WaylandError(format!("Compositor rejected the dma-buf: {}", e)))?;
+        Ok(WaylandBuffer {
+            buffer,
+            busy,
+            width: descriptor.width,
+            height: descriptor.height,
+        })
+    }
+
+    /// Imports a DRM syncobj for explicit sync, e.g. a timeline semaphore exported
+    /// with `export_timeline_semaphore_fd` on drivers whose opaque fds are syncobjs,
+    /// such as Mesa's. The caller keeps `syncobj_fd`.
+    pub fn import_timeline(&mut self, syncobj_fd: BorrowedFd<'_>) -> Result<WaylandTimeline> {
+        let manager = self.syncobj.as_ref().ok_or(GeyserError::OperationNotSupported)?;
+        let timeline = manager.import_timeline(syncobj_fd, &self.queue.handle(), ());
+        self.queue
+            .roundtrip(&mut self.state)
+            .map_err(|e| GeyserError::WaylandError(format!("Compositor rejected the timeline: {}", e)))?;
+        Ok(WaylandTimeline { timeline })
+    }
+
+    /// Shows `buffer` in the window from the next compositor repaint on.
+    ///
+    /// Once a frame was presented with `sync`, every later frame has to be too, as
+    /// the compositor then no longer tracks implicit fences for the surface.
+    pub fn present(&mut self, buffer: &WaylandBuffer, sync: Option<&ExplicitSync<'_>>) -> Result<()> {
+        if self.state.closed {
+            return Err(GeyserError::WaylandError("The window was closed".to_string()));
+        }
+        match (sync, &self.surface_sync) {
+            (Some(sync), _) => {
+                let manager = self.syncobj.as_ref().ok_or(GeyserError::OperationNotSupported)?;
+                let qh = self.queue.handle();
+                let surface_sync = self.surface_sync.get_or_insert_with(|| manager.get_surface(&self.surface, &qh, ()));
+                let (acquire_hi, acquire_lo) = split_u64(sync.acquire.1);
+                let (release_hi, release_lo) = split_u64(sync.release.1);
+                surface_sync.set_acquire_point(&sync.acquire.0.timeline, acquire_hi, acquire_lo);
+                surface_sync.set_release_point(&sync.release.0.timeline, release_hi, release_lo);
+            }
+            (None, Some(_)) => {
+                return Err(GeyserError::WaylandError(
+                    "Frames must carry timeline points once explicit sync is in use".to_string(),
+                ))
+            }
+            (None, None) => {}
+        }
+
+        buffer.busy.store(true, Ordering::Release);
+        self.surface.attach(Some(&buffer.buffer), 0, 0);
+        self.surface.damage_buffer(0, 0, buffer.width as i32, buffer.height as i32);
+        self.surface.commit();
+        self.connection
+            .flush()
+            .map_err(|e| GeyserError::WaylandError(format!("Failed to send frame: {}", e)))
+    }
+
+    /// Handles compositor events, waiting up to `timeout` for the first one. Returns
+    /// false once the window was closed.
+    pub fn dispatch(&mut self, timeout: Duration) -> Result<bool> {
+        let dispatch_error = |e: &dyn std::fmt::Display| GeyserError::WaylandError(format!("Failed to dispatch events: {}", e));
+        self.queue.flush().map_err(|e| dispatch_error(&e))?;
+        // Events may already be queued, in which case there is nothing to read
+        if let Some(guard) = self.queue.prepare_read() {
+            let mut pollfd = libc::pollfd {
+                fd: guard.connection_fd().as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            let timeout = timeout.as_millis().min(i32::MAX as u128) as i32;
+            let ready = unsafe { libc::poll(&mut pollfd, 1, timeout) };
+            if ready < 0 {
+                let error = std::io::Error::last_os_error();
+                if error.kind() != std::io::ErrorKind::Interrupted {
+                    return Err(dispatch_error(&error));
+                }
+            } else if ready > 0 {
+                match guard.read() {
+                    Ok(_) => {}
+                    Err(wayland_client::backend::WaylandError::Io(e)) if e.kind() == std::io::ErrorKind::WouldBlock => {}
+                    Err(e) => return Err(dispatch_error(&e)),
+                }
+            }
+        }
+        self.queue.dispatch_pending(&mut self.state).map_err(|e| dispatch_error(&e))?;
+        Ok(!self.state.closed)
+    }
+}
+
+impl Drop for WaylandPresenter {
+    fn drop(&mut self) {
+        if let Some(surface_sync) = self.surface_sync.take() {
+            surface_sync.destroy();
+        }
+        if let Some(syncobj) = self.syncobj.take() {
+            syncobj.destroy();
+        }
+        self.toplevel.destroy();
+        self.xdg_surface.destroy();
+        self.surface.destroy();
+        self.dmabuf.destroy();
+        self.wm_base.destroy();
+        let _ = self.connection.flush();
+    }
+}
+
+// Checks that the compositor can import `handle` as a texture described by `descriptor`
+fn check_buffer(handle: &DmaBufHandle, descriptor: &TextureDescriptor, formats: &[(u32, u64)]) -> Result<()> {
+    if handle.fourcc != to_drm_fourcc(descriptor.format)? {
+        return Err(GeyserError::UnsupportedFormat(format!(
+            "dma-buf fourcc {:#x} does not hold {}",
+            handle.fourcc, descriptor.format
+        )));
+    }
+    if !formats.contains(&(handle.fourcc, handle.modifier)) {
+        return Err(GeyserError::UnsupportedFormat(format!(
+            "The compositor cannot import fourcc {:#x} with modifier {:#x}",
+            handle.fourcc, handle.modifier
+        )));
+    }
+    Ok(())
+}
+
+// Splits a 64-bit value into the (high, low) halves the protocols send
+fn split_u64(value: u64) -> (u32, u32) {
+    ((value >> 32) as u32, value as u32)
+}
+
+impl Dispatch<WlRegistry, GlobalListContents> for PresenterState {
+    fn event(
+        _state: &mut Self,
+        _registry: &WlRegistry,
+        _event: <WlRegistry as Proxy>::Event,
+        _data: &GlobalListContents,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        // Globals appearing later are not used
+    }
+}
+
+impl Dispatch<XdgWmBase, ()> for PresenterState {
+    fn event(
+        _state: &mut Self,
+        wm_base: &XdgWmBase,
+        event: xdg_wm_base::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        if let xdg_wm_base::Event::Ping { serial } = event {
+            wm_base.pong(serial);
+        }
+    }
+}
+
+impl Dispatch<XdgSurface, ()> for PresenterState {
+    fn event(
+        state: &mut Self,
+        xdg_surface: &XdgSurface,
+        event: xdg_surface::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        if let xdg_surface::Event::Configure { serial } = event {
+            xdg_surface.ack_configure(serial);
+            state.configured = true;
+        }
+    }
+}
+
+impl Dispatch<XdgToplevel, ()> for PresenterState {
+    fn event(
+        state: &mut Self,
+        _toplevel: &XdgToplevel,
+        event: xdg_toplevel::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        // The window keeps the size of the presented buffers, so configured sizes are ignored
+        if let xdg_toplevel::Event::Close = event {
+            state.closed = true;
+        }
+    }
+}
+
+impl Dispatch<ZwpLinuxDmabufV1, ()> for PresenterState {
+    fn event(
+        state: &mut Self,
+        _dmabuf: &ZwpLinuxDmabufV1,
+        event: zwp_linux_dmabuf_v1::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        if let zwp_linux_dmabuf_v1::Event::Modifier {
+            format,
+            modifier_hi,
+            modifier_lo,
+        } = event
+        {
+            state.formats.push((format, (modifier_hi as u64) << 32 | modifier_lo as u64));
+        }
+    }
+}
+
+impl Dispatch<WlBuffer, Arc<AtomicBool>> for PresenterState {
+    fn event(
+        _state: &mut Self,
+        _buffer: &WlBuffer,
+        _event: <WlBuffer as Proxy>::Event,
+        busy: &Arc<AtomicBool>,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        // `release` is the only event
+        busy.store(false, Ordering::Release);
+    }
+}
+
+delegate_noop!(PresenterState: ignore WlCompositor);
+delegate_noop!(PresenterState: ignore WlSurface);
+delegate_noop!(PresenterState: ignore ZwpLinuxBufferParamsV1);
+delegate_noop!(PresenterState: ignore WpLinuxDrmSyncobjManagerV1);
+delegate_noop!(PresenterState: ignore WpLinuxDrmSyncobjSurfaceV1);
+delegate_noop!(PresenterState: ignore WpLinuxDrmSyncobjTimelineV1);
+
+#[cfg(test)]
+mod tests;
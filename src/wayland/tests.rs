@@ -0,0 +1,118 @@
+//! Unit tests for Wayland dma-buf presentation
+
+use std::os::fd::{FromRawFd, IntoRawFd, OwnedFd};
+
+use super::*;
+use crate::common::{TextureFormat, TextureMemoryLocation, TextureTiling, TextureUsage, DRM_FORMAT_MOD_LINEAR};
+
+const MOD_TILED: u64 = 0x0100_0000_0000_0001;
+
+fn descriptor(width: u32, height: u32) -> TextureDescriptor {
+    TextureDescriptor::new(width, height, TextureFormat::Bgra8Unorm, vec![TextureUsage::TextureBinding])
+        .with_tiling(TextureTiling::Linear)
+        .with_memory_location(TextureMemoryLocation::CpuToGpu)
+}
+
+fn handle(fd: i32, modifier: u64, stride: u32) -> DmaBufHandle {
+    DmaBufHandle {
+        fd,
+        fourcc: to_drm_fourcc(TextureFormat::Bgra8Unorm).unwrap(),
+        modifier,
+        offset: 0,
+        stride,
+    }
+}
+
+// A dma-buf backed by a memfd through /dev/udmabuf, or None where the module is missing
+fn udmabuf(size: usize) -> Option<OwnedFd> {
+    #[repr(C)]
+    struct UdmabufCreate {
+        memfd: u32,
+        flags: u32,
+        offset: u64,
+        size: u64,
+    }
+    const UDMABUF_CREATE: libc::c_ulong = 0x4018_7542;
+
+    let device = std::fs::File::open("/dev/udmabuf").ok()?;
+    let memfd = unsafe { libc::memfd_create(c"geyser-test".as_ptr(), libc::MFD_CLOEXEC | libc::MFD_ALLOW_SEALING) };
+    assert!(memfd >= 0);
+    let memfd = unsafe { OwnedFd::from_raw_fd(memfd) };
+    assert_eq!(unsafe { libc::ftruncate(memfd.as_raw_fd(), size as libc::off_t) }, 0);
+    assert_eq!(unsafe { libc::fcntl(memfd.as_raw_fd(), libc::F_ADD_SEALS, libc::F_SEAL_SHRINK) }, 0);
+    let create = UdmabufCreate {
+        memfd: memfd.as_raw_fd() as u32,
+        flags: 0x01, // UDMABUF_FLAGS_CLOEXEC
+        offset: 0,
+        size: size as u64,
+    };
+    let fd = unsafe { libc::ioctl(device.as_raw_fd(), UDMABUF_CREATE, &create as *const UdmabufCreate) };
+    (fd >= 0).then(|| unsafe { OwnedFd::from_raw_fd(fd) })
+}
+
+#[test]
+fn test_split_u64() {
+    assert_eq!(split_u64(MOD_TILED), (0x0100_0000, 1));
+    assert_eq!(split_u64(DRM_FORMAT_MOD_LINEAR), (0, 0));
+    assert_eq!(split_u64(u64::MAX), (u32::MAX, u32::MAX));
+}
+
+#[test]
+fn test_buffer_checks() {
+    let desc = descriptor(64, 64);
+    let formats = [(handle(-1, 0, 256).fourcc, DRM_FORMAT_MOD_LINEAR)];
+    assert!(check_buffer(&handle(-1, DRM_FORMAT_MOD_LINEAR, 256), &desc, &formats).is_ok());
+    // Modifier the compositor did not announce
+    assert!(matches!(
+        check_buffer(&handle(-1, MOD_TILED, 256), &desc, &formats),
+        Err(GeyserError::UnsupportedFormat(_))
+    ));
+    // Handle holding a different format than described
+    let rgba = TextureDescriptor {
+        format: TextureFormat::Rgba8Unorm,
+        ..desc
+    };
+    assert!(check_buffer(&handle(-1, DRM_FORMAT_MOD_LINEAR, 256), &rgba, &formats).is_err());
+}
+
+// Runs under a compositor, e.g. `weston --backend=headless --renderer=gl` with
+// WAYLAND_DISPLAY pointing at it; skipped elsewhere
+#[test]
+fn test_headless_compositor_presents_dma_buf() {
+    if std::env::var_os("WAYLAND_DISPLAY").is_none() {
+        eprintln!("Skipping: WAYLAND_DISPLAY is not set");
+        return;
+    }
+    let mut presenter = match WaylandPresenter::connect("geyser test") {
+        Ok(presenter) => presenter,
+        Err(e) => {
+            eprintln!("Skipping: {}", e);
+            return;
+        }
+    };
+    let desc = descriptor(64, 64);
+    let fourcc = to_drm_fourcc(desc.format).unwrap();
+    if !presenter.formats().contains(&(fourcc, DRM_FORMAT_MOD_LINEAR)) {
+        eprintln!("Skipping: compositor cannot import linear {}", desc.format);
+        return;
+    }
+    let Some(fd) = udmabuf(64 * 64 * 4) else {
+        eprintln!("Skipping: /dev/udmabuf is unavailable");
+        return;
+    };
+
+    let dma_buf = handle(fd.into_raw_fd(), DRM_FORMAT_MOD_LINEAR, 64 * 4);
+    let buffer = presenter.create_buffer(&dma_buf, &desc).unwrap();
+    // The buffer holds its own duplicate of the dma-buf
+    drop(unsafe { OwnedFd::from_raw_fd(dma_buf.fd) });
+    assert_eq!((buffer.width(), buffer.height()), (64, 64));
+
+    presenter.present(&buffer, None).unwrap();
+    assert!(buffer.is_busy());
+    assert!(presenter.dispatch(Duration::from_millis(100)).unwrap());
+    assert!(!presenter.is_closed());
+
+    // Unannounced modifiers are refused before anything reaches the compositor
+    assert!(presenter.create_buffer(&handle(-1, MOD_TILED, 256), &desc).is_err());
+    assert!(presenter.dispatch(Duration::ZERO).unwrap());
+}
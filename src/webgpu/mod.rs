@@ -1,7 +1,502 @@
-//! WebGPU backend for texture sharing (future work).
+//! WebGPU backend for texture sharing, implemented on top of wgpu.
+//!
+//! `WgpuTextureShareManager` gives wgpu applications the same `TextureShareManager`
+//! API as raw Vulkan users:
+//! - On Vulkan-backed devices created with the external memory extensions (see
+//!   `wgpu_bridge::request_shareable_device`), textures live in Vulkan external memory
+//!   and are shared without copies. This needs the `wgpu` feature.
+//! - Everywhere else, textures are shared by copying through mapped buffers into a
+//!   staging area held by the exported `WebGpuTextureShareHandle`. Contents only move
+//!   on `publish` (exporter) and `refresh` (importer), so this only works within a
+//!   process.
+
+use crate::common::{ApiTextureHandle, TextureDescriptor, TextureFormat, TextureUsage};
+use crate::error::{GeyserError, Result};
+use crate::{SharedTexture, TextureShareManager};
+use std::any::Any;
+use std::fmt;
+use std::sync::{mpsc, Arc, Mutex};
+
+#[cfg(feature = "wgpu")]
+use crate::{vulkan::VulkanTextureShareManager, wgpu_bridge::ManagedVulkanTexture};
 
 /// WebGPU-specific texture share handle.
+/// Refers to the staging copy of a texture exported without external memory; it
+/// can only be imported by a `WgpuTextureShareManager` in the same process.
 #[derive(Debug, Clone)]
 pub struct WebGpuTextureShareHandle {
-    // TODO: Implement WebGPU texture sharing mechanism
+    pub(crate) staging: Arc<StagingBuffer>,
+    pub width: u32,
+    pub height: u32,
+    pub format: TextureFormat,
 }
+
+// Texture contents with tightly packed rows, written by `publish`
+#[derive(Default)]
+pub(crate) struct StagingBuffer {
+    data: Mutex<Vec<u8>>,
+}
+
+impl fmt::Debug for StagingBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let len = self.data.lock().map(|data| data.len()).unwrap_or_default();
+        f.debug_struct("StagingBuffer").field("len", &len).finish()
+    }
+}
+
+/// Convert Geyser TextureFormat to wgpu-types TextureFormat
+pub fn to_wgpu_format(format: TextureFormat) -> wgpu_types::TextureFormat {
+    match format {
+        // 8-bit formats
+        TextureFormat::Rgba8Unorm => wgpu_types::TextureFormat::Rgba8Unorm,
+        TextureFormat::Bgra8Unorm => wgpu_types::TextureFormat::Bgra8Unorm,
+        TextureFormat::Rgba8Srgb => wgpu_types::TextureFormat::Rgba8UnormSrgb,
+        TextureFormat::Bgra8Srgb => wgpu_types::TextureFormat::Bgra8UnormSrgb,
+        TextureFormat::R8Unorm => wgpu_types::TextureFormat::R8Unorm,
+        TextureFormat::Rg8Unorm => wgpu_types::TextureFormat::Rg8Unorm,
+
+        // 16-bit formats
+        TextureFormat::R16Float => wgpu_types::TextureFormat::R16Float,
+        TextureFormat::Rg16Float => wgpu_types::TextureFormat::Rg16Float,
+        TextureFormat::Rgba16Float => wgpu_types::TextureFormat::Rgba16Float,
+        TextureFormat::R16Uint => wgpu_types::TextureFormat::R16Uint,
+        TextureFormat::R16Sint => wgpu_types::TextureFormat::R16Sint,
+
+        // 32-bit formats
+        TextureFormat::R32Float => wgpu_types::TextureFormat::R32Float,
+        TextureFormat::Rg32Float => wgpu_types::TextureFormat::Rg32Float,
+        TextureFormat::Rgba32Float => wgpu_types::TextureFormat::Rgba32Float,
+        TextureFormat::R32Uint => wgpu_types::TextureFormat::R32Uint,
+        TextureFormat::R32Sint => wgpu_types::TextureFormat::R32Sint,
+
+        // Depth/Stencil formats
+        TextureFormat::Depth32Float => wgpu_types::TextureFormat::Depth32Float,
+        TextureFormat::Depth24Plus => wgpu_types::TextureFormat::Depth24Plus,
+        TextureFormat::Depth24PlusStencil8 => wgpu_types::TextureFormat::Depth24PlusStencil8,
+
+        // HDR formats
+        TextureFormat::Rgb10a2Unorm => wgpu_types::TextureFormat::Rgb10a2Unorm,
+        TextureFormat::Rg11b10Float => wgpu_types::TextureFormat::Rg11b10Ufloat,
+    }
+}
+
+/// Convert Geyser TextureUsage to wgpu-types TextureUsages bitflags
+pub fn to_wgpu_usage(usage: &[TextureUsage]) -> wgpu_types::TextureUsages {
+    let mut wgpu_usage = wgpu_types::TextureUsages::empty();
+
+    for u in usage {
+        wgpu_usage |= match u {
+            TextureUsage::CopySrc => wgpu_types::TextureUsages::COPY_SRC,
+            TextureUsage::CopyDst => wgpu_types::TextureUsages::COPY_DST,
+            TextureUsage::TextureBinding => wgpu_types::TextureUsages::TEXTURE_BINDING,
+            TextureUsage::RenderAttachment => wgpu_types::TextureUsages::RENDER_ATTACHMENT,
+            TextureUsage::StorageBinding => wgpu_types::TextureUsages::STORAGE_BINDING,
+        };
+    }
+
+    wgpu_usage
+}
+
+/// Convert wgpu-types TextureFormat back to Geyser format
+pub fn from_wgpu_format(format: wgpu_types::TextureFormat) -> Result<TextureFormat> {
+    match format {
+        wgpu_types::TextureFormat::Rgba8Unorm => Ok(TextureFormat::Rgba8Unorm),
+        wgpu_types::TextureFormat::Bgra8Unorm => Ok(TextureFormat::Bgra8Unorm),
+        wgpu_types::TextureFormat::Rgba8UnormSrgb => Ok(TextureFormat::Rgba8Srgb),
+        wgpu_types::TextureFormat::Bgra8UnormSrgb => Ok(TextureFormat::Bgra8Srgb),
+        wgpu_types::TextureFormat::R8Unorm => Ok(TextureFormat::R8Unorm),
+        wgpu_types::TextureFormat::Rg8Unorm => Ok(TextureFormat::Rg8Unorm),
+        wgpu_types::TextureFormat::R16Float => Ok(TextureFormat::R16Float),
+        wgpu_types::TextureFormat::Rg16Float => Ok(TextureFormat::Rg16Float),
+        wgpu_types::TextureFormat::Rgba16Float => Ok(TextureFormat::Rgba16Float),
+        wgpu_types::TextureFormat::R16Uint => Ok(TextureFormat::R16Uint),
+        wgpu_types::TextureFormat::R16Sint => Ok(TextureFormat::R16Sint),
+        wgpu_types::TextureFormat::R32Float => Ok(TextureFormat::R32Float),
+        wgpu_types::TextureFormat::Rg32Float => Ok(TextureFormat::Rg32Float),
+        wgpu_types::TextureFormat::Rgba32Float => Ok(TextureFormat::Rgba32Float),
+        wgpu_types::TextureFormat::R32Uint => Ok(TextureFormat::R32Uint),
+        wgpu_types::TextureFormat::R32Sint => Ok(TextureFormat::R32Sint),
+        wgpu_types::TextureFormat::Depth32Float => Ok(TextureFormat::Depth32Float),
+        wgpu_types::TextureFormat::Depth24Plus => Ok(TextureFormat::Depth24Plus),
+        wgpu_types::TextureFormat::Depth24PlusStencil8 => Ok(TextureFormat::Depth24PlusStencil8),
+        wgpu_types::TextureFormat::Rgb10a2Unorm => Ok(TextureFormat::Rgb10a2Unorm),
+        wgpu_types::TextureFormat::Rg11b10Ufloat => Ok(TextureFormat::Rg11b10Float),
+        _ => Err(GeyserError::UnsupportedFormat(format!("Unsupported wgpu format: {:?}", format))),
+    }
+}
+
+/// A wgpu texture created or imported by a `WgpuTextureShareManager`
+pub struct WgpuSharedTexture {
+    texture: wgpu::Texture,
+    descriptor: TextureDescriptor,
+    backing: SharedBacking,
+    // Mappable buffer `publish` copies staging contents through, created on first use.
+    // Locked for the whole copy, since a buffer can only be mapped once at a time.
+    readback: Mutex<Option<wgpu::Buffer>>,
+}
+
+// Where a texture's contents are shared through
+enum SharedBacking {
+    // Vulkan external memory; the texture is only kept for textures this manager
+    // created, since imported memory cannot be exported again. Its memory is freed
+    // through the Vulkan manager once both wgpu and this texture are done with it.
+    #[cfg(feature = "wgpu")]
+    External(Option<Arc<ManagedVulkanTexture>>),
+    // Staging copy moved by `publish`/`refresh`
+    Staging(Arc<StagingBuffer>),
+}
+
+impl WgpuSharedTexture {
+    /// Returns the wgpu texture.
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self.texture
+    }
+
+    /// Returns true if the texture shares memory instead of copying through a staging buffer.
+    pub fn is_zero_copy(&self) -> bool {
+        self.staging().is_none()
+    }
+
+    fn staging(&self) -> Option<&Arc<StagingBuffer>> {
+        match &self.backing {
+            SharedBacking::Staging(staging) => Some(staging),
+            #[allow(unreachable_patterns)]
+            _ => None,
+        }
+    }
+}
+
+impl SharedTexture for WgpuSharedTexture {
+    fn width(&self) -> u32 {
+        self.descriptor.width
+    }
+
+    fn height(&self) -> u32 {
+        self.descriptor.height
+    }
+
+    fn format(&self) -> TextureFormat {
+        self.descriptor.format
+    }
+
+    fn usage(&self) -> &[TextureUsage] {
+        &self.descriptor.usage
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Texture share manager for a `wgpu::Device`.
+///
+/// Textures sharing external memory go through a `VulkanTextureShareManager` on wgpu's
+/// device (see `wgpu_bridge::manager_from_wgpu_device`). This manager only creates,
+/// imports and exports through it, which never submits to wgpu's `VkQueue`, so it is
+/// safe to use while other threads submit to wgpu. Staging copies are submitted
+/// through `wgpu::Queue` like any other wgpu work.
+pub struct WgpuTextureShareManager {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    // Set when the device is Vulkan-backed with the external memory extensions enabled
+    #[cfg(feature = "wgpu")]
+    vulkan: Option<Arc<VulkanTextureShareManager>>,
+}
+
+impl WgpuTextureShareManager {
+    /// Creates a manager for `device`. Textures are shared through Vulkan external
+    /// memory when the device supports it and through staging copies otherwise.
+    pub fn new(device: wgpu::Device, queue: wgpu::Queue) -> Self {
+        Self {
+            #[cfg(feature = "wgpu")]
+            vulkan: crate::wgpu_bridge::manager_from_wgpu_device(&device).ok().map(Arc::new),
+            device,
+            queue,
+        }
+    }
+
+    /// Returns the wgpu device textures are created on.
+    pub fn device(&self) -> &wgpu::Device {
+        &self.device
+    }
+
+    /// Returns the queue used for staging copies.
+    pub fn queue(&self) -> &wgpu::Queue {
+        &self.queue
+    }
+
+    /// Returns true if new textures are shared through Vulkan external memory.
+    pub fn uses_external_memory(&self) -> bool {
+        #[cfg(feature = "wgpu")]
+        if self.vulkan.is_some() {
+            return true;
+        }
+        false
+    }
+
+    /// Creates a new shareable wgpu texture.
+    /// Textures shared through staging copies also get `COPY_SRC` and `COPY_DST` usage.
+    pub fn create_wgpu_texture(&self, descriptor: &TextureDescriptor) -> Result<WgpuSharedTexture> {
+        #[cfg(feature = "wgpu")]
+        if let Some(vulkan) = &self.vulkan {
+            crate::wgpu_bridge::to_wgpu_shared_format(descriptor.format)?;
+            let texture = vulkan.create_vulkan_texture(descriptor)?;
+            let shared = Arc::new(ManagedVulkanTexture::new(texture, None, vulkan.clone()));
+            let owner = shared.clone();
+            // Safety: the image was created by a manager on this device from `descriptor`,
+            // and the callback keeps it alive for as long as wgpu uses it
+            let texture = unsafe {
+                crate::wgpu_bridge::texture_from_vulkan(
+                    &self.device,
+                    shared.texture().image(),
+                    Box::new(move || drop(owner)),
+                    descriptor,
+                )
+            };
+            return Ok(WgpuSharedTexture {
+                texture,
+                descriptor: descriptor.clone(),
+                backing: SharedBacking::External(Some(shared)),
+                readback: Mutex::new(None),
+            });
+        }
+
+        check_staging_format(descriptor.format)?;
+        Ok(WgpuSharedTexture {
+            texture: self.create_staging_texture(descriptor),
+            descriptor: descriptor.clone(),
+            backing: SharedBacking::Staging(Arc::default()),
+            readback: Mutex::new(None),
+        })
+    }
+
+    /// Imports a texture exported by another manager.
+    /// Staging handles are refreshed once, so the texture starts out with the
+    /// contents last published by the exporter.
+    pub fn import_wgpu_texture(&self, handle: ApiTextureHandle, descriptor: &TextureDescriptor) -> Result<WgpuSharedTexture> {
+        match handle {
+            ApiTextureHandle::WebGpu(handle) => {
+                check_staging_handle(&handle, descriptor)?;
+                let texture = WgpuSharedTexture {
+                    texture: self.create_staging_texture(descriptor),
+                    descriptor: descriptor.clone(),
+                    backing: SharedBacking::Staging(handle.staging),
+                    readback: Mutex::new(None),
+                };
+                self.refresh(&texture)?;
+                Ok(texture)
+            }
+            #[cfg(feature = "wgpu")]
+            ApiTextureHandle::Vulkan(handle) => {
+                let vulkan = self.vulkan.as_ref().ok_or(GeyserError::OperationNotSupported)?;
+                let imported = crate::wgpu_bridge::import_vulkan_texture(vulkan, &self.device, &handle, descriptor)?;
+                Ok(WgpuSharedTexture {
+                    texture: imported.into_texture(),
+                    descriptor: descriptor.clone(),
+                    backing: SharedBacking::External(None),
+                    readback: Mutex::new(None),
+                })
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(GeyserError::InvalidTextureHandle),
+        }
+    }
+
+    /// Copies `texture`'s current contents into its staging buffer, where importers
+    /// pick them up on `refresh`. Waits for the copy to complete. Does nothing for
+    /// textures sharing memory.
+    ///
+    /// The buffer the contents are read back through is created on the first call and
+    /// reused by later ones.
+    pub fn publish(&self, texture: &WgpuSharedTexture) -> Result<()> {
+        let Some(staging) = texture.staging() else {
+            return Ok(());
+        };
+
+        let row_size = packed_row_size(texture.texture.format(), texture.width())?;
+        let padded_row_size = padded_row_size(row_size);
+        // Only put back once unmapped, so a failed copy does not leave it mapped
+        let mut cached_readback = texture.readback.lock().unwrap();
+        let readback = cached_readback.take().unwrap_or_else(|| {
+            self.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Geyser staging readback"),
+                size: padded_row_size as u64 * texture.height() as u64,
+                usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            })
+        });
+
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Geyser staging publish"),
+        });
+        encoder.copy_texture_to_buffer(
+            texture.texture.as_image_copy(),
+            wgpu::TexelCopyBufferInfo {
+                buffer: &readback,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row_size),
+                    rows_per_image: Some(texture.height()),
+                },
+            },
+            texture.texture.size(),
+        );
+        let submission = self.queue.submit(Some(encoder.finish()));
+
+        let (sender, receiver) = mpsc::channel();
+        readback.map_async(wgpu::MapMode::Read, .., move |result| {
+            let _ = sender.send(result);
+        });
+        self.device
+            .poll(wgpu::PollType::Wait {
+                submission_index: Some(submission),
+                timeout: None,
+            })
+            .map_err(|e| GeyserError::WebGpuError(format!("Failed to wait for staging copy: {}", e)))?;
+        receiver
+            .recv()
+            .map_err(|e| GeyserError::WebGpuError(format!("Staging buffer was never mapped: {}", e)))?
+            .map_err(|e| GeyserError::WebGpuError(format!("Failed to map staging buffer: {}", e)))?;
+
+        {
+            let mapped = readback.get_mapped_range(..);
+            let mut data = staging.data.lock().unwrap();
+            data.clear();
+            for row in mapped.chunks(padded_row_size as usize) {
+                data.extend_from_slice(&row[..row_size as usize]);
+            }
+        }
+        readback.unmap();
+        *cached_readback = Some(readback);
+        Ok(())
+    }
+
+    /// Uploads the contents last published by the exporter into `texture`. The upload
+    /// is ordered before any work submitted to the queue afterwards. Does nothing for
+    /// textures sharing memory or if nothing has been published yet.
+    pub fn refresh(&self, texture: &WgpuSharedTexture) -> Result<()> {
+        let Some(staging) = texture.staging() else {
+            return Ok(());
+        };
+
+        let data = staging.data.lock().unwrap();
+        if data.is_empty() {
+            return Ok(());
+        }
+        let row_size = packed_row_size(texture.texture.format(), texture.width())?;
+        self.queue.write_texture(
+            texture.texture.as_image_copy(),
+            &data,
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(row_size),
+                rows_per_image: Some(texture.height()),
+            },
+            texture.texture.size(),
+        );
+        Ok(())
+    }
+
+    fn create_staging_texture(&self, descriptor: &TextureDescriptor) -> wgpu::Texture {
+        self.device.create_texture(&wgpu::TextureDescriptor {
+            label: descriptor.label.as_deref(),
+            size: wgpu::Extent3d {
+                width: descriptor.width,
+                height: descriptor.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: to_wgpu_format(descriptor.format),
+            usage: to_wgpu_usage(&descriptor.usage) | wgpu::TextureUsages::COPY_SRC | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        })
+    }
+}
+
+impl TextureShareManager for WgpuTextureShareManager {
+    fn create_shareable_texture(&self, descriptor: &TextureDescriptor) -> Result<Box<dyn SharedTexture>> {
+        Ok(Box::new(self.create_wgpu_texture(descriptor)?))
+    }
+
+    /// Exports through Vulkan external memory where available. Staging exports publish
+    /// the texture's current contents first.
+    fn export_texture(&self, texture: &dyn SharedTexture) -> Result<ApiTextureHandle> {
+        let texture = texture
+            .as_any()
+            .downcast_ref::<WgpuSharedTexture>()
+            .ok_or(GeyserError::InvalidTextureHandle)?;
+
+        match &texture.backing {
+            #[cfg(feature = "wgpu")]
+            SharedBacking::External(shared) => {
+                let shared = shared.as_ref().ok_or(GeyserError::OperationNotSupported)?;
+                let vulkan = self.vulkan.as_ref().ok_or(GeyserError::OperationNotSupported)?;
+                vulkan.export_texture(shared.texture())
+            }
+            SharedBacking::Staging(staging) => {
+                self.publish(texture)?;
+                Ok(ApiTextureHandle::WebGpu(WebGpuTextureShareHandle {
+                    staging: staging.clone(),
+                    width: texture.width(),
+                    height: texture.height(),
+                    format: texture.format(),
+                }))
+            }
+        }
+    }
+
+    fn import_texture(&self, handle: ApiTextureHandle, descriptor: &TextureDescriptor) -> Result<Box<dyn SharedTexture>> {
+        Ok(Box::new(self.import_wgpu_texture(handle, descriptor)?))
+    }
+
+    fn release_texture_handle(&self, handle: ApiTextureHandle) -> Result<()> {
+        match handle {
+            // The staging buffer is freed with the last texture or handle referring to it
+            ApiTextureHandle::WebGpu(_) => Ok(()),
+            #[cfg(feature = "wgpu")]
+            ApiTextureHandle::Vulkan(handle) => self
+                .vulkan
+                .as_ref()
+                .ok_or(GeyserError::OperationNotSupported)?
+                .release_texture_handle(ApiTextureHandle::Vulkan(handle)),
+            #[allow(unreachable_patterns)]
+            _ => Err(GeyserError::InvalidTextureHandle),
+        }
+    }
+}
+
+// Formats whose whole texel can be copied to and from buffers
+fn check_staging_format(format: TextureFormat) -> Result<()> {
+    if to_wgpu_format(format).is_depth_stencil_format() {
+        return Err(GeyserError::UnsupportedFormat(format!(
+            "{} cannot be shared through staging copies",
+            format
+        )));
+    }
+    Ok(())
+}
+
+fn check_staging_handle(handle: &WebGpuTextureShareHandle, descriptor: &TextureDescriptor) -> Result<()> {
+    check_staging_format(descriptor.format)?;
+    if handle.width != descriptor.width || handle.height != descriptor.height || handle.format != descriptor.format {
+        return Err(GeyserError::InvalidTextureHandle);
+    }
+    Ok(())
+}
+
+fn packed_row_size(format: wgpu::TextureFormat, width: u32) -> Result<u32> {
+    let texel_size = format.block_copy_size(None).ok_or_else(|| {
+        GeyserError::UnsupportedFormat(format!("{:?} cannot be shared through staging copies", format))
+    })?;
+    Ok(texel_size * width)
+}
+
+// Buffer rows in texture copies must be aligned to `COPY_BYTES_PER_ROW_ALIGNMENT`
+fn padded_row_size(row_size: u32) -> u32 {
+    row_size.div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT) * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT
+}
+
+#[cfg(test)]
+mod tests;
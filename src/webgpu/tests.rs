@@ -0,0 +1,69 @@
+//! Unit tests for the WebGPU backend
+
+use super::*;
+
+fn descriptor(format: TextureFormat) -> TextureDescriptor {
+    TextureDescriptor::new(100, 50, format, vec![TextureUsage::TextureBinding])
+}
+
+fn staging_handle(format: TextureFormat) -> WebGpuTextureShareHandle {
+    WebGpuTextureShareHandle {
+        staging: Arc::default(),
+        width: 100,
+        height: 50,
+        format,
+    }
+}
+
+#[test]
+fn test_staging_row_sizes() {
+    let row_size = packed_row_size(wgpu::TextureFormat::Rgba8Unorm, 100).unwrap();
+    assert_eq!(row_size, 400);
+    assert_eq!(padded_row_size(row_size), 512);
+    assert_eq!(padded_row_size(256), 256);
+
+    assert_eq!(packed_row_size(wgpu::TextureFormat::Rgba32Float, 3).unwrap(), 48);
+    assert!(packed_row_size(wgpu::TextureFormat::Depth24PlusStencil8, 100).is_err());
+}
+
+#[test]
+fn test_staging_rejects_depth_formats() {
+    for format in [
+        TextureFormat::Depth32Float,
+        TextureFormat::Depth24Plus,
+        TextureFormat::Depth24PlusStencil8,
+    ] {
+        assert!(matches!(
+            check_staging_format(format),
+            Err(GeyserError::UnsupportedFormat(_))
+        ));
+    }
+    assert!(check_staging_format(TextureFormat::Rgb10a2Unorm).is_ok());
+}
+
+#[test]
+fn test_staging_handle_must_match_descriptor() {
+    let handle = staging_handle(TextureFormat::Rgba8Unorm);
+    assert!(check_staging_handle(&handle, &descriptor(TextureFormat::Rgba8Unorm)).is_ok());
+    assert!(matches!(
+        check_staging_handle(&handle, &descriptor(TextureFormat::Bgra8Unorm)),
+        Err(GeyserError::InvalidTextureHandle)
+    ));
+
+    let mut resized = descriptor(TextureFormat::Rgba8Unorm);
+    resized.width = 200;
+    assert!(matches!(
+        check_staging_handle(&handle, &resized),
+        Err(GeyserError::InvalidTextureHandle)
+    ));
+}
+
+#[test]
+fn test_staging_handle_clones_share_contents() {
+    let handle = staging_handle(TextureFormat::R8Unorm);
+    let clone = handle.clone();
+    handle.staging.data.lock().unwrap().extend_from_slice(&[1, 2, 3]);
+
+    assert_eq!(*clone.staging.data.lock().unwrap(), vec![1, 2, 3]);
+    assert_eq!(format!("{:?}", clone.staging), "StagingBuffer { len: 3 }");
+}
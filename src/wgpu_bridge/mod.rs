@@ -0,0 +1,407 @@
+//! wgpu Bridge for Zero-Copy Texture Sharing
+//!
+//! This module provides utilities to bridge between Geyser's texture handles
+//! and wgpu, enabling zero-copy texture sharing with applications (and Bevy)
+//! rendering through wgpu.
+//!
+//! # Architecture
+//! - Format and usage conversion between Geyser and wgpu types
+//! - Safe wrapper around wgpu textures backed by shared memory
+//! - Import/export of textures through the wgpu device's Vulkan HAL
+//!
+//! # Platform Support
+//! - Vulkan (via VK_KHR_external_memory)
+//! - Metal (via IOSurface) - TODO
+//! - D3D12 (via NT handles) - TODO
+
+use crate::common::{ApiTextureHandle, TextureFormat, TextureDescriptor};
+use crate::error::GeyserError;
+use crate::vulkan::{required_device_extensions, VulkanSharedTexture, VulkanTextureShareHandle, VulkanTextureShareManager};
+use crate::TextureShareManager;
+use ash::vk;
+use std::ffi::CStr;
+use std::mem::ManuallyDrop;
+use std::sync::Arc;
+
+pub use crate::webgpu::{from_wgpu_format, to_wgpu_format, to_wgpu_usage};
+
+/// Convert a Geyser format to the wgpu format a shared Vulkan image can be wrapped as.
+/// Rejects formats whose Vulkan representation in Geyser differs from the one wgpu
+/// picks (`Depth24Plus*`, which wgpu resolves per device, and `Rgb10a2Unorm`, which
+/// Geyser stores as `A2R10G10B10`).
+pub fn to_wgpu_shared_format(format: TextureFormat) -> Result<wgpu_types::TextureFormat, GeyserError> {
+    match format {
+        TextureFormat::Depth24Plus | TextureFormat::Depth24PlusStencil8 | TextureFormat::Rgb10a2Unorm => {
+            Err(GeyserError::UnsupportedFormat(format!("{} cannot be shared with wgpu", format)))
+        }
+        _ => Ok(to_wgpu_format(format)),
+    }
+}
+
+/// A wgpu texture backed by memory shared through Geyser
+pub struct WgpuTextureHandle {
+    /// The wgpu texture; owns the underlying image and memory
+    pub(crate) texture: wgpu::Texture,
+    /// Texture descriptor
+    pub descriptor: TextureDescriptor,
+    /// Backend type (for safe downcasting)
+    pub backend_type: WgpuBackendType,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WgpuBackendType {
+    Vulkan,
+    Metal,
+    Dx12,
+    Gl,
+}
+
+impl WgpuTextureHandle {
+    /// Create a new WgpuTextureHandle (internal use)
+    pub(crate) fn new(
+        texture: wgpu::Texture,
+        descriptor: TextureDescriptor,
+        backend_type: WgpuBackendType,
+    ) -> Self {
+        Self {
+            texture,
+            descriptor,
+            backend_type,
+        }
+    }
+
+    /// Returns the wgpu texture.
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self.texture
+    }
+
+    /// Consumes the handle, returning the wgpu texture.
+    pub fn into_texture(self) -> wgpu::Texture {
+        self.texture
+    }
+    
+    /// Try to get the underlying Vulkan image
+    pub fn as_vulkan_raw(&self) -> Option<vk::Image> {
+        if self.backend_type != WgpuBackendType::Vulkan {
+            return None;
+        }
+        unsafe {
+            self.texture
+                .as_hal::<wgpu::hal::api::Vulkan>()
+                .map(|texture| texture.raw_handle())
+        }
+    }
+}
+
+// A texture wrapped as a wgpu texture. Dropped from wgpu's drop callback, it hands the
+// texture back to its manager so the allocation and any imported memory are freed.
+pub(crate) struct ManagedVulkanTexture {
+    texture: ManuallyDrop<VulkanSharedTexture>,
+    // Handle an imported texture's memory is tracked under; created textures have none
+    handle: Option<ApiTextureHandle>,
+    manager: Arc<VulkanTextureShareManager>,
+}
+
+impl ManagedVulkanTexture {
+    pub(crate) fn new(
+        texture: VulkanSharedTexture,
+        handle: Option<ApiTextureHandle>,
+        manager: Arc<VulkanTextureShareManager>,
+    ) -> Self {
+        Self { texture: ManuallyDrop::new(texture), handle, manager }
+    }
+
+    pub(crate) fn texture(&self) -> &VulkanSharedTexture {
+        &self.texture
+    }
+}
+
+impl Drop for ManagedVulkanTexture {
+    fn drop(&mut self) {
+        // Safety: the texture is not used again after this
+        let texture = unsafe { ManuallyDrop::take(&mut self.texture) };
+        // Nothing can report the error from inside wgpu's drop callback
+        let _ = match self.handle.take() {
+            Some(handle) => self.manager.destroy_texture(texture, handle),
+            None => self.manager.destroy_owned_texture(texture),
+        };
+    }
+}
+
+/// Creates a `VulkanTextureShareManager` on the same `VkDevice` as a wgpu device, so
+/// that textures it imports or exports can be used by wgpu.
+/// Fails with `MissingExtensions` unless the device was created with the external
+/// memory/semaphore/fence extensions enabled (wgpu does not enable them by default).
+///
+/// The manager submits on wgpu's own `VkQueue`, but wgpu does not expose the lock it
+/// holds around `vkQueueSubmit` and `vkQueuePresentKHR`, so the manager's queue lock
+/// cannot serialize against it. Vulkan requires submissions to one queue to be
+/// externally synchronized: only call the manager's submitting methods
+/// (`submit_shared_frame`, `queue_wait_semaphore`, `queue_signal_semaphore`, `blit`,
+/// `copy`, `refresh_copied_texture` and imports that fall back to copying) while no
+/// other thread can call `wgpu::Queue::submit` or present a surface, e.g. from the
+/// thread that drives wgpu's submissions. Imports and exports without a copy fallback
+/// do not touch the queue.
+pub fn manager_from_wgpu_device(device: &wgpu::Device) -> Result<VulkanTextureShareManager, GeyserError> {
+    let hal_device = unsafe { device.as_hal::<wgpu::hal::api::Vulkan>() }
+        .ok_or(GeyserError::OperationNotSupported)?;
+
+    let missing: Vec<String> = required_device_extensions()
+        .into_iter()
+        .filter(|name| !hal_device.enabled_device_extensions().contains(name))
+        .map(|name| name.to_string_lossy().into_owned())
+        .collect();
+    if !missing.is_empty() {
+        return Err(GeyserError::MissingExtensions(missing));
+    }
+
+    // wgpu owns the instance and device; the manager only borrows them. wgpu enables
+    // timeline semaphores whenever the adapter supports them, but never synchronization2
+    Ok(VulkanTextureShareManager::new(
+        Arc::new(hal_device.shared_instance().raw_instance().clone()),
+        Arc::new(hal_device.raw_device().clone()),
+        hal_device.raw_physical_device(),
+        hal_device.queue_family_index(),
+    )?
+    .with_sync_features(true, false))
+}
+
+/// Adds the external memory/semaphore/fence extensions Geyser needs to a wgpu device
+/// being created, skipping any `adapter` does not support. Call it from an
+/// `open_with_callback` device callback; `request_shareable_device` does this for you.
+pub fn enable_shareable_extensions(extensions: &mut Vec<&'static CStr>, adapter: &wgpu::hal::vulkan::Adapter) {
+    let capabilities = adapter.physical_device_capabilities();
+    for name in required_device_extensions() {
+        if capabilities.supports_extension(name) && !extensions.contains(&name) {
+            extensions.push(name);
+        }
+    }
+}
+
+/// Requests a wgpu device with the extensions `manager_from_wgpu_device` needs enabled.
+/// Fails with `OperationNotSupported` if `adapter` is not backed by Vulkan.
+pub fn request_shareable_device(
+    adapter: &wgpu::Adapter,
+    desc: &wgpu::DeviceDescriptor<'_>,
+) -> Result<(wgpu::Device, wgpu::Queue), GeyserError> {
+    let hal_adapter = unsafe { adapter.as_hal::<wgpu::hal::api::Vulkan>() }
+        .ok_or(GeyserError::OperationNotSupported)?;
+    let open_device = unsafe {
+        hal_adapter.open_with_callback(
+            desc.required_features,
+            &desc.memory_hints,
+            Some(Box::new(|args| enable_shareable_extensions(args.extensions, &hal_adapter))),
+        )
+    }
+    .map_err(|e| GeyserError::VulkanInitializationError(format!("Failed to open wgpu device: {}", e)))?;
+    drop(hal_adapter);
+
+    unsafe { adapter.create_device_from_hal::<wgpu::hal::api::Vulkan>(open_device, desc) }
+        .map_err(|e| GeyserError::VulkanInitializationError(format!("Failed to create wgpu device: {}", e)))
+}
+
+/// Import a Geyser texture handle into wgpu.
+/// The memory is imported through `manager`, which must share `device`'s `VkDevice`
+/// (see `manager_from_wgpu_device`), and is released through it once wgpu drops the texture.
+pub fn import_vulkan_texture(
+    manager: &Arc<VulkanTextureShareManager>,
+    device: &wgpu::Device,
+    handle: &VulkanTextureShareHandle,
+    descriptor: &TextureDescriptor,
+) -> Result<WgpuTextureHandle, GeyserError> {
+    ensure_same_device(manager, device)?;
+    to_wgpu_shared_format(descriptor.format)?;
+    let handle = ApiTextureHandle::Vulkan(handle.clone());
+    let texture = manager.import_vulkan_texture(handle.clone(), descriptor)?;
+    let owner = ManagedVulkanTexture::new(texture, Some(handle), manager.clone());
+    let image = owner.texture().image();
+    let texture = unsafe { texture_from_vulkan(device, image, Box::new(move || drop(owner)), descriptor) };
+    Ok(WgpuTextureHandle::new(texture, descriptor.clone(), WgpuBackendType::Vulkan))
+}
+
+/// Create a wgpu texture in exportable memory, returning it with the handle other
+/// processes or APIs import it from. The memory is freed through `manager` once wgpu
+/// drops the texture.
+pub fn export_vulkan_texture(
+    manager: &Arc<VulkanTextureShareManager>,
+    device: &wgpu::Device,
+    descriptor: &TextureDescriptor,
+) -> Result<(WgpuTextureHandle, ApiTextureHandle), GeyserError> {
+    ensure_same_device(manager, device)?;
+    to_wgpu_shared_format(descriptor.format)?;
+    let texture = manager.create_vulkan_texture(descriptor)?;
+    let owner = ManagedVulkanTexture::new(texture, None, manager.clone());
+    let api_handle = manager.export_texture(owner.texture())?;
+    let image = owner.texture().image();
+    let texture = unsafe { texture_from_vulkan(device, image, Box::new(move || drop(owner)), descriptor) };
+    Ok((WgpuTextureHandle::new(texture, descriptor.clone(), WgpuBackendType::Vulkan), api_handle))
+}
+
+// Images created by `manager` are only usable by wgpu on the same `VkDevice`
+fn ensure_same_device(manager: &VulkanTextureShareManager, device: &wgpu::Device) -> Result<(), GeyserError> {
+    let hal_device = unsafe { device.as_hal::<wgpu::hal::api::Vulkan>() }
+        .ok_or(GeyserError::OperationNotSupported)?;
+    if hal_device.raw_device().handle() != manager.device().handle() {
+        return Err(GeyserError::Other(
+            "Texture share manager does not use the wgpu device's VkDevice".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+// Wraps `image` as a new wgpu texture. `drop_callback` runs once wgpu is done with the
+// texture and must keep the image alive until then.
+//
+// Safety: `image` must belong to `device`'s `VkDevice` and match `descriptor`, whose
+// format must pass `to_wgpu_shared_format`.
+pub(crate) unsafe fn texture_from_vulkan(
+    device: &wgpu::Device,
+    image: vk::Image,
+    drop_callback: wgpu::hal::DropCallback,
+    descriptor: &TextureDescriptor,
+) -> wgpu::Texture {
+    let size = wgpu::Extent3d {
+        width: descriptor.width,
+        height: descriptor.height,
+        depth_or_array_layers: 1,
+    };
+    let format = to_wgpu_format(descriptor.format);
+    let usage = to_wgpu_usage(&descriptor.usage);
+
+    let hal_desc = wgpu::hal::TextureDescriptor {
+        label: descriptor.label.as_deref(),
+        size,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: to_hal_texture_uses(usage, format),
+        memory_flags: wgpu::hal::MemoryFlags::empty(),
+        view_formats: Vec::new(),
+    };
+    let hal_texture = {
+        let hal_device = device
+            .as_hal::<wgpu::hal::api::Vulkan>()
+            .expect("wgpu device is not backed by Vulkan");
+        hal_device.texture_from_raw(image, &hal_desc, Some(drop_callback))
+    };
+
+    device.create_texture_from_hal::<wgpu::hal::api::Vulkan>(
+        hal_texture,
+        &wgpu::TextureDescriptor {
+            label: descriptor.label.as_deref(),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage,
+            view_formats: &[],
+        },
+    )
+}
+
+// Mirrors wgpu-core's internal usage mapping, which wgpu-hal needs for its descriptor
+fn to_hal_texture_uses(usage: wgpu::TextureUsages, format: wgpu::TextureFormat) -> wgpu::TextureUses {
+    use wgpu::TextureUses;
+
+    let mut uses = TextureUses::empty();
+    if usage.contains(wgpu::TextureUsages::COPY_SRC) {
+        uses |= TextureUses::COPY_SRC;
+    }
+    if usage.contains(wgpu::TextureUsages::COPY_DST) {
+        uses |= TextureUses::COPY_DST;
+    }
+    if usage.contains(wgpu::TextureUsages::TEXTURE_BINDING) {
+        uses |= TextureUses::RESOURCE;
+    }
+    if usage.contains(wgpu::TextureUsages::STORAGE_BINDING) {
+        uses |= TextureUses::STORAGE_READ_ONLY | TextureUses::STORAGE_WRITE_ONLY | TextureUses::STORAGE_READ_WRITE;
+    }
+    if usage.contains(wgpu::TextureUsages::RENDER_ATTACHMENT) {
+        uses |= if format.is_depth_stencil_format() {
+            TextureUses::DEPTH_STENCIL_READ | TextureUses::DEPTH_STENCIL_WRITE
+        } else {
+            TextureUses::COLOR_TARGET
+        };
+    }
+    uses
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::TextureUsage;
+
+    #[test]
+    fn test_format_conversion_roundtrip() {
+        let formats = vec![
+            TextureFormat::Rgba8Unorm,
+            TextureFormat::Bgra8Unorm,
+            TextureFormat::R32Float,
+            TextureFormat::Depth32Float,
+        ];
+        
+        for format in formats {
+            let wgpu_format = to_wgpu_format(format);
+            let back = from_wgpu_format(wgpu_format).unwrap();
+            assert_eq!(format, back);
+        }
+    }
+    
+    #[test]
+    fn test_shared_format_rejects_mismatched_vulkan_formats() {
+        for format in [
+            TextureFormat::Depth24Plus,
+            TextureFormat::Depth24PlusStencil8,
+            TextureFormat::Rgb10a2Unorm,
+        ] {
+            assert!(matches!(
+                to_wgpu_shared_format(format),
+                Err(GeyserError::UnsupportedFormat(_))
+            ));
+        }
+
+        assert_eq!(
+            to_wgpu_shared_format(TextureFormat::Rgba16Float).unwrap(),
+            wgpu_types::TextureFormat::Rgba16Float
+        );
+        assert_eq!(
+            to_wgpu_shared_format(TextureFormat::Depth32Float).unwrap(),
+            wgpu_types::TextureFormat::Depth32Float
+        );
+    }
+    
+    #[test]
+    fn test_usage_conversion() {
+        let usage = vec![
+            TextureUsage::CopySrc,
+            TextureUsage::TextureBinding,
+            TextureUsage::RenderAttachment,
+        ];
+        
+        let wgpu_usage = to_wgpu_usage(&usage);
+        
+        assert!(wgpu_usage.contains(wgpu_types::TextureUsages::COPY_SRC));
+        assert!(wgpu_usage.contains(wgpu_types::TextureUsages::TEXTURE_BINDING));
+        assert!(wgpu_usage.contains(wgpu_types::TextureUsages::RENDER_ATTACHMENT));
+    }
+    
+    #[test]
+    fn test_hal_texture_uses() {
+        use wgpu::TextureUses;
+
+        let sampled = to_hal_texture_uses(
+            wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::RENDER_ATTACHMENT,
+            wgpu::TextureFormat::Rgba8Unorm,
+        );
+        assert!(sampled.contains(TextureUses::RESOURCE | TextureUses::COLOR_TARGET));
+
+        let depth = to_hal_texture_uses(
+            wgpu::TextureUsages::RENDER_ATTACHMENT,
+            wgpu::TextureFormat::Depth32Float,
+        );
+        assert!(depth.contains(TextureUses::DEPTH_STENCIL_WRITE));
+        assert!(!depth.contains(TextureUses::COLOR_TARGET));
+    }
+}
@@ -1,116 +1,19 @@
 // Integration tests for Geyser texture sharing
 
-use geyser::{
-    common::{TextureDescriptor, TextureFormat, TextureUsage},
-    TextureShareManager,
-};
+use geyser::common::{TextureDescriptor, TextureFormat, TextureMemoryLocation, TextureTiling, TextureUsage};
+#[cfg(any(feature = "vulkan", feature = "metal"))]
+use geyser::TextureShareManager;
 
 #[cfg(feature = "vulkan")]
 mod vulkan_tests {
     use super::*;
     use geyser::vulkan::VulkanTextureShareManager;
-    use ash::{vk, Entry, Instance, Device};
-    use std::{ffi::CString, sync::Arc};
 
-    fn create_test_vulkan_context() -> (Arc<Instance>, Arc<Device>, vk::PhysicalDevice, u32) {
-        let entry = unsafe { Entry::load().expect("Failed to load Vulkan") };
-        let app_name = CString::new("GeyserTest").unwrap();
-        
-        let app_info = vk::ApplicationInfo {
-            s_type: vk::StructureType::APPLICATION_INFO,
-            p_next: std::ptr::null(),
-            p_application_name: app_name.as_ptr(),
-            application_version: 0,
-            p_engine_name: std::ptr::null(),
-            engine_version: 0,
-            api_version: vk::make_api_version(0, 1, 2, 0),
-            _marker: std::marker::PhantomData,
-        };
-
-        // Enable required extensions for external memory on Windows
-        #[cfg(target_os = "windows")]
-        let extension_names = [
-            ash::khr::external_memory_capabilities::NAME.as_ptr(),
-            ash::khr::get_physical_device_properties2::NAME.as_ptr(),
-        ];
-        
-        #[cfg(not(target_os = "windows"))]
-        let extension_names: [*const i8; 0] = [];
-
-        let create_info = vk::InstanceCreateInfo {
-            s_type: vk::StructureType::INSTANCE_CREATE_INFO,
-            p_next: std::ptr::null(),
-            flags: vk::InstanceCreateFlags::empty(),
-            p_application_info: &app_info,
-            enabled_layer_count: 0,
-            pp_enabled_layer_names: std::ptr::null(),
-            enabled_extension_count: extension_names.len() as u32,
-            pp_enabled_extension_names: extension_names.as_ptr(),
-            _marker: std::marker::PhantomData,
-        };
-        
-        let instance = unsafe { entry.create_instance(&create_info, None).expect("Failed to create instance") };
-
-        let physical_devices = unsafe { instance.enumerate_physical_devices().expect("No devices") };
-        let physical_device = physical_devices[0];
-
-        let queue_family_properties = unsafe { instance.get_physical_device_queue_family_properties(physical_device) };
-        let queue_family_index = queue_family_properties
-            .iter()
-            .enumerate()
-            .find_map(|(i, props)| {
-                if props.queue_flags.contains(vk::QueueFlags::GRAPHICS) {
-                    Some(i as u32)
-                } else {
-                    None
-                }
-            })
-            .expect("No suitable queue family");
-
-        let queue_priority = 1.0;
-        let queue_create_info = vk::DeviceQueueCreateInfo {
-            s_type: vk::StructureType::DEVICE_QUEUE_CREATE_INFO,
-            p_next: std::ptr::null(),
-            flags: vk::DeviceQueueCreateFlags::empty(),
-            queue_family_index,
-            queue_count: 1,
-            p_queue_priorities: &queue_priority,
-            _marker: std::marker::PhantomData,
-        };
-
-        // Enable required device extensions for external memory
-        #[cfg(target_os = "windows")]
-        let device_extension_names = [
-            ash::khr::external_memory::NAME.as_ptr(),
-            ash::khr::external_memory_win32::NAME.as_ptr(),
-        ];
-        
-        #[cfg(target_os = "linux")]
-        let device_extension_names = [
-            ash::khr::external_memory::NAME.as_ptr(),
-            ash::khr::external_memory_fd::NAME.as_ptr(),
-        ];
-        
-        #[cfg(not(any(target_os = "windows", target_os = "linux")))]
-        let device_extension_names: [*const i8; 0] = [];
-
-        let device_create_info = vk::DeviceCreateInfo {
-            s_type: vk::StructureType::DEVICE_CREATE_INFO,
-            p_next: std::ptr::null(),
-            flags: vk::DeviceCreateFlags::empty(),
-            queue_create_info_count: 1,
-            p_queue_create_infos: &queue_create_info,
-            enabled_layer_count: 0,
-            pp_enabled_layer_names: std::ptr::null(),
-            enabled_extension_count: device_extension_names.len() as u32,
-            pp_enabled_extension_names: device_extension_names.as_ptr(),
-            p_enabled_features: std::ptr::null(),
-            _marker: std::marker::PhantomData,
-        };
-
-        let device = unsafe { instance.create_device(physical_device, &device_create_info, None).expect("Failed to create device") };
-
-        (Arc::new(instance), Arc::new(device), physical_device, queue_family_index)
+    fn create_test_manager() -> VulkanTextureShareManager {
+        VulkanTextureShareManager::builder()
+            .application_name("GeyserTest")
+            .build()
+            .expect("Failed to create manager")
     }
 
     fn test_descriptor() -> TextureDescriptor {
@@ -120,21 +23,22 @@ mod vulkan_tests {
             format: TextureFormat::Rgba8Unorm,
             usage: vec![TextureUsage::TextureBinding],
             label: Some("TestTexture".to_string()),
+            tiling: TextureTiling::Optimal,
+            memory_location: TextureMemoryLocation::GpuOnly,
         }
     }
 
     #[test]
     fn test_vulkan_manager_creation() {
-        let (instance, device, physical_device, queue_family_index) = create_test_vulkan_context();
-        let manager = VulkanTextureShareManager::new(instance, device, physical_device, queue_family_index);
+        let manager = VulkanTextureShareManager::builder()
+            .application_name("GeyserTest")
+            .build();
         assert!(manager.is_ok());
     }
 
     #[test]
     fn test_vulkan_texture_creation() {
-        let (instance, device, physical_device, queue_family_index) = create_test_vulkan_context();
-        let manager = VulkanTextureShareManager::new(instance, device, physical_device, queue_family_index)
-            .expect("Failed to create manager");
+        let manager = create_test_manager();
         
         let descriptor = test_descriptor();
         let texture = manager.create_shareable_texture(&descriptor);
@@ -148,9 +52,7 @@ mod vulkan_tests {
 
     #[test]
     fn test_vulkan_export() {
-        let (instance, device, physical_device, queue_family_index) = create_test_vulkan_context();
-        let manager = VulkanTextureShareManager::new(instance, device, physical_device, queue_family_index)
-            .expect("Failed to create manager");
+        let manager = create_test_manager();
         
         let descriptor = test_descriptor();
         let texture = manager.create_shareable_texture(&descriptor).expect("Failed to create texture");
@@ -161,9 +63,7 @@ mod vulkan_tests {
 
     #[test]
     fn test_vulkan_format_mappings() {
-        let (instance, device, physical_device, queue_family_index) = create_test_vulkan_context();
-        let manager = VulkanTextureShareManager::new(instance, device, physical_device, queue_family_index)
-            .expect("Failed to create manager");
+        let manager = create_test_manager();
         
         // Test various formats
         let formats = vec![
@@ -182,6 +82,8 @@ mod vulkan_tests {
                 format,
                 usage: vec![TextureUsage::TextureBinding],
                 label: Some(format!("Test{:?}", format)),
+                tiling: TextureTiling::Optimal,
+                memory_location: TextureMemoryLocation::GpuOnly,
             };
             
             let result = manager.create_shareable_texture(&desc);
@@ -205,6 +107,8 @@ mod metal_tests {
             format: TextureFormat::Rgba8Unorm,
             usage: vec![TextureUsage::TextureBinding],
             label: Some("TestTexture".to_string()),
+            tiling: TextureTiling::Optimal,
+            memory_location: TextureMemoryLocation::GpuOnly,
         }
     }
 
@@ -274,6 +178,8 @@ mod metal_tests {
                 format,
                 usage: vec![TextureUsage::TextureBinding],
                 label: Some(format!("Test{:?}", format)),
+                tiling: TextureTiling::Optimal,
+                memory_location: TextureMemoryLocation::GpuOnly,
             };
             
             let result = manager.create_shareable_texture(&desc);
@@ -291,6 +197,8 @@ fn test_texture_descriptor_creation() {
         format: TextureFormat::Rgba8Unorm,
         usage: vec![TextureUsage::RenderAttachment, TextureUsage::TextureBinding],
         label: Some("CommonTest".to_string()),
+        tiling: TextureTiling::Optimal,
+        memory_location: TextureMemoryLocation::GpuOnly,
     };
     
     assert_eq!(desc.width, 1920);
@@ -306,3 +214,145 @@ fn test_format_display() {
     let display_str = format!("{}", format);
     assert!(!display_str.is_empty());
 }
+
+// Packaging behavior of backends that share memory but no synchronization primitives
+#[cfg(feature = "vulkan")]
+mod default_sync_package_tests {
+    use super::*;
+    use geyser::common::SyncHandle;
+    use geyser::vulkan::{VulkanFenceHandle, VulkanSemaphoreHandle, VulkanTextureShareHandle};
+    use geyser::{ApiTextureHandle, GeyserError, Result, SharedTexture, SyncPrimitives, TextureSharePackage};
+    use std::cell::Cell;
+
+    struct MemoryOnlyTexture {
+        descriptor: TextureDescriptor,
+    }
+
+    impl SharedTexture for MemoryOnlyTexture {
+        fn width(&self) -> u32 {
+            self.descriptor.width
+        }
+        fn height(&self) -> u32 {
+            self.descriptor.height
+        }
+        fn format(&self) -> TextureFormat {
+            self.descriptor.format
+        }
+        fn usage(&self) -> &[TextureUsage] {
+            &self.descriptor.usage
+        }
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+    }
+
+    // Exports `exported` for every texture and counts calls to `import_texture`
+    struct MemoryOnlyManager {
+        exported: ApiTextureHandle,
+        imports: Cell<usize>,
+    }
+
+    impl MemoryOnlyManager {
+        fn new(exported: ApiTextureHandle) -> Self {
+            Self { exported, imports: Cell::new(0) }
+        }
+    }
+
+    impl geyser::TextureShareManager for MemoryOnlyManager {
+        fn create_shareable_texture(&self, descriptor: &TextureDescriptor) -> Result<Box<dyn SharedTexture>> {
+            Ok(Box::new(MemoryOnlyTexture { descriptor: descriptor.clone() }))
+        }
+        fn export_texture(&self, _texture: &dyn SharedTexture) -> Result<ApiTextureHandle> {
+            Ok(self.exported.clone())
+        }
+        fn import_texture(&self, _handle: ApiTextureHandle, descriptor: &TextureDescriptor) -> Result<Box<dyn SharedTexture>> {
+            self.imports.set(self.imports.get() + 1);
+            Ok(Box::new(MemoryOnlyTexture { descriptor: descriptor.clone() }))
+        }
+        fn release_texture_handle(&self, _handle: ApiTextureHandle) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    fn vulkan_handle(raw_handle: u64) -> ApiTextureHandle {
+        ApiTextureHandle::Vulkan(VulkanTextureShareHandle {
+            raw_handle,
+            memory_type_index: 0,
+            size: 256 * 256 * 4,
+            handle_type: ash::vk::ExternalMemoryHandleTypeFlags::OPAQUE_FD,
+            dedicated_allocation: true,
+            device_identity: Default::default(),
+            plane_layouts: Vec::new(),
+        })
+    }
+
+    fn descriptor() -> TextureDescriptor {
+        TextureDescriptor::new(256, 256, TextureFormat::Rgba8Unorm, vec![TextureUsage::TextureBinding])
+    }
+
+    // Round-trips a texture through the default `*_with_sync` methods of a manager exporting `handle`
+    fn roundtrip_memory_only(handle: ApiTextureHandle) -> ApiTextureHandle {
+        let manager = MemoryOnlyManager::new(handle);
+        assert!(matches!(
+            manager.create_shareable_texture_with_sync(&descriptor()),
+            Err(GeyserError::OperationNotSupported)
+        ));
+
+        let texture = manager.create_shareable_texture(&descriptor()).unwrap();
+        let package = manager.export_texture_with_sync(texture.as_ref()).unwrap();
+        assert!(package.sync.semaphore.is_none());
+        assert!(package.sync.fence.is_none());
+
+        let exported = package.texture.clone();
+        let imported = manager.import_texture_with_sync(package, &descriptor()).unwrap();
+        assert_eq!(imported.width(), 256);
+        assert_eq!(manager.imports.get(), 1);
+        exported
+    }
+
+    #[test]
+    fn test_default_sync_export_packages_memory_only() {
+        let exported = roundtrip_memory_only(vulkan_handle(42));
+        assert!(matches!(exported, ApiTextureHandle::Vulkan(handle) if handle.raw_handle == 42));
+    }
+
+    #[test]
+    fn test_default_sync_export_packages_dma_buf_memory_only() {
+        let handle = geyser::DmaBufHandle {
+            fd: 42,
+            fourcc: 0,
+            modifier: 0,
+            offset: 0,
+            stride: 1024,
+        };
+        let exported = roundtrip_memory_only(ApiTextureHandle::DmaBuf(handle));
+        assert!(matches!(exported, ApiTextureHandle::DmaBuf(handle) if handle.fd == 42));
+    }
+
+    #[test]
+    fn test_default_sync_import_rejects_sync_handles() {
+        let manager = MemoryOnlyManager::new(vulkan_handle(3));
+        let semaphore = SyncHandle::VulkanSemaphore(VulkanSemaphoreHandle {
+            raw_handle: 7,
+            handle_type: ash::vk::ExternalSemaphoreHandleTypeFlags::OPAQUE_FD,
+            is_timeline: true,
+        });
+        let fence = SyncHandle::VulkanFence(VulkanFenceHandle {
+            raw_handle: 8,
+            handle_type: ash::vk::ExternalFenceHandleTypeFlags::OPAQUE_FD,
+        });
+
+        for sync in [
+            SyncPrimitives { semaphore: Some(semaphore), fence: None },
+            SyncPrimitives { semaphore: None, fence: Some(fence) },
+        ] {
+            let package = TextureSharePackage { texture: vulkan_handle(3), sync };
+            assert!(matches!(
+                manager.import_texture_with_sync(package, &descriptor()),
+                Err(GeyserError::OperationNotSupported)
+            ));
+        }
+        // Rejected before the memory is touched
+        assert_eq!(manager.imports.get(), 0);
+    }
+}